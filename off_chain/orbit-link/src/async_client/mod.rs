@@ -8,6 +8,7 @@ use anchor_client::{
     solana_sdk::{
         account::Account, clock::Slot, commitment_config::CommitmentConfig, hash::Hash,
         pubkey::Pubkey, signature::Signature, transaction::VersionedTransaction,
+        transaction_context::TransactionReturnData,
     },
 };
 use async_trait::async_trait;
@@ -43,6 +44,16 @@ pub trait AsyncClient: Sync {
         signatures: &[Signature],
     ) -> Result<Vec<Option<TransactionStatus>>>;
 
+    /// Get the data returned by the last instruction setting it in a confirmed transaction.
+    ///
+    /// Clients that do not keep the metadata of processed transactions return `None`.
+    async fn get_transaction_return_data(
+        &self,
+        _signature: &Signature,
+    ) -> Result<Option<TransactionReturnData>> {
+        Ok(None)
+    }
+
     async fn get_latest_blockhash(&self) -> Result<Hash>;
 
     async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64>;
@@ -1,12 +1,20 @@
+use std::str::FromStr;
+
 use async_trait::async_trait;
+use base64::engine::{general_purpose::STANDARD as BS64, Engine};
 use solana_account_decoder::UiAccountEncoding;
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig,
+};
 use solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType};
+use solana_transaction_status::{
+    UiReturnDataEncoding, UiTransactionEncoding, UiTransactionReturnData,
+};
 use tracing::{debug, trace};
 
 use super::*;
-use crate::Result;
+use crate::{errors::ErrorKind, Result};
 
 #[async_trait]
 impl AsyncClient for RpcClient {
@@ -42,6 +50,33 @@ impl AsyncClient for RpcClient {
             .map_err(Into::into)
     }
 
+    async fn get_transaction_return_data(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<TransactionReturnData>> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        let tx = <RpcClient>::get_transaction_with_config(self, signature, config).await?;
+        let return_data: Option<UiTransactionReturnData> =
+            tx.transaction.meta.and_then(|meta| meta.return_data.into());
+        return_data
+            .map(|return_data| {
+                let (data, encoding) = return_data.data;
+                let data = match encoding {
+                    UiReturnDataEncoding::Base64 => BS64
+                        .decode(data)
+                        .map_err(|e| ErrorKind::DeserializationError(e.to_string()))?,
+                };
+                let program_id = Pubkey::from_str(&return_data.program_id)
+                    .map_err(|e| ErrorKind::DeserializationError(e.to_string()))?;
+                Ok(TransactionReturnData { program_id, data })
+            })
+            .transpose()
+    }
+
     async fn get_latest_blockhash(&self) -> Result<Hash> {
         <RpcClient>::get_latest_blockhash(self)
             .await
@@ -18,6 +18,7 @@ use anchor_client::{
         signer::{Signer, SignerError},
         system_instruction,
         transaction::{TransactionError, VersionedTransaction},
+        transaction_context::TransactionReturnData,
     },
 };
 use errors::ErrorKind;
@@ -366,6 +367,14 @@ where
         self.client.send_transaction(tx).await
     }
 
+    /// Get the data returned by a confirmed transaction, if any.
+    pub async fn get_transaction_return_data(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<TransactionReturnData>> {
+        self.client.get_transaction_return_data(signature).await
+    }
+
     /// Send a group of transactions and wait for them to be confirmed.
     /// Transactions are not guaranteed to be processed in the same order as they are sent.
     ///
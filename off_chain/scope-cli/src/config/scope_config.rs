@@ -91,6 +91,7 @@ mod tests {
                 oracle_type: OracleType::Pyth,
                 twap_enabled: false,
                 twap_source: None,
+                generic_data: None,
             },
         );
         token_conf_list.tokens.insert(
@@ -103,6 +104,7 @@ mod tests {
                 oracle_type: OracleType::RaydiumAmmV3AtoB,
                 twap_enabled: false,
                 twap_source: None,
+                generic_data: None,
             },
         );
         token_conf_list.tokens.insert(
@@ -115,6 +117,7 @@ mod tests {
                 oracle_type: OracleType::SwitchboardV2,
                 twap_enabled: false,
                 twap_source: None,
+                generic_data: None,
             },
         );
         token_conf_list.tokens.insert(
@@ -127,6 +130,7 @@ mod tests {
                 oracle_type: OracleType::CToken,
                 twap_enabled: false,
                 twap_source: None,
+                generic_data: None,
             },
         );
         token_conf_list.tokens.insert(
@@ -139,6 +143,7 @@ mod tests {
                 oracle_type: OracleType::KToken,
                 twap_enabled: false,
                 twap_source: None,
+                generic_data: None,
             },
         );
 
@@ -29,6 +29,10 @@ pub struct TokenConfig {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub twap_source: Option<u16>,
+
+    /// Oracle type specific configuration stored in the oracle mapping.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generic_data: Option<[u8; 20]>,
 }
 
 pub fn pubkey_is_default(pk: &Pubkey) -> bool {
@@ -52,6 +56,7 @@ mod tests {
             oracle_type: OracleType::Pyth,
             twap_enabled: false,
             twap_source: None,
+            generic_data: None,
         };
 
         let json = r#"{
@@ -14,7 +14,7 @@ use anchor_client::{
     },
     Cluster,
 };
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::{Parser, Subcommand};
 use orbit_link::{async_client::AsyncClient, OrbitLink};
 use scope_client::utils::PriceTypeFilter;
@@ -152,6 +152,10 @@ enum Actions {
         /// Log old prices as errors when prices are still too old after all retries
         #[clap(long, env)]
         old_price_is_error: bool,
+        /// Fetch the result of each refresh transaction to log the prices that were not updated
+        /// (one extra RPC call per refresh)
+        #[clap(long, env)]
+        fetch_refresh_statuses: bool,
     },
 
     /// Get a list of all pubkeys that are needed for price refreshed according to the configuration.
@@ -186,6 +190,32 @@ enum Actions {
     /// This requires admin_cached keypair
     #[clap()]
     ApproveAdminCached {},
+
+    /// Close all the accounts of the feed, per entry accounts included, and return their rent to
+    /// the admin
+    /// This requires admin keypair
+    #[clap()]
+    CloseFeed {},
+
+    /// Upgrade the layout of the feed accounts to the version of the program
+    /// This requires admin keypair
+    #[clap()]
+    Migrate {},
+
+    /// Set the programs whose instructions are allowed before a refresh (e.g. tips or memos)
+    /// Any previously allowed program not listed is removed.
+    /// This requires admin keypair
+    #[clap()]
+    SetRefreshAllowedPrograms {
+        /// The program ids to allow
+        #[clap(long, env, parse(try_from_str))]
+        programs: Vec<Pubkey>,
+    },
+
+    /// Check the on-chain mapping for inconsistencies (e.g. TWAP entries with an invalid source)
+    /// Fails if any is found.
+    #[clap()]
+    LintFeed {},
 }
 
 #[tokio::main]
@@ -285,7 +315,9 @@ async fn main() -> Result<()> {
                 old_price_alert_snooze_time_s,
                 alert_old_price_after_slots,
                 old_price_is_error,
+                fetch_refresh_statuses,
             } => {
+                scope.set_fetch_refresh_statuses(fetch_refresh_statuses);
                 let _server_handle = if server {
                     Some(web::server::thread_start(server_port).await)
                 } else {
@@ -308,6 +340,12 @@ async fn main() -> Result<()> {
                 set_admin_cached(&mut scope, admin_cached).await
             }
             Actions::ApproveAdminCached {} => approve_admin_cached(&mut scope).await,
+            Actions::CloseFeed {} => close_feed(&scope).await,
+            Actions::Migrate {} => migrate(&scope).await,
+            Actions::SetRefreshAllowedPrograms { programs } => {
+                set_refresh_allowed_programs(&scope, programs).await
+            }
+            Actions::LintFeed {} => lint_feed(&scope).await,
         }
     }
 }
@@ -497,3 +535,29 @@ async fn approve_admin_cached<T: AsyncClient, S: Signer>(
 ) -> Result<()> {
     scope.ix_approve_admin_cached().await
 }
+
+async fn close_feed<T: AsyncClient, S: Signer>(scope: &ScopeClient<T, S>) -> Result<()> {
+    // The per entry accounts can only be closed while the feed exists
+    scope.ix_close_entry_accounts().await?;
+    scope.ix_close_feed().await
+}
+
+async fn migrate<T: AsyncClient, S: Signer>(scope: &ScopeClient<T, S>) -> Result<()> {
+    scope.ix_migrate().await
+}
+
+async fn set_refresh_allowed_programs<T: AsyncClient, S: Signer>(
+    scope: &ScopeClient<T, S>,
+    programs: Vec<Pubkey>,
+) -> Result<()> {
+    scope.ix_update_refresh_allowed_programs(programs).await
+}
+
+async fn lint_feed<T: AsyncClient, S: Signer>(scope: &ScopeClient<T, S>) -> Result<()> {
+    let issues = scope.lint_feed().await?;
+    if issues > 0 {
+        bail!("Found {issues} inconsistencies in the feed mapping");
+    }
+    info!("No inconsistency found in the feed mapping");
+    Ok(())
+}
@@ -0,0 +1,97 @@
+//! Implementation of helper for prices read from an account field
+
+use std::fmt::{Debug, Display};
+
+use anchor_client::solana_sdk::clock;
+use anchor_client::solana_sdk::signer::Signer;
+use anyhow::{Context, Result};
+use orbit_link::async_client::AsyncClient;
+use orbit_link::OrbitLink;
+use scope::{anchor_lang::prelude::Pubkey, oracles::OracleType, AccountFieldReader, DatedPrice};
+
+use super::{OracleHelper, TokenEntry};
+use crate::config::TokenConfig;
+
+#[derive(Debug)]
+pub struct AccountFieldOracle {
+    label: String,
+    /// Pubkey to the account field reader account
+    mapping: Pubkey,
+
+    /// Account read
+    account: Pubkey,
+
+    /// Configured max age
+    max_age: clock::Slot,
+
+    twap_enabled: bool,
+}
+
+impl AccountFieldOracle {
+    pub async fn new<T: AsyncClient, S: Signer>(
+        conf: &TokenConfig,
+        default_max_age: clock::Slot,
+        rpc: &OrbitLink<T, S>,
+    ) -> Result<Self> {
+        let mapping = conf.oracle_mapping;
+        let reader: AccountFieldReader = rpc
+            .get_anchor_account(&mapping)
+            .await
+            .context("Retrieving account field reader account")?;
+
+        Ok(Self {
+            label: conf.label.clone(),
+            mapping,
+            account: reader.account,
+            max_age: conf.max_age.map(|nz| nz.into()).unwrap_or(default_max_age),
+            twap_enabled: conf.twap_enabled,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl OracleHelper for AccountFieldOracle {
+    fn get_type(&self) -> OracleType {
+        OracleType::AccountField
+    }
+
+    fn get_number_of_extra_accounts(&self) -> usize {
+        1
+    }
+
+    fn get_mapping_account(&self) -> Option<Pubkey> {
+        Some(self.mapping)
+    }
+
+    async fn get_extra_accounts(&self, _rpc: Option<&dyn AsyncClient>) -> Result<Vec<Pubkey>> {
+        Ok(vec![self.account])
+    }
+
+    fn get_max_age(&self) -> clock::Slot {
+        self.max_age
+    }
+
+    fn get_label(&self) -> &str {
+        &self.label
+    }
+
+    async fn need_refresh(
+        &self,
+        _scope_price: &DatedPrice,
+        _rpc: &dyn AsyncClient,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn is_twap_enabled(&self) -> bool {
+        self.twap_enabled
+    }
+}
+
+impl Display for AccountFieldOracle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.label)
+    }
+}
+
+impl TokenEntry for AccountFieldOracle {}
@@ -0,0 +1,206 @@
+//! Implementation of helper for constant-product pools (Raydium AMM v4 and CPMM, Orca legacy
+//! token swap, Meteora dynamic AMM) and their LP tokens
+
+use std::fmt::{Debug, Display};
+
+use anchor_client::solana_sdk::clock;
+use anyhow::{anyhow, Context, Result};
+use orbit_link::async_client::AsyncClient;
+use scope::{
+    anchor_lang::prelude::Pubkey,
+    oracles::{
+        meteora_dynamic_amm::meteora_amm, orca_token_swap::orca_swap, raydium_amm_v4::raydium_amm,
+        raydium_cpmm::raydium_cp_swap, OracleType,
+    },
+    DatedPrice,
+};
+
+use super::{OracleHelper, TokenEntry};
+use crate::config::TokenConfig;
+
+#[derive(Debug)]
+pub struct ConstantProductOracle {
+    oracle_type: OracleType,
+    label: String,
+    /// Pubkey to the pool account
+    mapping: Pubkey,
+
+    /// Vaults, mints and other accounts needed to compute the pool reserves
+    extra_accounts: Vec<Pubkey>,
+
+    /// Configured max age
+    max_age: clock::Slot,
+
+    twap_enabled: bool,
+
+    /// Minimum liquidity configuration
+    generic_data: [u8; 20],
+}
+
+impl ConstantProductOracle {
+    pub async fn new(
+        conf: &TokenConfig,
+        default_max_age: clock::Slot,
+        rpc: &dyn AsyncClient,
+    ) -> Result<Self> {
+        let mapping = conf.oracle_mapping;
+        let pool_raw = rpc
+            .get_account(&mapping)
+            .await
+            .context("Retrieving constant product pool account")?;
+
+        let expected_program = match conf.oracle_type {
+            OracleType::RaydiumAmmV4AtoB | OracleType::RaydiumAmmV4BtoA => raydium_amm::ID,
+            OracleType::RaydiumCpmmAtoB | OracleType::RaydiumCpmmBtoA => raydium_cp_swap::ID,
+            OracleType::OrcaTokenSwapAtoB | OracleType::OrcaTokenSwapBtoA => orca_swap::ID,
+            OracleType::MeteoraDynamicAmmAtoB | OracleType::MeteoraDynamicAmmBtoA => {
+                meteora_amm::ID
+            }
+            // The pool program is only known from the pool account
+            OracleType::ConstantProductLpToken => pool_raw.owner,
+            _ => anyhow::bail!("Wrong oracle type for ConstantProductOracle"),
+        };
+        if pool_raw.owner != expected_program {
+            anyhow::bail!(
+                "Pool {mapping} is owned by {} instead of {expected_program}",
+                pool_raw.owner
+            );
+        }
+
+        let (mut extra_accounts, lp_mint) =
+            get_pool_accounts(&pool_raw.owner, &pool_raw.data, rpc).await?;
+        if conf.oracle_type == OracleType::ConstantProductLpToken {
+            extra_accounts.push(lp_mint);
+        }
+
+        Ok(Self {
+            oracle_type: conf.oracle_type,
+            label: conf.label.clone(),
+            mapping,
+            extra_accounts,
+            max_age: conf.max_age.map(|nz| nz.into()).unwrap_or(default_max_age),
+            twap_enabled: conf.twap_enabled,
+            generic_data: conf.generic_data.unwrap_or_default(),
+        })
+    }
+}
+
+/// Get the extra accounts needed to compute the reserves of a pool and its LP mint
+async fn get_pool_accounts(
+    pool_program: &Pubkey,
+    pool_data: &[u8],
+    rpc: &dyn AsyncClient,
+) -> Result<(Vec<Pubkey>, Pubkey)> {
+    if *pool_program == raydium_amm::ID {
+        let amm_info = raydium_amm::AmmInfo::unpack(pool_data)
+            .map_err(|e| anyhow!("Deserializing Raydium AMM account: {e}"))?;
+        Ok((
+            vec![amm_info.coin_vault, amm_info.pc_vault],
+            amm_info.lp_mint,
+        ))
+    } else if *pool_program == raydium_cp_swap::ID {
+        let pool_state = raydium_cp_swap::PoolState::unpack(pool_data)
+            .map_err(|e| anyhow!("Deserializing Raydium CPMM account: {e}"))?;
+        Ok((
+            vec![pool_state.token_0_vault, pool_state.token_1_vault],
+            pool_state.lp_mint,
+        ))
+    } else if *pool_program == orca_swap::ID {
+        let swap = orca_swap::SwapV1::unpack(pool_data)
+            .map_err(|e| anyhow!("Deserializing Orca swap account: {e}"))?;
+        Ok((
+            vec![
+                swap.token_a,
+                swap.token_b,
+                swap.token_a_mint,
+                swap.token_b_mint,
+            ],
+            swap.pool_mint,
+        ))
+    } else if *pool_program == meteora_amm::ID {
+        let pool = meteora_amm::Pool::unpack(pool_data)
+            .map_err(|e| anyhow!("Deserializing Meteora pool account: {e}"))?;
+        if pool.curve_type != meteora_amm::CurveType::ConstantProduct {
+            anyhow::bail!(
+                "Meteora pool curve {:?} is not constant product",
+                pool.curve_type
+            );
+        }
+        let mut vault_lp_mints = Vec::with_capacity(2);
+        for vault_pk in [pool.a_vault, pool.b_vault] {
+            let vault_raw = rpc
+                .get_account(&vault_pk)
+                .await
+                .context("Retrieving Meteora vault account")?;
+            let vault = meteora_amm::Vault::unpack(&vault_raw.data)
+                .map_err(|e| anyhow!("Deserializing Meteora vault account: {e}"))?;
+            vault_lp_mints.push(vault.lp_mint);
+        }
+        Ok((
+            vec![
+                pool.token_a_mint,
+                pool.token_b_mint,
+                pool.a_vault,
+                pool.b_vault,
+                pool.a_vault_lp,
+                pool.b_vault_lp,
+                vault_lp_mints[0],
+                vault_lp_mints[1],
+            ],
+            pool.lp_mint,
+        ))
+    } else {
+        anyhow::bail!("Pool program {pool_program} is not a supported constant product AMM")
+    }
+}
+
+#[async_trait::async_trait]
+impl OracleHelper for ConstantProductOracle {
+    fn get_type(&self) -> OracleType {
+        self.oracle_type
+    }
+
+    fn get_number_of_extra_accounts(&self) -> usize {
+        self.extra_accounts.len()
+    }
+
+    fn get_mapping_account(&self) -> Option<Pubkey> {
+        Some(self.mapping)
+    }
+
+    async fn get_extra_accounts(&self, _rpc: Option<&dyn AsyncClient>) -> Result<Vec<Pubkey>> {
+        Ok(self.extra_accounts.clone())
+    }
+
+    fn get_max_age(&self) -> clock::Slot {
+        self.max_age
+    }
+
+    fn get_label(&self) -> &str {
+        &self.label
+    }
+
+    async fn need_refresh(
+        &self,
+        _scope_price: &DatedPrice,
+        _rpc: &dyn AsyncClient,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn is_twap_enabled(&self) -> bool {
+        self.twap_enabled
+    }
+
+    fn get_generic_data(&self) -> [u8; 20] {
+        self.generic_data
+    }
+}
+
+impl Display for ConstantProductOracle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.label)
+    }
+}
+
+impl TokenEntry for ConstantProductOracle {}
@@ -0,0 +1,96 @@
+//! Provides a generic implementation for all oracle prices fully configured by
+//! their generic data, without any mapping account (such as fixed rate, capped
+//! or floored, min/max, inverse prices and TWAP statistics)
+//!
+//! Fixed rate parameters can be updated on chain with the `update_fixed_rate`
+//! instruction, the configuration must then be updated accordingly to avoid the
+//! parameters being reverted on the next mapping update.
+
+use std::fmt::{Debug, Display};
+
+use anchor_client::solana_sdk::clock;
+use anyhow::Result;
+use orbit_link::async_client::AsyncClient;
+use scope::{anchor_lang::prelude::Pubkey, oracles::OracleType, DatedPrice};
+
+use super::{OracleHelper, TokenEntry};
+use crate::config::TokenConfig;
+
+#[derive(Debug)]
+pub struct GenericDataOracle {
+    label: String,
+    oracle_type: OracleType,
+
+    /// Configured max age
+    max_age: clock::Slot,
+
+    twap_enabled: bool,
+
+    /// Oracle type specific configuration
+    generic_data: [u8; 20],
+}
+
+impl GenericDataOracle {
+    pub fn new(conf: &TokenConfig, default_max_age: clock::Slot) -> Result<Self> {
+        Ok(Self {
+            label: conf.label.clone(),
+            oracle_type: conf.oracle_type,
+            max_age: conf.max_age.map(|nz| nz.into()).unwrap_or(default_max_age),
+            twap_enabled: conf.twap_enabled,
+            generic_data: conf
+                .generic_data
+                .ok_or_else(|| anyhow::anyhow!("{:?} requires generic data", conf.oracle_type))?,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl OracleHelper for GenericDataOracle {
+    fn get_type(&self) -> OracleType {
+        self.oracle_type
+    }
+
+    fn get_number_of_extra_accounts(&self) -> usize {
+        0_usize
+    }
+
+    fn get_mapping_account(&self) -> Option<Pubkey> {
+        None
+    }
+
+    async fn get_extra_accounts(&self, _rpc: Option<&dyn AsyncClient>) -> Result<Vec<Pubkey>> {
+        Ok(Vec::with_capacity(0))
+    }
+
+    fn get_max_age(&self) -> clock::Slot {
+        self.max_age
+    }
+
+    fn get_label(&self) -> &str {
+        &self.label
+    }
+
+    async fn need_refresh(
+        &self,
+        _scope_price: &DatedPrice,
+        _rpc: &dyn AsyncClient,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn is_twap_enabled(&self) -> bool {
+        self.twap_enabled
+    }
+
+    fn get_generic_data(&self) -> [u8; 20] {
+        self.generic_data
+    }
+}
+
+impl Display for GenericDataOracle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.label)
+    }
+}
+
+impl TokenEntry for GenericDataOracle {}
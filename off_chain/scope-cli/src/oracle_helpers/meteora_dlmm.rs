@@ -6,7 +6,7 @@ use anchor_client::anchor_lang::__private::bytemuck;
 use anchor_client::solana_sdk::clock;
 use anyhow::{Context, Result};
 use orbit_link::async_client::AsyncClient;
-use scope::oracles::meteora_dlmm::lb_clmm::LbPair;
+use scope::oracles::meteora_dlmm::lb_clmm::{self, LbPair};
 use scope::{anchor_lang::prelude::Pubkey, oracles::OracleType, DatedPrice};
 
 use super::{OracleHelper, TokenEntry};
@@ -28,6 +28,9 @@ pub struct MeteoraDlmmOracle {
     max_age: clock::Slot,
 
     twap_enabled: bool,
+
+    /// Minimum liquidity configuration
+    generic_data: [u8; 20],
 }
 
 impl MeteoraDlmmOracle {
@@ -59,8 +62,13 @@ impl MeteoraDlmmOracle {
             token_b_mint,
             max_age: conf.max_age.map(|nz| nz.into()).unwrap_or(default_max_age),
             twap_enabled: conf.twap_enabled,
+            generic_data: conf.generic_data.unwrap_or_default(),
         })
     }
+
+    fn is_min_liquidity_enabled(&self) -> bool {
+        self.generic_data[..8] != [0; 8]
+    }
 }
 
 #[async_trait::async_trait]
@@ -70,15 +78,33 @@ impl OracleHelper for MeteoraDlmmOracle {
     }
 
     fn get_number_of_extra_accounts(&self) -> usize {
-        2
+        if self.is_min_liquidity_enabled() {
+            3
+        } else {
+            2
+        }
     }
 
     fn get_mapping_account(&self) -> Option<Pubkey> {
         Some(self.mapping)
     }
 
-    async fn get_extra_accounts(&self, _rpc: Option<&dyn AsyncClient>) -> Result<Vec<Pubkey>> {
-        Ok(vec![self.token_a_mint, self.token_b_mint])
+    async fn get_extra_accounts(&self, rpc: Option<&dyn AsyncClient>) -> Result<Vec<Pubkey>> {
+        let mut accounts = vec![self.token_a_mint, self.token_b_mint];
+        // The active bin array can only be known with an up to date pool state
+        if let (true, Some(rpc)) = (self.is_min_liquidity_enabled(), rpc) {
+            let lb_pair_raw = rpc
+                .get_account(&self.mapping)
+                .await
+                .context("Retrieving Meteora's lb pair account")?;
+            let lb_pair: &LbPair = bytemuck::from_bytes(&lb_pair_raw.data[8..]);
+            let bin_array_index = lb_clmm::bin_id_to_bin_array_index(lb_pair.active_id);
+            accounts.push(lb_clmm::derive_bin_array_pda(
+                &self.mapping,
+                bin_array_index,
+            ));
+        }
+        Ok(accounts)
     }
 
     fn get_max_age(&self) -> clock::Slot {
@@ -100,6 +126,10 @@ impl OracleHelper for MeteoraDlmmOracle {
     fn is_twap_enabled(&self) -> bool {
         self.twap_enabled
     }
+
+    fn get_generic_data(&self) -> [u8; 20] {
+        self.generic_data
+    }
 }
 
 impl Display for MeteoraDlmmOracle {
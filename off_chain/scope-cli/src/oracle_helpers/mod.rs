@@ -15,12 +15,18 @@ use orbit_link::async_client::{self, AsyncClient};
 use orbit_link::OrbitLink;
 use scope::{anchor_lang::prelude::Pubkey, oracles::OracleType, DatedPrice};
 
+pub mod account_field;
+pub mod constant_product;
+pub mod generic_data_oracle;
 pub mod jupiter_lp_compute;
 pub mod jupiter_lp_fetch;
 #[cfg(feature = "yvaults")]
 pub mod ktokens;
 pub mod meteora_dlmm;
+pub mod openbook_v2;
 pub mod orca_whirlpool;
+pub mod raydium_ammv3_twap;
+pub mod signed_price;
 pub mod single_account_oracle;
 pub mod twap;
 
@@ -80,6 +86,11 @@ pub trait OracleHelper: Sync {
 
     /// Tell if this token should have a twap computed
     fn is_twap_enabled(&self) -> bool;
+
+    /// Get the oracle type specific configuration to store in the oracle mapping
+    fn get_generic_data(&self) -> [u8; 20] {
+        [0; 20]
+    }
 }
 
 pub async fn entry_from_config<T, S>(
@@ -94,7 +105,13 @@ where
     Ok(match token_conf.oracle_type {
         OracleType::Pyth
         | OracleType::SwitchboardV2
+        | OracleType::Chainlink
+        | OracleType::PhoenixMidPrice
+        | OracleType::IndexPrice
         | OracleType::CToken
+        | OracleType::KLendCToken
+        | OracleType::SolendBorrowRate
+        | OracleType::SolendSupplyRate
         | OracleType::SplStake
         | OracleType::MsolStake
         | OracleType::PythEMA
@@ -126,6 +143,45 @@ where
         OracleType::MeteoraDlmmAtoB | OracleType::MeteoraDlmmBtoA => Box::new(
             meteora_dlmm::MeteoraDlmmOracle::new(token_conf, default_max_age, &rpc.client).await?,
         ),
+        OracleType::RaydiumAmmV3TwapAtoB | OracleType::RaydiumAmmV3TwapBtoA => Box::new(
+            raydium_ammv3_twap::RaydiumAmmV3TwapOracle::new(
+                token_conf,
+                default_max_age,
+                &rpc.client,
+            )
+            .await?,
+        ),
+        OracleType::RaydiumAmmV4AtoB
+        | OracleType::RaydiumAmmV4BtoA
+        | OracleType::RaydiumCpmmAtoB
+        | OracleType::RaydiumCpmmBtoA
+        | OracleType::OrcaTokenSwapAtoB
+        | OracleType::OrcaTokenSwapBtoA
+        | OracleType::MeteoraDynamicAmmAtoB
+        | OracleType::MeteoraDynamicAmmBtoA
+        | OracleType::ConstantProductLpToken => Box::new(
+            constant_product::ConstantProductOracle::new(token_conf, default_max_age, &rpc.client)
+                .await?,
+        ),
+        OracleType::OpenBookV2MidPrice => Box::new(
+            openbook_v2::OpenBookV2Oracle::new(token_conf, default_max_age, &rpc.client).await?,
+        ),
+        OracleType::FixedRate
+        | OracleType::CappedFloored
+        | OracleType::MinOfTwo
+        | OracleType::MaxOfTwo
+        | OracleType::InversePrice
+        | OracleType::TwapStats => Box::new(generic_data_oracle::GenericDataOracle::new(
+            token_conf,
+            default_max_age,
+        )?),
+        OracleType::AccountField => Box::new(
+            account_field::AccountFieldOracle::new(token_conf, default_max_age, rpc).await?,
+        ),
+        OracleType::SignedPrice => Box::new(signed_price::SignedPriceOracle::new(
+            token_conf,
+            default_max_age,
+        )?),
         OracleType::DeprecatedPlaceholder1 | OracleType::DeprecatedPlaceholder2 => {
             panic!("DeprecatedPlaceholder is not a valid oracle type")
         }
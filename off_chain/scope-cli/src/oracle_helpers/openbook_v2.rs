@@ -0,0 +1,115 @@
+//! Implementation of helper for OpenBook v2 markets mid-price
+
+use std::fmt::{Debug, Display};
+
+use anchor_client::solana_sdk::clock;
+use anyhow::{anyhow, Context, Result};
+use orbit_link::async_client::AsyncClient;
+use scope::{
+    anchor_lang::prelude::Pubkey,
+    oracles::{openbook_v2::openbook::Market, OracleType},
+    DatedPrice,
+};
+
+use super::{OracleHelper, TokenEntry};
+use crate::config::TokenConfig;
+
+#[derive(Debug)]
+pub struct OpenBookV2Oracle {
+    label: String,
+    /// Pubkey to the market account
+    mapping: Pubkey,
+
+    /// Market bids account
+    bids: Pubkey,
+    /// Market asks account
+    asks: Pubkey,
+
+    /// Configured max age
+    max_age: clock::Slot,
+
+    twap_enabled: bool,
+
+    /// Spread and depth configuration
+    generic_data: [u8; 20],
+}
+
+impl OpenBookV2Oracle {
+    pub async fn new(
+        conf: &TokenConfig,
+        default_max_age: clock::Slot,
+        rpc: &dyn AsyncClient,
+    ) -> Result<Self> {
+        if conf.oracle_type != OracleType::OpenBookV2MidPrice {
+            anyhow::bail!("Wrong oracle type for OpenBookV2Oracle");
+        }
+
+        let mapping = conf.oracle_mapping;
+        let market_raw = rpc
+            .get_account(&mapping)
+            .await
+            .context("Retrieving OpenBook market account")?;
+        let market = Market::unpack(&market_raw.data)
+            .map_err(|e| anyhow!("Deserializing OpenBook market account: {e}"))?;
+        Ok(Self {
+            label: conf.label.clone(),
+            mapping,
+            bids: market.bids,
+            asks: market.asks,
+            max_age: conf.max_age.map(|nz| nz.into()).unwrap_or(default_max_age),
+            twap_enabled: conf.twap_enabled,
+            generic_data: conf.generic_data.unwrap_or_default(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl OracleHelper for OpenBookV2Oracle {
+    fn get_type(&self) -> OracleType {
+        OracleType::OpenBookV2MidPrice
+    }
+
+    fn get_number_of_extra_accounts(&self) -> usize {
+        2
+    }
+
+    fn get_mapping_account(&self) -> Option<Pubkey> {
+        Some(self.mapping)
+    }
+
+    async fn get_extra_accounts(&self, _rpc: Option<&dyn AsyncClient>) -> Result<Vec<Pubkey>> {
+        Ok(vec![self.bids, self.asks])
+    }
+
+    fn get_max_age(&self) -> clock::Slot {
+        self.max_age
+    }
+
+    fn get_label(&self) -> &str {
+        &self.label
+    }
+
+    async fn need_refresh(
+        &self,
+        _scope_price: &DatedPrice,
+        _rpc: &dyn AsyncClient,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn is_twap_enabled(&self) -> bool {
+        self.twap_enabled
+    }
+
+    fn get_generic_data(&self) -> [u8; 20] {
+        self.generic_data
+    }
+}
+
+impl Display for OpenBookV2Oracle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.label)
+    }
+}
+
+impl TokenEntry for OpenBookV2Oracle {}
@@ -29,6 +29,9 @@ pub struct OrcaWhirlpoolOracle {
     max_age: clock::Slot,
 
     twap_enabled: bool,
+
+    /// Minimum liquidity configuration
+    generic_data: [u8; 20],
 }
 
 impl OrcaWhirlpoolOracle {
@@ -62,6 +65,7 @@ impl OrcaWhirlpoolOracle {
             token_b_mint,
             max_age: conf.max_age.map(|nz| nz.into()).unwrap_or(default_max_age),
             twap_enabled: conf.twap_enabled,
+            generic_data: conf.generic_data.unwrap_or_default(),
         })
     }
 }
@@ -103,6 +107,10 @@ impl OracleHelper for OrcaWhirlpoolOracle {
     fn is_twap_enabled(&self) -> bool {
         self.twap_enabled
     }
+
+    fn get_generic_data(&self) -> [u8; 20] {
+        self.generic_data
+    }
 }
 
 impl Display for OrcaWhirlpoolOracle {
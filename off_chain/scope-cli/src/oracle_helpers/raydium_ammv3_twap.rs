@@ -0,0 +1,121 @@
+//! Implementation of helper for Raydium's AMM v3 TWAP computed from the pool observations
+
+use std::fmt::{Debug, Display};
+
+use anchor_client::anchor_lang::AccountDeserialize;
+use anchor_client::solana_sdk::clock;
+use anyhow::{Context, Result};
+use orbit_link::async_client::AsyncClient;
+use scope::{
+    anchor_lang::prelude::Pubkey, oracles::OracleType, raydium_amm_v3::states::PoolState,
+    DatedPrice,
+};
+
+use super::{OracleHelper, TokenEntry};
+use crate::config::TokenConfig;
+
+#[derive(Debug)]
+pub struct RaydiumAmmV3TwapOracle {
+    oracle_type: OracleType,
+    label: String,
+    /// Pubkey to the Pool account
+    mapping: Pubkey,
+
+    /// Pool observation account
+    observation: Pubkey,
+
+    /// Configured max age
+    max_age: clock::Slot,
+
+    twap_enabled: bool,
+
+    /// TWAP window configuration
+    generic_data: [u8; 20],
+}
+
+impl RaydiumAmmV3TwapOracle {
+    pub async fn new(
+        conf: &TokenConfig,
+        default_max_age: clock::Slot,
+        rpc: &dyn AsyncClient,
+    ) -> Result<Self> {
+        if !matches!(
+            conf.oracle_type,
+            OracleType::RaydiumAmmV3TwapAtoB | OracleType::RaydiumAmmV3TwapBtoA
+        ) {
+            anyhow::bail!("Wrong oracle type for RaydiumAmmV3TwapOracle");
+        }
+        let generic_data = conf
+            .generic_data
+            .context("RaydiumAmmV3TwapOracle requires the TWAP window in generic_data")?;
+
+        let mapping = conf.oracle_mapping;
+        let pool_raw = rpc
+            .get_account(&mapping)
+            .await
+            .context("Retrieving Raydium pool account")?;
+        let mut ref_slice = pool_raw.data.as_slice();
+        let pool: PoolState = PoolState::try_deserialize(&mut ref_slice)
+            .context("Trying to deserialize Raydium pool account")?;
+        Ok(Self {
+            oracle_type: conf.oracle_type,
+            label: conf.label.clone(),
+            mapping,
+            observation: pool.observation_key,
+            max_age: conf.max_age.map(|nz| nz.into()).unwrap_or(default_max_age),
+            twap_enabled: conf.twap_enabled,
+            generic_data,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl OracleHelper for RaydiumAmmV3TwapOracle {
+    fn get_type(&self) -> OracleType {
+        self.oracle_type
+    }
+
+    fn get_number_of_extra_accounts(&self) -> usize {
+        1
+    }
+
+    fn get_mapping_account(&self) -> Option<Pubkey> {
+        Some(self.mapping)
+    }
+
+    async fn get_extra_accounts(&self, _rpc: Option<&dyn AsyncClient>) -> Result<Vec<Pubkey>> {
+        Ok(vec![self.observation])
+    }
+
+    fn get_max_age(&self) -> clock::Slot {
+        self.max_age
+    }
+
+    fn get_label(&self) -> &str {
+        &self.label
+    }
+
+    async fn need_refresh(
+        &self,
+        _scope_price: &DatedPrice,
+        _rpc: &dyn AsyncClient,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn is_twap_enabled(&self) -> bool {
+        self.twap_enabled
+    }
+
+    fn get_generic_data(&self) -> [u8; 20] {
+        self.generic_data
+    }
+}
+
+impl Display for RaydiumAmmV3TwapOracle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.label)
+    }
+}
+
+impl TokenEntry for RaydiumAmmV3TwapOracle {}
@@ -0,0 +1,90 @@
+//! Implementation of helper for prices pushed by whitelisted publishers
+//!
+//! The refresh of these prices must be preceded by an ed25519 instruction verifying a
+//! message signed by a publisher, which the bot is not able to provide: the entries
+//! are only refreshed by the publishers themselves and should be configured with a
+//! `max_age` matching their publication rate.
+
+use std::fmt::{Debug, Display};
+
+use anchor_client::solana_sdk::{clock, sysvar};
+use anyhow::Result;
+use orbit_link::async_client::AsyncClient;
+use scope::{anchor_lang::prelude::Pubkey, oracles::OracleType, DatedPrice};
+
+use super::{OracleHelper, TokenEntry};
+use crate::config::TokenConfig;
+
+#[derive(Debug)]
+pub struct SignedPriceOracle {
+    label: String,
+    /// Pubkey to the price publishers account of the entry
+    mapping: Pubkey,
+
+    /// Configured max age
+    max_age: clock::Slot,
+
+    twap_enabled: bool,
+}
+
+impl SignedPriceOracle {
+    pub fn new(conf: &TokenConfig, default_max_age: clock::Slot) -> Result<Self> {
+        if conf.oracle_type != OracleType::SignedPrice {
+            anyhow::bail!("Wrong oracle type for SignedPriceOracle");
+        }
+
+        Ok(Self {
+            label: conf.label.clone(),
+            mapping: conf.oracle_mapping,
+            max_age: conf.max_age.map(|nz| nz.into()).unwrap_or(default_max_age),
+            twap_enabled: conf.twap_enabled,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl OracleHelper for SignedPriceOracle {
+    fn get_type(&self) -> OracleType {
+        OracleType::SignedPrice
+    }
+
+    fn get_number_of_extra_accounts(&self) -> usize {
+        1
+    }
+
+    fn get_mapping_account(&self) -> Option<Pubkey> {
+        Some(self.mapping)
+    }
+
+    async fn get_extra_accounts(&self, _rpc: Option<&dyn AsyncClient>) -> Result<Vec<Pubkey>> {
+        Ok(vec![sysvar::instructions::ID])
+    }
+
+    fn get_max_age(&self) -> clock::Slot {
+        self.max_age
+    }
+
+    fn get_label(&self) -> &str {
+        &self.label
+    }
+
+    async fn need_refresh(
+        &self,
+        _scope_price: &DatedPrice,
+        _rpc: &dyn AsyncClient,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn is_twap_enabled(&self) -> bool {
+        self.twap_enabled
+    }
+}
+
+impl Display for SignedPriceOracle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.label)
+    }
+}
+
+impl TokenEntry for SignedPriceOracle {}
@@ -17,6 +17,7 @@ pub struct SingleAccountOracle {
     pub oracle_type: OracleType,
     pub max_age: clock::Slot,
     pub twap_enabled: bool,
+    pub generic_data: [u8; 20],
 }
 
 impl SingleAccountOracle {
@@ -27,6 +28,7 @@ impl SingleAccountOracle {
             oracle_type: conf.oracle_type,
             max_age: conf.max_age.map(|nz| nz.into()).unwrap_or(default_max_age),
             twap_enabled: conf.twap_enabled,
+            generic_data: conf.generic_data.unwrap_or_default(),
         }
     }
 }
@@ -68,6 +70,10 @@ impl OracleHelper for SingleAccountOracle {
     fn is_twap_enabled(&self) -> bool {
         self.twap_enabled
     }
+
+    fn get_generic_data(&self) -> [u8; 20] {
+        self.generic_data
+    }
 }
 
 impl Display for SingleAccountOracle {
@@ -24,8 +24,9 @@ use orbit_link::tx_builder::TxBuilder;
 use orbit_link::{async_client::AsyncClient, OrbitLink};
 use scope::oracles::OracleType;
 use scope::{
-    accounts, instruction, Configuration, OracleMappings, OraclePrices, OracleTwaps,
-    TokenMetadatas, UpdateTokenMetadataMode,
+    accounts, anchor_lang::AnchorDeserialize, instruction, Configuration, OracleMappings,
+    OraclePrices, OracleTwaps, RefreshStatus, TokenMetadatas, UpdateTokenMetadataMode,
+    FEED_LAYOUT_VERSION, MAX_ENTRIES,
 };
 use tracing::{debug, error, info, trace, warn};
 
@@ -38,6 +39,13 @@ use crate::{
 
 /// Max number of refresh per tx
 const MAX_REFRESH_CHUNK_SIZE: usize = 24;
+/// Max number of per entry accounts closed per tx
+const MAX_CLOSE_ENTRY_ACCOUNTS_CHUNK_SIZE: usize = 24;
+/// Max number of accounts fetched per rpc call
+const MAX_GET_MULTIPLE_ACCOUNTS: usize = 100;
+/// Seeds prefixes of the per entry accounts (price publishers, index definitions, account field
+/// readers and price histories)
+const ENTRY_ACCOUNTS_SEEDS: [&[u8]; 4] = [b"publishers", b"index", b"field_reader", b"history"];
 /// Token gap to max age that still trigger refresh (in slots)
 const REMAINING_AGE_TO_REFRESH: i64 = 10;
 /// Base URL for mainnet explorer
@@ -60,6 +68,8 @@ pub struct ScopeClient<T: AsyncClient, S: Signer> {
     tokens: TokenEntryList,
     multisig: bool,
     is_localnet: bool,
+    /// Fetch and log the per-token statuses of each successful refresh (one extra RPC call)
+    fetch_refresh_statuses: bool,
 }
 
 impl<T, S> ScopeClient<T, S>
@@ -79,10 +89,18 @@ where
         let (configuration_acc, _) =
             Pubkey::find_program_address(&[b"conf", price_feed.as_bytes()], &program_id);
 
-        let Configuration { oracle_mappings, oracle_prices, tokens_metadata, oracle_twaps, admin_cached, .. } = client
+        let Configuration { oracle_mappings, oracle_prices, tokens_metadata, oracle_twaps, admin_cached, version, .. } = client
             .get_anchor_account::<Configuration>(&configuration_acc).await
             .context("Error while retrieving program configuration account, the program might be uninitialized")?;
 
+        if version != FEED_LAYOUT_VERSION {
+            warn!(
+                version,
+                expected = FEED_LAYOUT_VERSION,
+                "Feed accounts layout version differs from the client one, the feed might need a migration"
+            );
+        }
+
         let client = Self {
             client,
             program_id,
@@ -96,6 +114,7 @@ where
             tokens: IntMap::default(),
             multisig,
             is_localnet,
+            fetch_refresh_statuses: false,
         };
 
         debug!(%oracle_prices, %oracle_mappings, %configuration_acc, %tokens_metadata, %price_feed);
@@ -150,9 +169,16 @@ where
             tokens: IntMap::default(),
             multisig,
             is_localnet,
+            fetch_refresh_statuses: false,
         })
     }
 
+    /// Enable fetching the statuses returned by each successful refresh to log the tokens that
+    /// were not updated. Disabled by default as it costs an RPC call per refresh transaction.
+    pub fn set_fetch_refresh_statuses(&mut self, fetch_refresh_statuses: bool) {
+        self.fetch_refresh_statuses = fetch_refresh_statuses;
+    }
+
     pub async fn reset_twap_price(&self, token: u16) -> Result<()> {
         Self::ix_reset_twap(self, token).await?;
 
@@ -185,6 +211,7 @@ where
         let onchain_price_type_mapping = program_mapping.price_types;
         let onchain_twap_enabled = program_mapping.twap_enabled;
         let onchain_twap_source = program_mapping.twap_source;
+        let onchain_generic_data = program_mapping.generic;
         let token_metadatas = self.get_token_metadatas().await?;
 
         let filter = |(_, entry): &(_, &Box<dyn TokenEntry>)| match mode {
@@ -193,8 +220,12 @@ where
             PriceTypeFilter::Twap => matches!(entry.get_type(), OracleType::ScopeTwap),
         };
 
+        // TWAP entries are uploaded after the spot ones as their source must be mapped first
+        let mut entries: Vec<_> = self.tokens.iter().filter(filter).collect();
+        entries.sort_by_key(|(_, entry)| matches!(entry.get_type(), OracleType::ScopeTwap));
+
         // For all "token" local and remote
-        for (&token_idx, local_entry) in self.tokens.iter().filter(filter) {
+        for (&token_idx, local_entry) in entries {
             let idx: usize = token_idx.try_into().unwrap();
             let rem_mapping = if onchain_accounts_mapping[idx] == Pubkey::default()
                 || onchain_accounts_mapping[idx] == self.program_id
@@ -210,15 +241,18 @@ where
             } else {
                 None
             };
+            let rem_generic_data = onchain_generic_data[idx];
             // Update remote in case of difference
             let local_mapping_pk = local_entry.get_mapping_account();
             let loc_price_type_u8: u8 = local_entry.get_type().into();
             let loc_twap_enabled = local_entry.is_twap_enabled();
             let loc_twap_source = local_entry.get_twap_source();
+            let loc_generic_data = local_entry.get_generic_data();
             if rem_mapping != local_mapping_pk
                 || rem_price_type != loc_price_type_u8
                 || rem_twap_enabled != loc_twap_enabled
                 || rem_twap_source != loc_twap_source
+                || rem_generic_data != loc_generic_data
             {
                 self.ix_update_mapping(
                     local_mapping_pk,
@@ -226,6 +260,7 @@ where
                     loc_price_type_u8,
                     loc_twap_enabled,
                     loc_twap_source,
+                    loc_generic_data,
                 )
                 .await?;
             }
@@ -250,16 +285,24 @@ where
         }
 
         // if the token mapping contains entries that are not in the local mapping make their mapping account default
-        for (idx, rem_mapping) in onchain_accounts_mapping.iter().enumerate() {
-            if rem_mapping != &Pubkey::default()
-                && !self
-                    .tokens
-                    .iter()
-                    .any(|(local_id, _)| idx == usize::from(*local_id))
-            {
-                self.ix_update_mapping(None, idx.try_into().unwrap(), 0, false, None)
-                    .await?;
-            }
+        let mut removed: Vec<usize> = onchain_accounts_mapping
+            .iter()
+            .enumerate()
+            .filter(|(idx, rem_mapping)| {
+                *rem_mapping != &Pubkey::default()
+                    && !self
+                        .tokens
+                        .iter()
+                        .any(|(local_id, _)| *idx == usize::from(*local_id))
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        // TWAP entries are removed first as their source cannot be unmapped while they read it
+        removed
+            .sort_by_key(|&idx| onchain_price_type_mapping[idx] != u8::from(OracleType::ScopeTwap));
+        for idx in removed {
+            self.ix_update_mapping(None, idx.try_into().unwrap(), 0, false, None, [0; 20])
+                .await?;
         }
         Ok(())
     }
@@ -272,6 +315,7 @@ where
         let onchain_types = onchain_oracle_mapping.price_types;
         let twaps_enabled = &onchain_oracle_mapping.twap_enabled;
         let twap_sources = &onchain_oracle_mapping.twap_source;
+        let generic_datas = &onchain_oracle_mapping.generic;
 
         let zero_pk = Pubkey::default();
         let rpc = self.get_orbit_link();
@@ -282,11 +326,15 @@ where
             .zip(onchain_types)
             .zip(twaps_enabled.iter())
             .zip(twap_sources.iter())
+            .zip(generic_datas.iter())
             .zip(token_metadatas.metadatas_array.iter())
-            .filter(|(((((_, &oracle_mapping), _), _), _), _)| oracle_mapping != zero_pk)
+            .filter(|((((((_, &oracle_mapping), _), _), _), _), _)| oracle_mapping != zero_pk)
             .map(
                 |(
-                    ((((idx, &oracle_mapping), oracle_type), twap_enabled), twap_source),
+                    (
+                        ((((idx, &oracle_mapping), oracle_type), twap_enabled), twap_source),
+                        generic_data,
+                    ),
                     token_metadata,
                 )| async move {
                     let id: u16 = idx.try_into()?;
@@ -296,6 +344,7 @@ where
                         Some(*twap_source)
                     };
                     let twap_enabled = *twap_enabled != 0;
+                    let generic_data = Some(*generic_data).filter(|data| *data != [0; 20]);
                     let first_0_or_length = token_metadata
                         .name
                         .iter()
@@ -313,6 +362,7 @@ where
                         oracle_mapping,
                         twap_enabled,
                         twap_source,
+                        generic_data,
                     };
                     let entry = entry_from_config(&oracle_conf, default_max_age, rpc).await?;
                     Result::<(u16, Box<dyn TokenEntry>)>::Ok((id, entry))
@@ -341,6 +391,8 @@ where
                         max_age: std::num::NonZeroU64::new(entry.get_max_age()),
                         twap_enabled: entry.is_twap_enabled(),
                         twap_source: entry.get_twap_source(),
+                        generic_data: Some(entry.get_generic_data())
+                            .filter(|generic_data| *generic_data != [0; 20]),
                     },
                 )
             })
@@ -498,6 +550,8 @@ where
             let exponent = (dated_price.price.exp + 1) as usize;
             let price_type = entry.get_type();
             let age_in_slots: i64 = current_slot as i64 - dated_price.last_updated_slot as i64;
+            let refreshed_slot = dated_price.refreshed_slot();
+            let refresh_age_in_slots: i64 = current_slot as i64 - refreshed_slot as i64;
             let max_age = entry.get_max_age() as i64;
             let age_string = if age_in_slots > max_age {
                 format!("\x1b[1m\x1b[31m{age_in_slots}\x1b[0m")
@@ -505,7 +559,7 @@ where
                 format!("\x1b[32m{age_in_slots}\x1b[0m")
             };
             // For easier parsing of these logs don't use tracing here.
-            println!("id={id}, entry='{entry}', price='{price:.exponent$}', price_type='{price_type:?}', age={age_in_slots}, age_c={age_string}, max_age={max_age}");
+            println!("id={id}, entry='{entry}', price='{price:.exponent$}', price_type='{price_type:?}', age={age_in_slots}, age_c={age_string}, max_age={max_age}, source_slot={}, source_ts={}, refreshed_slot={refreshed_slot}, refresh_age={refresh_age_in_slots}", dated_price.last_updated_slot, dated_price.unix_timestamp);
         }
         Ok(())
     }
@@ -525,6 +579,18 @@ where
             .collect())
     }
 
+    /// Log the inconsistencies of the on-chain mapping and return how many were found.
+    pub async fn lint_feed(&self) -> Result<usize> {
+        let mapping = self.get_program_mapping().await?;
+        let mut count = 0;
+        for (token, err) in mapping.twap_source_errors() {
+            let twap_source = mapping.get_twap_source(token);
+            warn!(token, twap_source, ?err, "Invalid TWAP source");
+            count += 1;
+        }
+        Ok(count)
+    }
+
     /// Print a list of all pubkeys that are needed for price refreshed.
     pub async fn print_pubkeys(&self) -> Result<()> {
         // Print only unique pubkeys
@@ -683,6 +749,7 @@ where
         price_type: u8,
         twap_enabled: bool,
         twap_source: Option<u16>,
+        generic_data: [u8; 20],
     ) -> Result<()> {
         // Manually skip auto anchor resolution of optional account because of issues with mainnet/devnet/localnet builds.
         let price_info = Some(oracle_account.unwrap_or(self.program_id));
@@ -705,6 +772,7 @@ where
                 twap_enabled,
                 twap_source: twap_source.unwrap_or(u16::MAX),
                 feed_name: self.feed_name.clone(),
+                generic_data,
             },
         );
 
@@ -813,6 +881,130 @@ where
         Ok(())
     }
 
+    /// Close the existing per entry accounts of the feed, `close_feed` does not close them
+    #[tracing::instrument(skip(self))]
+    pub async fn ix_close_entry_accounts(&self) -> Result<()> {
+        let pdas: Vec<Pubkey> = (0..MAX_ENTRIES as u64)
+            .flat_map(|token| {
+                ENTRY_ACCOUNTS_SEEDS.map(|seed| {
+                    Pubkey::find_program_address(
+                        &[seed, self.oracle_prices_acc.as_ref(), &token.to_le_bytes()],
+                        &self.program_id,
+                    )
+                    .0
+                })
+            })
+            .collect();
+
+        let mut existing = Vec::new();
+        for chunk in pdas.chunks(MAX_GET_MULTIPLE_ACCOUNTS) {
+            let accounts = self.get_rpc().get_multiple_accounts(chunk).await?;
+            existing.extend(
+                chunk
+                    .iter()
+                    .zip(accounts)
+                    .filter(|(_, account)| account.is_some())
+                    .map(|(pda, _)| *pda),
+            );
+        }
+        info!("Closing {} per entry accounts", existing.len());
+
+        for chunk in existing.chunks(MAX_CLOSE_ENTRY_ACCOUNTS_CHUNK_SIZE) {
+            let mut accounts = accounts::CloseEntryAccounts {
+                admin: self.client.payer_pubkey(),
+                configuration: self.configuration_acc,
+                oracle_prices: self.oracle_prices_acc,
+                oracle_mappings: self.oracle_mappings_acc,
+            }
+            .to_account_metas(None);
+            accounts.extend(chunk.iter().map(|pda| AccountMeta::new(*pda, false)));
+
+            let args = instruction::CloseEntryAccounts {
+                feed_name: self.feed_name.clone(),
+            };
+
+            let request = self.client.tx_builder();
+
+            let tx_builder = request.add_anchor_ix(&self.program_id, accounts, args);
+
+            self.send_transaction(tx_builder).await?;
+        }
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn ix_close_feed(&self) -> Result<()> {
+        let accounts = accounts::CloseFeed {
+            admin: self.client.payer_pubkey(),
+            configuration: self.configuration_acc,
+            oracle_prices: self.oracle_prices_acc,
+            oracle_mappings: self.oracle_mappings_acc,
+            oracle_twaps: self.oracle_twaps_acc,
+            tokens_metadata: self.tokens_metadata_acc,
+        }
+        .to_account_metas(None);
+
+        let args = instruction::CloseFeed {
+            feed_name: self.feed_name.clone(),
+        };
+
+        let request = self.client.tx_builder();
+
+        let tx_builder = request.add_anchor_ix(&self.program_id, accounts, args);
+
+        self.send_transaction(tx_builder).await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn ix_migrate(&self) -> Result<()> {
+        let accounts = accounts::Migrate {
+            admin: self.client.payer_pubkey(),
+            configuration: self.configuration_acc,
+            oracle_prices: self.oracle_prices_acc,
+            oracle_mappings: self.oracle_mappings_acc,
+            oracle_twaps: self.oracle_twaps_acc,
+            tokens_metadata: self.tokens_metadata_acc,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+
+        let args = instruction::Migrate {
+            feed_name: self.feed_name.clone(),
+        };
+
+        let request = self.client.tx_builder();
+
+        let tx_builder = request.add_anchor_ix(&self.program_id, accounts, args);
+
+        self.send_transaction(tx_builder).await?;
+
+        Ok(())
+    }
+
+    pub async fn ix_update_refresh_allowed_programs(&self, programs: Vec<Pubkey>) -> Result<()> {
+        let accounts = accounts::UpdateRefreshAllowedPrograms {
+            admin: self.client.payer_pubkey(),
+            configuration: self.configuration_acc,
+        }
+        .to_account_metas(None);
+
+        let args = instruction::UpdateRefreshAllowedPrograms {
+            feed_name: self.feed_name.clone(),
+            programs,
+        };
+
+        let request = self.client.tx_builder();
+
+        let tx_builder = request.add_anchor_ix(&self.program_id, accounts, args);
+
+        self.send_transaction(tx_builder).await?;
+
+        Ok(())
+    }
+
     async fn send_transaction<'a>(&self, tx_builder: TxBuilder<'a, T, S>) -> Result<()> {
         if self.multisig {
             if !std::io::stdout().is_terminal() {
@@ -863,6 +1055,8 @@ where
 
         let rpc = self.get_rpc();
         let mut cu_budget = 15_000;
+        let program_mapping = self.get_program_mapping().await?;
+        let mut price_histories = Vec::new();
 
         for token_idx in tokens {
             let entry = self
@@ -874,13 +1068,25 @@ where
                 entry.get_mapping_account().unwrap_or(self.program_id),
                 false,
             ));
+            if program_mapping.is_history_enabled(usize::from(*token_idx)) {
+                let (price_history, _) = Pubkey::find_program_address(
+                    &[
+                        b"history",
+                        self.oracle_prices_acc.as_ref(),
+                        &u64::from(*token_idx).to_le_bytes(),
+                    ],
+                    &self.program_id,
+                );
+                price_histories.push(AccountMeta::new(price_history, false));
+            }
             for extra in entry.get_extra_accounts(Some(rpc)).await? {
                 refresh_accounts.push(AccountMeta::new_readonly(extra, false));
             }
             cu_budget += entry.get_update_cu_budget();
         }
-
-        let tokens = tokens.to_vec();
+        // The price histories and the configuration come last, after all the price accounts
+        refresh_accounts.append(&mut price_histories);
+        refresh_accounts.push(AccountMeta::new_readonly(self.configuration_acc, false));
 
         let tx = self
             .client
@@ -888,7 +1094,9 @@ where
             .add_anchor_ix_with_budget(
                 &self.program_id,
                 refresh_accounts,
-                instruction::RefreshPriceList { tokens },
+                instruction::RefreshPriceList {
+                    tokens: tokens.to_vec(),
+                },
                 cu_budget,
             )
             .build_with_budget_and_fee(&[])
@@ -899,6 +1107,9 @@ where
         match tx_res {
             Some(Ok(())) => {
                 info!(%signature, "Prices list refreshed successfully");
+                if self.fetch_refresh_statuses {
+                    self.log_refresh_statuses(&signature, tokens).await;
+                }
             }
             Some(Err(err)) => {
                 error!(%signature, ?err, "Failed to refresh price list");
@@ -911,6 +1122,43 @@ where
         Ok(signature)
     }
 
+    /// Log the tokens that could not be refreshed from the statuses returned by a refresh.
+    async fn log_refresh_statuses(&self, signature: &Signature, tokens: &[u16]) {
+        let return_data = match self.client.get_transaction_return_data(signature).await {
+            Ok(Some(return_data)) if return_data.program_id == self.program_id => return_data,
+            Ok(_) => {
+                debug!(%signature, "No refresh statuses returned");
+                return;
+            }
+            Err(err) => {
+                warn!(%signature, ?err, "Failed to get the refresh statuses");
+                return;
+            }
+        };
+        let statuses = match Vec::<RefreshStatus>::try_from_slice(&return_data.data) {
+            Ok(statuses) => statuses,
+            Err(err) => {
+                warn!(%signature, ?err, "Failed to decode the refresh statuses");
+                return;
+            }
+        };
+        for (token, status) in tokens.iter().zip(statuses) {
+            let label = self
+                .tokens
+                .get(token)
+                .map(|entry| entry.get_label())
+                .unwrap_or_default();
+            match status {
+                RefreshStatus::Updated => trace!(token, label, "Price updated"),
+                RefreshStatus::Unchanged => debug!(token, label, "Price unchanged"),
+                RefreshStatus::SkippedUnmapped => warn!(token, label, "Price not mapped"),
+                RefreshStatus::Failed { error_code } => {
+                    warn!(token, label, error_code, "Price refresh failed")
+                }
+            }
+        }
+    }
+
     #[tracing::instrument(skip(self))]
     async fn refresh_price_list_print_res(&self, tokens: Vec<u16>) {
         if let Err(err) = self.ix_refresh_price_list(&tokens).await {
@@ -61,6 +61,58 @@ pub struct LbPair {
     pub _reserved: [u8; 64],
 }
 
+/// Number of bins stored in one [`BinArray`]
+pub const MAX_BIN_PER_ARRAY: usize = 70;
+
+// size = 10128 (0x2790), align = 0x8
+#[account(zero_copy)]
+pub struct BinArray {
+    pub index: i64, // Size 8, align 8
+    /// Version of the bin array
+    pub version: u8,
+    pub _padding: [u8; 7],
+    pub lb_pair: Pubkey,
+    pub bins: [Bin; MAX_BIN_PER_ARRAY],
+}
+
+// size = 144 (0x90), align = 0x8
+#[zero_copy]
+pub struct Bin {
+    /// Amount of token X in the bin
+    pub amount_x: u64,
+    /// Amount of token Y in the bin
+    pub amount_y: u64,
+    /// Price, liquidity supply, rewards and fees (contains u128 fields, kept as u64 for alignment)
+    pub _other_fields_buff: [u64; 128 / 8],
+}
+
+impl BinArray {
+    /// Get the bin of the given id if it belongs to this bin array
+    pub fn get_bin(&self, bin_id: i32) -> Option<&Bin> {
+        let lower_bin_id = self.index.checked_mul(MAX_BIN_PER_ARRAY as i64)?;
+        let offset = i64::from(bin_id).checked_sub(lower_bin_id)?;
+        self.bins.get(usize::try_from(offset).ok()?)
+    }
+}
+
+/// Get the index of the bin array containing the given bin id
+pub fn bin_id_to_bin_array_index(bin_id: i32) -> i64 {
+    i64::from(bin_id).div_euclid(MAX_BIN_PER_ARRAY as i64)
+}
+
+/// Get the address of the bin array of the given index for the given pair
+pub fn derive_bin_array_pda(lb_pair: &Pubkey, bin_array_index: i64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"bin_array",
+            lb_pair.as_ref(),
+            &bin_array_index.to_le_bytes(),
+        ],
+        &ID,
+    )
+    .0
+}
+
 /// Calculate price based on the given bin id. Eg: 1.0001 ^ 5555. The returned value is in Q64.64
 pub fn get_x64_price_from_id(active_id: i32, bin_step: u16) -> Option<U128> {
     // bin_step is in bps, convert to a fraction scaled by 64 bits (Q64x64).
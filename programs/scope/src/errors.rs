@@ -84,6 +84,114 @@ pub enum ScopeError {
 
     #[msg("The stake pool fee is higher than the maximum allowed")]
     StakeFeeTooHigh,
+
+    #[msg("The generic data provided is not valid for this oracle type")]
+    InvalidGenericData,
+
+    #[msg("Not enough pool observations to cover the TWAP window")]
+    TwapNotEnoughPoolObservations,
+
+    #[msg("The pool liquidity is below the configured minimum")]
+    PoolLiquidityTooLow,
+
+    #[msg("The pool has no reserves")]
+    PoolReservesEmpty,
+
+    #[msg("The orderbook has no bid or no ask")]
+    OrderbookEmpty,
+
+    #[msg("The orderbook spread is above the configured maximum")]
+    OrderbookSpreadTooWide,
+
+    #[msg("The orderbook depth is below the configured minimum")]
+    OrderbookDepthTooLow,
+
+    #[msg("Too many price publishers provided")]
+    TooManyPricePublishers,
+
+    #[msg("Invalid index definition")]
+    InvalidIndexDefinition,
+
+    #[msg("Invalid account field reader configuration")]
+    InvalidAccountFieldReader,
+
+    #[msg("The feed accounts must be migrated to the current layout version")]
+    FeedNotMigrated,
+
+    #[msg("Unknown feed layout version")]
+    UnknownFeedVersion,
+
+    #[msg("Price is out of the range of the price representation")]
+    PriceOutOfRange,
+
+    #[msg("Deprecated oracle types cannot be used")]
+    DeprecatedOracleType,
+
+    #[msg("The oracle type is not available in this build of the program")]
+    OracleTypeNotAvailable,
+
+    #[msg("The token metadata value has an invalid length")]
+    InvalidTokenMetadataValue,
+
+    #[msg("The token name must be at most 32 bytes of valid UTF-8")]
+    InvalidTokenName,
+
+    #[msg("The account is not a valid Pyth price account")]
+    PythAccountNotValid,
+
+    #[msg("No recent enough price in the Pyth price account")]
+    PythPriceStale,
+
+    #[msg("The Pyth price is not positive or has a positive exponent")]
+    PythPriceNotValid,
+
+    #[msg("The Pyth price confidence interval is too wide")]
+    PythConfidenceTooWide,
+
+    #[msg("The account is not a valid Switchboard V2 aggregator")]
+    SwitchboardV2AccountNotValid,
+
+    #[msg("The Switchboard V2 aggregator has no valid result")]
+    SwitchboardV2NoResult,
+
+    #[msg("The Switchboard V2 price is negative")]
+    SwitchboardV2NegativePrice,
+
+    #[msg("The Switchboard V2 price standard deviation is too wide")]
+    SwitchboardV2ConfidenceTooWide,
+
+    #[msg("The Chainlink feed is flagged")]
+    ChainlinkFeedFlagged,
+
+    #[msg("The Chainlink feed has no round")]
+    ChainlinkNoRound,
+
+    #[msg("The Chainlink latest round is too old")]
+    ChainlinkPriceStale,
+
+    #[msg("The Chainlink answer is not a valid price")]
+    ChainlinkAnswerNotValid,
+
+    #[msg("The SPL stake pool has not been refreshed in the current epoch")]
+    SplStakeNotRefreshed,
+
+    #[msg("Too many programs allowed before refresh instructions")]
+    TooManyRefreshAllowedPrograms,
+
+    #[msg("An instruction before the refresh writes to one of the refreshed accounts")]
+    RefreshedAccountWrittenBeforeRefresh,
+
+    #[msg("The TWAP source entry is not mapped")]
+    TwapSourceNotMapped,
+
+    #[msg("The TWAP source entry does not have TWAP enabled")]
+    TwapSourceNotEnabled,
+
+    #[msg("The TWAP source entry is itself a TWAP")]
+    TwapSourceIsTwap,
+
+    #[msg("Only constant-product pools can be priced")]
+    UnsupportedPoolCurve,
 }
 
 impl<T> From<TryFromPrimitiveError<T>> for ScopeError
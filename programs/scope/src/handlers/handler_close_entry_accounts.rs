@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+use anchor_lang::{AccountsClose, Discriminator, ZeroCopy};
+
+use crate::{
+    oracles::check_entry_account_feed, AccountFieldReader, IndexDefinition, OracleMappings,
+    PriceHistory, PricePublishers, ScopeError,
+};
+
+// Close per entry accounts of a feed (price publishers, index definitions, account field readers,
+// price histories), given as remaining accounts, and return their lamports to the admin.
+// An entry still mapped to a closed account is reset (unmapped), as it could not be refreshed.
+// They are not closed by `close_feed`, which must come last as it removes the admin of the feed.
+#[derive(Accounts)]
+#[instruction(feed_name: String)]
+pub struct CloseEntryAccounts<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"conf", feed_name.as_bytes()], bump,
+                has_one = admin,
+                has_one = oracle_prices,
+                has_one = oracle_mappings,
+            )]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+
+    pub oracle_prices: AccountLoader<'info, crate::OraclePrices>,
+
+    #[account(mut)]
+    pub oracle_mappings: AccountLoader<'info, crate::OracleMappings>,
+    // Note: use remaining accounts as the per entry accounts to close
+}
+
+pub fn process<'info>(
+    ctx: Context<'_, '_, '_, 'info, CloseEntryAccounts<'info>>,
+    feed_name: String,
+) -> Result<()> {
+    msg!(
+        "closing {} entry accounts of feed {}",
+        ctx.remaining_accounts.len(),
+        feed_name
+    );
+
+    // Per entry accounts only exist on migrated feeds
+    if !ctx.accounts.configuration.load()?.is_migrated() {
+        return err!(ScopeError::FeedNotMigrated);
+    }
+
+    let oracle_prices_pk = ctx.accounts.oracle_prices.key();
+    let admin = ctx.accounts.admin.to_account_info();
+    let mut oracle_mappings = ctx.accounts.oracle_mappings.load_mut()?;
+
+    for account in ctx.remaining_accounts {
+        let discriminator: [u8; 8] = account
+            .try_borrow_data()?
+            .get(..8)
+            .and_then(|discriminator| discriminator.try_into().ok())
+            .ok_or(ScopeError::UnexpectedAccount)?;
+        let index = if discriminator == PricePublishers::DISCRIMINATOR {
+            close_entry_account::<PricePublishers>(account, &admin, &oracle_prices_pk, |p| {
+                (p.oracle_prices, p.index)
+            })?
+        } else if discriminator == IndexDefinition::DISCRIMINATOR {
+            close_entry_account::<IndexDefinition>(account, &admin, &oracle_prices_pk, |d| {
+                (d.oracle_prices, d.index)
+            })?
+        } else if discriminator == AccountFieldReader::DISCRIMINATOR {
+            close_entry_account::<AccountFieldReader>(account, &admin, &oracle_prices_pk, |r| {
+                (r.oracle_prices, r.index)
+            })?
+        } else if discriminator == PriceHistory::DISCRIMINATOR {
+            let index =
+                close_entry_account::<PriceHistory>(account, &admin, &oracle_prices_pk, |h| {
+                    (h.oracle_prices, h.index)
+                })?;
+            let history_enabled = oracle_mappings
+                .history_enabled
+                .get_mut(usize::from(index))
+                .ok_or(ScopeError::BadTokenNb)?;
+            *history_enabled = 0;
+            index
+        } else {
+            msg!("Account {} is not a per entry account", account.key());
+            return err!(ScopeError::UnexpectedAccount);
+        };
+        unmap_closed_account(&mut oracle_mappings, usize::from(index), account.key)?;
+    }
+
+    Ok(())
+}
+
+/// Reset the mapping of the entry if its price account is the closed account
+fn unmap_closed_account(
+    oracle_mappings: &mut OracleMappings,
+    index: usize,
+    closed_account: &Pubkey,
+) -> Result<()> {
+    let price_info_account = oracle_mappings
+        .price_info_accounts
+        .get_mut(index)
+        .ok_or(ScopeError::BadTokenNb)?;
+    if price_info_account == closed_account {
+        msg!("Unmapping entry {} as its account is closed", index);
+        *price_info_account = Pubkey::default();
+        oracle_mappings.price_types[index] = 0;
+    }
+    Ok(())
+}
+
+/// Check that a per entry account belongs to the feed and close it, returning its entry index
+fn close_entry_account<'info, T: ZeroCopy + Owner>(
+    account: &AccountInfo<'info>,
+    admin: &AccountInfo<'info>,
+    oracle_prices_pk: &Pubkey,
+    feed_and_index: impl FnOnce(&T) -> (Pubkey, u16),
+) -> Result<u16> {
+    let loader = AccountLoader::<T>::try_from(account)?;
+    let (oracle_prices, index) = feed_and_index(&*loader.load()?);
+    check_entry_account_feed(&oracle_prices, oracle_prices_pk)?;
+    loader.close(admin.clone())?;
+    Ok(index)
+}
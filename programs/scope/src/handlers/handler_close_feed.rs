@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+use crate::oracles::check_context;
+
+// Close all the accounts of a feed and return their lamports to the admin.
+// Per entry accounts (price publishers, index definitions, account field readers, price
+// histories) are not closed here, close them first with `close_entry_accounts`.
+#[derive(Accounts)]
+#[instruction(feed_name: String)]
+pub struct CloseFeed<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut, seeds = [b"conf", feed_name.as_bytes()], bump,
+                close = admin,
+                has_one = admin,
+                has_one = oracle_prices,
+                has_one = oracle_mappings,
+                has_one = oracle_twaps,
+                has_one = tokens_metadata,
+            )]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+
+    #[account(mut, close = admin)]
+    pub oracle_prices: AccountLoader<'info, crate::OraclePrices>,
+
+    #[account(mut, close = admin)]
+    pub oracle_mappings: AccountLoader<'info, crate::OracleMappings>,
+
+    #[account(mut, close = admin)]
+    pub oracle_twaps: AccountLoader<'info, crate::OracleTwaps>,
+
+    #[account(mut, close = admin)]
+    pub tokens_metadata: AccountLoader<'info, crate::TokenMetadatas>,
+}
+
+pub fn process(ctx: Context<CloseFeed>, feed_name: String) -> Result<()> {
+    check_context(&ctx)?;
+
+    msg!("closing feed {}", feed_name);
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::oracles::{account_field, account_field::AccountFieldParams, check_context};
+
+#[derive(Accounts)]
+#[instruction(token: u64, feed_name: String)]
+pub struct InitAccountFieldReader<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"conf", feed_name.as_bytes()], bump,
+                has_one = admin,
+                has_one = oracle_prices,
+            )]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+
+    pub oracle_prices: AccountLoader<'info, crate::OraclePrices>,
+
+    #[account(init,
+                seeds = [b"field_reader", oracle_prices.key().as_ref(), &token.to_le_bytes()],
+                bump,
+                payer = admin,
+                space = 8 + std::mem::size_of::<crate::AccountFieldReader>(),
+            )]
+    pub account_field_reader: AccountLoader<'info, crate::AccountFieldReader>,
+
+    /// CHECK: Owner and discriminator are checked against the configuration
+    pub account: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process(
+    ctx: Context<InitAccountFieldReader>,
+    token: usize,
+    feed_name: String,
+    params: AccountFieldParams,
+) -> Result<()> {
+    check_context(&ctx)?;
+
+    msg!(
+        "init account field reader for token {} feed_name {}: {:?}",
+        token,
+        feed_name,
+        params
+    );
+
+    if token >= crate::MAX_ENTRIES {
+        return err!(crate::ScopeError::BadTokenNb);
+    }
+
+    let mut reader = ctx.accounts.account_field_reader.load_init()?;
+    reader.oracle_prices = ctx.accounts.oracle_prices.key();
+    reader.index = token
+        .try_into()
+        .map_err(|_| crate::ScopeError::OutOfRangeIntegralConversion)?;
+    account_field::configure_reader(&mut reader, &ctx.accounts.account, &params)?;
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::oracles::check_context;
+
+#[derive(Accounts)]
+#[instruction(token: u64, feed_name: String)]
+pub struct InitIndexDefinition<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"conf", feed_name.as_bytes()], bump,
+                has_one = admin,
+                has_one = oracle_prices,
+            )]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+
+    pub oracle_prices: AccountLoader<'info, crate::OraclePrices>,
+
+    #[account(init,
+                seeds = [b"index", oracle_prices.key().as_ref(), &token.to_le_bytes()],
+                bump,
+                payer = admin,
+                space = 8 + std::mem::size_of::<crate::IndexDefinition>(),
+            )]
+    pub index_definition: AccountLoader<'info, crate::IndexDefinition>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process(
+    ctx: Context<InitIndexDefinition>,
+    token: usize,
+    feed_name: String,
+    entries: Vec<u16>,
+    weights: Vec<u64>,
+    weights_exp: u8,
+    divisor: u64,
+) -> Result<()> {
+    check_context(&ctx)?;
+
+    msg!(
+        "init index definition for token {} feed_name {}",
+        token,
+        feed_name
+    );
+
+    if token >= crate::MAX_ENTRIES {
+        return err!(crate::ScopeError::BadTokenNb);
+    }
+
+    let mut index_definition = ctx.accounts.index_definition.load_init()?;
+    index_definition.oracle_prices = ctx.accounts.oracle_prices.key();
+    index_definition.index = token
+        .try_into()
+        .map_err(|_| crate::ScopeError::OutOfRangeIntegralConversion)?;
+    index_definition.set_components(&entries, &weights, weights_exp, divisor)?;
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+use crate::{oracles::check_context, ScopeError};
+
+#[derive(Accounts)]
+#[instruction(token: u64, feed_name: String)]
+pub struct InitPriceHistory<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"conf", feed_name.as_bytes()], bump,
+                has_one = admin,
+                has_one = oracle_prices,
+                has_one = oracle_mappings,
+            )]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+
+    pub oracle_prices: AccountLoader<'info, crate::OraclePrices>,
+
+    #[account(mut)]
+    pub oracle_mappings: AccountLoader<'info, crate::OracleMappings>,
+
+    #[account(init,
+                seeds = [b"history", oracle_prices.key().as_ref(), &token.to_le_bytes()],
+                bump,
+                payer = admin,
+                space = 8 + std::mem::size_of::<crate::PriceHistory>(),
+            )]
+    pub price_history: AccountLoader<'info, crate::PriceHistory>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process(ctx: Context<InitPriceHistory>, token: usize, feed_name: String) -> Result<()> {
+    check_context(&ctx)?;
+
+    msg!(
+        "init price history for token {} feed_name {}",
+        token,
+        feed_name
+    );
+
+    // history_enabled has been carved out of reserved space in version 1
+    if !ctx.accounts.configuration.load()?.is_migrated() {
+        return err!(ScopeError::FeedNotMigrated);
+    }
+
+    let mut oracle_mappings = ctx.accounts.oracle_mappings.load_mut()?;
+    let history_enabled = oracle_mappings
+        .history_enabled
+        .get_mut(token)
+        .ok_or(ScopeError::BadTokenNb)?;
+    *history_enabled = 1;
+
+    let mut price_history = ctx.accounts.price_history.load_init()?;
+    price_history.oracle_prices = ctx.accounts.oracle_prices.key();
+    price_history.index = token
+        .try_into()
+        .map_err(|_| ScopeError::OutOfRangeIntegralConversion)?;
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+
+use crate::oracles::check_context;
+
+#[derive(Accounts)]
+#[instruction(token: u64, feed_name: String)]
+pub struct InitPricePublishers<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"conf", feed_name.as_bytes()], bump,
+                has_one = admin,
+                has_one = oracle_prices,
+            )]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+
+    pub oracle_prices: AccountLoader<'info, crate::OraclePrices>,
+
+    #[account(init,
+                seeds = [b"publishers", oracle_prices.key().as_ref(), &token.to_le_bytes()],
+                bump,
+                payer = admin,
+                space = 8 + std::mem::size_of::<crate::PricePublishers>(),
+            )]
+    pub price_publishers: AccountLoader<'info, crate::PricePublishers>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process(
+    ctx: Context<InitPricePublishers>,
+    token: usize,
+    feed_name: String,
+    publishers: Vec<Pubkey>,
+) -> Result<()> {
+    check_context(&ctx)?;
+
+    msg!(
+        "init price publishers for token {} feed_name {}",
+        token,
+        feed_name
+    );
+
+    if token >= crate::MAX_ENTRIES {
+        return err!(crate::ScopeError::BadTokenNb);
+    }
+
+    let mut price_publishers = ctx.accounts.price_publishers.load_init()?;
+    price_publishers.oracle_prices = ctx.accounts.oracle_prices.key();
+    price_publishers.index = token
+        .try_into()
+        .map_err(|_| crate::ScopeError::OutOfRangeIntegralConversion)?;
+    price_publishers.set_publishers(&publishers)?;
+
+    Ok(())
+}
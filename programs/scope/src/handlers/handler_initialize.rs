@@ -50,6 +50,7 @@ pub fn process(ctx: Context<Initialize>, _: String) -> Result<()> {
     configuration.oracle_prices = prices_pbk;
     configuration.oracle_twaps = twaps_pbk;
     configuration.admin_cached = Pubkey::default();
+    configuration.version = crate::FEED_LAYOUT_VERSION;
 
     // Initialize oracle twap account
     let mut oracle_twaps = ctx.accounts.oracle_twaps.load_init()?;
@@ -0,0 +1,94 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::{oracles::check_context, OracleMappings, ScopeError, FEED_LAYOUT_VERSION};
+
+#[derive(Accounts)]
+#[instruction(feed_name: String)]
+pub struct Migrate<'info> {
+    // Pays the rent of the reallocated accounts
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut, seeds = [b"conf", feed_name.as_bytes()], bump,
+                has_one = admin,
+                has_one = oracle_prices,
+                has_one = oracle_mappings,
+                has_one = oracle_twaps,
+                has_one = tokens_metadata,
+            )]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+
+    #[account(mut)]
+    pub oracle_prices: AccountLoader<'info, crate::OraclePrices>,
+
+    #[account(mut)]
+    pub oracle_mappings: AccountLoader<'info, OracleMappings>,
+
+    #[account(mut)]
+    pub oracle_twaps: AccountLoader<'info, crate::OracleTwaps>,
+
+    #[account(mut)]
+    pub tokens_metadata: AccountLoader<'info, crate::TokenMetadatas>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn process(ctx: Context<Migrate>, feed_name: String) -> Result<()> {
+    check_context(&ctx)?;
+
+    let mut configuration = ctx.accounts.configuration.load_mut()?;
+
+    msg!(
+        "migrate feed {} from version {} to {}",
+        feed_name,
+        configuration.version,
+        FEED_LAYOUT_VERSION
+    );
+
+    if configuration.version > FEED_LAYOUT_VERSION {
+        return err!(ScopeError::UnknownFeedVersion);
+    }
+
+    // Upgrade the layouts one version at a time
+    while configuration.version < FEED_LAYOUT_VERSION {
+        match configuration.version {
+            // `generic` has been appended to the mappings, the other fields added in version 1
+            // are valid when zeroed
+            0 => realloc_oracle_mappings(&ctx)?,
+            _ => return err!(ScopeError::UnknownFeedVersion),
+        }
+        configuration.version += 1;
+    }
+
+    Ok(())
+}
+
+fn realloc_oracle_mappings(ctx: &Context<Migrate>) -> Result<()> {
+    let oracle_mappings = ctx.accounts.oracle_mappings.to_account_info();
+    let new_len = 8 + std::mem::size_of::<OracleMappings>();
+    if oracle_mappings.data_len() >= new_len {
+        return Ok(());
+    }
+
+    let rent_exempt_lamports = Rent::get()?.minimum_balance(new_len);
+    let missing_lamports = rent_exempt_lamports.saturating_sub(oracle_mappings.lamports());
+    if missing_lamports > 0 {
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.admin.to_account_info(),
+                    to: oracle_mappings.clone(),
+                },
+            ),
+            missing_lamports,
+        )?;
+    }
+
+    // The appended generic data is zeroed, i.e. no generic configuration
+    oracle_mappings.realloc(new_len, true)?;
+    Ok(())
+}
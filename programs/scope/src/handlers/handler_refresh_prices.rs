@@ -1,8 +1,10 @@
 use std::convert::TryInto;
 
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, Discriminator};
 use solana_program::{
+    ed25519_program,
     instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT},
+    program::{set_return_data, MAX_RETURN_DATA},
     pubkey,
     sysvar::instructions::{
         load_current_index_checked, load_instruction_at_checked, ID as SYSVAR_INSTRUCTIONS_ID,
@@ -11,11 +13,28 @@ use solana_program::{
 
 use crate::{
     oracles::{get_price, OracleType},
+    utils::zero_copy_deserialize,
     ScopeError,
 };
 
 const COMPUTE_BUDGET_ID: Pubkey = pubkey!("ComputeBudget111111111111111111111111111111");
 
+/// Outcome of the refresh of one token of [`refresh_price_list`].
+///
+/// The outcome of every requested token is set, in the order of the request, as the return data
+/// of the instruction (borsh serialized `Vec<RefreshStatus>`).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RefreshStatus {
+    /// The price has been updated from a new price of the source
+    Updated,
+    /// The source did not provide a new price since the last refresh
+    Unchanged,
+    /// The token has no mapping set and has been skipped
+    SkippedUnmapped,
+    /// The price could not be computed, `error_code` is the program error code of the failure
+    Failed { error_code: u64 },
+}
+
 #[derive(Accounts)]
 pub struct RefreshList<'info> {
     #[account(mut, has_one = oracle_mappings)]
@@ -27,15 +46,36 @@ pub struct RefreshList<'info> {
     /// CHECK: Sysvar fixed address
     #[account(address = SYSVAR_INSTRUCTIONS_ID)]
     pub instruction_sysvar_account_info: AccountInfo<'info>,
-    // Note: use remaining accounts as price accounts
+    // Note: use remaining accounts as price accounts, optionally followed by the price histories
+    // of the refreshed entries (see `split_price_histories`) then by the feed configuration (see
+    // `split_configuration`)
 }
 
 pub fn refresh_price_list<'info>(
     ctx: Context<'_, '_, '_, 'info, RefreshList<'info>>,
     tokens: &[u16],
 ) -> Result<()> {
-    check_execution_ctx(&ctx.accounts.instruction_sysvar_account_info)?;
+    let (price_accounts, configuration) =
+        split_configuration(ctx.remaining_accounts, &ctx.accounts.oracle_prices.key())?;
+    let (price_accounts, price_histories) = split_price_histories(price_accounts)?;
+    let configuration = configuration
+        .as_ref()
+        .map(|configuration| configuration.load())
+        .transpose()?;
+    if let Some(configuration) = &configuration {
+        if !configuration.is_migrated() {
+            return err!(ScopeError::FeedNotMigrated);
+        }
+    }
+    check_execution_ctx(
+        &ctx.accounts.instruction_sysvar_account_info,
+        configuration.as_ref().map_or(&[][..], |configuration| {
+            configuration.get_refresh_allowed_programs()
+        }),
+        price_accounts,
+    )?;
 
+    crate::OracleMappings::check_account_size(&ctx.accounts.oracle_mappings.to_account_info())?;
     let oracle_mappings = &ctx.accounts.oracle_mappings.load()?;
     let mut oracle_twaps = ctx.accounts.oracle_twaps.load_mut()?;
 
@@ -49,7 +89,7 @@ pub fn refresh_price_list<'info>(
         return Err(ProgramError::InvalidArgument.into());
     }
     // Check the received token list is at least as long as the number of provided accounts
-    if tokens.len() > ctx.remaining_accounts.len() {
+    if tokens.len() > price_accounts.len() {
         return err!(ScopeError::AccountsAndTokenMismatch);
     }
 
@@ -58,7 +98,8 @@ pub fn refresh_price_list<'info>(
 
     let zero_pk: Pubkey = Pubkey::default();
 
-    let mut accounts_iter = ctx.remaining_accounts.iter();
+    let mut accounts_iter = price_accounts.iter();
+    let mut statuses = Vec::with_capacity(tokens.len());
 
     for &token_nb in tokens.iter() {
         let token_idx: usize = token_nb.into();
@@ -75,6 +116,7 @@ pub fn refresh_price_list<'info>(
         // Ignore unset mapping accounts
         if zero_pk == *oracle_mapping {
             msg!("Skipping token {} as no mapping is set", token_idx);
+            statuses.push(RefreshStatus::SkippedUnmapped);
             continue;
         }
         // Check that the provided oracle accounts are the one referenced in oracleMapping
@@ -87,15 +129,45 @@ pub fn refresh_price_list<'info>(
             return err!(ScopeError::UnexpectedAccount);
         }
         let clock = Clock::get()?;
-        let price_res = get_price(
-            price_type,
-            received_account,
-            &mut accounts_iter,
-            &clock,
-            &oracle_twaps,
-            oracle_mappings,
-            token_nb.into(),
-        );
+        let history_account = if oracle_mappings.is_history_enabled(token_idx) {
+            let history_account =
+                find_price_history(price_histories, &ctx.accounts.oracle_prices.key(), token_nb);
+            if history_account.is_none() {
+                msg!("No price history provided for token {}", token_idx);
+            }
+            history_account
+        } else {
+            None
+        };
+        let price_res = {
+            // Read only load to allow prices to be computed from other entries of this feed
+            let oracle_prices = ctx.accounts.oracle_prices.load()?;
+            get_price(
+                price_type,
+                received_account,
+                &mut accounts_iter,
+                &clock,
+                &oracle_twaps,
+                oracle_mappings,
+                &oracle_prices,
+                &ctx.accounts.oracle_prices.key(),
+                token_nb.into(),
+            )
+        }
+        .and_then(|mut price| {
+            price.index = token_nb;
+            price.set_refreshed_slot(clock.slot);
+            // The history is appended first so that a failure leaves the price unchanged
+            if let Some(history_account) = history_account {
+                crate::oracles::history::append_to_history(
+                    history_account,
+                    &ctx.accounts.oracle_prices.key(),
+                    token_idx,
+                    &price,
+                )?;
+            }
+            Ok(price)
+        });
         let price = if fail_tx_on_error {
             price_res?
         } else {
@@ -105,6 +177,9 @@ pub fn refresh_price_list<'info>(
                     msg!(
                         "Price skipped as validation failed (token {token_idx}, type {price_type:?}): {e}",
                     );
+                    statuses.push(RefreshStatus::Failed {
+                        error_code: ProgramError::from(e).into(),
+                    });
                     continue;
                 }
             }
@@ -135,18 +210,109 @@ pub fn refresh_price_list<'info>(
             clock.slot,
         );
 
+        let status = if to_update.last_updated_slot == price.last_updated_slot
+            && to_update.price == price.price
+        {
+            RefreshStatus::Unchanged
+        } else {
+            RefreshStatus::Updated
+        };
+
         *to_update = price;
-        to_update.index = token_nb;
+        statuses.push(status);
+    }
+
+    let statuses = statuses
+        .try_to_vec()
+        .map_err(|_| ScopeError::ConversionFailure)?;
+    if statuses.len() <= MAX_RETURN_DATA {
+        set_return_data(&statuses);
+    } else {
+        msg!("Too many tokens to return the refresh statuses");
     }
 
     Ok(())
 }
 
+/// Split the optional feed configuration from the price accounts.
+///
+/// The configuration is passed after all the price accounts, as the last remaining account, so
+/// that the account list of the clients that do not provide it is unchanged. It is recognized as
+/// an account of this program with the configuration discriminator, which no price account has.
+/// Without it only compute budget and ed25519 instructions are allowed before the refresh and the
+/// feed migration is only enforced through the size of the mappings.
+fn split_configuration<'a, 'info>(
+    remaining_accounts: &'a [AccountInfo<'info>],
+    oracle_prices: &Pubkey,
+) -> Result<(
+    &'a [AccountInfo<'info>],
+    Option<AccountLoader<'info, crate::Configuration>>,
+)> {
+    let (last, price_accounts) = match remaining_accounts.split_last() {
+        Some((last, price_accounts)) if is_configuration(last)? => (last, price_accounts),
+        _ => return Ok((remaining_accounts, None)),
+    };
+    let configuration = AccountLoader::<crate::Configuration>::try_from(last)?;
+    if configuration.load()?.oracle_prices != *oracle_prices {
+        msg!(
+            "Configuration {} is not the one of the refreshed feed",
+            last.key
+        );
+        return err!(ScopeError::UnexpectedAccount);
+    }
+    Ok((price_accounts, Some(configuration)))
+}
+
+/// Split the price histories passed after the price accounts.
+///
+/// Like the configuration, the price histories are recognized by their owner and discriminator so
+/// that the price accounts and the extra accounts of the refreshed entries are read unchanged.
+fn split_price_histories<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<(&'a [AccountInfo<'info>], &'a [AccountInfo<'info>])> {
+    let mut price_accounts_len = accounts.len();
+    while price_accounts_len > 0
+        && is_program_account::<crate::PriceHistory>(&accounts[price_accounts_len - 1])?
+    {
+        price_accounts_len -= 1;
+    }
+    Ok(accounts.split_at(price_accounts_len))
+}
+
+/// Find the price history of the entry `token` of the feed, if provided
+fn find_price_history<'a, 'info>(
+    price_histories: &'a [AccountInfo<'info>],
+    oracle_prices: &Pubkey,
+    token: u16,
+) -> Option<&'a AccountInfo<'info>> {
+    price_histories.iter().find(|account| {
+        zero_copy_deserialize::<crate::PriceHistory>(account)
+            .map(|history| history.oracle_prices == *oracle_prices && history.index == token)
+            .unwrap_or(false)
+    })
+}
+
+fn is_configuration(account: &AccountInfo) -> Result<bool> {
+    is_program_account::<crate::Configuration>(account)
+}
+
+fn is_program_account<T: Discriminator>(account: &AccountInfo) -> Result<bool> {
+    Ok(*account.owner == crate::ID && account.try_borrow_data()?.starts_with(&T::DISCRIMINATOR))
+}
+
 /// Ensure that the refresh instruction is executed directly to avoid any manipulation:
 ///
 /// - Check that the current instruction is executed by our program id (not in CPI).
-/// - Check that instructions preceding the refresh are compute budget instructions.
-fn check_execution_ctx(instruction_sysvar_account_info: &AccountInfo) -> Result<()> {
+/// - Check that instructions preceding the refresh are compute budget instructions, ed25519
+///   signature verifications (used by [`OracleType::SignedPrice`]) or instructions of the
+///   `allowed_programs` of the feed configuration (none when the configuration is not provided).
+/// - Check that the instructions of the allowed programs cannot modify any of the
+///   `refreshed_accounts` (e.g. by swapping in a priced pool).
+fn check_execution_ctx(
+    instruction_sysvar_account_info: &AccountInfo,
+    allowed_programs: &[Pubkey],
+    refreshed_accounts: &[AccountInfo],
+) -> Result<()> {
     let current_index: usize = load_current_index_checked(instruction_sysvar_account_info)?.into();
 
     // 1- Check that the current instruction is executed by our program id (not in CPI).
@@ -162,12 +328,29 @@ fn check_execution_ctx(instruction_sysvar_account_info: &AccountInfo) -> Result<
         return err!(ScopeError::RefreshInCPI);
     }
 
-    // 2- Check that instructions preceding the refresh are compute budget instructions.
+    // 2- Check that instructions preceding the refresh are compute budget instructions,
+    //    ed25519 signature verifications or instructions of allowed programs.
     for ixn in 0..current_index {
         let ix = load_instruction_at_checked(ixn, instruction_sysvar_account_info)?;
-        if ix.program_id != COMPUTE_BUDGET_ID {
+        if ix.program_id == COMPUTE_BUDGET_ID || ix.program_id == ed25519_program::ID {
+            continue;
+        }
+        if !allowed_programs.contains(&ix.program_id) {
             return err!(ScopeError::RefreshWithUnexpectedIxs);
         }
+        // 3- Any account written by the instruction (or by its CPIs) must be one of its
+        //    accounts, so it cannot modify the refreshed accounts if none of them is writable.
+        if let Some(written) = ix.accounts.iter().find(|meta| {
+            meta.is_writable && refreshed_accounts.iter().any(|acc| *acc.key == meta.pubkey)
+        }) {
+            msg!(
+                "Instruction {} of program {} writes to refreshed account {}",
+                ixn,
+                ix.program_id,
+                written.pubkey
+            );
+            return err!(ScopeError::RefreshedAccountWrittenBeforeRefresh);
+        }
     }
 
     Ok(())
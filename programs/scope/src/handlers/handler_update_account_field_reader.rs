@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::oracles::{account_field, account_field::AccountFieldParams, check_context};
+
+#[derive(Accounts)]
+#[instruction(feed_name: String)]
+pub struct UpdateAccountFieldReader<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"conf", feed_name.as_bytes()], bump,
+                has_one = admin,
+                has_one = oracle_prices,
+            )]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+
+    pub oracle_prices: AccountLoader<'info, crate::OraclePrices>,
+
+    #[account(mut, has_one = oracle_prices)]
+    pub account_field_reader: AccountLoader<'info, crate::AccountFieldReader>,
+
+    /// CHECK: Owner and discriminator are checked against the configuration
+    pub account: AccountInfo<'info>,
+}
+
+pub fn process(
+    ctx: Context<UpdateAccountFieldReader>,
+    feed_name: String,
+    params: AccountFieldParams,
+) -> Result<()> {
+    check_context(&ctx)?;
+
+    let mut reader = ctx.accounts.account_field_reader.load_mut()?;
+
+    msg!(
+        "update account field reader for token {} feed_name {}: {:?}",
+        reader.index,
+        feed_name,
+        params
+    );
+
+    account_field::configure_reader(&mut reader, &ctx.accounts.account, &params)?;
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    oracles::{check_context, fixed_rate::FixedRateConfig, OracleType},
+    OracleMappings, Price, ScopeError,
+};
+
+#[derive(Accounts)]
+#[instruction(token: u64, feed_name: String)]
+pub struct UpdateFixedRate<'info> {
+    pub admin: Signer<'info>,
+    #[account(seeds = [b"conf", feed_name.as_bytes()], bump, has_one = admin, has_one = oracle_mappings)]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+    #[account(mut)]
+    pub oracle_mappings: AccountLoader<'info, OracleMappings>,
+}
+
+pub fn process(
+    ctx: Context<UpdateFixedRate>,
+    token: usize,
+    _: String,
+    start_price_value: u64,
+    start_price_exp: u8,
+    start_timestamp: u64,
+    apy_bps: u16,
+) -> Result<()> {
+    check_context(&ctx)?;
+
+    msg!(
+        "UpdateFixedRate, token: {}, start price: {}e-{}, start timestamp: {}, apy_bps: {}",
+        token,
+        start_price_value,
+        start_price_exp,
+        start_timestamp,
+        apy_bps
+    );
+
+    if !ctx.accounts.configuration.load()?.is_migrated() {
+        return err!(ScopeError::FeedNotMigrated);
+    }
+    OracleMappings::check_account_size(&ctx.accounts.oracle_mappings.to_account_info())?;
+    let mut oracle_mappings = ctx.accounts.oracle_mappings.load_mut()?;
+    let price_type: OracleType = (*oracle_mappings
+        .price_types
+        .get(token)
+        .ok_or(ScopeError::BadTokenNb)?)
+    .try_into()
+    .map_err(|_| ScopeError::BadTokenType)?;
+    if price_type != OracleType::FixedRate {
+        msg!("Token {} is not a fixed rate entry", token);
+        return err!(ScopeError::BadTokenType);
+    }
+
+    let config = FixedRateConfig {
+        start_price: Price {
+            value: start_price_value,
+            exp: start_price_exp.into(),
+        },
+        start_timestamp,
+        apy_bps,
+    };
+    let generic_data = config.to_generic_data()?;
+    // Ensure the new parameters are valid
+    FixedRateConfig::from_generic_data(&generic_data)?;
+
+    oracle_mappings.generic[token] = generic_data;
+
+    Ok(())
+}
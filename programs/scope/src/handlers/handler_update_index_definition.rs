@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+use crate::oracles::check_context;
+
+#[derive(Accounts)]
+#[instruction(feed_name: String)]
+pub struct UpdateIndexDefinition<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"conf", feed_name.as_bytes()], bump,
+                has_one = admin,
+                has_one = oracle_prices,
+            )]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+
+    pub oracle_prices: AccountLoader<'info, crate::OraclePrices>,
+
+    #[account(mut, has_one = oracle_prices)]
+    pub index_definition: AccountLoader<'info, crate::IndexDefinition>,
+}
+
+pub fn process(
+    ctx: Context<UpdateIndexDefinition>,
+    feed_name: String,
+    entries: Vec<u16>,
+    weights: Vec<u64>,
+    weights_exp: u8,
+    divisor: u64,
+) -> Result<()> {
+    check_context(&ctx)?;
+
+    let mut index_definition = ctx.accounts.index_definition.load_mut()?;
+
+    msg!(
+        "update index definition for token {} feed_name {}",
+        index_definition.index,
+        feed_name
+    );
+
+    index_definition.set_components(&entries, &weights, weights_exp, divisor)?;
+
+    Ok(())
+}
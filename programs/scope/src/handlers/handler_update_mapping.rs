@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 
 use crate::{
-    oracles::{check_context, validate_oracle_account, OracleType},
+    oracles::{check_context, validate_computed_generic_data, validate_oracle_account, OracleType},
     OracleMappings, ScopeError,
 };
 
@@ -24,6 +24,7 @@ pub fn process(
     twap_enabled: bool,
     twap_source: u16,
     _: String,
+    generic_data: [u8; 20],
 ) -> Result<()> {
     check_context(&ctx)?;
 
@@ -35,6 +36,14 @@ pub fn process(
         twap_source
     );
 
+    let oracle_prices_pk = {
+        let configuration = ctx.accounts.configuration.load()?;
+        if !configuration.is_migrated() {
+            return err!(ScopeError::FeedNotMigrated);
+        }
+        configuration.oracle_prices
+    };
+    OracleMappings::check_account_size(&ctx.accounts.oracle_mappings.to_account_info())?;
     let mut oracle_mappings = ctx.accounts.oracle_mappings.load_mut()?;
     let ref_price_pubkey = oracle_mappings
         .price_info_accounts
@@ -46,24 +55,35 @@ pub fn process(
 
     match &ctx.accounts.price_info {
         Some(price_info_acc) => {
-            validate_oracle_account(price_type, price_info_acc)?;
+            validate_oracle_account(price_type, price_info_acc, &generic_data, &oracle_prices_pk)?;
             // Every check succeeded, replace current with new
             let new_price_pubkey = price_info_acc.key();
             *ref_price_pubkey = new_price_pubkey;
         }
+        None if price_type.is_computed_from_feed() => {
+            validate_computed_generic_data(price_type, &generic_data)?;
+            *ref_price_pubkey = crate::id();
+        }
         None => {
-            if price_type == OracleType::ScopeTwap {
-                *ref_price_pubkey = crate::id();
-            } else {
-                // if no price_info account is passed, it means that the mapping has to be removed so it is set to Pubkey::default
-                *ref_price_pubkey = Pubkey::default();
-            }
+            // if no price_info account is passed, it means that the mapping has to be removed so it is set to Pubkey::default
+            *ref_price_pubkey = Pubkey::default();
         }
     }
 
     oracle_mappings.price_types[token] = price_type.into();
     oracle_mappings.twap_enabled[token] = u8::from(twap_enabled);
     oracle_mappings.twap_source[token] = twap_source;
+    oracle_mappings.generic[token] = generic_data;
+
+    // Catch a wrong source now rather than when the TWAP is first read, both for this entry and
+    // for the TWAP entries reading it
+    let mappings: &OracleMappings = &oracle_mappings;
+    if let Some((twap_token, error)) = mappings.twap_source_errors().find(|&(twap_token, _)| {
+        twap_token == token || mappings.get_twap_source(twap_token) == token
+    }) {
+        msg!("Invalid source for the TWAP entry {twap_token}");
+        return Err(error.into());
+    }
 
     Ok(())
 }
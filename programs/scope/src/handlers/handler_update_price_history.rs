@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+use crate::{oracles::check_context, ScopeError};
+
+// Enable or disable the history of an entry, the history account is kept when disabled
+#[derive(Accounts)]
+#[instruction(token: u64, feed_name: String)]
+pub struct UpdatePriceHistory<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"conf", feed_name.as_bytes()], bump,
+                has_one = admin,
+                has_one = oracle_prices,
+                has_one = oracle_mappings,
+            )]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+
+    pub oracle_prices: AccountLoader<'info, crate::OraclePrices>,
+
+    #[account(mut)]
+    pub oracle_mappings: AccountLoader<'info, crate::OracleMappings>,
+
+    #[account(seeds = [b"history", oracle_prices.key().as_ref(), &token.to_le_bytes()], bump)]
+    pub price_history: AccountLoader<'info, crate::PriceHistory>,
+}
+
+pub fn process(
+    ctx: Context<UpdatePriceHistory>,
+    token: usize,
+    feed_name: String,
+    enabled: bool,
+) -> Result<()> {
+    check_context(&ctx)?;
+
+    msg!(
+        "update price history for token {} feed_name {} to enabled {}",
+        token,
+        feed_name,
+        enabled
+    );
+
+    if !ctx.accounts.configuration.load()?.is_migrated() {
+        return err!(ScopeError::FeedNotMigrated);
+    }
+
+    let mut oracle_mappings = ctx.accounts.oracle_mappings.load_mut()?;
+    let history_enabled = oracle_mappings
+        .history_enabled
+        .get_mut(token)
+        .ok_or(ScopeError::BadTokenNb)?;
+    *history_enabled = enabled.into();
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::oracles::check_context;
+
+#[derive(Accounts)]
+#[instruction(feed_name: String)]
+pub struct UpdatePricePublishers<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [b"conf", feed_name.as_bytes()], bump,
+                has_one = admin,
+                has_one = oracle_prices,
+            )]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+
+    pub oracle_prices: AccountLoader<'info, crate::OraclePrices>,
+
+    #[account(mut, has_one = oracle_prices)]
+    pub price_publishers: AccountLoader<'info, crate::PricePublishers>,
+}
+
+pub fn process(
+    ctx: Context<UpdatePricePublishers>,
+    feed_name: String,
+    publishers: Vec<Pubkey>,
+) -> Result<()> {
+    check_context(&ctx)?;
+
+    let mut price_publishers = ctx.accounts.price_publishers.load_mut()?;
+
+    msg!(
+        "update price publishers for token {} feed_name {}",
+        price_publishers.index,
+        feed_name
+    );
+
+    price_publishers.set_publishers(&publishers)?;
+
+    Ok(())
+}
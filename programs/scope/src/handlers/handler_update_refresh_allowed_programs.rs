@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::oracles::check_context;
+
+#[derive(Accounts)]
+#[instruction(feed_name: String)]
+pub struct UpdateRefreshAllowedPrograms<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut, seeds = [b"conf", feed_name.as_bytes()], bump, has_one = admin)]
+    pub configuration: AccountLoader<'info, crate::Configuration>,
+}
+
+pub fn process(
+    ctx: Context<UpdateRefreshAllowedPrograms>,
+    feed_name: String,
+    programs: Vec<Pubkey>,
+) -> Result<()> {
+    check_context(&ctx)?;
+
+    let mut configuration = ctx.accounts.configuration.load_mut()?;
+
+    msg!(
+        "update refresh allowed programs for feed_name {} to {:?}",
+        feed_name,
+        programs
+    );
+
+    configuration.set_refresh_allowed_programs(&programs)?;
+
+    Ok(())
+}
@@ -52,23 +52,27 @@ pub fn process(
         .map_err(|_| ScopeError::InvalidTokenUpdateMode)?;
     match mode {
         UpdateTokenMetadataMode::MaxPriceAgeSeconds => {
-            let value = u64::from_le_bytes(value[..8].try_into().unwrap());
+            let value = value
+                .get(..8)
+                .and_then(|bytes| bytes.try_into().ok())
+                .map(u64::from_le_bytes)
+                .ok_or(ScopeError::InvalidTokenMetadataValue)?;
             msg!("Setting token max age for index {:?} to {}", index, value);
             token_metadata.max_age_price_seconds = value;
         }
         UpdateTokenMetadataMode::Name => {
-            assert!(
-                value.len() <= 32,
-                "Name is longer should be less than 32 bytes"
-            );
+            if value.len() > token_metadata.name.len() {
+                msg!("Name must be at most 32 bytes");
+                return err!(ScopeError::InvalidTokenName);
+            }
+            let str_name = std::str::from_utf8(&value).map_err(|_| ScopeError::InvalidTokenName)?;
+            msg!("Setting token name for index {} to {}", index, str_name);
             token_metadata.name.fill(0);
             token_metadata
                 .name
                 .iter_mut()
                 .zip(value.iter())
                 .for_each(|(a, b)| *a = *b);
-            let str_name = std::str::from_utf8(&token_metadata.name).unwrap();
-            msg!("Setting token name for index {} to {}", index, str_name);
         }
     }
 
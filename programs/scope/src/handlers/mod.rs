@@ -1,15 +1,41 @@
 pub mod handler_approve_admin_cached;
+pub mod handler_close_entry_accounts;
+pub mod handler_close_feed;
+pub mod handler_init_account_field_reader;
+pub mod handler_init_index_definition;
+pub mod handler_init_price_history;
+pub mod handler_init_price_publishers;
 pub mod handler_initialize;
+pub mod handler_migrate;
 pub mod handler_refresh_prices;
 pub mod handler_reset_twap;
 pub mod handler_set_admin_cached;
+pub mod handler_update_account_field_reader;
+pub mod handler_update_fixed_rate;
+pub mod handler_update_index_definition;
 pub mod handler_update_mapping;
+pub mod handler_update_price_history;
+pub mod handler_update_price_publishers;
+pub mod handler_update_refresh_allowed_programs;
 pub mod handler_update_token_metadata;
 
 pub use handler_approve_admin_cached::*;
+pub use handler_close_entry_accounts::*;
+pub use handler_close_feed::*;
+pub use handler_init_account_field_reader::*;
+pub use handler_init_index_definition::*;
+pub use handler_init_price_history::*;
+pub use handler_init_price_publishers::*;
 pub use handler_initialize::*;
+pub use handler_migrate::*;
 pub use handler_refresh_prices::*;
 pub use handler_reset_twap::*;
 pub use handler_set_admin_cached::*;
+pub use handler_update_account_field_reader::*;
+pub use handler_update_fixed_rate::*;
+pub use handler_update_index_definition::*;
 pub use handler_update_mapping::*;
+pub use handler_update_price_history::*;
+pub use handler_update_price_publishers::*;
+pub use handler_update_refresh_allowed_programs::*;
 pub use handler_update_token_metadata::*;
@@ -15,6 +15,7 @@ use anchor_lang::prelude::*;
 use handlers::*;
 pub use num_enum;
 use program_id::PROGRAM_ID;
+pub use raydium_amm_v3;
 pub use whirlpool;
 #[cfg(feature = "yvaults")]
 pub use yvaults;
@@ -22,7 +23,9 @@ pub use yvaults;
 pub use crate::errors::*;
 pub use crate::states::*;
 pub use crate::utils::scope_chain;
+pub use handler_refresh_prices::RefreshStatus;
 pub use handler_update_token_metadata::UpdateTokenMetadataMode;
+pub use oracles::account_field::AccountFieldParams;
 
 declare_id!(PROGRAM_ID);
 
@@ -31,6 +34,8 @@ pub const MAX_ENTRIES_U16: u16 = 512;
 // Note: Need to be directly integer value to not confuse the IDL generator
 pub const MAX_ENTRIES: usize = 512;
 pub const VALUE_BYTE_ARRAY_LEN: usize = 32;
+// Note: Need to be directly integer value to not confuse the IDL generator
+pub const GENERIC_DATA_LEN: usize = 20;
 
 #[program]
 pub mod scope {
@@ -55,6 +60,7 @@ pub mod scope {
         twap_enabled: bool,
         twap_source: u16,
         feed_name: String,
+        generic_data: [u8; 20],
     ) -> Result<()> {
         let token: usize = token
             .try_into()
@@ -66,6 +72,7 @@ pub mod scope {
             twap_enabled,
             twap_source,
             feed_name,
+            generic_data,
         )
     }
 
@@ -106,4 +113,154 @@ pub mod scope {
     pub fn approve_admin_cached(ctx: Context<ApproveAdminCached>, feed_name: String) -> Result<()> {
         handler_approve_admin_cached::process(ctx, feed_name)
     }
+
+    pub fn close_feed(ctx: Context<CloseFeed>, feed_name: String) -> Result<()> {
+        handler_close_feed::process(ctx, feed_name)
+    }
+
+    pub fn close_entry_accounts<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseEntryAccounts<'info>>,
+        feed_name: String,
+    ) -> Result<()> {
+        handler_close_entry_accounts::process(ctx, feed_name)
+    }
+
+    pub fn migrate(ctx: Context<Migrate>, feed_name: String) -> Result<()> {
+        handler_migrate::process(ctx, feed_name)
+    }
+
+    pub fn init_price_publishers(
+        ctx: Context<InitPricePublishers>,
+        token: u64,
+        feed_name: String,
+        publishers: Vec<Pubkey>,
+    ) -> Result<()> {
+        let token: usize = token
+            .try_into()
+            .map_err(|_| ScopeError::OutOfRangeIntegralConversion)?;
+        handler_init_price_publishers::process(ctx, token, feed_name, publishers)
+    }
+
+    pub fn update_price_publishers(
+        ctx: Context<UpdatePricePublishers>,
+        feed_name: String,
+        publishers: Vec<Pubkey>,
+    ) -> Result<()> {
+        handler_update_price_publishers::process(ctx, feed_name, publishers)
+    }
+
+    pub fn update_refresh_allowed_programs(
+        ctx: Context<UpdateRefreshAllowedPrograms>,
+        feed_name: String,
+        programs: Vec<Pubkey>,
+    ) -> Result<()> {
+        handler_update_refresh_allowed_programs::process(ctx, feed_name, programs)
+    }
+
+    pub fn update_fixed_rate(
+        ctx: Context<UpdateFixedRate>,
+        token: u64,
+        feed_name: String,
+        start_price_value: u64,
+        start_price_exp: u8,
+        start_timestamp: u64,
+        apy_bps: u16,
+    ) -> Result<()> {
+        let token: usize = token
+            .try_into()
+            .map_err(|_| ScopeError::OutOfRangeIntegralConversion)?;
+        handler_update_fixed_rate::process(
+            ctx,
+            token,
+            feed_name,
+            start_price_value,
+            start_price_exp,
+            start_timestamp,
+            apy_bps,
+        )
+    }
+
+    pub fn init_index_definition(
+        ctx: Context<InitIndexDefinition>,
+        token: u64,
+        feed_name: String,
+        entries: Vec<u16>,
+        weights: Vec<u64>,
+        weights_exp: u8,
+        divisor: u64,
+    ) -> Result<()> {
+        let token: usize = token
+            .try_into()
+            .map_err(|_| ScopeError::OutOfRangeIntegralConversion)?;
+        handler_init_index_definition::process(
+            ctx,
+            token,
+            feed_name,
+            entries,
+            weights,
+            weights_exp,
+            divisor,
+        )
+    }
+
+    pub fn update_index_definition(
+        ctx: Context<UpdateIndexDefinition>,
+        feed_name: String,
+        entries: Vec<u16>,
+        weights: Vec<u64>,
+        weights_exp: u8,
+        divisor: u64,
+    ) -> Result<()> {
+        handler_update_index_definition::process(
+            ctx,
+            feed_name,
+            entries,
+            weights,
+            weights_exp,
+            divisor,
+        )
+    }
+
+    pub fn init_account_field_reader(
+        ctx: Context<InitAccountFieldReader>,
+        token: u64,
+        feed_name: String,
+        params: AccountFieldParams,
+    ) -> Result<()> {
+        let token: usize = token
+            .try_into()
+            .map_err(|_| ScopeError::OutOfRangeIntegralConversion)?;
+        handler_init_account_field_reader::process(ctx, token, feed_name, params)
+    }
+
+    pub fn update_account_field_reader(
+        ctx: Context<UpdateAccountFieldReader>,
+        feed_name: String,
+        params: AccountFieldParams,
+    ) -> Result<()> {
+        handler_update_account_field_reader::process(ctx, feed_name, params)
+    }
+
+    pub fn init_price_history(
+        ctx: Context<InitPriceHistory>,
+        token: u64,
+        feed_name: String,
+    ) -> Result<()> {
+        let token: usize = token
+            .try_into()
+            .map_err(|_| ScopeError::OutOfRangeIntegralConversion)?;
+        handler_init_price_history::process(ctx, token, feed_name)
+    }
+
+    pub fn update_price_history(
+        ctx: Context<UpdatePriceHistory>,
+        token: u64,
+        feed_name: String,
+        enabled: bool,
+    ) -> Result<()> {
+        let token: usize = token
+            .try_into()
+            .map_err(|_| ScopeError::OutOfRangeIntegralConversion)?;
+        handler_update_price_history::process(ctx, token, feed_name, enabled)
+    }
 }
@@ -0,0 +1,243 @@
+//! Price read from an integer field of an account owned by a configured program
+//!
+//! Useful for protocols publishing an exchange rate (or a total value and a supply) in their
+//! state account, without writing a dedicated adapter.
+//!
+//! The mapping account is an [`AccountFieldReader`] account describing the account to read and the
+//! layout of the fields, the account read is the only extra account.
+//! The owner and discriminator of the account read are checked when configuring the reader and on
+//! every refresh.
+//!
+//! `price = value * 10^-exp / divisor`
+
+use anchor_lang::prelude::*;
+use decimal_wad::{common::TryDiv, decimal::Decimal};
+
+use super::check_entry_account_feed;
+use crate::utils::{math::ten_pow, zero_copy_deserialize};
+use crate::{AccountFieldReader, DatedPrice, Price, Result, ScopeError, ScopeResult};
+
+const DISCRIMINATOR_LEN: usize = 8;
+const VALID_FIELD_SIZES: [u8; 5] = [1, 2, 4, 8, 16];
+const MAX_EXP: u8 = 18;
+
+/// Configuration of an [`AccountFieldReader`] given by the admin
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccountFieldParams {
+    pub owner: Pubkey,
+    pub discriminator: [u8; 8],
+    pub value_offset: u32,
+    pub value_size: u8,
+    pub exp: u8,
+    pub divisor_offset: u32,
+    /// 0 for no divisor
+    pub divisor_size: u8,
+}
+
+/// Set the configuration of a reader after checking it against the account to read
+pub fn configure_reader(
+    reader: &mut AccountFieldReader,
+    account: &AccountInfo,
+    params: &AccountFieldParams,
+) -> Result<()> {
+    reader.account = account.key();
+    reader.owner = params.owner;
+    reader.discriminator = params.discriminator;
+    reader.value_offset = params.value_offset;
+    reader.value_size = params.value_size;
+    reader.exp = params.exp;
+    reader.divisor_offset = params.divisor_offset;
+    reader.divisor_size = params.divisor_size;
+
+    validate_layout(reader)?;
+    check_account(reader, account)?;
+    Ok(())
+}
+
+pub fn get_price<'a, 'b>(
+    reader_account: &AccountInfo,
+    clock: &Clock,
+    extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+    oracle_prices_pk: &Pubkey,
+    index: usize,
+) -> Result<DatedPrice>
+where
+    'a: 'b,
+{
+    let reader = load_reader(reader_account)?;
+    check_entry_account_feed(&reader.oracle_prices, oracle_prices_pk)?;
+    if usize::from(reader.index) != index {
+        msg!(
+            "Account field reader is configured for entry {} instead of {index}",
+            reader.index
+        );
+        return err!(ScopeError::UnexpectedAccount);
+    }
+    let account = extra_accounts
+        .next()
+        .ok_or(ScopeError::AccountsAndTokenMismatch)?;
+    check_account(&reader, account)?;
+
+    let data = account.data.borrow();
+    let price = read_price(&reader, &data)?;
+
+    Ok(DatedPrice {
+        price,
+        last_updated_slot: clock.slot,
+        unix_timestamp: u64::try_from(clock.unix_timestamp)
+            .map_err(|_| ScopeError::BadTimestamp)?,
+        ..Default::default()
+    })
+}
+
+pub fn validate_reader_account(
+    reader_account: &AccountInfo,
+    oracle_prices_pk: &Pubkey,
+) -> Result<()> {
+    let reader = load_reader(reader_account)?;
+    check_entry_account_feed(&reader.oracle_prices, oracle_prices_pk)?;
+    validate_layout(&reader)?;
+    Ok(())
+}
+
+fn load_reader<'a>(
+    reader_account: &'a AccountInfo,
+) -> Result<std::cell::Ref<'a, AccountFieldReader>> {
+    require_keys_eq!(
+        *reader_account.owner,
+        crate::ID,
+        ScopeError::UnexpectedAccount
+    );
+    Ok(zero_copy_deserialize::<AccountFieldReader>(reader_account)?)
+}
+
+fn validate_layout(reader: &AccountFieldReader) -> ScopeResult<()> {
+    if !VALID_FIELD_SIZES.contains(&reader.value_size)
+        || (reader.divisor_size != 0 && !VALID_FIELD_SIZES.contains(&reader.divisor_size))
+    {
+        msg!("Invalid field sizes");
+        return Err(ScopeError::InvalidAccountFieldReader);
+    }
+    if reader.exp > MAX_EXP {
+        msg!("Exponent is above {MAX_EXP}");
+        return Err(ScopeError::InvalidAccountFieldReader);
+    }
+    Ok(())
+}
+
+/// Check the account read is the configured one, with the expected owner and discriminator
+fn check_account(reader: &AccountFieldReader, account: &AccountInfo) -> Result<()> {
+    require_keys_eq!(
+        account.key(),
+        reader.account,
+        ScopeError::AccountsAndTokenMismatch
+    );
+    require_keys_eq!(*account.owner, reader.owner, ScopeError::UnexpectedAccount);
+    let data = account.data.borrow();
+    if data.get(..DISCRIMINATOR_LEN) != Some(&reader.discriminator[..]) {
+        msg!("Unexpected discriminator for account {}", account.key());
+        return err!(ScopeError::UnexpectedAccount);
+    }
+    Ok(())
+}
+
+fn read_price(reader: &AccountFieldReader, data: &[u8]) -> ScopeResult<Price> {
+    let value = read_le_field(data, reader.value_offset, reader.value_size)?;
+    if reader.divisor_size == 0 {
+        return Ok(Price {
+            value: u64::try_from(value)?,
+            exp: reader.exp.into(),
+        });
+    }
+
+    let divisor = read_le_field(data, reader.divisor_offset, reader.divisor_size)?;
+    if divisor == 0 {
+        msg!("Divisor field is zero");
+        return Err(ScopeError::PriceNotValid);
+    }
+    let price = Decimal::from(value)
+        .try_div(Decimal::from(ten_pow(reader.exp)?))?
+        .try_div(Decimal::from(divisor))?;
+    price.try_into()
+}
+
+/// Read an unsigned little endian integer of `size` bytes at `offset`
+fn read_le_field(data: &[u8], offset: u32, size: u8) -> ScopeResult<u128> {
+    let offset = usize::try_from(offset)?;
+    let field = data
+        .get(offset..offset + usize::from(size))
+        .ok_or(ScopeError::InvalidAccountFieldReader)?;
+    let mut bytes = [0_u8; 16];
+    bytes[..field.len()].copy_from_slice(field);
+    Ok(u128::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reader(divisor_size: u8) -> AccountFieldReader {
+        let mut reader: AccountFieldReader = bytemuck::Zeroable::zeroed();
+        reader.value_offset = 8;
+        reader.value_size = 8;
+        reader.divisor_offset = 16;
+        reader.divisor_size = divisor_size;
+        reader.exp = 9;
+        reader
+    }
+
+    fn data(value: u64, divisor: u32) -> Vec<u8> {
+        let mut data = vec![0; 20];
+        data[8..16].copy_from_slice(&value.to_le_bytes());
+        data[16..20].copy_from_slice(&divisor.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_read_value() {
+        let price = read_price(&reader(0), &data(1_050_000_000, 0)).unwrap();
+        assert_eq!(
+            price,
+            Price {
+                value: 1_050_000_000,
+                exp: 9
+            }
+        );
+    }
+
+    #[test]
+    fn test_read_value_with_divisor() {
+        // Total value of 2_100 (9 decimals) for a supply of 2_000
+        let price = read_price(&reader(4), &data(2_100_000_000_000, 2_000)).unwrap();
+        assert_eq!(price, Price { value: 105, exp: 2 });
+        assert_eq!(
+            read_price(&reader(4), &data(1, 0)).unwrap_err(),
+            ScopeError::PriceNotValid
+        );
+    }
+
+    #[test]
+    fn test_field_out_of_bounds() {
+        let mut reader = reader(0);
+        reader.value_offset = 16;
+        assert_eq!(
+            read_price(&reader, &data(1, 0)).unwrap_err(),
+            ScopeError::InvalidAccountFieldReader
+        );
+    }
+
+    #[test]
+    fn test_invalid_layout() {
+        let mut reader = reader(3);
+        assert_eq!(
+            validate_layout(&reader).unwrap_err(),
+            ScopeError::InvalidAccountFieldReader
+        );
+        reader.divisor_size = 0;
+        reader.value_size = 0;
+        assert_eq!(
+            validate_layout(&reader).unwrap_err(),
+            ScopeError::InvalidAccountFieldReader
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Price of another entry of the feed clamped to a configured range
+//!
+//! The range is configured in the entry generic data, see [`CappedFlooredConfig`].
+//! The clamped price keeps the slot and timestamp of the source entry.
+//!
+//! The source price is read from the feed prices account, the entry is mapped to the scope
+//! program id.
+
+use anchor_lang::prelude::*;
+use arrayref::array_ref;
+
+use crate::utils::price_impl::MAX_PRICE_EXP;
+use crate::{
+    DatedPrice, OraclePrices, Price, Result, ScopeError, ScopeResult, GENERIC_DATA_LEN, MAX_ENTRIES,
+};
+
+/// Clamp configuration stored in the entry generic data
+///
+/// - `[0..2]`: index of the source entry (u16, little endian)
+/// - `[2..10]`: floor value (u64, little endian), 0 for no floor
+/// - `[10..18]`: cap value (u64, little endian), 0 for no cap
+/// - `[18]`: exponent of the floor and cap values
+/// - `[19]`: flags, bit 0 set for the "stable cap" mode capping the price at 1.0
+///   (the cap value must then be 0)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CappedFlooredConfig {
+    pub source_index: usize,
+    pub floor: Option<Price>,
+    pub cap: Option<Price>,
+}
+
+impl CappedFlooredConfig {
+    const STABLE_CAP_FLAG: u8 = 1;
+
+    pub fn from_generic_data(generic_data: &[u8; GENERIC_DATA_LEN]) -> ScopeResult<Self> {
+        let source_index = usize::from(u16::from_le_bytes(*array_ref![generic_data, 0, 2]));
+        if source_index >= MAX_ENTRIES {
+            msg!("Invalid source entry index {source_index}");
+            return Err(ScopeError::InvalidGenericData);
+        }
+        let exp = u64::from(generic_data[18]);
+        if exp > MAX_PRICE_EXP {
+            msg!("Invalid floor and cap exponent {exp}");
+            return Err(ScopeError::InvalidGenericData);
+        }
+        let to_price = |value: u64| (value != 0).then_some(Price { value, exp });
+        let floor = to_price(u64::from_le_bytes(*array_ref![generic_data, 2, 8]));
+        let mut cap = to_price(u64::from_le_bytes(*array_ref![generic_data, 10, 8]));
+        if generic_data[19] & Self::STABLE_CAP_FLAG != 0 {
+            if cap.is_some() {
+                msg!("A cap value cannot be configured in stable cap mode");
+                return Err(ScopeError::InvalidGenericData);
+            }
+            cap = Some(Price { value: 1, exp: 0 });
+        }
+        if let (Some(floor), Some(cap)) = (floor, cap) {
+            if floor > cap {
+                msg!("The floor must not be above the cap");
+                return Err(ScopeError::InvalidGenericData);
+            }
+        }
+        Ok(Self {
+            source_index,
+            floor,
+            cap,
+        })
+    }
+
+    /// Clamp a price to the configured range
+    pub fn clamp(&self, price: Price) -> Price {
+        match (self.floor, self.cap) {
+            (Some(floor), _) if price < floor => floor,
+            (_, Some(cap)) if price > cap => cap,
+            _ => price,
+        }
+    }
+}
+
+pub fn get_price(
+    oracle_prices: &OraclePrices,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> Result<DatedPrice> {
+    let config = CappedFlooredConfig::from_generic_data(generic_data)?;
+    let source = oracle_prices.prices[config.source_index];
+    if source.price.value == 0 {
+        msg!("Source entry {} has no price", config.source_index);
+        return err!(ScopeError::PriceNotValid);
+    }
+
+    Ok(DatedPrice {
+        price: config.clamp(source.price),
+        last_updated_slot: source.last_updated_slot,
+        unix_timestamp: source.unix_timestamp,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generic_data(floor: u64, cap: u64, exp: u8, flags: u8) -> [u8; GENERIC_DATA_LEN] {
+        let mut generic_data = [0; GENERIC_DATA_LEN];
+        generic_data[0..2].copy_from_slice(&4_u16.to_le_bytes());
+        generic_data[2..10].copy_from_slice(&floor.to_le_bytes());
+        generic_data[10..18].copy_from_slice(&cap.to_le_bytes());
+        generic_data[18] = exp;
+        generic_data[19] = flags;
+        generic_data
+    }
+
+    fn price(value: u64, exp: u64) -> Price {
+        Price { value, exp }
+    }
+
+    #[test]
+    fn test_clamp() {
+        let config = CappedFlooredConfig::from_generic_data(&generic_data(95, 105, 2, 0)).unwrap();
+        assert_eq!(config.source_index, 4);
+        assert_eq!(config.clamp(price(9_000, 4)), price(95, 2));
+        assert_eq!(config.clamp(price(10_010, 4)), price(10_010, 4));
+        assert_eq!(config.clamp(price(2, 0)), price(105, 2));
+    }
+
+    #[test]
+    fn test_clamp_small_prices() {
+        // Below the 18 decimals of a `Decimal`
+        let config = CappedFlooredConfig::from_generic_data(&generic_data(5, 10, 25, 0)).unwrap();
+        assert_eq!(config.clamp(price(3, 25)), price(5, 25));
+        assert_eq!(config.clamp(price(7, 25)), price(7, 25));
+        assert_eq!(config.clamp(price(110, 26)), price(10, 25));
+    }
+
+    #[test]
+    fn test_stable_cap() {
+        let config = CappedFlooredConfig::from_generic_data(&generic_data(0, 0, 0, 1)).unwrap();
+        assert_eq!(config.clamp(price(1_001, 3)), price(1, 0));
+        assert_eq!(config.clamp(price(999, 3)), price(999, 3));
+        assert_eq!(
+            CappedFlooredConfig::from_generic_data(&generic_data(0, 2, 0, 1)).unwrap_err(),
+            ScopeError::InvalidGenericData
+        );
+    }
+
+    #[test]
+    fn test_invalid_range() {
+        assert_eq!(
+            CappedFlooredConfig::from_generic_data(&generic_data(106, 105, 2, 0)).unwrap_err(),
+            ScopeError::InvalidGenericData
+        );
+    }
+
+    #[test]
+    fn test_invalid_exp() {
+        let exp = u8::try_from(MAX_PRICE_EXP).unwrap();
+        assert!(CappedFlooredConfig::from_generic_data(&generic_data(95, 105, exp, 0)).is_ok());
+        assert_eq!(
+            CappedFlooredConfig::from_generic_data(&generic_data(95, 105, exp + 1, 0)).unwrap_err(),
+            ScopeError::InvalidGenericData
+        );
+    }
+
+    #[test]
+    fn test_source_price_and_dates_are_kept() {
+        let mut oracle_prices: OraclePrices = bytemuck::Zeroable::zeroed();
+        oracle_prices.prices[4] = DatedPrice {
+            price: price(98, 2),
+            last_updated_slot: 42,
+            unix_timestamp: 1_700_000_000,
+            ..Default::default()
+        };
+        let dated_price = get_price(&oracle_prices, &generic_data(0, 0, 0, 1)).unwrap();
+        assert_eq!(dated_price.price, price(98, 2));
+        assert_eq!(dated_price.last_updated_slot, 42);
+        assert_eq!(dated_price.unix_timestamp, 1_700_000_000);
+    }
+}
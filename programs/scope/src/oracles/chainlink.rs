@@ -0,0 +1,265 @@
+//! Toolings to retrieve Chainlink OCR2 prices from the Solana store program
+//!
+//! The mapped account is the feed `Transmissions` account of the store program. It holds a header
+//! followed by a ring buffer of the latest rounds ("live" transmissions) written by the OCR2 program.
+//!
+//! 1. The account owner, discriminator and header are checked upon registration in the oracle
+//!    mapping in [`validate_transmissions_account`].
+//! 2. Upon usage the latest round is rejected if it is older than [`STALENESS_SLOT_THRESHOLD`]
+//!    or if the feed has been flagged.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::clock::DEFAULT_MS_PER_SLOT;
+
+use self::ocr2::Transmissions;
+use crate::{DatedPrice, Price, Result, ScopeError};
+
+/// Only update with prices not older than 10 minutes, users can still check actual price age
+const STALENESS_SLOT_THRESHOLD: u64 = (10 * 60 * 1000) / DEFAULT_MS_PER_SLOT; // 10 minutes
+
+pub fn get_price(transmissions_info: &AccountInfo, clock: &Clock) -> Result<DatedPrice> {
+    let data = transmissions_info.try_borrow_data()?;
+    get_price_from_data(&data, clock).map_err(|e| {
+        msg!(
+            "No valid price in chainlink account {}",
+            transmissions_info.key
+        );
+        e
+    })
+}
+
+fn get_price_from_data(data: &[u8], clock: &Clock) -> Result<DatedPrice> {
+    let transmissions = Transmissions::unpack(data)?;
+
+    if transmissions.state != ocr2::STATE_NORMAL {
+        msg!("Chainlink feed is flagged");
+        return err!(ScopeError::ChainlinkFeedFlagged);
+    }
+
+    let round = transmissions.latest_round(data)?;
+
+    let oldest_accepted_slot = clock.slot.saturating_sub(STALENESS_SLOT_THRESHOLD);
+    if round.slot < oldest_accepted_slot {
+        msg!(
+            "Chainlink latest round is too old (slot {}, oldest accepted {})",
+            round.slot,
+            oldest_accepted_slot
+        );
+        return err!(ScopeError::ChainlinkPriceStale);
+    }
+
+    let value = u64::try_from(round.answer).map_err(|_| {
+        msg!("Chainlink answer {} is not a valid price", round.answer);
+        error!(ScopeError::ChainlinkAnswerNotValid)
+    })?;
+    if value == 0 {
+        return err!(ScopeError::ChainlinkAnswerNotValid);
+    }
+
+    Ok(DatedPrice {
+        price: Price {
+            value,
+            exp: transmissions.decimals.into(),
+        },
+        last_updated_slot: round.slot,
+        unix_timestamp: round.timestamp.into(),
+        ..Default::default()
+    })
+}
+
+pub fn validate_transmissions_account(transmissions_info: &AccountInfo) -> Result<()> {
+    if cfg!(feature = "skip_price_validation") {
+        return Ok(());
+    }
+    require_keys_eq!(
+        *transmissions_info.owner,
+        ocr2::ID,
+        ScopeError::UnexpectedAccount
+    );
+    let data = transmissions_info.try_borrow_data()?;
+    let transmissions = Transmissions::unpack(&data)?;
+    // Ensure the feed has been written at least once
+    let _ = transmissions.latest_round(&data)?;
+    Ok(())
+}
+
+/// Partial layout of the Chainlink OCR2 store program accounts
+pub mod ocr2 {
+    use arrayref::array_ref;
+
+    use super::*;
+
+    declare_id!("HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny");
+
+    pub const TRANSMISSIONS_DISCRIMINATOR: [u8; 8] = [96, 179, 69, 66, 128, 129, 73, 117];
+
+    /// Feed state when the answers can be used
+    pub const STATE_NORMAL: u8 = 0;
+
+    /// Size of the header, including the discriminator
+    pub const HEADER_LEN: usize = 8 + 192;
+    /// Size of one transmission in the ring buffer
+    pub const TRANSMISSION_LEN: usize = 48;
+
+    const VERSION_OFFSET: usize = 8;
+    const STATE_OFFSET: usize = 9;
+    const DESCRIPTION_OFFSET: usize = 106;
+    const DECIMALS_OFFSET: usize = 138;
+    const LATEST_ROUND_ID_OFFSET: usize = 143;
+    const LIVE_LENGTH_OFFSET: usize = 148;
+    const LIVE_CURSOR_OFFSET: usize = 152;
+
+    /// Header of a feed transmissions account (partial)
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Transmissions {
+        pub version: u8,
+        pub state: u8,
+        /// Raw UTF-8 description of the feed (e.g. "SOL / USD")
+        pub description: [u8; 32],
+        pub decimals: u8,
+        pub latest_round_id: u32,
+        /// Number of transmissions in the live ring buffer
+        pub live_length: u32,
+        /// Position of the next transmission to write in the live ring buffer
+        pub live_cursor: u32,
+    }
+
+    /// A round answer
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Transmission {
+        pub slot: u64,
+        pub timestamp: u32,
+        pub answer: i128,
+    }
+
+    impl Transmissions {
+        pub fn unpack(data: &[u8]) -> Result<Self> {
+            if data.len() < HEADER_LEN {
+                msg!("Chainlink transmissions account is too small");
+                return err!(ScopeError::UnableToDeserializeAccount);
+            }
+            if data[..8] != TRANSMISSIONS_DISCRIMINATOR {
+                msg!("Chainlink transmissions account has an invalid discriminator");
+                return err!(ScopeError::InvalidAccountDiscriminator);
+            }
+            let read_u32 = |offset: usize| u32::from_le_bytes(*array_ref![data, offset, 4]);
+            Ok(Self {
+                version: data[VERSION_OFFSET],
+                state: data[STATE_OFFSET],
+                description: *array_ref![data, DESCRIPTION_OFFSET, 32],
+                decimals: data[DECIMALS_OFFSET],
+                latest_round_id: read_u32(LATEST_ROUND_ID_OFFSET),
+                live_length: read_u32(LIVE_LENGTH_OFFSET),
+                live_cursor: read_u32(LIVE_CURSOR_OFFSET),
+            })
+        }
+
+        /// Get the latest round from the live ring buffer of the account `data`
+        pub fn latest_round(&self, data: &[u8]) -> Result<Transmission> {
+            if self.latest_round_id == 0 || self.live_length == 0 {
+                msg!("Chainlink feed has no round");
+                return err!(ScopeError::ChainlinkNoRound);
+            }
+            // The cursor points to the next transmission to write
+            let index = (self.live_cursor + self.live_length - 1) % self.live_length;
+            let offset = HEADER_LEN
+                + usize::try_from(index).map_err(|_| ScopeError::OutOfRangeIntegralConversion)?
+                    * TRANSMISSION_LEN;
+            let transmission = data
+                .get(offset..offset + TRANSMISSION_LEN)
+                .ok_or(ScopeError::UnableToDeserializeAccount)?;
+            Ok(Transmission {
+                slot: u64::from_le_bytes(*array_ref![transmission, 0, 8]),
+                timestamp: u32::from_le_bytes(*array_ref![transmission, 8, 4]),
+                answer: i128::from_le_bytes(*array_ref![transmission, 16, 16]),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ocr2::*, *};
+
+    const LIVE_LENGTH: u32 = 3;
+
+    fn feed_bytes(live_cursor: u32, rounds: &[(u64, u32, i128)]) -> Vec<u8> {
+        let mut buff = vec![0_u8; HEADER_LEN + LIVE_LENGTH as usize * TRANSMISSION_LEN];
+        buff[..8].copy_from_slice(&TRANSMISSIONS_DISCRIMINATOR);
+        buff[8] = 2; // version
+        buff[138] = 8; // decimals
+        buff[143..147].copy_from_slice(&(rounds.len() as u32).to_le_bytes());
+        buff[148..152].copy_from_slice(&LIVE_LENGTH.to_le_bytes());
+        buff[152..156].copy_from_slice(&live_cursor.to_le_bytes());
+        for (i, (slot, timestamp, answer)) in rounds.iter().enumerate() {
+            let offset = HEADER_LEN + i * TRANSMISSION_LEN;
+            buff[offset..offset + 8].copy_from_slice(&slot.to_le_bytes());
+            buff[offset + 8..offset + 12].copy_from_slice(&timestamp.to_le_bytes());
+            buff[offset + 16..offset + 32].copy_from_slice(&answer.to_le_bytes());
+        }
+        buff
+    }
+
+    fn clock(slot: u64) -> Clock {
+        Clock {
+            slot,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    pub fn test_latest_round() {
+        let buff = feed_bytes(2, &[(10, 100, 1), (20, 200, 2_000_000_000)]);
+        let price = get_price_from_data(&buff, &clock(30)).unwrap();
+        assert_eq!(price.price.value, 2_000_000_000);
+        assert_eq!(price.price.exp, 8);
+        assert_eq!(price.last_updated_slot, 20);
+        assert_eq!(price.unix_timestamp, 200);
+    }
+
+    #[test]
+    pub fn test_latest_round_wrapped_cursor() {
+        let buff = feed_bytes(0, &[(10, 100, 1), (20, 200, 2), (30, 300, 3)]);
+        let price = get_price_from_data(&buff, &clock(30)).unwrap();
+        assert_eq!(price.price.value, 3);
+        assert_eq!(price.unix_timestamp, 300);
+    }
+
+    #[test]
+    pub fn test_stale_round() {
+        let buff = feed_bytes(1, &[(10, 100, 1)]);
+        assert!(get_price_from_data(&buff, &clock(10 + STALENESS_SLOT_THRESHOLD)).is_ok());
+        assert_eq!(
+            get_price_from_data(&buff, &clock(11 + STALENESS_SLOT_THRESHOLD)).unwrap_err(),
+            error!(ScopeError::ChainlinkPriceStale)
+        );
+    }
+
+    #[test]
+    pub fn test_negative_answer() {
+        let buff = feed_bytes(1, &[(10, 100, -1)]);
+        assert_eq!(
+            get_price_from_data(&buff, &clock(10)).unwrap_err(),
+            error!(ScopeError::ChainlinkAnswerNotValid)
+        );
+    }
+
+    #[test]
+    pub fn test_flagged_feed() {
+        let mut buff = feed_bytes(1, &[(10, 100, 1)]);
+        buff[9] = 1;
+        assert_eq!(
+            get_price_from_data(&buff, &clock(10)).unwrap_err(),
+            error!(ScopeError::ChainlinkFeedFlagged)
+        );
+    }
+
+    #[test]
+    pub fn test_no_round() {
+        let buff = feed_bytes(0, &[]);
+        assert_eq!(
+            get_price_from_data(&buff, &clock(10)).unwrap_err(),
+            error!(ScopeError::ChainlinkNoRound)
+        );
+    }
+}
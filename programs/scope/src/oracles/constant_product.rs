@@ -0,0 +1,130 @@
+//! Shared logic of the oracles pricing a token pair from the reserves of a constant-product pool
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::state::{Account as TokenAccount, Mint};
+use solana_program::program_pack::Pack;
+
+use super::{meteora_dynamic_amm, orca_token_swap, raydium_amm_v4, raydium_cpmm};
+use crate::utils::{check_min_liquidity, math::reserves_to_price};
+use crate::{DatedPrice, Result, ScopeError, GENERIC_DATA_LEN};
+
+/// Reserves (in lamports) of a constant-product pool
+pub struct PoolReserves {
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    pub decimals_a: u8,
+    pub decimals_b: u8,
+    /// Mint of the pool LP token
+    pub lp_mint: Pubkey,
+}
+
+/// Gives the reserves of a constant-product pool, the pool type is selected from the account owner
+///
+/// The extra accounts expected are the ones of the pool type oracle, see:
+/// - [`super::raydium_amm_v4::get_reserves`]
+/// - [`super::raydium_cpmm::get_reserves`]
+/// - [`super::orca_token_swap::get_reserves`]
+/// - [`super::meteora_dynamic_amm::get_reserves`]
+pub fn get_pool_reserves<'a, 'b>(
+    pool: &AccountInfo,
+    clock: &Clock,
+    extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+) -> Result<PoolReserves>
+where
+    'a: 'b,
+{
+    let owner = pool.owner;
+    if *owner == raydium_amm_v4::raydium_amm::ID {
+        raydium_amm_v4::get_reserves(pool, extra_accounts)
+    } else if *owner == raydium_cpmm::raydium_cp_swap::ID {
+        raydium_cpmm::get_reserves(pool, extra_accounts)
+    } else if *owner == orca_token_swap::orca_swap::ID {
+        orca_token_swap::get_reserves(pool, extra_accounts)
+    } else if *owner == meteora_dynamic_amm::meteora_amm::ID {
+        meteora_dynamic_amm::get_reserves(pool, clock, extra_accounts)
+    } else {
+        msg!("Pool {} is not owned by a supported program", pool.key);
+        err!(ScopeError::UnexpectedAccount)
+    }
+}
+
+/// Validate that the given account is a pool of one of the supported constant-product programs
+pub fn validate_pool_account(pool: &AccountInfo) -> Result<()> {
+    let owner = pool.owner;
+    if *owner == raydium_amm_v4::raydium_amm::ID {
+        raydium_amm_v4::validate_pool_account(pool)
+    } else if *owner == raydium_cpmm::raydium_cp_swap::ID {
+        raydium_cpmm::validate_pool_account(pool)
+    } else if *owner == orca_token_swap::orca_swap::ID {
+        orca_token_swap::validate_pool_account(pool)
+    } else if *owner == meteora_dynamic_amm::meteora_amm::ID {
+        meteora_dynamic_amm::validate_pool_account(pool)
+    } else {
+        msg!("Pool {} is not owned by a supported program", pool.key);
+        err!(ScopeError::UnexpectedAccount)
+    }
+}
+
+/// Gives the price of the given token pair from the pool reserves
+///
+/// The reserve of the quote token is checked against the minimum liquidity configured in the entry
+/// generic data, see [`crate::utils::get_min_liquidity`].
+pub fn get_price_from_reserves(
+    a_to_b: bool,
+    pool: &Pubkey,
+    reserves: &PoolReserves,
+    clock: &Clock,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> Result<DatedPrice> {
+    let PoolReserves {
+        reserve_a,
+        reserve_b,
+        decimals_a,
+        decimals_b,
+        ..
+    } = *reserves;
+    let (base_reserve, quote_reserve, base_decimals, quote_decimals) = if a_to_b {
+        (reserve_a, reserve_b, decimals_a, decimals_b)
+    } else {
+        (reserve_b, reserve_a, decimals_b, decimals_a)
+    };
+
+    // Check pool liquidity
+    check_min_liquidity(pool, quote_reserve.into(), generic_data)?;
+
+    // Compute price
+    let price = reserves_to_price(base_reserve, quote_reserve, base_decimals, quote_decimals)?;
+
+    // Return price
+    Ok(DatedPrice {
+        price,
+        last_updated_slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp as u64,
+        ..Default::default()
+    })
+}
+
+/// Get the amount of tokens held by a token account (vault) after checking its key
+pub fn get_vault_amount(vault: &AccountInfo, expected_vault: &Pubkey) -> Result<u64> {
+    require_keys_eq!(
+        vault.key(),
+        *expected_vault,
+        ScopeError::AccountsAndTokenMismatch
+    );
+    let data = vault.data.borrow();
+    // Token-2022 accounts share the same base layout, extensions come after it
+    let base_data = data
+        .get(..TokenAccount::LEN)
+        .ok_or(ScopeError::UnableToDeserializeAccount)?;
+    Ok(TokenAccount::unpack_from_slice(base_data)?.amount)
+}
+
+/// Get the decimals of a mint after checking its key
+pub fn get_mint_decimals(mint: &AccountInfo, expected_mint: &Pubkey) -> Result<u8> {
+    require_keys_eq!(
+        mint.key(),
+        *expected_mint,
+        ScopeError::AccountsAndTokenMismatch
+    );
+    let mint_borrow = mint.data.borrow();
+    Ok(Mint::unpack(&mint_borrow)?.decimals)
+}
@@ -0,0 +1,212 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::state::Mint;
+use arrayref::array_ref;
+use decimal_wad::common::{TryDiv, TryMul};
+use decimal_wad::decimal::Decimal;
+use raydium_amm_v3::libraries::U256;
+use solana_program::program_pack::Pack;
+
+use super::constant_product::{self, get_pool_reserves, PoolReserves};
+use crate::utils::math::ten_pow;
+use crate::{
+    DatedPrice, OraclePrices, Price, Result, ScopeError, ScopeResult, GENERIC_DATA_LEN, MAX_ENTRIES,
+};
+
+/// Gives the price of 1 LP token of a constant-product pool
+///
+/// The price is computed with the "fair reserves" formula `2 * sqrt(k * price_a * price_b) / lp_supply`
+/// where `k = reserve_a * reserve_b` and `price_a`/`price_b` are taken from the Scope entries configured
+/// in the generic data (see [`get_price_indexes`]). The pool reserves ratio is never used so
+/// the price cannot be manipulated by swapping in the pool.
+///
+/// The extra accounts are the ones needed to get the pool reserves (see [`get_pool_reserves`])
+/// followed by the LP token mint.
+///
+/// The LP token price timestamp is the one of the least-recently updated price of token A and token B.
+pub fn get_price<'a, 'b>(
+    pool: &AccountInfo,
+    clock: &Clock,
+    extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+    oracle_prices: &OraclePrices,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> Result<DatedPrice>
+where
+    'a: 'b,
+{
+    let (price_a_index, price_b_index) = get_price_indexes(generic_data)?;
+
+    let reserves = get_pool_reserves(pool, clock, extra_accounts)?;
+
+    // Get the LP mint
+    let lp_mint_account_info = extra_accounts
+        .next()
+        .ok_or(ScopeError::AccountsAndTokenMismatch)?;
+    require_keys_eq!(
+        reserves.lp_mint,
+        lp_mint_account_info.key(),
+        ScopeError::AccountsAndTokenMismatch
+    );
+    let lp_mint = {
+        let mint_borrow = lp_mint_account_info.data.borrow();
+        Mint::unpack(&mint_borrow)?
+    };
+
+    // Get the token prices from the Scope entries
+    let dated_price_a = oracle_prices
+        .prices
+        .get(price_a_index)
+        .ok_or(ScopeError::BadTokenNb)?;
+    let dated_price_b = oracle_prices
+        .prices
+        .get(price_b_index)
+        .ok_or(ScopeError::BadTokenNb)?;
+    if dated_price_a.price.value == 0 || dated_price_b.price.value == 0 {
+        msg!("Token A or token B entry has no price");
+        return err!(ScopeError::PriceNotValid);
+    }
+
+    let price = fair_lp_token_price(
+        &reserves,
+        dated_price_a.price,
+        dated_price_b.price,
+        lp_mint.supply,
+        lp_mint.decimals,
+    )?;
+
+    Ok(DatedPrice {
+        price,
+        last_updated_slot: dated_price_a
+            .last_updated_slot
+            .min(dated_price_b.last_updated_slot),
+        unix_timestamp: dated_price_a
+            .unix_timestamp
+            .min(dated_price_b.unix_timestamp),
+        ..Default::default()
+    })
+}
+
+/// Get the indexes of the Scope entries giving the prices of token A and token B
+/// stored in the generic data (2 x u16, little endian)
+pub fn get_price_indexes(generic_data: &[u8; GENERIC_DATA_LEN]) -> ScopeResult<(usize, usize)> {
+    let price_a_index = usize::from(u16::from_le_bytes(*array_ref![generic_data, 0, 2]));
+    let price_b_index = usize::from(u16::from_le_bytes(*array_ref![generic_data, 2, 2]));
+    if price_a_index >= MAX_ENTRIES || price_b_index >= MAX_ENTRIES {
+        msg!("Invalid token price indexes {price_a_index} and {price_b_index}");
+        return Err(ScopeError::InvalidGenericData);
+    }
+    Ok((price_a_index, price_b_index))
+}
+
+pub fn validate_pool_account(
+    pool: &AccountInfo,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> Result<()> {
+    let (price_a_index, price_b_index) = get_price_indexes(generic_data)?;
+    if price_a_index == price_b_index {
+        msg!("Token A and token B prices must come from different entries");
+        return err!(ScopeError::InvalidGenericData);
+    }
+    constant_product::validate_pool_account(pool)
+}
+
+fn fair_lp_token_price(
+    reserves: &PoolReserves,
+    price_a: Price,
+    price_b: Price,
+    lp_supply: u64,
+    lp_decimals: u8,
+) -> ScopeResult<Price> {
+    if lp_supply == 0 {
+        return Err(ScopeError::PoolReservesEmpty);
+    }
+
+    // Value of each side of the pool, in the quote currency of the prices
+    let value_a = Decimal::from(reserves.reserve_a)
+        .try_mul(Decimal::from(price_a))?
+        .try_div(Decimal::from(ten_pow(reserves.decimals_a)?))?;
+    let value_b = Decimal::from(reserves.reserve_b)
+        .try_mul(Decimal::from(price_b))?
+        .try_div(Decimal::from(ten_pow(reserves.decimals_b)?))?;
+
+    // sqrt(k * price_a * price_b) = sqrt(value_a * value_b)
+    let value_a_scaled: u128 = value_a.to_scaled_val()?;
+    let value_b_scaled: u128 = value_b.to_scaled_val()?;
+    let sqrt_product_scaled =
+        (U256::from(value_a_scaled) * U256::from(value_b_scaled)).integer_sqrt();
+    let fair_pool_value = Decimal::from_scaled_val(sqrt_product_scaled.as_u128()).try_mul(2)?;
+
+    let lp_supply = Decimal::from(lp_supply).try_div(Decimal::from(ten_pow(lp_decimals)?))?;
+
+    fair_pool_value.try_div(lp_supply)?.try_into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reserves(reserve_a: u64, decimals_a: u8, reserve_b: u64, decimals_b: u8) -> PoolReserves {
+        PoolReserves {
+            reserve_a,
+            reserve_b,
+            decimals_a,
+            decimals_b,
+            lp_mint: Pubkey::default(),
+        }
+    }
+
+    #[test]
+    fn test_fair_lp_price_balanced_pool() {
+        // 1000 SOL at 20 USD and 20_000 USDC at 1 USD, 100 LP tokens
+        let reserves = reserves(1_000_000_000_000, 9, 20_000_000_000, 6);
+        let price_a = Price { value: 20, exp: 0 };
+        let price_b = Price { value: 1, exp: 0 };
+        let price = fair_lp_token_price(&reserves, price_a, price_b, 100_000_000, 6).unwrap();
+        let price_f64: f64 = price.into();
+        assert!((price_f64 - 400.0).abs() < 0.000001, "price: {price_f64}");
+    }
+
+    #[test]
+    fn test_fair_lp_price_manipulated_pool() {
+        // Same k as the balanced pool, but the pool ratio was pushed to 80 USD/SOL
+        let reserves = reserves(500_000_000_000, 9, 40_000_000_000, 6);
+        let price_a = Price { value: 20, exp: 0 };
+        let price_b = Price { value: 1, exp: 0 };
+        let price = fair_lp_token_price(&reserves, price_a, price_b, 100_000_000, 6).unwrap();
+        let price_f64: f64 = price.into();
+        assert!((price_f64 - 400.0).abs() < 0.000001, "price: {price_f64}");
+    }
+
+    #[test]
+    fn test_fair_lp_price_no_supply() {
+        let reserves = reserves(1_000_000, 6, 1_000_000, 6);
+        let price = Price { value: 1, exp: 0 };
+        assert_eq!(
+            fair_lp_token_price(&reserves, price, price, 0, 6).unwrap_err(),
+            ScopeError::PoolReservesEmpty
+        );
+    }
+
+    #[test]
+    fn test_fair_lp_price_unsupported_decimals() {
+        let reserves = reserves(1_000_000, 31, 1_000_000, 6);
+        let price = Price { value: 1, exp: 0 };
+        assert_eq!(
+            fair_lp_token_price(&reserves, price, price, 1_000_000, 6).unwrap_err(),
+            ScopeError::MathOverflow
+        );
+    }
+
+    #[test]
+    fn test_price_indexes() {
+        let mut generic_data = [0; GENERIC_DATA_LEN];
+        generic_data[0..2].copy_from_slice(&3_u16.to_le_bytes());
+        generic_data[2..4].copy_from_slice(&7_u16.to_le_bytes());
+        assert_eq!(get_price_indexes(&generic_data).unwrap(), (3, 7));
+
+        generic_data[2..4].copy_from_slice(&(MAX_ENTRIES as u16).to_le_bytes());
+        assert_eq!(
+            get_price_indexes(&generic_data).unwrap_err(),
+            ScopeError::InvalidGenericData
+        );
+    }
+}
@@ -3,8 +3,10 @@ use anchor_lang::{
     solana_program::{clock, program_pack::Pack},
 };
 
+use decimal_wad::decimal::Decimal;
+
 use self::solend::Reserve;
-use crate::{DatedPrice, Price, Result, ScopeResult};
+use crate::{DatedPrice, Price, Result, ScopeError, ScopeResult};
 
 const DECIMALS: u32 = 15u32;
 
@@ -12,23 +14,7 @@ const DECIMALS: u32 = 15u32;
 pub fn get_price(solend_reserve_account: &AccountInfo, clock: &Clock) -> Result<DatedPrice> {
     let mut reserve = Reserve::unpack(&solend_reserve_account.data.borrow())?;
 
-    // Manual refresh of the reserve to ensure the most accurate price
-    let (last_updated_slot, unix_timestamp) = if reserve.accrue_interest(clock.slot).is_ok() {
-        // We have just refreshed the price so we can use the current slot
-        (clock.slot, u64::try_from(clock.unix_timestamp).unwrap())
-    } else {
-        // This should never happen but on simulations when the current slot is not valid
-        // yet we have a default value
-        (
-            reserve.last_update.slot,
-            u64::try_from(clock.unix_timestamp).unwrap().saturating_sub(
-                clock
-                    .slot
-                    .saturating_sub(reserve.last_update.slot)
-                    .saturating_mul(clock::DEFAULT_MS_PER_SLOT),
-            ),
-        )
-    };
+    let (last_updated_slot, unix_timestamp) = refresh_reserve(&mut reserve, clock)?;
 
     let value = scaled_rate(&reserve)?;
 
@@ -46,6 +32,74 @@ pub fn get_price(solend_reserve_account: &AccountInfo, clock: &Clock) -> Result<
     Ok(dated_price)
 }
 
+/// Interest rate of a reserve that can be published as a price
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReserveRate {
+    /// Annual rate paid by the borrowers
+    Borrow,
+    /// Annual rate earned by the depositors
+    Supply,
+}
+
+// Gives the current APR of a Solend reserve as a fraction (0.05 for 5%)
+pub fn get_rate(
+    solend_reserve_account: &AccountInfo,
+    clock: &Clock,
+    reserve_rate: ReserveRate,
+) -> Result<DatedPrice> {
+    let mut reserve = Reserve::unpack(&solend_reserve_account.data.borrow())?;
+
+    // The utilization rate depends on the interests accrued since the last refresh
+    let (last_updated_slot, unix_timestamp) = refresh_reserve(&mut reserve, clock)?;
+
+    let rate = match reserve_rate {
+        ReserveRate::Borrow => reserve.current_borrow_rate()?,
+        ReserveRate::Supply => reserve.current_supply_rate()?,
+    };
+
+    Ok(DatedPrice {
+        price: Decimal::from(rate).try_into()?,
+        last_updated_slot,
+        unix_timestamp,
+        ..Default::default()
+    })
+}
+
+/// Manual refresh of the reserve to ensure the most accurate price
+///
+/// Returns the slot and timestamp at which the reserve state is valid
+fn refresh_reserve(reserve: &mut Reserve, clock: &Clock) -> ScopeResult<(u64, u64)> {
+    let now = u64::try_from(clock.unix_timestamp).map_err(|_| ScopeError::BadTimestamp)?;
+    let dates = if reserve.accrue_interest(clock.slot).is_ok() {
+        // We have just refreshed the price so we can use the current slot
+        (clock.slot, now)
+    } else {
+        // This should never happen but on simulations when the current slot is not valid
+        // yet we have a default value
+        (
+            reserve.last_update.slot,
+            now.saturating_sub(
+                clock
+                    .slot
+                    .saturating_sub(reserve.last_update.slot)
+                    .saturating_mul(clock::DEFAULT_MS_PER_SLOT),
+            ),
+        )
+    };
+    Ok(dates)
+}
+
+/// Check that the account is an initialized reserve of the Solend program
+pub fn validate_reserve_account(reserve_account: &AccountInfo) -> Result<()> {
+    require_keys_eq!(
+        *reserve_account.owner,
+        solend::ID,
+        ScopeError::UnexpectedAccount
+    );
+    let _ = Reserve::unpack(&reserve_account.data.borrow())?;
+    Ok(())
+}
+
 fn scaled_rate(reserve: &Reserve) -> ScopeResult<u64> {
     const FACTOR: u64 = 10u64.pow(DECIMALS);
     let rate = reserve.collateral_exchange_rate()?;
@@ -120,6 +174,46 @@ mod test {
         // Expect ctoken price to be 2 tokens
         assert_eq!(scaled_rate(&reserve).unwrap(), 2 * 10u64.pow(DECIMALS));
     }
+
+    #[test]
+    pub fn borrow_and_supply_rates() {
+        let reserve = Reserve {
+            version: 1,
+            lending_market: Pubkey::default(),
+            liquidity: ReserveLiquidity {
+                available_amount: 50,
+                borrowed_amount_wads: Decimal::from(50u64),
+                ..Default::default()
+            },
+            config: ReserveConfig {
+                optimal_utilization_rate: 80,
+                min_borrow_rate: 0,
+                optimal_borrow_rate: 10,
+                max_borrow_rate: 50,
+                protocol_take_rate: 10,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        // 50% utilization: 0.5 / 0.8 * 10%
+        let borrow_rate: f64 =
+            Price::try_from(Decimal::from(reserve.current_borrow_rate().unwrap()))
+                .unwrap()
+                .into();
+        assert!(
+            (borrow_rate - 0.0625).abs() < 1e-12,
+            "borrow rate: {borrow_rate}"
+        );
+        // Half of the supply is borrowed and 10% of the interests go to the protocol
+        let supply_rate: f64 =
+            Price::try_from(Decimal::from(reserve.current_supply_rate().unwrap()))
+                .unwrap()
+                .into();
+        assert!(
+            (supply_rate - 0.028125).abs() < 1e-12,
+            "supply rate: {supply_rate}"
+        );
+    }
 }
 
 pub mod solend {
@@ -141,6 +235,8 @@ pub mod solend {
     use super::*;
     use crate::{ScopeError, ScopeResult};
 
+    declare_id!("So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo");
+
     /// Current version of the program and all new accounts created
     pub const PROGRAM_VERSION: u8 = 1;
 
@@ -228,6 +324,18 @@ pub mod solend {
                 Ok(normalized_rate.try_mul(rate_range)?.try_add(min_rate)?)
             }
         }
+
+        /// Calculate the current supply rate: the borrow interests, net of the protocol
+        /// take rate, shared between all the depositors
+        pub fn current_supply_rate(&self) -> ScopeResult<Rate> {
+            let utilization_rate = self.liquidity.utilization_rate()?;
+            let depositors_share =
+                Rate::one().try_sub(Rate::from_percent(self.config.protocol_take_rate))?;
+            Ok(self
+                .current_borrow_rate()?
+                .try_mul(utilization_rate)?
+                .try_mul(depositors_share)?)
+        }
     }
 
     const RESERVE_LEN: usize = 619; // 1 + 8 + 1 + 32 + 32 + 1 + 32 + 32 + 32 + 8 + 16 + 16 + 16 + 32 + 8 + 32 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 8 + 8 + 1 + 8 + 8 + 32 + 1 + 1 + 16 + 230
@@ -0,0 +1,150 @@
+//! Price of an asset accruing at a fixed rate (e.g. tokenized T-bills between two NAV updates)
+//!
+//! The price is computed from a start price, a start timestamp and an APY stored in the entry
+//! generic data (see [`FixedRateConfig`]), compounding every second:
+//!
+//! `price = start_price * (1 + apy / SECONDS_PER_YEAR) ^ (now - start_timestamp)`
+//!
+//! Nothing but the clock is read, the entry is mapped to the scope program id.
+
+use anchor_lang::prelude::*;
+use arrayref::array_ref;
+use decimal_wad::{
+    common::{TryAdd, TryDiv, TryMul},
+    decimal::Decimal,
+    rate::Rate,
+};
+
+use crate::{DatedPrice, Price, Result, ScopeError, ScopeResult, GENERIC_DATA_LEN};
+
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+/// Accrual parameters stored in the entry generic data
+///
+/// - `[0..8]`: start price value (u64, little endian)
+/// - `[8]`: start price exponent
+/// - `[9..17]`: start unix timestamp (u64, little endian)
+/// - `[17..19]`: APY in bps (u16, little endian)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedRateConfig {
+    pub start_price: Price,
+    pub start_timestamp: u64,
+    pub apy_bps: u16,
+}
+
+impl FixedRateConfig {
+    pub fn from_generic_data(generic_data: &[u8; GENERIC_DATA_LEN]) -> ScopeResult<Self> {
+        let start_price = Price {
+            value: u64::from_le_bytes(*array_ref![generic_data, 0, 8]),
+            exp: generic_data[8].into(),
+        };
+        if start_price.value == 0 {
+            msg!("The fixed rate start price must be configured");
+            return Err(ScopeError::InvalidGenericData);
+        }
+        Ok(Self {
+            start_price,
+            start_timestamp: u64::from_le_bytes(*array_ref![generic_data, 9, 8]),
+            apy_bps: u16::from_le_bytes(*array_ref![generic_data, 17, 2]),
+        })
+    }
+
+    pub fn to_generic_data(&self) -> ScopeResult<[u8; GENERIC_DATA_LEN]> {
+        let mut generic_data = [0; GENERIC_DATA_LEN];
+        generic_data[0..8].copy_from_slice(&self.start_price.value.to_le_bytes());
+        generic_data[8] = u8::try_from(self.start_price.exp)?;
+        generic_data[9..17].copy_from_slice(&self.start_timestamp.to_le_bytes());
+        generic_data[17..19].copy_from_slice(&self.apy_bps.to_le_bytes());
+        Ok(generic_data)
+    }
+
+    /// Price accrued at `unix_timestamp`
+    ///
+    /// Before the start timestamp, the start price is returned.
+    pub fn accrued_price(&self, unix_timestamp: u64) -> ScopeResult<Price> {
+        let seconds_elapsed = unix_timestamp.saturating_sub(self.start_timestamp);
+        if seconds_elapsed == 0 || self.apy_bps == 0 {
+            return Ok(self.start_price);
+        }
+        let apy = Decimal::from(u64::from(self.apy_bps)).try_div(10_000)?;
+        let second_rate = Rate::try_from(apy)?.try_div(SECONDS_PER_YEAR)?;
+        let compounded_rate = Rate::one().try_add(second_rate)?.try_pow(seconds_elapsed)?;
+        let start_price: Decimal = self.start_price.into();
+        start_price.try_mul(compounded_rate)?.try_into()
+    }
+}
+
+pub fn get_price(generic_data: &[u8; GENERIC_DATA_LEN], clock: &Clock) -> Result<DatedPrice> {
+    let config = FixedRateConfig::from_generic_data(generic_data)?;
+    let unix_timestamp =
+        u64::try_from(clock.unix_timestamp).map_err(|_| ScopeError::BadTimestamp)?;
+    let price = config.accrued_price(unix_timestamp)?;
+
+    Ok(DatedPrice {
+        price,
+        last_updated_slot: clock.slot,
+        unix_timestamp,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(apy_bps: u16) -> FixedRateConfig {
+        FixedRateConfig {
+            start_price: Price {
+                value: 100_000_000,
+                exp: 8,
+            },
+            start_timestamp: 1_700_000_000,
+            apy_bps,
+        }
+    }
+
+    #[test]
+    fn test_generic_data_roundtrip() {
+        let config = config(525);
+        let generic_data = config.to_generic_data().unwrap();
+        assert_eq!(
+            FixedRateConfig::from_generic_data(&generic_data).unwrap(),
+            config
+        );
+        assert_eq!(
+            FixedRateConfig::from_generic_data(&[0; GENERIC_DATA_LEN]).unwrap_err(),
+            ScopeError::InvalidGenericData
+        );
+    }
+
+    #[test]
+    fn test_accrued_price() {
+        let config = config(500);
+        assert_eq!(
+            config.accrued_price(config.start_timestamp - 10).unwrap(),
+            config.start_price
+        );
+        assert_eq!(
+            config.accrued_price(config.start_timestamp).unwrap(),
+            config.start_price
+        );
+        // One year compounded every second: e^0.05 ~= 1.051271
+        let price: Decimal = config
+            .accrued_price(config.start_timestamp + SECONDS_PER_YEAR)
+            .unwrap()
+            .into();
+        let price_micro: u64 = price.try_mul(1_000_000).unwrap().try_floor().unwrap();
+        assert_eq!(price_micro, 1_051_271);
+    }
+
+    #[test]
+    fn test_zero_apy() {
+        let config = config(0);
+        assert_eq!(
+            config
+                .accrued_price(config.start_timestamp + SECONDS_PER_YEAR)
+                .unwrap(),
+            config.start_price
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! Optional history of the last observations of an entry
+//!
+//! When the history is enabled for an entry, its [`PriceHistory`] account is passed to
+//! `refresh_price_list` after all the price accounts (and before the optional configuration).
+//! A new observation is appended every time the refreshed price has a more recent slot than the
+//! last observation. Without its history account the entry is refreshed without appending, and a
+//! failed append fails the refresh of the entry. The history is enabled by `init_price_history`
+//! and can be disabled, or enabled again, with `update_price_history`.
+
+use anchor_lang::prelude::*;
+
+use crate::utils::zero_copy_deserialize_mut;
+use crate::{DatedPrice, PriceHistory, ScopeError, PRICE_HISTORY_LEN};
+
+impl PriceHistory {
+    /// Last observation appended, if any
+    pub fn last(&self) -> Option<&DatedPrice> {
+        let idx = self.num_observations.checked_sub(1)?;
+        Some(&self.observations[ring_index(idx)])
+    }
+
+    /// Append an observation if its slot is more recent than the last one
+    ///
+    /// Returns true if the observation has been appended.
+    pub fn append(&mut self, price: &DatedPrice) -> bool {
+        if let Some(last) = self.last() {
+            if price.last_updated_slot <= last.last_updated_slot {
+                return false;
+            }
+        }
+        self.observations[ring_index(self.num_observations)] = *price;
+        self.num_observations += 1;
+        true
+    }
+
+    /// Iterate over the stored observations, most recent first
+    pub fn iter_recent_first(&self) -> impl Iterator<Item = &DatedPrice> {
+        let stored = self.num_observations.min(PRICE_HISTORY_LEN as u64);
+        (0..stored).map(move |age| &self.observations[ring_index(self.num_observations - 1 - age)])
+    }
+
+    /// Get the most recent observation with a timestamp at or before `unix_timestamp`
+    ///
+    /// Returns `None` if all the stored observations are more recent (or if there is none).
+    pub fn price_at_or_before(&self, unix_timestamp: u64) -> Option<&DatedPrice> {
+        self.iter_recent_first()
+            .find(|observation| observation.unix_timestamp <= unix_timestamp)
+    }
+}
+
+fn ring_index(observation_nb: u64) -> usize {
+    (observation_nb % PRICE_HISTORY_LEN as u64) as usize
+}
+
+/// Append a refreshed price to the history account of the entry `token`
+pub fn append_to_history(
+    history_account: &AccountInfo,
+    oracle_prices: &Pubkey,
+    token: usize,
+    price: &DatedPrice,
+) -> Result<()> {
+    require_keys_eq!(
+        *history_account.owner,
+        crate::ID,
+        ScopeError::UnexpectedAccount
+    );
+    let mut history = zero_copy_deserialize_mut::<PriceHistory>(history_account)?;
+    if history.oracle_prices != *oracle_prices || usize::from(history.index) != token {
+        msg!(
+            "Invalid history account {} for token {token}",
+            history_account.key()
+        );
+        return err!(ScopeError::UnexpectedAccount);
+    }
+    history.append(price);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Price;
+
+    fn observation(slot: u64) -> DatedPrice {
+        DatedPrice {
+            price: Price {
+                value: slot * 10,
+                exp: 1,
+            },
+            last_updated_slot: slot,
+            unix_timestamp: slot * 2,
+            ..Default::default()
+        }
+    }
+
+    fn history() -> Box<PriceHistory> {
+        Box::new(bytemuck::Zeroable::zeroed())
+    }
+
+    #[test]
+    fn test_append_only_when_slot_advances() {
+        let mut history = history();
+        assert!(history.last().is_none());
+        assert!(history.append(&observation(10)));
+        assert!(!history.append(&observation(10)));
+        assert!(!history.append(&observation(9)));
+        assert!(history.append(&observation(11)));
+        assert_eq!(history.num_observations, 2);
+        assert_eq!(history.last(), Some(&observation(11)));
+    }
+
+    #[test]
+    fn test_price_at_or_before() {
+        let mut history = history();
+        assert!(history.price_at_or_before(100).is_none());
+        for slot in [10, 20, 30] {
+            history.append(&observation(slot));
+        }
+        assert!(history.price_at_or_before(19).is_none());
+        assert_eq!(history.price_at_or_before(20), Some(&observation(10)));
+        assert_eq!(history.price_at_or_before(59), Some(&observation(20)));
+        assert_eq!(history.price_at_or_before(1_000), Some(&observation(30)));
+    }
+
+    #[test]
+    fn test_ring_buffer_wraps() {
+        let mut history = history();
+        let total = PRICE_HISTORY_LEN as u64 + 5;
+        for slot in 1..=total {
+            history.append(&observation(slot));
+        }
+        assert_eq!(history.iter_recent_first().count(), PRICE_HISTORY_LEN);
+        assert_eq!(history.last(), Some(&observation(total)));
+        // The 5 oldest observations have been overwritten
+        assert!(history.price_at_or_before(2 * 5).is_none());
+        assert_eq!(history.price_at_or_before(2 * 6), Some(&observation(6)));
+    }
+}
@@ -0,0 +1,209 @@
+//! Weighted basket index computed from other entries of the feed
+//!
+//! The mapping account is an [`IndexDefinition`] account giving the component entries and their
+//! weights. The index price is:
+//!
+//! `price = sum(weight_i * price_i) / divisor`
+//!
+//! The sum is computed with integers at [`MAX_PRICE_EXP`] decimals, so components with more
+//! decimals than a `Decimal` keep their precision.
+//!
+//! The result carries the oldest slot and timestamp of the components.
+
+use anchor_lang::prelude::*;
+use raydium_amm_v3::libraries::U256;
+
+use super::check_entry_account_feed;
+use crate::utils::{price_impl::MAX_PRICE_EXP, zero_copy_deserialize};
+use crate::{
+    DatedPrice, IndexComponent, IndexDefinition, OraclePrices, Price, Result, ScopeError,
+    ScopeResult,
+};
+
+pub fn get_price(
+    index_definition_account: &AccountInfo,
+    oracle_prices: &OraclePrices,
+    oracle_prices_pk: &Pubkey,
+    index: usize,
+) -> Result<DatedPrice> {
+    let index_definition = load_index_definition(index_definition_account)?;
+    check_entry_account_feed(&index_definition.oracle_prices, oracle_prices_pk)?;
+    if usize::from(index_definition.index) != index {
+        msg!(
+            "Index definition is configured for entry {} instead of {index}",
+            index_definition.index
+        );
+        return err!(ScopeError::UnexpectedAccount);
+    }
+
+    Ok(compute_index_price(&index_definition, oracle_prices)?)
+}
+
+fn compute_index_price(
+    index_definition: &IndexDefinition,
+    oracle_prices: &OraclePrices,
+) -> ScopeResult<DatedPrice> {
+    let components = index_definition.components();
+    if components.is_empty() {
+        return Err(ScopeError::InvalidIndexDefinition);
+    }
+
+    // sum(weight_value_i * price_i) scaled by 10^(MAX_PRICE_EXP + weights_exp)
+    let mut sum = U256::zero();
+    let mut last_updated_slot = u64::MAX;
+    let mut unix_timestamp = u64::MAX;
+    for IndexComponent { entry, weight, .. } in components {
+        let component = oracle_prices
+            .prices
+            .get(usize::from(*entry))
+            .ok_or(ScopeError::BadTokenNb)?;
+        if component.price.value == 0 {
+            msg!("Index component {entry} has no price");
+            return Err(ScopeError::PriceNotValid);
+        }
+        let weighted_value = U256::from(component.price.value) * U256::from(*weight);
+        sum = sum
+            .checked_add(scale_to_max_exp(weighted_value, component.price.exp))
+            .ok_or(ScopeError::IntegerOverflow)?;
+        last_updated_slot = last_updated_slot.min(component.last_updated_slot);
+        unix_timestamp = unix_timestamp.min(component.unix_timestamp);
+    }
+
+    // The divisor has the same exponent as the weights
+    let price = if index_definition.divisor != 0 {
+        Price::from_scaled_ratio(sum, MAX_PRICE_EXP, index_definition.divisor.into())?
+    } else {
+        Price::from_scaled_ratio(
+            sum,
+            MAX_PRICE_EXP + index_definition.weights_exp,
+            U256::one(),
+        )?
+    };
+
+    Ok(DatedPrice {
+        price,
+        last_updated_slot,
+        unix_timestamp,
+        ..Default::default()
+    })
+}
+
+/// Scale `value * 10^-exp` to [`MAX_PRICE_EXP`] decimals, dropping the digits beyond them
+fn scale_to_max_exp(value: U256, exp: u64) -> U256 {
+    if exp <= MAX_PRICE_EXP {
+        // At most 10^30, the product fits in a U256 since `value` is below 2^128
+        value * U256::from(10).pow(U256::from(MAX_PRICE_EXP - exp))
+    } else {
+        u32::try_from(exp - MAX_PRICE_EXP)
+            .ok()
+            .and_then(|diff| 10_u128.checked_pow(diff))
+            .map_or_else(U256::zero, |factor| value / U256::from(factor))
+    }
+}
+
+pub fn validate_index_definition_account(
+    index_definition_account: &AccountInfo,
+    oracle_prices_pk: &Pubkey,
+) -> Result<()> {
+    let index_definition = load_index_definition(index_definition_account)?;
+    check_entry_account_feed(&index_definition.oracle_prices, oracle_prices_pk)?;
+    if index_definition.components().is_empty() {
+        return err!(ScopeError::InvalidIndexDefinition);
+    }
+    Ok(())
+}
+
+fn load_index_definition<'a>(
+    index_definition_account: &'a AccountInfo,
+) -> Result<std::cell::Ref<'a, IndexDefinition>> {
+    require_keys_eq!(
+        *index_definition_account.owner,
+        crate::ID,
+        ScopeError::UnexpectedAccount
+    );
+    Ok(zero_copy_deserialize::<IndexDefinition>(
+        index_definition_account,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index_definition(entries: &[u16], weights: &[u64], divisor: u64) -> IndexDefinition {
+        let mut index_definition: IndexDefinition = bytemuck::Zeroable::zeroed();
+        index_definition
+            .set_components(entries, weights, 2, divisor)
+            .unwrap();
+        index_definition
+    }
+
+    fn oracle_prices(prices: &[(u16, u64, u64, u64)]) -> OraclePrices {
+        let mut oracle_prices: OraclePrices = bytemuck::Zeroable::zeroed();
+        for &(entry, value, exp, slot) in prices {
+            oracle_prices.prices[usize::from(entry)] = DatedPrice {
+                price: Price { value, exp },
+                last_updated_slot: slot,
+                unix_timestamp: slot,
+                ..Default::default()
+            };
+        }
+        oracle_prices
+    }
+
+    #[test]
+    fn test_weighted_index() {
+        // 0.5 * 100 + 0.25 * 20 + 0.25 * 4 = 56, divided by 2
+        let index_definition = index_definition(&[1, 5, 9], &[50, 25, 25], 200);
+        let oracle_prices = oracle_prices(&[(1, 100, 0, 10), (5, 2_000, 2, 8), (9, 4, 0, 12)]);
+        let price = compute_index_price(&index_definition, &oracle_prices).unwrap();
+        assert_eq!(price.price, Price { value: 28, exp: 0 });
+        assert_eq!(price.last_updated_slot, 8);
+        assert_eq!(price.unix_timestamp, 8);
+    }
+
+    #[test]
+    fn test_small_prices_index() {
+        // Average of 3e-25 and 4e-26, below the 18 decimals of a `Decimal`
+        let index_definition = index_definition(&[1, 2], &[100, 100], 200);
+        let oracle_prices = oracle_prices(&[(1, 3, 25, 10), (2, 4, 26, 10)]);
+        let price = compute_index_price(&index_definition, &oracle_prices).unwrap();
+        assert_eq!(price.price, Price { value: 17, exp: 26 });
+    }
+
+    #[test]
+    fn test_missing_component_price() {
+        let index_definition = index_definition(&[1, 5], &[50, 50], 0);
+        let oracle_prices = oracle_prices(&[(1, 100, 0, 10)]);
+        assert_eq!(
+            compute_index_price(&index_definition, &oracle_prices).unwrap_err(),
+            ScopeError::PriceNotValid
+        );
+    }
+
+    #[test]
+    fn test_invalid_definition() {
+        let mut index_definition: IndexDefinition = bytemuck::Zeroable::zeroed();
+        assert_eq!(
+            index_definition.set_components(&[1, 2], &[1], 0, 0),
+            Err(ScopeError::InvalidIndexDefinition)
+        );
+        assert_eq!(
+            index_definition.set_components(&[], &[], 0, 0),
+            Err(ScopeError::InvalidIndexDefinition)
+        );
+        assert_eq!(
+            index_definition.set_components(&[1], &[0], 0, 0),
+            Err(ScopeError::InvalidIndexDefinition)
+        );
+        assert_eq!(
+            index_definition.set_components(&[1], &[1], 31, 0),
+            Err(ScopeError::InvalidIndexDefinition)
+        );
+        index_definition.index = 2;
+        assert_eq!(
+            index_definition.set_components(&[1, 2], &[1, 1], 0, 0),
+            Err(ScopeError::InvalidIndexDefinition)
+        );
+    }
+}
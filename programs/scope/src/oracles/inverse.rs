@@ -0,0 +1,120 @@
+//! Inverse (1/x) of the price of another entry of the feed
+//!
+//! The index of the source entry is stored in the entry generic data (u16, little endian).
+//! The inverse price keeps the slot and timestamp of the source entry.
+//!
+//! Like the other entries derived from the feed prices, it is mapped to the scope program id.
+
+use anchor_lang::prelude::*;
+use arrayref::array_ref;
+
+use crate::utils::{math::ten_pow, price_impl::MAX_PRICE_EXP};
+use crate::{
+    DatedPrice, OraclePrices, Price, Result, ScopeError, ScopeResult, GENERIC_DATA_LEN, MAX_ENTRIES,
+};
+
+pub fn get_price(
+    oracle_prices: &OraclePrices,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> Result<DatedPrice> {
+    let source_index = get_source_index(generic_data)?;
+    let source = oracle_prices.prices[source_index];
+
+    Ok(DatedPrice {
+        price: inverse(source.price)?,
+        last_updated_slot: source.last_updated_slot,
+        unix_timestamp: source.unix_timestamp,
+        ..Default::default()
+    })
+}
+
+/// Get the index of the source entry stored in the generic data
+pub fn get_source_index(generic_data: &[u8; GENERIC_DATA_LEN]) -> ScopeResult<usize> {
+    let source_index = usize::from(u16::from_le_bytes(*array_ref![generic_data, 0, 2]));
+    if source_index >= MAX_ENTRIES {
+        msg!("Invalid source entry index {source_index}");
+        return Err(ScopeError::InvalidGenericData);
+    }
+    Ok(source_index)
+}
+
+fn inverse(price: Price) -> ScopeResult<Price> {
+    if price.value == 0 {
+        msg!("Cannot inverse a zero price");
+        return Err(ScopeError::PriceNotValid);
+    }
+    // 1 / (value * 10^-exp) = 10^exp / value, computed with integers to keep the precision of
+    // very small inverses
+    let exp = u8::try_from(price.exp)
+        .ok()
+        .filter(|exp| u64::from(*exp) <= MAX_PRICE_EXP)
+        .ok_or(ScopeError::PriceOutOfRange)?;
+    Price::from_ratio(ten_pow(exp)?, price.value.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inverse() {
+        assert_eq!(
+            inverse(Price {
+                value: 2_500,
+                exp: 2
+            })
+            .unwrap(),
+            Price { value: 4, exp: 2 }
+        );
+        assert_eq!(
+            inverse(Price {
+                value: 800_000_000,
+                exp: 8
+            })
+            .unwrap(),
+            Price { value: 125, exp: 3 }
+        );
+        assert_eq!(
+            inverse(Price { value: 0, exp: 0 }).unwrap_err(),
+            ScopeError::PriceNotValid
+        );
+    }
+
+    #[test]
+    fn test_inverse_big_price() {
+        // 1 / 3e12 keeps 16 significant digits
+        let price = inverse(Price {
+            value: 3_000_000_000_000,
+            exp: 0,
+        })
+        .unwrap();
+        assert_eq!(
+            price,
+            Price {
+                value: 3_333_333_333_333_333,
+                exp: 28
+            }
+        );
+        assert_eq!(
+            inverse(Price { value: 1, exp: 31 }).unwrap_err(),
+            ScopeError::PriceOutOfRange
+        );
+    }
+
+    #[test]
+    fn test_source_dates_are_kept() {
+        let mut oracle_prices: OraclePrices = bytemuck::Zeroable::zeroed();
+        oracle_prices.prices[7] = DatedPrice {
+            price: Price { value: 5, exp: 1 },
+            last_updated_slot: 42,
+            unix_timestamp: 1_700_000_000,
+            ..Default::default()
+        };
+        let mut generic_data = [0; GENERIC_DATA_LEN];
+        generic_data[0..2].copy_from_slice(&7_u16.to_le_bytes());
+        let dated_price = get_price(&oracle_prices, &generic_data).unwrap();
+        assert_eq!(dated_price.price, Price { value: 2, exp: 0 });
+        assert_eq!(dated_price.last_updated_slot, 42);
+        assert_eq!(dated_price.unix_timestamp, 1_700_000_000);
+    }
+}
@@ -6,7 +6,7 @@ use solana_program::program_pack::Pack;
 
 use crate::utils::account_deserialize;
 use crate::utils::math::ten_pow;
-use crate::{DatedPrice, Price, Result, ScopeError};
+use crate::{DatedPrice, Price, Result, ScopeError, ScopeResult};
 
 pub use jup_perp_itf as perpetuals;
 pub use perpetuals::utils::{check_mint_pk, get_mint_pk};
@@ -47,10 +47,11 @@ where
 
     let price_dec = Decimal::from(lp_value) / lp_token_supply;
     let dated_price = DatedPrice {
-        price: price_dec.into(),
+        price: price_dec.try_into()?,
         // TODO: find a way to get the last update time
         last_updated_slot: clock.slot,
-        unix_timestamp: u64::try_from(clock.unix_timestamp).unwrap(),
+        unix_timestamp: u64::try_from(clock.unix_timestamp)
+            .map_err(|_| ScopeError::BadTimestamp)?,
         ..Default::default()
     };
 
@@ -127,7 +128,10 @@ where
         mint.supply
     };
 
-    let mut oldest_price_ts: u64 = clock.unix_timestamp.try_into().unwrap();
+    let mut oldest_price_ts: u64 = clock
+        .unix_timestamp
+        .try_into()
+        .map_err(|_| ScopeError::BadTimestamp)?;
     let mut oldest_price_slot: u64 = clock.slot;
 
     // 4. Get AUM with CPI
@@ -156,7 +160,7 @@ where
     let price_dec = Decimal::from(lp_value) / lp_token_supply;
 
     let dated_price = DatedPrice {
-        price: price_dec.into(),
+        price: price_dec.try_into()?,
         last_updated_slot: oldest_price_slot,
         unix_timestamp: oldest_price_ts,
         ..Default::default()
@@ -194,7 +198,7 @@ fn compute_custody_aum(
 
     let (token_amount_usd, trader_short_profits) = if custody.is_stable {
         (
-            asset_amount_to_usd(&price, custody.assets.owned, custody.decimals),
+            asset_amount_to_usd(&price, custody.assets.owned, custody.decimals)?,
             0,
         )
     } else {
@@ -205,9 +209,9 @@ fn compute_custody_aum(
             let (global_pnl_delta, trader_has_profit) = custody
                 .get_global_short_pnl(
                     price
-                        .to_scaled_value(POOL_VALUE_SCALE_DECIMALS)
+                        .to_scaled_value(POOL_VALUE_SCALE_DECIMALS)?
                         .try_into()
-                        .unwrap(),
+                        .map_err(|_| ScopeError::OutOfRangeIntegralConversion)?,
                 )
                 .ok_or_else(|| error!(ScopeError::MathOverflow))?;
 
@@ -227,7 +231,7 @@ fn compute_custody_aum(
             .owned
             .checked_sub(custody.assets.locked)
             .ok_or_else(|| error!(ScopeError::MathOverflow))?;
-        let net_assets_usd = asset_amount_to_usd(&price, net_assets_token, custody.decimals);
+        let net_assets_usd = asset_amount_to_usd(&price, net_assets_token, custody.decimals)?;
         pool_amount_usd += net_assets_usd;
 
         (pool_amount_usd, trader_short_profits)
@@ -242,22 +246,29 @@ fn compute_custody_aum(
 }
 
 /// Return the value of the number of tokens in USD scaled by `POOL_VALUE_SCALE_DECIMALS` decimals
-fn asset_amount_to_usd(price: &Price, token_amount: u64, token_decimals: u8) -> u128 {
+fn asset_amount_to_usd(price: &Price, token_amount: u64, token_decimals: u8) -> ScopeResult<u128> {
     let price_value: u128 = price.value.into();
     let token_amount: u128 = token_amount.into();
     let token_decimals: u8 = token_decimals;
-    let price_decimals: u8 = price.exp.try_into().unwrap();
+    let price_decimals: u8 = price.exp.try_into()?;
 
     // price * 10^(-price_decimals) * token_amount * 10^(-token_decimals) * 10^POOL_VALUE_SCALE_DECIMALS
-    if price_decimals + token_decimals > POOL_VALUE_SCALE_DECIMALS {
-        let diff = price_decimals + token_decimals - POOL_VALUE_SCALE_DECIMALS;
-        let nom = price_value * token_amount;
-        let denom = ten_pow(diff);
-
-        nom / denom
+    let total_decimals = price_decimals
+        .checked_add(token_decimals)
+        .ok_or(ScopeError::MathOverflow)?;
+    // price_value * token_amount always fits in a u128
+    let nom = price_value * token_amount;
+    if total_decimals > POOL_VALUE_SCALE_DECIMALS {
+        let diff = total_decimals - POOL_VALUE_SCALE_DECIMALS;
+        let denom = 10_u128
+            .checked_pow(diff.into())
+            .ok_or(ScopeError::MathOverflow)?;
+
+        Ok(nom / denom)
     } else {
-        let diff = POOL_VALUE_SCALE_DECIMALS - (price_decimals + token_decimals);
-        price_value * token_amount * ten_pow(diff)
+        let diff = POOL_VALUE_SCALE_DECIMALS - total_decimals;
+        nom.checked_mul(ten_pow(diff)?)
+            .ok_or(ScopeError::MathOverflow)
     }
 }
 
@@ -286,7 +297,7 @@ mod tests {
         let token_amount: u64 = token_amount;
         let token_decimals: u8 = token_decimals;
 
-        let usd = asset_amount_to_usd(&price, token_amount, token_decimals);
+        let usd = asset_amount_to_usd(&price, token_amount, token_decimals).unwrap();
         assert_eq!(usd, expected);
     }
 }
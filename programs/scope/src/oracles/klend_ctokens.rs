@@ -0,0 +1,426 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+
+use self::klend::Reserve;
+use crate::{DatedPrice, Price, Result, ScopeError, ScopeResult};
+
+const DECIMALS: u32 = 15u32;
+
+// Gives the price of 1 cToken of a Kamino Lending reserve in the liquidity token
+pub fn get_price(klend_reserve_account: &AccountInfo, clock: &Clock) -> Result<DatedPrice> {
+    let mut reserve = Reserve::from_account(klend_reserve_account)?;
+
+    let now = u64::try_from(clock.unix_timestamp).map_err(|_| ScopeError::BadTimestamp)?;
+
+    // Manual refresh of the reserve to ensure the most accurate price
+    let (last_updated_slot, unix_timestamp) = if reserve.accrue_interest(clock.slot).is_ok() {
+        // We have just refreshed the price so we can use the current slot
+        (clock.slot, now)
+    } else {
+        // This should never happen but on simulations when the current slot is not valid
+        // yet we have a default value
+        (
+            reserve.last_update_slot,
+            now.saturating_sub(
+                clock
+                    .slot
+                    .saturating_sub(reserve.last_update_slot)
+                    .saturating_mul(clock::DEFAULT_MS_PER_SLOT),
+            ),
+        )
+    };
+
+    let value = scaled_rate(&reserve)?;
+
+    let price = Price {
+        value,
+        exp: DECIMALS.into(),
+    };
+    let dated_price = DatedPrice {
+        price,
+        last_updated_slot,
+        unix_timestamp,
+        ..Default::default()
+    };
+
+    Ok(dated_price)
+}
+
+pub fn validate_reserve_account(reserve_account: &AccountInfo) -> Result<()> {
+    let _ = Reserve::from_account(reserve_account)?;
+    Ok(())
+}
+
+fn scaled_rate(reserve: &Reserve) -> ScopeResult<u64> {
+    const FACTOR: u64 = 10u64.pow(DECIMALS);
+    let value = reserve.collateral_to_liquidity(FACTOR)?;
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod test {
+    use decimal_wad::{common::TryDiv, decimal::Decimal};
+
+    use self::klend::*;
+    use super::*;
+
+    fn flat_curve(borrow_rate_bps: u32) -> [CurvePoint; CURVE_POINTS] {
+        let mut points = [CurvePoint {
+            utilization_rate_bps: 10_000,
+            borrow_rate_bps,
+        }; CURVE_POINTS];
+        points[0].utilization_rate_bps = 0;
+        points
+    }
+
+    fn reserve(available_amount: u64, borrowed_amount: u64, mint_total_supply: u64) -> Reserve {
+        Reserve {
+            last_update_slot: 0,
+            available_amount,
+            borrowed_amount: Decimal::from(borrowed_amount),
+            total_fees: Decimal::zero(),
+            collateral_mint_total_supply: mint_total_supply,
+            protocol_take_rate_pct: 0,
+            host_fixed_interest_rate_bps: 0,
+            borrow_rate_curve: flat_curve(0),
+        }
+    }
+
+    #[test]
+    pub fn minted_ctoken_is_equal_to_token_in_vault() {
+        let reserve = reserve(10u64.pow(5), 0, 10u64.pow(5));
+        assert_eq!(scaled_rate(&reserve).unwrap(), 10u64.pow(DECIMALS));
+    }
+
+    #[test]
+    pub fn minted_ctoken_is_2xtoken_in_vault() {
+        let reserve = reserve(10u64.pow(5), 0, 2 * 10u64.pow(5));
+        // Expect ctoken price to be 0.5 token
+        assert_eq!(scaled_rate(&reserve).unwrap(), 5 * 10u64.pow(DECIMALS - 1));
+    }
+
+    #[test]
+    pub fn token_in_vault_and_borrowed_is_2xctoken_minted() {
+        let reserve = reserve(10u64.pow(5), 10u64.pow(5), 10u64.pow(5));
+        // Expect ctoken price to be 2 tokens
+        assert_eq!(scaled_rate(&reserve).unwrap(), 2 * 10u64.pow(DECIMALS));
+    }
+
+    #[test]
+    pub fn accrued_interest_increases_ctoken_price() {
+        let mut reserve = reserve(10u64.pow(9), 10u64.pow(9), 2 * 10u64.pow(9));
+        reserve.borrow_rate_curve = flat_curve(1_000);
+        reserve.protocol_take_rate_pct = 10;
+        // One year of slots at 10% APR, 10% of the interest goes to the protocol
+        reserve.accrue_interest(SLOTS_PER_YEAR).unwrap();
+        let rate = scaled_rate(&reserve).unwrap();
+        // (2e9 + 1e9 * (e^0.1 - 1) * 0.9) / 2e9 ~= 1.0473
+        let expected = 10_473 * 10u64.pow(DECIMALS - 4);
+        assert!(
+            rate.abs_diff(expected) < 10u64.pow(DECIMALS - 4),
+            "rate: {rate}"
+        );
+    }
+
+    /// Reserve account laid out as the upstream `Reserve` struct (8624 bytes), with a USDC
+    /// reserve holding 60k available, 40k borrowed, 1k of fees and 90k cTokens minted
+    const USDC_RESERVE: &[u8] = include_bytes!("../../tests/fixtures/klend_usdc_reserve.bin");
+
+    #[test]
+    pub fn test_unpack_reserve() {
+        let reserve = Reserve::unpack(USDC_RESERVE).unwrap();
+        assert_eq!(reserve.last_update_slot, 250_000_000);
+        assert_eq!(reserve.available_amount, 60_000_000_000);
+        assert_eq!(reserve.borrowed_amount, Decimal::from(40_000_000_000_u64));
+        assert_eq!(reserve.total_fees, Decimal::from(1_000_000_000_u64));
+        assert_eq!(reserve.collateral_mint_total_supply, 90_000_000_000);
+        assert_eq!(reserve.protocol_take_rate_pct, 15);
+        assert_eq!(reserve.host_fixed_interest_rate_bps, 25);
+        assert_eq!(
+            reserve.borrow_rate_curve[..3],
+            [
+                CurvePoint {
+                    utilization_rate_bps: 0,
+                    borrow_rate_bps: 0,
+                },
+                CurvePoint {
+                    utilization_rate_bps: 8_000,
+                    borrow_rate_bps: 1_000,
+                },
+                CurvePoint {
+                    utilization_rate_bps: 9_000,
+                    borrow_rate_bps: 3_000,
+                },
+            ]
+        );
+        // (60k + 40k - 1k) / 90k = 1.1
+        assert_eq!(scaled_rate(&reserve).unwrap(), 11 * 10u64.pow(DECIMALS - 1));
+
+        let mut wrong_discriminator = USDC_RESERVE.to_vec();
+        wrong_discriminator[0] ^= 1;
+        assert!(Reserve::unpack(&wrong_discriminator).is_err());
+    }
+
+    #[test]
+    pub fn borrow_rate_curve_interpolation() {
+        let mut points = flat_curve(5_000);
+        points[0].borrow_rate_bps = 0;
+        points[1] = CurvePoint {
+            utilization_rate_bps: 8_000,
+            borrow_rate_bps: 1_000,
+        };
+        let curve = BorrowRateCurve { points };
+        let rate_at = |utilization_bps: u64| {
+            let utilization = Decimal::from(utilization_bps).try_div(10_000).unwrap();
+            curve.get_borrow_rate(utilization).unwrap()
+        };
+        assert_eq!(rate_at(0), Decimal::zero());
+        assert_eq!(rate_at(4_000), Decimal::from(5u64).try_div(100).unwrap());
+        assert_eq!(rate_at(8_000), Decimal::from(10u64).try_div(100).unwrap());
+        assert_eq!(rate_at(9_000), Decimal::from(30u64).try_div(100).unwrap());
+        assert_eq!(rate_at(10_000), Decimal::from(50u64).try_div(100).unwrap());
+    }
+}
+
+/// Partial Kamino Lending reserve layout, only the fields needed to compute the collateral
+/// exchange rate are read.
+pub mod klend {
+    use anchor_lang::solana_program::clock::Slot;
+    use arrayref::array_ref;
+    use decimal_wad::{
+        common::{TryAdd, TryDiv, TryMul, TrySub, WAD},
+        decimal::Decimal,
+        rate::Rate,
+    };
+    use raydium_amm_v3::libraries::U256;
+
+    use super::*;
+
+    declare_id!("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD");
+
+    /// Number of slots per year
+    // 2 (slots per second) * 60 * 60 * 24 * 365 = 63072000
+    pub const SLOTS_PER_YEAR: u64 = 63072000;
+
+    pub const CURVE_POINTS: usize = 11;
+
+    pub const RESERVE_DISCRIMINATOR: [u8; 8] = [43, 242, 204, 202, 26, 247, 59, 127];
+
+    const LAST_UPDATE_SLOT_OFFSET: usize = 16;
+    const LIQUIDITY_OFFSET: usize = 128;
+    const AVAILABLE_AMOUNT_OFFSET: usize = LIQUIDITY_OFFSET + 96;
+    const BORROWED_AMOUNT_SF_OFFSET: usize = LIQUIDITY_OFFSET + 104;
+    const ACCUMULATED_PROTOCOL_FEES_SF_OFFSET: usize = LIQUIDITY_OFFSET + 216;
+    const ACCUMULATED_REFERRER_FEES_SF_OFFSET: usize = LIQUIDITY_OFFSET + 232;
+    const PENDING_REFERRER_FEES_SF_OFFSET: usize = LIQUIDITY_OFFSET + 248;
+    // `ReserveLiquidity` is 1232 bytes and followed by 150 u64 of padding
+    const COLLATERAL_OFFSET: usize = 2560;
+    const COLLATERAL_MINT_TOTAL_SUPPLY_OFFSET: usize = COLLATERAL_OFFSET + 32;
+    // `ReserveCollateral` is 1096 bytes and followed by 150 u64 of padding
+    const CONFIG_OFFSET: usize = 4856;
+    const HOST_FIXED_INTEREST_RATE_BPS_OFFSET: usize = CONFIG_OFFSET + 2;
+    const PROTOCOL_TAKE_RATE_PCT_OFFSET: usize = CONFIG_OFFSET + 14;
+    const BORROW_RATE_CURVE_OFFSET: usize = CONFIG_OFFSET + 64;
+    const MIN_RESERVE_LEN: usize = BORROW_RATE_CURVE_OFFSET + CURVE_POINTS * 8;
+
+    /// Point of the borrow rate curve
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct CurvePoint {
+        pub utilization_rate_bps: u32,
+        pub borrow_rate_bps: u32,
+    }
+
+    /// Piecewise linear borrow rate curve of a reserve
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct BorrowRateCurve {
+        pub points: [CurvePoint; CURVE_POINTS],
+    }
+
+    impl BorrowRateCurve {
+        /// Get the annual borrow rate at the given utilization rate
+        pub fn get_borrow_rate(&self, utilization_rate: Decimal) -> ScopeResult<Decimal> {
+            let utilization_rate = if utilization_rate > Decimal::one() {
+                Decimal::one()
+            } else {
+                utilization_rate
+            };
+            let from_bps = |bps: u32| Decimal::from(u64::from(bps)).try_div(10_000);
+
+            for segment in self.points.windows(2) {
+                let (start, end) = (segment[0], segment[1]);
+                let start_utilization = from_bps(start.utilization_rate_bps)?;
+                let end_utilization = from_bps(end.utilization_rate_bps)?;
+                if utilization_rate < start_utilization || utilization_rate > end_utilization {
+                    continue;
+                }
+                let start_rate = from_bps(start.borrow_rate_bps)?;
+                let end_rate = from_bps(end.borrow_rate_bps)?;
+                if end_utilization == start_utilization {
+                    return Ok(end_rate);
+                }
+                // The curve is validated by klend to be non-decreasing
+                let slope = end_rate
+                    .try_sub(start_rate)?
+                    .try_div(end_utilization.try_sub(start_utilization)?)?;
+                return Ok(utilization_rate
+                    .try_sub(start_utilization)?
+                    .try_mul(slope)?
+                    .try_add(start_rate)?);
+            }
+            msg!("Klend borrow rate curve does not cover the current utilization rate");
+            Err(ScopeError::MathOverflow)
+        }
+    }
+
+    /// Lending market reserve state (partial)
+    #[derive(Clone, Debug)]
+    pub struct Reserve {
+        /// Last slot when supply and rates updated
+        pub last_update_slot: Slot,
+        /// Reserve liquidity available
+        pub available_amount: u64,
+        /// Reserve liquidity borrowed
+        pub borrowed_amount: Decimal,
+        /// Protocol and referrer fees (accumulated and pending), excluded from the supply
+        pub total_fees: Decimal,
+        /// Reserve collateral mint supply, used for exchange rate
+        pub collateral_mint_total_supply: u64,
+        /// Cut of the variable interests that the protocol receives, as a percentage
+        pub protocol_take_rate_pct: u8,
+        /// Fixed interest rate paid to the host, in bps
+        pub host_fixed_interest_rate_bps: u16,
+        pub borrow_rate_curve: [CurvePoint; CURVE_POINTS],
+    }
+
+    impl Reserve {
+        pub fn from_account(account: &AccountInfo) -> Result<Self> {
+            require_keys_eq!(*account.owner, ID, ScopeError::UnexpectedAccount);
+            Self::unpack(&account.data.borrow())
+        }
+
+        pub fn unpack(data: &[u8]) -> Result<Self> {
+            if data.len() < MIN_RESERVE_LEN {
+                msg!("Klend reserve account has an invalid size");
+                return err!(ScopeError::UnableToDeserializeAccount);
+            }
+            if data[..8] != RESERVE_DISCRIMINATOR {
+                msg!("Klend reserve account has an invalid discriminator");
+                return err!(ScopeError::InvalidAccountDiscriminator);
+            }
+            let read_u64 = |offset: usize| u64::from_le_bytes(*array_ref![data, offset, 8]);
+            let read_sf =
+                |offset: usize| sf_to_decimal(u128::from_le_bytes(*array_ref![data, offset, 16]));
+
+            let mut borrow_rate_curve = [CurvePoint::default(); CURVE_POINTS];
+            for (i, point) in borrow_rate_curve.iter_mut().enumerate() {
+                let offset = BORROW_RATE_CURVE_OFFSET + i * 8;
+                point.utilization_rate_bps = u32::from_le_bytes(*array_ref![data, offset, 4]);
+                point.borrow_rate_bps = u32::from_le_bytes(*array_ref![data, offset + 4, 4]);
+            }
+
+            Ok(Self {
+                last_update_slot: read_u64(LAST_UPDATE_SLOT_OFFSET),
+                available_amount: read_u64(AVAILABLE_AMOUNT_OFFSET),
+                borrowed_amount: read_sf(BORROWED_AMOUNT_SF_OFFSET),
+                total_fees: read_sf(ACCUMULATED_PROTOCOL_FEES_SF_OFFSET)
+                    .try_add(read_sf(ACCUMULATED_REFERRER_FEES_SF_OFFSET))
+                    .and_then(|fees| fees.try_add(read_sf(PENDING_REFERRER_FEES_SF_OFFSET)))
+                    .map_err(ScopeError::from)?,
+                collateral_mint_total_supply: read_u64(COLLATERAL_MINT_TOTAL_SUPPLY_OFFSET),
+                protocol_take_rate_pct: data[PROTOCOL_TAKE_RATE_PCT_OFFSET],
+                host_fixed_interest_rate_bps: u16::from_le_bytes(*array_ref![
+                    data,
+                    HOST_FIXED_INTEREST_RATE_BPS_OFFSET,
+                    2
+                ]),
+                borrow_rate_curve,
+            })
+        }
+
+        /// Calculate the total reserve supply including active loans
+        pub fn total_supply(&self) -> ScopeResult<Decimal> {
+            Ok(Decimal::from(self.available_amount)
+                .try_add(self.borrowed_amount)?
+                .try_sub(self.total_fees)?)
+        }
+
+        /// Calculate the liquidity utilization rate of the reserve
+        pub fn utilization_rate(&self) -> ScopeResult<Decimal> {
+            let total_supply = self.total_supply()?;
+            if total_supply == Decimal::zero() {
+                return Ok(Decimal::zero());
+            }
+            Ok(self.borrowed_amount.try_div(total_supply)?)
+        }
+
+        /// Update borrow rate and accrue interest
+        ///
+        /// Interests are compounded every slot, klend uses an approximation of the compounding
+        /// that differs only marginally on the short periods elapsed between two refreshes.
+        pub fn accrue_interest(&mut self, current_slot: Slot) -> ScopeResult<()> {
+            let slots_elapsed = current_slot
+                .checked_sub(self.last_update_slot)
+                .ok_or(ScopeError::IntegerOverflow)?;
+            if slots_elapsed == 0 {
+                return Ok(());
+            }
+            let curve = BorrowRateCurve {
+                points: self.borrow_rate_curve,
+            };
+            let current_borrow_rate = curve.get_borrow_rate(self.utilization_rate()?)?;
+            let host_fixed_interest_rate =
+                Decimal::from(u64::from(self.host_fixed_interest_rate_bps)).try_div(10_000)?;
+            let protocol_take_rate = Rate::from_percent(self.protocol_take_rate_pct);
+
+            let compounded_interest_rate = compound_rate(
+                current_borrow_rate.try_add(host_fixed_interest_rate)?,
+                slots_elapsed,
+            )?;
+            let compounded_fixed_rate = compound_rate(host_fixed_interest_rate, slots_elapsed)?;
+
+            let previous_debt = self.borrowed_amount;
+            let new_debt = previous_debt.try_mul(compounded_interest_rate)?;
+            let fixed_host_fee = previous_debt
+                .try_mul(compounded_fixed_rate)?
+                .try_sub(previous_debt)?;
+            let net_new_variable_debt = new_debt.try_sub(previous_debt)?.try_sub(fixed_host_fee)?;
+            let variable_protocol_fee = net_new_variable_debt.try_mul(protocol_take_rate)?;
+
+            self.total_fees = self
+                .total_fees
+                .try_add(variable_protocol_fee)?
+                .try_add(fixed_host_fee)?;
+            self.borrowed_amount = new_debt;
+            self.last_update_slot = current_slot;
+            Ok(())
+        }
+
+        /// Convert an amount of collateral (cTokens) to liquidity
+        pub fn collateral_to_liquidity(&self, collateral_amount: u64) -> ScopeResult<u64> {
+            let total_supply = self.total_supply()?;
+            if self.collateral_mint_total_supply == 0 || total_supply == Decimal::zero() {
+                // Initial collateral rate is 1
+                return Ok(collateral_amount);
+            }
+            Ok(Decimal::from(collateral_amount)
+                .try_mul(total_supply)?
+                .try_div(self.collateral_mint_total_supply)?
+                .try_floor()?)
+        }
+    }
+
+    /// Compound an annual rate over the elapsed slots
+    fn compound_rate(annual_rate: Decimal, slots_elapsed: u64) -> ScopeResult<Rate> {
+        let slot_interest_rate = Rate::try_from(annual_rate)?.try_div(SLOTS_PER_YEAR)?;
+        Ok(Rate::one()
+            .try_add(slot_interest_rate)?
+            .try_pow(slots_elapsed)?)
+    }
+
+    /// Convert a klend scaled fraction (60 fractional bits) to a decimal
+    fn sf_to_decimal(sf: u128) -> Decimal {
+        // Max value is 2^68 * 10^18 which fits in a u128
+        let scaled = (U256::from(sf) * U256::from(WAD)) >> 60;
+        Decimal::from_scaled_val(scaled.as_u128())
+    }
+}
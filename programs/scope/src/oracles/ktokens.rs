@@ -142,7 +142,7 @@ where
         holdings.total_sum,
         strategy_account_ref.shares_issued,
         strategy_account_ref.shares_mint_decimals,
-    );
+    )?;
 
     // Get the least-recently updated component price from both scope chains
     let (last_updated_slot, unix_timestamp) = get_component_px_last_update(
@@ -164,7 +164,7 @@ fn get_clmm<'a, 'info>(
     position: &'a AccountInfo<'info>,
     strategy: &WhirlpoolStrategy,
 ) -> Result<Box<dyn Clmm + 'a>> {
-    let dex = DEX::try_from(strategy.strategy_dex).unwrap();
+    let dex = DEX::try_from(strategy.strategy_dex).map_err(|_| ScopeError::ConversionFailure)?;
     let clmm: Box<dyn Clmm> = match dex {
         DEX::Orca => {
             let pool = account_deserialize::<OrcaWhirlpool>(pool)?;
@@ -311,15 +311,15 @@ fn get_price_per_full_share(
     total_holdings_value_scaled: U128,
     shares_issued: u64,
     shares_decimals: u64,
-) -> Price {
+) -> Result<Price> {
     if shares_issued == 0 {
         // Assume price is 0 without shares issued
-        Price { value: 0, exp: 1 }
+        Ok(Price { value: 0, exp: 1 })
     } else {
         let price_decimal = Decimal::from(underlying_unit(shares_decimals))
             * total_holdings_value_scaled
             / (u128::from(SCALE_FACTOR) * u128::from(shares_issued));
-        (price_decimal).into()
+        Ok(price_decimal.try_into()?)
     }
 }
 
@@ -334,20 +334,16 @@ pub(super) mod price_utils {
         U128::from(base).pow(U128::from(exp))
     }
 
-    fn abs_diff(a: i32, b: i32) -> u32 {
-        if a > b {
-            a.checked_sub(b).unwrap().try_into().unwrap()
-        } else {
-            b.checked_sub(a).unwrap().try_into().unwrap()
-        }
-    }
-
     fn decimals_factor(decimals_a: u64, decimals_b: u64) -> Result<(U128, u64)> {
         let decimals_a = i32::try_from(decimals_a).map_err(|_e| ScopeError::IntegerOverflow)?;
         let decimals_b = i32::try_from(decimals_b).map_err(|_e| ScopeError::IntegerOverflow)?;
 
-        let diff = abs_diff(decimals_a, decimals_b);
-        let factor = U128::from(10_u64.pow(diff));
+        let diff = decimals_a.abs_diff(decimals_b);
+        let factor = U128::from(
+            10_u64
+                .checked_pow(diff)
+                .ok_or(ScopeError::IntegerOverflow)?,
+        );
         Ok((factor, u64::from(diff)))
     }
 
@@ -369,7 +365,7 @@ pub(super) mod price_utils {
 
         let price_a_to_b_dec = price_a_dec / price_b_dec;
 
-        let price_a_to_b: crate::Price = price_a_to_b_dec.into();
+        let price_a_to_b: crate::Price = price_a_to_b_dec.try_into()?;
 
         Ok(yvaults::utils::price::Price {
             value: price_a_to_b.value,
@@ -392,11 +388,21 @@ pub(super) mod price_utils {
         let (decimals_factor, decimals_diff) = decimals_factor(decimals_a, decimals_b)?;
         let px = U128::from(price.value);
         let (scaled_price, final_exp) = if decimals_b > decimals_a {
-            (px.checked_mul(decimals_factor).unwrap(), price.exp)
+            (
+                px.checked_mul(decimals_factor)
+                    .ok_or(ScopeError::IntegerOverflow)?,
+                price.exp,
+            )
         } else {
             // If we divide by 10 ^ (decimals_a - decimals_b) here we lose precision
             // So instead we lift the price even more (by the diff) and assume a bigger exp
-            (px, price.exp.checked_add(decimals_diff).unwrap())
+            (
+                px,
+                price
+                    .exp
+                    .checked_add(decimals_diff)
+                    .ok_or(ScopeError::IntegerOverflow)?,
+            )
         };
 
         let two_factor = pow(2, 64);
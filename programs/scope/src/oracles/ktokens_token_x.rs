@@ -138,13 +138,14 @@ where
 
     // Get the least-recently updated component price from both scope chains
     let last_updated_slot = clock.slot;
-    let unix_timestamp = u64::try_from(clock.unix_timestamp).expect("Unix timestamp negative");
+    let unix_timestamp =
+        u64::try_from(clock.unix_timestamp).map_err(|_| ScopeError::BadTimestamp)?;
 
     let price = if num_shares == 0 {
         // Assume price is 0 without shares issued
         Price { value: 0, exp: 1 }
     } else {
-        let price_lamport_to_lamport = u64_div_to_price(num_token_x, num_shares);
+        let price_lamport_to_lamport = u64_div_to_price(num_token_x, num_shares)?;
 
         // Final price need to be adjusted by the number of decimals of the kToken and the token X
         let share_decimals = strategy_account_ref.shares_mint_decimals;
@@ -157,7 +158,7 @@ where
             price_lamport_to_lamport,
             share_decimals,
             token_decimals,
-        )
+        )?
     };
 
     Ok(DatedPrice {
@@ -173,7 +174,7 @@ fn get_clmm<'a, 'info>(
     position: &'a AccountInfo<'info>,
     strategy: &WhirlpoolStrategy,
 ) -> Result<Box<dyn Clmm + 'a>> {
-    let dex = DEX::try_from(strategy.strategy_dex).unwrap();
+    let dex = DEX::try_from(strategy.strategy_dex).map_err(|_| ScopeError::ConversionFailure)?;
     let clmm: Box<dyn Clmm> = match dex {
         DEX::Orca => {
             let pool = account_deserialize::<OrcaWhirlpool>(pool)?;
@@ -6,15 +6,20 @@ use decimal_wad::decimal::U192;
 pub use lb_clmm_itf as lb_clmm;
 use solana_program::program_pack::Pack;
 
-use crate::utils::{math, zero_copy_deserialize};
-use crate::{DatedPrice, Result, ScopeError};
+use crate::utils::{check_min_liquidity, get_min_liquidity, math, zero_copy_deserialize};
+use crate::{DatedPrice, Result, ScopeError, GENERIC_DATA_LEN};
 
 /// Gives the price of the given token pair in the given pool
+///
+/// If a minimum liquidity is configured in the entry generic data (see [`crate::utils::get_min_liquidity`]),
+/// the bin array containing the active bin is expected as third extra account and the value of the active
+/// bin reserves is checked against this minimum.
 pub fn get_price<'a, 'b>(
     a_to_b: bool,
     pool: &AccountInfo,
     clock: &Clock,
     extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+    generic_data: &[u8; GENERIC_DATA_LEN],
 ) -> Result<DatedPrice>
 where
     'a: 'b,
@@ -61,6 +66,40 @@ where
                 msg!("Math overflow when calculating dlmm price");
                 error!(ScopeError::MathOverflow)
             })?;
+
+    // Check active bin liquidity
+    if get_min_liquidity(generic_data) > 0 {
+        let bin_array_account_info = extra_accounts
+            .next()
+            .ok_or(ScopeError::AccountsAndTokenMismatch)?;
+        require_keys_eq!(
+            *bin_array_account_info.owner,
+            lb_clmm::ID,
+            ScopeError::UnexpectedAccount
+        );
+        let bin_array: Ref<'_, lb_clmm::BinArray> = zero_copy_deserialize(bin_array_account_info)?;
+        require_keys_eq!(
+            bin_array.lb_pair,
+            pool.key(),
+            ScopeError::AccountsAndTokenMismatch
+        );
+        let active_bin = bin_array.get_bin(lb_pair_state.active_id).ok_or_else(|| {
+            msg!(
+                "Bin array {} does not contain the active bin {}",
+                bin_array_account_info.key,
+                lb_pair_state.active_id
+            );
+            error!(ScopeError::AccountsAndTokenMismatch)
+        })?;
+        let active_bin_value = math::dlmm_bin_value_in_quote(
+            a_to_b,
+            active_bin.amount_x,
+            active_bin.amount_y,
+            q64x64_price,
+        );
+        check_min_liquidity(pool.key, active_bin_value, generic_data)?;
+    }
+
     let q64x64_price = if a_to_b {
         U192::from(q64x64_price)
     } else {
@@ -78,7 +117,7 @@ where
         lamport_price,
         src_token_decimals.into(),
         dst_token_decimals.into(),
-    );
+    )?;
 
     // Return price
     Ok(DatedPrice {
@@ -0,0 +1,361 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::state::Mint;
+use solana_program::program_pack::Pack;
+
+use self::meteora_amm::{Pool, Vault};
+use super::constant_product::{
+    get_mint_decimals, get_price_from_reserves, get_vault_amount, PoolReserves,
+};
+use crate::{DatedPrice, Result, ScopeError, GENERIC_DATA_LEN};
+
+/// Gives the price of the given token pair in the given Meteora dynamic AMM pool
+///
+/// Only constant-product pools are supported, stable pools are rejected.
+pub fn get_price<'a, 'b>(
+    a_to_b: bool,
+    pool: &AccountInfo,
+    clock: &Clock,
+    extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> Result<DatedPrice>
+where
+    'a: 'b,
+{
+    let reserves = get_reserves(pool, clock, extra_accounts)?;
+    get_price_from_reserves(a_to_b, pool.key, &reserves, clock, generic_data)
+}
+
+/// Gives the reserves of the given Meteora dynamic AMM pool
+///
+/// The pool tokens are deposited in Meteora vaults, the reserves are the share of the unlocked
+/// amount of each vault owned by the pool (through its vault LP tokens).
+pub fn get_reserves<'a, 'b>(
+    pool: &AccountInfo,
+    clock: &Clock,
+    extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+) -> Result<PoolReserves>
+where
+    'a: 'b,
+{
+    // Get extra accounts
+    let mut next_account = || {
+        extra_accounts
+            .next()
+            .ok_or(ScopeError::AccountsAndTokenMismatch)
+    };
+    let mint_token_a_account_info = next_account()?;
+    let mint_token_b_account_info = next_account()?;
+    let a_vault_account_info = next_account()?;
+    let b_vault_account_info = next_account()?;
+    let a_vault_lp_account_info = next_account()?;
+    let b_vault_lp_account_info = next_account()?;
+    let a_vault_lp_mint_account_info = next_account()?;
+    let b_vault_lp_mint_account_info = next_account()?;
+
+    // Load main account
+    let pool_data = Pool::from_account(pool)?;
+    pool_data.check_constant_product()?;
+
+    // Check extra accounts pubkeys
+    require_keys_eq!(
+        pool_data.a_vault,
+        a_vault_account_info.key(),
+        ScopeError::AccountsAndTokenMismatch
+    );
+    require_keys_eq!(
+        pool_data.b_vault,
+        b_vault_account_info.key(),
+        ScopeError::AccountsAndTokenMismatch
+    );
+
+    // Load extra accounts
+    let mint_a_decimals = get_mint_decimals(mint_token_a_account_info, &pool_data.token_a_mint)?;
+    let mint_b_decimals = get_mint_decimals(mint_token_b_account_info, &pool_data.token_b_mint)?;
+
+    let current_ts: u64 = clock
+        .unix_timestamp
+        .try_into()
+        .map_err(|_| ScopeError::BadTimestamp)?;
+    let reserve_a = get_pool_vault_reserve(
+        a_vault_account_info,
+        a_vault_lp_account_info,
+        a_vault_lp_mint_account_info,
+        &pool_data.a_vault_lp,
+        current_ts,
+    )?;
+    let reserve_b = get_pool_vault_reserve(
+        b_vault_account_info,
+        b_vault_lp_account_info,
+        b_vault_lp_mint_account_info,
+        &pool_data.b_vault_lp,
+        current_ts,
+    )?;
+
+    Ok(PoolReserves {
+        reserve_a,
+        reserve_b,
+        decimals_a: mint_a_decimals,
+        decimals_b: mint_b_decimals,
+        lp_mint: pool_data.lp_mint,
+    })
+}
+
+/// Get the amount of tokens owned by the pool in a Meteora vault
+fn get_pool_vault_reserve(
+    vault_account_info: &AccountInfo,
+    pool_vault_lp_account_info: &AccountInfo,
+    vault_lp_mint_account_info: &AccountInfo,
+    expected_pool_vault_lp: &Pubkey,
+    current_ts: u64,
+) -> Result<u64> {
+    let vault = Vault::from_account(vault_account_info)?;
+    let pool_lp_amount = get_vault_amount(pool_vault_lp_account_info, expected_pool_vault_lp)?;
+
+    require_keys_eq!(
+        vault.lp_mint,
+        vault_lp_mint_account_info.key(),
+        ScopeError::AccountsAndTokenMismatch
+    );
+    let vault_lp_supply = {
+        let mint_borrow = vault_lp_mint_account_info.data.borrow();
+        Mint::unpack(&mint_borrow)?.supply
+    };
+
+    vault
+        .get_amount_by_share(current_ts, pool_lp_amount, vault_lp_supply)
+        .ok_or_else(|| error!(ScopeError::MathOverflow))
+}
+
+pub fn validate_pool_account(pool: &AccountInfo) -> Result<()> {
+    Pool::from_account(pool)?.check_constant_product()
+}
+
+pub mod meteora_amm {
+    use anchor_lang::prelude::*;
+    use arrayref::array_ref;
+
+    use crate::{Result, ScopeError};
+
+    declare_id!("Eo7WjKq67rjJQSZxS6z3YkapzY3eMj6Xy8X5EQVn5UaB");
+
+    pub mod vault_program {
+        use anchor_lang::prelude::*;
+
+        declare_id!("24Uqj9JCLxUeoC3hGfh5W3s9FM9uCHDS2SG3LYwBpyTi");
+    }
+
+    pub const LOCKED_PROFIT_DEGRADATION_DENOMINATOR: u128 = 1_000_000_000_000;
+
+    fn check_data(data: &[u8], discriminator: &[u8; 8], min_len: usize) -> Result<()> {
+        if data.len() < min_len {
+            msg!("Meteora account has an invalid size");
+            return err!(ScopeError::UnableToDeserializeAccount);
+        }
+        if data[..8] != *discriminator {
+            msg!("Meteora account has an invalid discriminator");
+            return err!(ScopeError::InvalidAccountDiscriminator);
+        }
+        Ok(())
+    }
+
+    /// Fields of Meteora's dynamic AMM `Pool` needed to price the pool
+    pub struct Pool {
+        pub lp_mint: Pubkey,
+        pub token_a_mint: Pubkey,
+        pub token_b_mint: Pubkey,
+        pub a_vault: Pubkey,
+        pub b_vault: Pubkey,
+        pub a_vault_lp: Pubkey,
+        pub b_vault_lp: Pubkey,
+        pub curve_type: CurveType,
+    }
+
+    /// Discriminant of the pool `CurveType` enum (its stable variant carries data we don't read)
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum CurveType {
+        ConstantProduct,
+        Stable,
+        Unknown(u8),
+    }
+
+    impl From<u8> for CurveType {
+        fn from(tag: u8) -> Self {
+            match tag {
+                0 => CurveType::ConstantProduct,
+                1 => CurveType::Stable,
+                tag => CurveType::Unknown(tag),
+            }
+        }
+    }
+
+    impl Pool {
+        pub const DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+
+        const LP_MINT_OFFSET: usize = 8;
+        const TOKEN_A_MINT_OFFSET: usize = 40;
+        const TOKEN_B_MINT_OFFSET: usize = 72;
+        const A_VAULT_OFFSET: usize = 104;
+        const B_VAULT_OFFSET: usize = 136;
+        const A_VAULT_LP_OFFSET: usize = 168;
+        const B_VAULT_LP_OFFSET: usize = 200;
+        // After the fees, the stake, the bootstrapping, the partner info and the padding
+        const CURVE_TYPE_OFFSET: usize = 874;
+        const MIN_LEN: usize = 875;
+
+        pub fn from_account(account: &AccountInfo) -> Result<Self> {
+            require_keys_eq!(*account.owner, ID, ScopeError::UnexpectedAccount);
+            Self::unpack(&account.data.borrow())
+        }
+
+        pub fn unpack(data: &[u8]) -> Result<Self> {
+            check_data(data, &Self::DISCRIMINATOR, Self::MIN_LEN)?;
+            let read_pubkey = |offset: usize| Pubkey::new_from_array(*array_ref![data, offset, 32]);
+            Ok(Self {
+                lp_mint: read_pubkey(Self::LP_MINT_OFFSET),
+                token_a_mint: read_pubkey(Self::TOKEN_A_MINT_OFFSET),
+                token_b_mint: read_pubkey(Self::TOKEN_B_MINT_OFFSET),
+                a_vault: read_pubkey(Self::A_VAULT_OFFSET),
+                b_vault: read_pubkey(Self::B_VAULT_OFFSET),
+                a_vault_lp: read_pubkey(Self::A_VAULT_LP_OFFSET),
+                b_vault_lp: read_pubkey(Self::B_VAULT_LP_OFFSET),
+                curve_type: data[Self::CURVE_TYPE_OFFSET].into(),
+            })
+        }
+
+        pub fn check_constant_product(&self) -> Result<()> {
+            if self.curve_type != CurveType::ConstantProduct {
+                msg!("Meteora pool curve is {:?}", self.curve_type);
+                return err!(ScopeError::UnsupportedPoolCurve);
+            }
+            Ok(())
+        }
+    }
+
+    /// Fields of Meteora's `Vault` needed to compute the amount owned by a pool
+    pub struct Vault {
+        pub total_amount: u64,
+        pub lp_mint: Pubkey,
+        pub last_updated_locked_profit: u64,
+        pub last_report: u64,
+        pub locked_profit_degradation: u64,
+    }
+
+    impl Vault {
+        pub const DISCRIMINATOR: [u8; 8] = [211, 8, 232, 43, 2, 152, 117, 119];
+
+        const TOTAL_AMOUNT_OFFSET: usize = 11;
+        const LP_MINT_OFFSET: usize = 115;
+        // After the 30 strategies, the base, the admin and the operator
+        const LAST_UPDATED_LOCKED_PROFIT_OFFSET: usize = 1203;
+        const LAST_REPORT_OFFSET: usize = 1211;
+        const LOCKED_PROFIT_DEGRADATION_OFFSET: usize = 1219;
+        const MIN_LEN: usize = 1227;
+
+        pub fn from_account(account: &AccountInfo) -> Result<Self> {
+            require_keys_eq!(
+                *account.owner,
+                vault_program::ID,
+                ScopeError::UnexpectedAccount
+            );
+            Self::unpack(&account.data.borrow())
+        }
+
+        pub fn unpack(data: &[u8]) -> Result<Self> {
+            check_data(data, &Self::DISCRIMINATOR, Self::MIN_LEN)?;
+            let read_u64 = |offset: usize| u64::from_le_bytes(*array_ref![data, offset, 8]);
+            Ok(Self {
+                total_amount: read_u64(Self::TOTAL_AMOUNT_OFFSET),
+                lp_mint: Pubkey::new_from_array(*array_ref![data, Self::LP_MINT_OFFSET, 32]),
+                last_updated_locked_profit: read_u64(Self::LAST_UPDATED_LOCKED_PROFIT_OFFSET),
+                last_report: read_u64(Self::LAST_REPORT_OFFSET),
+                locked_profit_degradation: read_u64(Self::LOCKED_PROFIT_DEGRADATION_OFFSET),
+            })
+        }
+
+        /// Profit of the last report that is still locked (linearly released over time)
+        pub fn calculate_locked_profit(&self, current_ts: u64) -> Option<u64> {
+            let duration = u128::from(current_ts.checked_sub(self.last_report)?);
+            let locked_fund_ratio = duration * u128::from(self.locked_profit_degradation);
+            if locked_fund_ratio > LOCKED_PROFIT_DEGRADATION_DENOMINATOR {
+                return Some(0);
+            }
+            let locked_profit = u128::from(self.last_updated_locked_profit)
+                * (LOCKED_PROFIT_DEGRADATION_DENOMINATOR - locked_fund_ratio)
+                / LOCKED_PROFIT_DEGRADATION_DENOMINATOR;
+            u64::try_from(locked_profit).ok()
+        }
+
+        pub fn get_unlocked_amount(&self, current_ts: u64) -> Option<u64> {
+            self.total_amount
+                .checked_sub(self.calculate_locked_profit(current_ts)?)
+        }
+
+        /// Amount of tokens corresponding to `share` vault LP tokens
+        pub fn get_amount_by_share(
+            &self,
+            current_ts: u64,
+            share: u64,
+            total_supply: u64,
+        ) -> Option<u64> {
+            if total_supply == 0 {
+                return Some(0);
+            }
+            let total_amount = self.get_unlocked_amount(current_ts)?;
+            let amount = u128::from(share) * u128::from(total_amount) / u128::from(total_supply);
+            u64::try_from(amount).ok()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn vault(total_amount: u64, locked_profit: u64, last_report: u64) -> Vault {
+            Vault {
+                total_amount,
+                lp_mint: Pubkey::default(),
+                last_updated_locked_profit: locked_profit,
+                last_report,
+                // Released in 1000 seconds
+                locked_profit_degradation: (LOCKED_PROFIT_DEGRADATION_DENOMINATOR / 1000) as u64,
+            }
+        }
+
+        fn pool_data(curve_type: u8) -> Vec<u8> {
+            let mut data = vec![0; Pool::MIN_LEN];
+            data[..8].copy_from_slice(&Pool::DISCRIMINATOR);
+            data[Pool::CURVE_TYPE_OFFSET] = curve_type;
+            data
+        }
+
+        #[test]
+        fn test_pool_curve_type() {
+            let pool = Pool::unpack(&pool_data(0)).unwrap();
+            assert_eq!(pool.curve_type, CurveType::ConstantProduct);
+            assert!(pool.check_constant_product().is_ok());
+
+            let pool = Pool::unpack(&pool_data(1)).unwrap();
+            assert_eq!(pool.curve_type, CurveType::Stable);
+            assert_eq!(
+                pool.check_constant_product().unwrap_err(),
+                error!(ScopeError::UnsupportedPoolCurve)
+            );
+        }
+
+        #[test]
+        fn test_locked_profit_release() {
+            let vault = vault(10_000, 1_000, 100);
+            assert_eq!(vault.get_unlocked_amount(100), Some(9_000));
+            assert_eq!(vault.get_unlocked_amount(600), Some(9_500));
+            assert_eq!(vault.get_unlocked_amount(1_100), Some(10_000));
+            assert_eq!(vault.get_unlocked_amount(5_000), Some(10_000));
+        }
+
+        #[test]
+        fn test_amount_by_share() {
+            let vault = vault(10_000, 0, 100);
+            assert_eq!(vault.get_amount_by_share(200, 250, 1_000), Some(2_500));
+            assert_eq!(vault.get_amount_by_share(200, 250, 0), Some(0));
+        }
+    }
+}
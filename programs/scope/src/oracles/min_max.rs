@@ -0,0 +1,153 @@
+//! Lower or higher price of two entries of the feed
+//!
+//! Typically used to value collateral at the lower of the spot price and its EMA and debt at
+//! the higher of them. The inputs are configured in the entry generic data, see
+//! [`MinMaxConfig`]. The result carries the older slot and timestamp of the two inputs.
+//!
+//! Both inputs come from the feed prices and TWAPs accounts, the entry is mapped to the scope
+//! program id.
+
+use anchor_lang::prelude::*;
+use arrayref::array_ref;
+
+use super::twap;
+use crate::{
+    DatedPrice, OraclePrices, OracleTwaps, Result, ScopeError, ScopeResult, GENERIC_DATA_LEN,
+    MAX_ENTRIES,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MinMax {
+    Min,
+    Max,
+}
+
+/// Inputs configured in the entry generic data
+///
+/// - `[0..2]`: index of the first entry (u16, little endian)
+/// - `[2..4]`: index of the second entry (u16, little endian)
+/// - `[4]`: flags, bit 0 set to use the EMA TWAP of the second entry (from the twaps account)
+///   instead of its price
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MinMaxConfig {
+    pub first_index: usize,
+    pub second_index: usize,
+    pub second_is_twap: bool,
+}
+
+impl MinMaxConfig {
+    const SECOND_IS_TWAP_FLAG: u8 = 1;
+
+    pub fn from_generic_data(generic_data: &[u8; GENERIC_DATA_LEN]) -> ScopeResult<Self> {
+        let first_index = usize::from(u16::from_le_bytes(*array_ref![generic_data, 0, 2]));
+        let second_index = usize::from(u16::from_le_bytes(*array_ref![generic_data, 2, 2]));
+        if first_index >= MAX_ENTRIES || second_index >= MAX_ENTRIES {
+            msg!("Invalid entry indexes {first_index} and {second_index}");
+            return Err(ScopeError::InvalidGenericData);
+        }
+        let second_is_twap = generic_data[4] & Self::SECOND_IS_TWAP_FLAG != 0;
+        if first_index == second_index && !second_is_twap {
+            msg!("The two inputs must be different");
+            return Err(ScopeError::InvalidGenericData);
+        }
+        Ok(Self {
+            first_index,
+            second_index,
+            second_is_twap,
+        })
+    }
+}
+
+pub fn get_price(
+    min_max: MinMax,
+    oracle_prices: &OraclePrices,
+    oracle_twaps: &OracleTwaps,
+    clock: &Clock,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> Result<DatedPrice> {
+    let config = MinMaxConfig::from_generic_data(generic_data)?;
+    let first = oracle_prices.prices[config.first_index];
+    let second = if config.second_is_twap {
+        twap::get_ema_price(oracle_twaps, config.second_index, clock)?
+    } else {
+        oracle_prices.prices[config.second_index]
+    };
+    if first.price.value == 0 || second.price.value == 0 {
+        msg!("One of the inputs has no price");
+        return err!(ScopeError::PriceNotValid);
+    }
+
+    Ok(select(min_max, &first, &second))
+}
+
+/// Select the lower or higher price of the two inputs, with the older of their dates
+fn select(min_max: MinMax, first: &DatedPrice, second: &DatedPrice) -> DatedPrice {
+    let first_is_lower = first.price <= second.price;
+    let price = match (min_max, first_is_lower) {
+        (MinMax::Min, true) | (MinMax::Max, false) => first.price,
+        (MinMax::Min, false) | (MinMax::Max, true) => second.price,
+    };
+    DatedPrice {
+        price,
+        last_updated_slot: first.last_updated_slot.min(second.last_updated_slot),
+        unix_timestamp: first.unix_timestamp.min(second.unix_timestamp),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Price;
+
+    fn dated_price(value: u64, exp: u64, slot: u64, ts: u64) -> DatedPrice {
+        DatedPrice {
+            price: Price { value, exp },
+            last_updated_slot: slot,
+            unix_timestamp: ts,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let spot = dated_price(10_150, 2, 100, 1_000);
+        let ema = dated_price(1_010, 1, 90, 990);
+
+        let min = select(MinMax::Min, &spot, &ema);
+        assert_eq!(min.price, ema.price);
+        assert_eq!(min.last_updated_slot, 90);
+        assert_eq!(min.unix_timestamp, 990);
+
+        let max = select(MinMax::Max, &spot, &ema);
+        assert_eq!(max.price, spot.price);
+        assert_eq!(max.last_updated_slot, 90);
+        assert_eq!(max.unix_timestamp, 990);
+
+        // Prices below the 18 decimals of a `Decimal` are still ordered
+        let small = dated_price(1, 25, 100, 1_000);
+        let smaller = dated_price(9, 26, 100, 1_000);
+        assert_eq!(select(MinMax::Min, &small, &smaller).price, smaller.price);
+        assert_eq!(select(MinMax::Max, &small, &smaller).price, small.price);
+    }
+
+    #[test]
+    fn test_config_from_generic_data() {
+        let mut generic_data = [0; GENERIC_DATA_LEN];
+        generic_data[0..2].copy_from_slice(&3_u16.to_le_bytes());
+        generic_data[2..4].copy_from_slice(&3_u16.to_le_bytes());
+        assert_eq!(
+            MinMaxConfig::from_generic_data(&generic_data).unwrap_err(),
+            ScopeError::InvalidGenericData
+        );
+        generic_data[4] = 1;
+        assert_eq!(
+            MinMaxConfig::from_generic_data(&generic_data).unwrap(),
+            MinMaxConfig {
+                first_index: 3,
+                second_index: 3,
+                second_is_twap: true,
+            }
+        );
+    }
+}
@@ -1,25 +1,50 @@
+pub mod account_field;
+pub mod capped_floored;
+pub mod chainlink;
+pub mod constant_product;
+pub mod constant_product_lp;
 pub mod ctokens;
+pub mod fixed_rate;
+pub mod history;
 #[cfg(feature = "yvaults")]
 pub mod ktokens;
 #[cfg(feature = "yvaults")]
 pub mod ktokens_token_x;
 
+pub mod index_price;
+pub mod inverse;
 pub mod jupiter_lp;
+pub mod klend_ctokens;
 pub mod meteora_dlmm;
+pub mod meteora_dynamic_amm;
+pub mod min_max;
 pub mod msol_stake;
+pub mod openbook_v2;
+pub mod orca_token_swap;
 pub mod orca_whirlpool;
+pub mod orderbook;
+pub mod phoenix;
 pub mod pyth;
 pub mod pyth_ema;
+pub mod raydium_amm_v4;
 pub mod raydium_ammv3;
+pub mod raydium_cpmm;
+pub mod signed_price;
 pub mod spl_stake;
 pub mod switchboard_v2;
 pub mod twap;
+pub mod twap_stats;
 
-use anchor_lang::prelude::{err, AccountInfo, Clock, Context, Result};
+use anchor_lang::prelude::{err, msg, require_keys_eq, AccountInfo, Clock, Context, Key, Result};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use serde::{Deserialize, Serialize};
 
-use crate::{DatedPrice, OracleMappings, OracleTwaps, ScopeError};
+use anchor_lang::prelude::Pubkey;
+
+use crate::{
+    DatedPrice, OracleMappings, OraclePrices, OracleTwaps, ScopeError, ScopeResult,
+    GENERIC_DATA_LEN,
+};
 
 use self::ktokens_token_x::TokenTypes;
 
@@ -32,6 +57,19 @@ pub fn check_context<T>(ctx: &Context<T>) -> Result<()> {
     Ok(())
 }
 
+/// Check that the configuration account of an entry (index definition, publishers...) was created
+/// for the feed of the given prices account
+pub fn check_entry_account_feed(
+    account_oracle_prices: &Pubkey,
+    oracle_prices_pk: &Pubkey,
+) -> ScopeResult<()> {
+    if account_oracle_prices != oracle_prices_pk {
+        msg!("Account is configured for the feed {account_oracle_prices} instead of {oracle_prices_pk}");
+        return Err(ScopeError::UnexpectedAccount);
+    }
+    Ok(())
+}
+
 #[derive(
     Serialize, Deserialize, IntoPrimitive, TryFromPrimitive, Clone, Copy, PartialEq, Eq, Debug,
 )]
@@ -89,6 +127,69 @@ pub enum OracleType {
     MeteoraDlmmAtoB = 18,
     /// Meteora's DLMM B to A
     MeteoraDlmmBtoA = 19,
+    /// Raydium's AMM v3 TWAP (CLMM) A to B, computed from the pool observations
+    RaydiumAmmV3TwapAtoB = 20,
+    /// Raydium's AMM v3 TWAP (CLMM) B to A, computed from the pool observations
+    RaydiumAmmV3TwapBtoA = 21,
+    /// Raydium's AMM v4 price (constant product) A to B
+    RaydiumAmmV4AtoB = 22,
+    /// Raydium's AMM v4 price (constant product) B to A
+    RaydiumAmmV4BtoA = 23,
+    /// Raydium's CPMM price (constant product) A to B
+    RaydiumCpmmAtoB = 24,
+    /// Raydium's CPMM price (constant product) B to A
+    RaydiumCpmmBtoA = 25,
+    /// Orca's legacy token swap price (constant product) A to B
+    OrcaTokenSwapAtoB = 26,
+    /// Orca's legacy token swap price (constant product) B to A
+    OrcaTokenSwapBtoA = 27,
+    /// Meteora's dynamic AMM price (constant product) A to B
+    MeteoraDynamicAmmAtoB = 28,
+    /// Meteora's dynamic AMM price (constant product) B to A
+    MeteoraDynamicAmmBtoA = 29,
+    /// LP token of a constant-product pool priced from the Scope prices of the pool tokens
+    ConstantProductLpToken = 30,
+    /// Kamino Lending's cTokens, number of liquidity tokens for 1 collateral token
+    KLendCToken = 31,
+    /// Solend's reserve current borrow APR, as a fraction (0.05 for 5%)
+    SolendBorrowRate = 32,
+    /// Solend's reserve current supply APR, as a fraction (0.05 for 5%)
+    SolendSupplyRate = 33,
+    /// Chainlink's OCR2 feed (transmissions account of the store program)
+    Chainlink = 34,
+    /// OpenBook v2 market mid-price (quote per base)
+    /// The best bid and ask can be stored along the price, see [`orderbook`]
+    OpenBookV2MidPrice = 35,
+    /// Phoenix market mid-price (quote per base)
+    /// The best bid and ask can be stored along the price, see [`orderbook`]
+    PhoenixMidPrice = 36,
+    /// Price pushed by a whitelisted publisher through an ed25519 signed message
+    /// The mapping is the [`crate::PricePublishers`] account of the entry, see [`signed_price`]
+    SignedPrice = 37,
+    /// Price accruing at a fixed APY from a start price and timestamp, see [`fixed_rate`]
+    /// The mapping is the scope program id
+    FixedRate = 38,
+    /// Price of another entry clamped to a configured range, see [`capped_floored`]
+    /// The mapping is the scope program id
+    CappedFloored = 39,
+    /// Lower price of two entries (or of an entry and an EMA TWAP), see [`min_max`]
+    /// The mapping is the scope program id
+    MinOfTwo = 40,
+    /// Higher price of two entries (or of an entry and an EMA TWAP), see [`min_max`]
+    /// The mapping is the scope program id
+    MaxOfTwo = 41,
+    /// Weighted basket index of other entries
+    /// The mapping is the [`crate::IndexDefinition`] account of the entry, see [`index_price`]
+    IndexPrice = 42,
+    /// Inverse (1/x) of the price of another entry, see [`inverse`]
+    /// The mapping is the scope program id
+    InversePrice = 43,
+    /// Integer field read from an account owned by a configured program
+    /// The mapping is the [`crate::AccountFieldReader`] account of the entry, see [`account_field`]
+    AccountField = 44,
+    /// Volatility, high or low of the EMA TWAP of another entry, see [`twap_stats`]
+    /// The mapping is the scope program id
+    TwapStats = 45,
 }
 
 impl OracleType {
@@ -96,6 +197,22 @@ impl OracleType {
         matches!(self, OracleType::ScopeTwap)
     }
 
+    /// The price is computed from the feed accounts and the entry generic data only
+    ///
+    /// Such entries are mapped to the scope program id, see [`validate_computed_generic_data`].
+    pub fn is_computed_from_feed(&self) -> bool {
+        matches!(
+            self,
+            OracleType::ScopeTwap
+                | OracleType::FixedRate
+                | OracleType::CappedFloored
+                | OracleType::MinOfTwo
+                | OracleType::MaxOfTwo
+                | OracleType::InversePrice
+                | OracleType::TwapStats
+        )
+    }
+
     /// Get the number of compute unit needed to refresh the price of a token
     pub fn get_update_cu_budget(&self) -> u32 {
         match self {
@@ -115,6 +232,26 @@ impl OracleType {
             | OracleType::RaydiumAmmV3BtoA => 20_000,
             OracleType::MeteoraDlmmAtoB | OracleType::MeteoraDlmmBtoA => 30_000,
             OracleType::JupiterLpCompute => 120_000,
+            OracleType::RaydiumAmmV3TwapAtoB | OracleType::RaydiumAmmV3TwapBtoA => 60_000,
+            OracleType::RaydiumAmmV4AtoB
+            | OracleType::RaydiumAmmV4BtoA
+            | OracleType::RaydiumCpmmAtoB
+            | OracleType::RaydiumCpmmBtoA => 25_000,
+            OracleType::OrcaTokenSwapAtoB | OracleType::OrcaTokenSwapBtoA => 30_000,
+            OracleType::MeteoraDynamicAmmAtoB | OracleType::MeteoraDynamicAmmBtoA => 50_000,
+            OracleType::ConstantProductLpToken => 80_000,
+            OracleType::KLendCToken => 130_000,
+            OracleType::SolendBorrowRate | OracleType::SolendSupplyRate => 130_000,
+            OracleType::Chainlink => 20_000,
+            OracleType::OpenBookV2MidPrice | OracleType::PhoenixMidPrice => 60_000,
+            OracleType::SignedPrice => 30_000,
+            OracleType::FixedRate => 20_000,
+            OracleType::CappedFloored => 10_000,
+            OracleType::MinOfTwo | OracleType::MaxOfTwo => 15_000,
+            OracleType::IndexPrice => 40_000,
+            OracleType::InversePrice => 10_000,
+            OracleType::AccountField => 20_000,
+            OracleType::TwapStats => 20_000,
             OracleType::DeprecatedPlaceholder1 | OracleType::DeprecatedPlaceholder2 => {
                 panic!("DeprecatedPlaceholder is not a valid oracle type")
             }
@@ -127,6 +264,7 @@ impl OracleType {
 /// The `base_account` should have been checked against the oracle mapping
 /// If needed the `extra_accounts` will be extracted from the provided iterator and checked
 /// with the data contained in the `base_account`
+#[allow(clippy::too_many_arguments)]
 pub fn get_price<'a, 'b>(
     price_type: OracleType,
     base_account: &AccountInfo<'a>,
@@ -134,6 +272,8 @@ pub fn get_price<'a, 'b>(
     clock: &Clock,
     oracle_twaps: &OracleTwaps,
     oracle_mappings: &OracleMappings,
+    oracle_prices: &OraclePrices,
+    oracle_prices_pk: &Pubkey,
     index: usize,
 ) -> crate::Result<DatedPrice>
 where
@@ -146,7 +286,8 @@ where
         OracleType::SplStake => spl_stake::get_price(base_account, clock),
         #[cfg(not(feature = "yvaults"))]
         OracleType::KToken => {
-            panic!("yvaults feature is not enabled, KToken oracle type is not available")
+            msg!("yvaults feature is not enabled, KToken oracle type is not available");
+            err!(ScopeError::OracleTypeNotAvailable)
         }
         OracleType::PythEMA => pyth_ema::get_price(base_account, clock),
         #[cfg(feature = "yvaults")]
@@ -167,47 +308,268 @@ where
         ),
         #[cfg(not(feature = "yvaults"))]
         OracleType::KTokenToTokenA => {
-            panic!("yvaults feature is not enabled, KToken oracle type is not available")
+            msg!("yvaults feature is not enabled, KToken oracle type is not available");
+            err!(ScopeError::OracleTypeNotAvailable)
         }
         #[cfg(not(feature = "yvaults"))]
         OracleType::KTokenToTokenB => {
-            panic!("yvaults feature is not enabled, KToken oracle type is not available")
+            msg!("yvaults feature is not enabled, KToken oracle type is not available");
+            err!(ScopeError::OracleTypeNotAvailable)
         }
         OracleType::MsolStake => msol_stake::get_price(base_account, clock),
         OracleType::JupiterLpFetch => {
             jupiter_lp::get_price_no_recompute(base_account, clock, extra_accounts)
         }
         OracleType::ScopeTwap => twap::get_price(oracle_mappings, oracle_twaps, index, clock),
-        OracleType::OrcaWhirlpoolAtoB => {
-            orca_whirlpool::get_price(true, base_account, clock, extra_accounts)
+        OracleType::OrcaWhirlpoolAtoB => orca_whirlpool::get_price(
+            true,
+            base_account,
+            clock,
+            extra_accounts,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::OrcaWhirlpoolBtoA => orca_whirlpool::get_price(
+            false,
+            base_account,
+            clock,
+            extra_accounts,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::RaydiumAmmV3AtoB => raydium_ammv3::get_price(
+            true,
+            base_account,
+            clock,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::RaydiumAmmV3BtoA => raydium_ammv3::get_price(
+            false,
+            base_account,
+            clock,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::RaydiumAmmV3TwapAtoB => raydium_ammv3::get_twap_price(
+            true,
+            base_account,
+            clock,
+            extra_accounts,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::RaydiumAmmV3TwapBtoA => raydium_ammv3::get_twap_price(
+            false,
+            base_account,
+            clock,
+            extra_accounts,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::MeteoraDlmmAtoB => meteora_dlmm::get_price(
+            true,
+            base_account,
+            clock,
+            extra_accounts,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::MeteoraDlmmBtoA => meteora_dlmm::get_price(
+            false,
+            base_account,
+            clock,
+            extra_accounts,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::RaydiumAmmV4AtoB => raydium_amm_v4::get_price(
+            true,
+            base_account,
+            clock,
+            extra_accounts,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::RaydiumAmmV4BtoA => raydium_amm_v4::get_price(
+            false,
+            base_account,
+            clock,
+            extra_accounts,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::RaydiumCpmmAtoB => raydium_cpmm::get_price(
+            true,
+            base_account,
+            clock,
+            extra_accounts,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::RaydiumCpmmBtoA => raydium_cpmm::get_price(
+            false,
+            base_account,
+            clock,
+            extra_accounts,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::OrcaTokenSwapAtoB => orca_token_swap::get_price(
+            true,
+            base_account,
+            clock,
+            extra_accounts,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::OrcaTokenSwapBtoA => orca_token_swap::get_price(
+            false,
+            base_account,
+            clock,
+            extra_accounts,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::MeteoraDynamicAmmAtoB => meteora_dynamic_amm::get_price(
+            true,
+            base_account,
+            clock,
+            extra_accounts,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::MeteoraDynamicAmmBtoA => meteora_dynamic_amm::get_price(
+            false,
+            base_account,
+            clock,
+            extra_accounts,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::ConstantProductLpToken => constant_product_lp::get_price(
+            base_account,
+            clock,
+            extra_accounts,
+            oracle_prices,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::KLendCToken => klend_ctokens::get_price(base_account, clock),
+        OracleType::SolendBorrowRate => {
+            ctokens::get_rate(base_account, clock, ctokens::ReserveRate::Borrow)
         }
-        OracleType::OrcaWhirlpoolBtoA => {
-            orca_whirlpool::get_price(false, base_account, clock, extra_accounts)
+        OracleType::SolendSupplyRate => {
+            ctokens::get_rate(base_account, clock, ctokens::ReserveRate::Supply)
         }
-        OracleType::RaydiumAmmV3AtoB => raydium_ammv3::get_price(true, base_account, clock),
-        OracleType::RaydiumAmmV3BtoA => raydium_ammv3::get_price(false, base_account, clock),
-        OracleType::MeteoraDlmmAtoB => {
-            meteora_dlmm::get_price(true, base_account, clock, extra_accounts)
+        OracleType::Chainlink => chainlink::get_price(base_account, clock),
+        OracleType::OpenBookV2MidPrice => openbook_v2::get_price(
+            base_account,
+            clock,
+            extra_accounts,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::PhoenixMidPrice => {
+            phoenix::get_price(base_account, clock, oracle_mappings.get_generic_data(index))
         }
-        OracleType::MeteoraDlmmBtoA => {
-            meteora_dlmm::get_price(false, base_account, clock, extra_accounts)
+        OracleType::SignedPrice => signed_price::get_price(
+            base_account,
+            clock,
+            extra_accounts,
+            oracle_prices,
+            oracle_prices_pk,
+            index,
+        ),
+        OracleType::FixedRate => {
+            fixed_rate::get_price(oracle_mappings.get_generic_data(index), clock)
+        }
+        OracleType::CappedFloored => {
+            capped_floored::get_price(oracle_prices, oracle_mappings.get_generic_data(index))
+        }
+        OracleType::MinOfTwo => min_max::get_price(
+            min_max::MinMax::Min,
+            oracle_prices,
+            oracle_twaps,
+            clock,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::MaxOfTwo => min_max::get_price(
+            min_max::MinMax::Max,
+            oracle_prices,
+            oracle_twaps,
+            clock,
+            oracle_mappings.get_generic_data(index),
+        ),
+        OracleType::IndexPrice => {
+            index_price::get_price(base_account, oracle_prices, oracle_prices_pk, index)
+        }
+        OracleType::AccountField => {
+            account_field::get_price(base_account, clock, extra_accounts, oracle_prices_pk, index)
+        }
+        OracleType::InversePrice => {
+            inverse::get_price(oracle_prices, oracle_mappings.get_generic_data(index))
+        }
+        OracleType::TwapStats => {
+            twap_stats::get_price(oracle_twaps, clock, oracle_mappings.get_generic_data(index))
         }
         OracleType::JupiterLpCompute => {
             jupiter_lp::get_price_recomputed(base_account, clock, extra_accounts)
         }
         OracleType::DeprecatedPlaceholder1 | OracleType::DeprecatedPlaceholder2 => {
-            panic!("DeprecatedPlaceholder is not a valid oracle type")
+            msg!("DeprecatedPlaceholder is not a valid oracle type");
+            err!(ScopeError::DeprecatedOracleType)
         }
     }
 }
 
+/// Validate the generic data of an oracle type computed from the feed
+///
+/// Such an entry has no price account of its own: the mapping can be set without one, or with the
+/// scope program id (see [`validate_computed_mapping`]).
+pub fn validate_computed_generic_data(
+    price_type: OracleType,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> crate::Result<()> {
+    match price_type {
+        // The TWAP source is checked with the mapping
+        OracleType::ScopeTwap => Ok(()),
+        OracleType::FixedRate => {
+            fixed_rate::FixedRateConfig::from_generic_data(generic_data)?;
+            Ok(())
+        }
+        OracleType::CappedFloored => {
+            capped_floored::CappedFlooredConfig::from_generic_data(generic_data)?;
+            Ok(())
+        }
+        OracleType::MinOfTwo | OracleType::MaxOfTwo => {
+            min_max::MinMaxConfig::from_generic_data(generic_data)?;
+            Ok(())
+        }
+        OracleType::InversePrice => {
+            inverse::get_source_index(generic_data)?;
+            Ok(())
+        }
+        OracleType::TwapStats => {
+            twap_stats::TwapStatsConfig::from_generic_data(generic_data)?;
+            Ok(())
+        }
+        _ => {
+            msg!("Oracle type {price_type:?} needs a price account");
+            err!(ScopeError::PriceAccountNotExpected)
+        }
+    }
+}
+
+/// Validate the mapping of an oracle type computed from the feed, given with a price account
+///
+/// The price account must be the scope program id, as set when no account is given.
+fn validate_computed_mapping(
+    price_type: OracleType,
+    price_account: &AccountInfo,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> crate::Result<()> {
+    require_keys_eq!(
+        price_account.key(),
+        crate::id(),
+        ScopeError::PriceAccountNotExpected
+    );
+    validate_computed_generic_data(price_type, generic_data)
+}
+
 /// Validate the given account as being an appropriate price account for the
-/// given oracle type.
+/// given oracle type, and the oracle type specific `generic_data` if any.
+///
+/// Configuration accounts of an entry must belong to the feed of `oracle_prices_pk`.
 ///
 /// This function shall be called before update of oracle mappings
 pub fn validate_oracle_account(
     price_type: OracleType,
     price_account: &AccountInfo,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+    oracle_prices_pk: &Pubkey,
 ) -> crate::Result<()> {
     match price_type {
         OracleType::Pyth => pyth::validate_pyth_price_info(price_account),
@@ -229,11 +591,58 @@ pub fn validate_oracle_account(
         OracleType::RaydiumAmmV3AtoB | OracleType::RaydiumAmmV3BtoA => {
             raydium_ammv3::validate_pool_account(price_account)
         }
+        OracleType::RaydiumAmmV3TwapAtoB | OracleType::RaydiumAmmV3TwapBtoA => {
+            raydium_ammv3::validate_twap_pool_account(price_account, generic_data)
+        }
         OracleType::MeteoraDlmmAtoB | OracleType::MeteoraDlmmBtoA => {
             meteora_dlmm::validate_pool_account(price_account)
         }
+        OracleType::RaydiumAmmV4AtoB | OracleType::RaydiumAmmV4BtoA => {
+            raydium_amm_v4::validate_pool_account(price_account)
+        }
+        OracleType::RaydiumCpmmAtoB | OracleType::RaydiumCpmmBtoA => {
+            raydium_cpmm::validate_pool_account(price_account)
+        }
+        OracleType::OrcaTokenSwapAtoB | OracleType::OrcaTokenSwapBtoA => {
+            orca_token_swap::validate_pool_account(price_account)
+        }
+        OracleType::MeteoraDynamicAmmAtoB | OracleType::MeteoraDynamicAmmBtoA => {
+            meteora_dynamic_amm::validate_pool_account(price_account)
+        }
+        OracleType::ConstantProductLpToken => {
+            constant_product_lp::validate_pool_account(price_account, generic_data)
+        }
+        OracleType::KLendCToken => klend_ctokens::validate_reserve_account(price_account),
+        OracleType::SolendBorrowRate | OracleType::SolendSupplyRate => {
+            ctokens::validate_reserve_account(price_account)
+        }
+        OracleType::Chainlink => chainlink::validate_transmissions_account(price_account),
+        OracleType::OpenBookV2MidPrice => {
+            openbook_v2::validate_market_account(price_account, generic_data)
+        }
+        OracleType::PhoenixMidPrice => {
+            phoenix::validate_market_account(price_account, generic_data)
+        }
+        OracleType::SignedPrice => {
+            signed_price::validate_publishers_account(price_account, oracle_prices_pk)
+        }
+        OracleType::FixedRate
+        | OracleType::CappedFloored
+        | OracleType::MinOfTwo
+        | OracleType::MaxOfTwo
+        | OracleType::InversePrice
+        | OracleType::TwapStats => {
+            validate_computed_mapping(price_type, price_account, generic_data)
+        }
+        OracleType::IndexPrice => {
+            index_price::validate_index_definition_account(price_account, oracle_prices_pk)
+        }
+        OracleType::AccountField => {
+            account_field::validate_reader_account(price_account, oracle_prices_pk)
+        }
         OracleType::DeprecatedPlaceholder1 | OracleType::DeprecatedPlaceholder2 => {
-            panic!("DeprecatedPlaceholder is not a valid oracle type")
+            msg!("DeprecatedPlaceholder is not a valid oracle type");
+            err!(ScopeError::DeprecatedOracleType)
         }
     }
 }
@@ -12,8 +12,9 @@ pub fn get_price(
     msol_pool_account_info: &AccountInfo,
     current_clock: &Clock,
 ) -> Result<DatedPrice> {
-    let stake_pool = try_from_slice_unchecked::<State>(&msol_pool_account_info.data.borrow()[8..])
-        .map_err(|_| {
+    let data = msol_pool_account_info.try_borrow_data()?;
+    let stake_pool =
+        try_from_slice_unchecked::<State>(data.get(8..).unwrap_or_default()).map_err(|_| {
             msg!("Provided pubkey is not a MSOL Stake account");
             ScopeError::UnexpectedAccount
         })?;
@@ -27,7 +28,8 @@ pub fn get_price(
     let dated_price = DatedPrice {
         price,
         last_updated_slot: current_clock.slot,
-        unix_timestamp: u64::try_from(current_clock.unix_timestamp).unwrap(),
+        unix_timestamp: u64::try_from(current_clock.unix_timestamp)
+            .map_err(|_| ScopeError::BadTimestamp)?,
         ..Default::default()
     };
 
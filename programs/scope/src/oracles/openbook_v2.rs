@@ -0,0 +1,409 @@
+//! OpenBook v2 market mid-price
+//!
+//! Only the fixed-price orders of the book are considered, oracle-pegged orders are ignored.
+use anchor_lang::prelude::*;
+use decimal_wad::common::{TryDiv, TryMul};
+use decimal_wad::decimal::Decimal;
+
+use self::openbook::{BookSide, Market};
+use super::orderbook::{self, BookOrder, OrderbookConfig, MAX_DEPTH_ORDERS};
+use crate::{utils::math::ten_pow, DatedPrice, Result, ScopeError, GENERIC_DATA_LEN};
+
+/// Gives the mid-price of an OpenBook v2 market (quote per base)
+///
+/// Extra accounts are the market bids and asks accounts.
+pub fn get_price<'a, 'b>(
+    market_account: &AccountInfo,
+    clock: &Clock,
+    extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> Result<DatedPrice>
+where
+    'a: 'b,
+{
+    let config = OrderbookConfig::from_generic_data(generic_data)?;
+    let market = Market::from_account(market_account)?;
+
+    let bids_account = extra_accounts
+        .next()
+        .ok_or(ScopeError::AccountsAndTokenMismatch)?;
+    let asks_account = extra_accounts
+        .next()
+        .ok_or(ScopeError::AccountsAndTokenMismatch)?;
+    require_keys_eq!(
+        bids_account.key(),
+        market.bids,
+        ScopeError::AccountsAndTokenMismatch
+    );
+    require_keys_eq!(
+        asks_account.key(),
+        market.asks,
+        ScopeError::AccountsAndTokenMismatch
+    );
+
+    let now_ts = u64::try_from(clock.unix_timestamp).map_err(|_| ScopeError::BadTimestamp)?;
+    let bids = get_best_orders(&market, bids_account, false, now_ts)?;
+    let asks = get_best_orders(&market, asks_account, true, now_ts)?;
+
+    Ok(orderbook::get_mid_price(
+        market_account.key,
+        &bids,
+        &asks,
+        clock,
+        &config,
+    )?)
+}
+
+pub fn validate_market_account(
+    market_account: &AccountInfo,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> Result<()> {
+    let _ = OrderbookConfig::from_generic_data(generic_data)?;
+    let _ = Market::from_account(market_account)?;
+    Ok(())
+}
+
+fn get_best_orders(
+    market: &Market,
+    book_side_account: &AccountInfo,
+    ascending: bool,
+    now_ts: u64,
+) -> Result<Vec<BookOrder>> {
+    let data = book_side_account.data.borrow();
+    let book_side = BookSide::new(&data)?;
+    book_side
+        .best_leaves(ascending, now_ts, MAX_DEPTH_ORDERS)?
+        .into_iter()
+        .map(|leaf| -> Result<BookOrder> {
+            Ok(BookOrder {
+                price: market.lots_to_ui_price(leaf.price_lots()?)?,
+                base_amount: market.base_lots_to_native(leaf.quantity)?,
+            })
+        })
+        .collect()
+}
+
+/// Partial layout of the OpenBook v2 accounts
+pub mod openbook {
+    use arrayref::array_ref;
+
+    use super::*;
+    use crate::ScopeResult;
+
+    declare_id!("opnb2LAfJYbRMAHHvqjCwQxanZn7ReEHp1k81EohpZb");
+
+    pub const MARKET_DISCRIMINATOR: [u8; 8] = [219, 190, 213, 55, 0, 227, 198, 154];
+    pub const BOOK_SIDE_DISCRIMINATOR: [u8; 8] = [72, 44, 225, 141, 178, 130, 97, 57];
+
+    const BASE_DECIMALS_OFFSET: usize = 9;
+    const QUOTE_DECIMALS_OFFSET: usize = 10;
+    const BIDS_OFFSET: usize = 200;
+    const ASKS_OFFSET: usize = 232;
+    const QUOTE_LOT_SIZE_OFFSET: usize = 448;
+    const BASE_LOT_SIZE_OFFSET: usize = 456;
+    const MARKET_MIN_LEN: usize = BASE_LOT_SIZE_OFFSET + 8;
+
+    /// Root of the fixed-price orders tree
+    const FIXED_ROOT_OFFSET: usize = 8;
+    const NODES_OFFSET: usize = 840;
+    const NODE_LEN: usize = 88;
+    pub const MAX_ORDERTREE_NODES: usize = 1024;
+    const BOOK_SIDE_LEN: usize = NODES_OFFSET + MAX_ORDERTREE_NODES * NODE_LEN;
+
+    const INNER_NODE_TAG: u8 = 1;
+    const LEAF_NODE_TAG: u8 = 2;
+
+    /// Market account (partial)
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Market {
+        pub base_decimals: u8,
+        pub quote_decimals: u8,
+        pub bids: Pubkey,
+        pub asks: Pubkey,
+        pub quote_lot_size: i64,
+        pub base_lot_size: i64,
+    }
+
+    impl Market {
+        pub fn from_account(account: &AccountInfo) -> Result<Self> {
+            require_keys_eq!(*account.owner, ID, ScopeError::UnexpectedAccount);
+            Self::unpack(&account.data.borrow())
+        }
+
+        pub fn unpack(data: &[u8]) -> Result<Self> {
+            if data.len() < MARKET_MIN_LEN {
+                msg!("OpenBook market account has an invalid size");
+                return err!(ScopeError::UnableToDeserializeAccount);
+            }
+            if data[..8] != MARKET_DISCRIMINATOR {
+                msg!("OpenBook market account has an invalid discriminator");
+                return err!(ScopeError::InvalidAccountDiscriminator);
+            }
+            let market = Self {
+                base_decimals: data[BASE_DECIMALS_OFFSET],
+                quote_decimals: data[QUOTE_DECIMALS_OFFSET],
+                bids: Pubkey::new_from_array(*array_ref![data, BIDS_OFFSET, 32]),
+                asks: Pubkey::new_from_array(*array_ref![data, ASKS_OFFSET, 32]),
+                quote_lot_size: i64::from_le_bytes(*array_ref![data, QUOTE_LOT_SIZE_OFFSET, 8]),
+                base_lot_size: i64::from_le_bytes(*array_ref![data, BASE_LOT_SIZE_OFFSET, 8]),
+            };
+            if market.quote_lot_size <= 0 || market.base_lot_size <= 0 {
+                msg!("OpenBook market has invalid lot sizes");
+                return err!(ScopeError::UnableToDeserializeAccount);
+            }
+            Ok(market)
+        }
+
+        /// Convert a price in lots to a price in quote tokens per base token
+        pub fn lots_to_ui_price(&self, price_lots: u64) -> ScopeResult<Decimal> {
+            // price_lots * quote_lot_size / base_lot_size is the price in lamports
+            let price = Decimal::from(price_lots)
+                .try_mul(u64::try_from(self.quote_lot_size)?)?
+                .try_mul(Decimal::from(ten_pow(self.base_decimals)?))?
+                .try_div(u64::try_from(self.base_lot_size)?)?
+                .try_div(Decimal::from(ten_pow(self.quote_decimals)?))?;
+            Ok(price)
+        }
+
+        /// Convert a quantity in base lots to lamports of the base token
+        pub fn base_lots_to_native(&self, base_lots: i64) -> ScopeResult<u64> {
+            u64::try_from(base_lots)?
+                .checked_mul(u64::try_from(self.base_lot_size)?)
+                .ok_or(ScopeError::IntegerOverflow)
+        }
+    }
+
+    /// A resting order of a book side (partial)
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct LeafNode {
+        pub time_in_force: u16,
+        /// Price data (upper 64 bits) and sequence number (lower 64 bits)
+        pub key: u128,
+        /// Quantity in base lots
+        pub quantity: i64,
+        pub timestamp: u64,
+    }
+
+    impl LeafNode {
+        /// Price of a fixed-price order, in lots
+        pub fn price_lots(&self) -> ScopeResult<u64> {
+            let price_lots = (self.key >> 64) as u64;
+            if price_lots == 0 || price_lots > i64::MAX as u64 {
+                msg!("OpenBook order has an invalid price");
+                return Err(ScopeError::PriceNotValid);
+            }
+            Ok(price_lots)
+        }
+
+        pub fn is_expired(&self, now_ts: u64) -> bool {
+            self.time_in_force > 0 && now_ts >= self.timestamp + u64::from(self.time_in_force)
+        }
+    }
+
+    /// View over the data of a book side (bids or asks) account
+    pub struct BookSide<'d> {
+        data: &'d [u8],
+    }
+
+    impl<'d> BookSide<'d> {
+        pub fn new(data: &'d [u8]) -> Result<Self> {
+            if data.len() < BOOK_SIDE_LEN {
+                msg!("OpenBook book side account has an invalid size");
+                return err!(ScopeError::UnableToDeserializeAccount);
+            }
+            if data[..8] != BOOK_SIDE_DISCRIMINATOR {
+                msg!("OpenBook book side account has an invalid discriminator");
+                return err!(ScopeError::InvalidAccountDiscriminator);
+            }
+            Ok(Self { data })
+        }
+
+        fn node(&self, index: u32) -> ScopeResult<&'d [u8]> {
+            let index = usize::try_from(index)?;
+            if index >= MAX_ORDERTREE_NODES {
+                return Err(ScopeError::UnableToDeserializeAccount);
+            }
+            let offset = NODES_OFFSET + index * NODE_LEN;
+            Ok(&self.data[offset..offset + NODE_LEN])
+        }
+
+        /// Get up to `max_orders` non-expired fixed-price orders, best first
+        ///
+        /// Asks are sorted by ascending keys and bids by descending keys.
+        pub fn best_leaves(
+            &self,
+            ascending: bool,
+            now_ts: u64,
+            max_orders: usize,
+        ) -> ScopeResult<Vec<LeafNode>> {
+            let root = u32::from_le_bytes(*array_ref![self.data, FIXED_ROOT_OFFSET, 4]);
+            let leaf_count = u32::from_le_bytes(*array_ref![self.data, FIXED_ROOT_OFFSET + 4, 4]);
+            let mut leaves = Vec::with_capacity(max_orders);
+            if leaf_count == 0 {
+                return Ok(leaves);
+            }
+            // Children are ordered by key: best child is the first one for asks, the second one for bids
+            let (best_child, other_child) = if ascending { (0, 1) } else { (1, 0) };
+            let mut stack = vec![root];
+            let mut visited_nodes = 0;
+            while let Some(index) = stack.pop() {
+                visited_nodes += 1;
+                if visited_nodes > MAX_ORDERTREE_NODES {
+                    // Prevent looping on a corrupted tree
+                    return Err(ScopeError::UnableToDeserializeAccount);
+                }
+                let node = self.node(index)?;
+                match node[0] {
+                    INNER_NODE_TAG => {
+                        let children = [
+                            u32::from_le_bytes(*array_ref![node, 24, 4]),
+                            u32::from_le_bytes(*array_ref![node, 28, 4]),
+                        ];
+                        stack.push(children[other_child]);
+                        stack.push(children[best_child]);
+                    }
+                    LEAF_NODE_TAG => {
+                        let leaf = LeafNode {
+                            time_in_force: u16::from_le_bytes(*array_ref![node, 2, 2]),
+                            key: u128::from_le_bytes(*array_ref![node, 8, 16]),
+                            quantity: i64::from_le_bytes(*array_ref![node, 56, 8]),
+                            timestamp: u64::from_le_bytes(*array_ref![node, 64, 8]),
+                        };
+                        if !leaf.is_expired(now_ts) {
+                            leaves.push(leaf);
+                            if leaves.len() == max_orders {
+                                break;
+                            }
+                        }
+                    }
+                    _ => {
+                        msg!("OpenBook book side has an unexpected node in the tree");
+                        return Err(ScopeError::UnableToDeserializeAccount);
+                    }
+                }
+            }
+            Ok(leaves)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_program::pubkey;
+
+    use super::openbook::*;
+    use super::*;
+
+    fn market(base_decimals: u8, quote_decimals: u8, base_lot: i64, quote_lot: i64) -> Market {
+        Market {
+            base_decimals,
+            quote_decimals,
+            bids: Pubkey::default(),
+            asks: Pubkey::default(),
+            quote_lot_size: quote_lot,
+            base_lot_size: base_lot,
+        }
+    }
+
+    /// Market account laid out as the upstream `Market` struct, with the decimals and lot sizes of
+    /// the SOL/USDC market
+    const SOL_USDC_MARKET: &[u8] =
+        include_bytes!("../../tests/fixtures/openbook_v2_sol_usdc_market.bin");
+
+    #[test]
+    fn test_unpack_market() {
+        let market = Market::unpack(SOL_USDC_MARKET).unwrap();
+        assert_eq!(
+            market,
+            Market {
+                base_decimals: 9,
+                quote_decimals: 6,
+                bids: pubkey!("Ad5skEiFoaeA27G3UhbpuwnFBCvmuuGEyoiijZhcd5xX"),
+                asks: pubkey!("53v47CBoaKwoM8tSEDN4oNyCc2ZJenDeuhMJTEw7fL2M"),
+                quote_lot_size: 1,
+                base_lot_size: 1_000_000,
+            }
+        );
+        assert_eq!(
+            market.lots_to_ui_price(20_000).unwrap(),
+            Decimal::from(20u64)
+        );
+
+        let mut wrong_discriminator = SOL_USDC_MARKET.to_vec();
+        wrong_discriminator[0] ^= 1;
+        assert!(Market::unpack(&wrong_discriminator).is_err());
+    }
+
+    #[test]
+    fn test_lots_to_ui_price() {
+        // SOL/USDC market: 1 base lot = 0.001 SOL, 1 quote lot = 0.000001 USDC
+        let market = market(9, 6, 1_000_000, 1);
+        // 20 USDC per SOL = 20 * 10^6 quote lamports / 10^9 base lamports
+        //                 = 20_000 quote lots per base lot
+        let price = market.lots_to_ui_price(20_000).unwrap();
+        assert_eq!(price, Decimal::from(20u64));
+        assert_eq!(market.base_lots_to_native(3).unwrap(), 3_000_000);
+    }
+
+    #[test]
+    fn test_lots_to_ui_price_unsupported_decimals() {
+        for (base_decimals, quote_decimals) in [(31, 6), (9, 31)] {
+            let market = market(base_decimals, quote_decimals, 1_000_000, 1);
+            assert!(market.lots_to_ui_price(20_000).is_err());
+        }
+    }
+
+    fn write_node(data: &mut [u8], index: usize, node: &[u8]) {
+        let offset = 840 + index * 88;
+        data[offset..offset + node.len()].copy_from_slice(node);
+    }
+
+    fn inner(children: [u32; 2]) -> Vec<u8> {
+        let mut node = vec![0; 32];
+        node[0] = 1;
+        node[24..28].copy_from_slice(&children[0].to_le_bytes());
+        node[28..32].copy_from_slice(&children[1].to_le_bytes());
+        node
+    }
+
+    fn leaf(price_lots: u64, quantity: i64, time_in_force: u16) -> Vec<u8> {
+        let mut node = vec![0; 72];
+        node[0] = 2;
+        node[2..4].copy_from_slice(&time_in_force.to_le_bytes());
+        node[8..24].copy_from_slice(&(u128::from(price_lots) << 64).to_le_bytes());
+        node[56..64].copy_from_slice(&quantity.to_le_bytes());
+        node
+    }
+
+    #[test]
+    fn test_best_leaves() {
+        let mut data = vec![0_u8; 840 + MAX_ORDERTREE_NODES * 88];
+        data[..8].copy_from_slice(&BOOK_SIDE_DISCRIMINATOR);
+        // root (0) -> [inner (1) -> [leaf 10 (3), leaf 11 (4)], leaf 12 (2)]
+        data[8..12].copy_from_slice(&0_u32.to_le_bytes());
+        data[12..16].copy_from_slice(&3_u32.to_le_bytes());
+        write_node(&mut data, 0, &inner([1, 2]));
+        write_node(&mut data, 1, &inner([3, 4]));
+        write_node(&mut data, 2, &leaf(12, 1, 0));
+        write_node(&mut data, 3, &leaf(10, 1, 0));
+        // Expired order
+        write_node(&mut data, 4, &leaf(11, 1, 1));
+        let book_side = BookSide::new(&data).unwrap();
+
+        let asks: Vec<u64> = book_side
+            .best_leaves(true, 100, 10)
+            .unwrap()
+            .iter()
+            .map(|leaf| leaf.price_lots().unwrap())
+            .collect();
+        assert_eq!(asks, vec![10, 12]);
+
+        let bids: Vec<u64> = book_side
+            .best_leaves(false, 100, 1)
+            .unwrap()
+            .iter()
+            .map(|leaf| leaf.price_lots().unwrap())
+            .collect();
+        assert_eq!(bids, vec![12]);
+    }
+}
@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+
+use self::orca_swap::SwapV1;
+use super::constant_product::{
+    get_mint_decimals, get_price_from_reserves, get_vault_amount, PoolReserves,
+};
+use crate::{DatedPrice, Result, ScopeError, GENERIC_DATA_LEN};
+
+/// Gives the price of the given token pair in the given Orca legacy (token-swap) pool
+///
+/// Only constant-product pools are supported, the curve type is checked on each refresh.
+pub fn get_price<'a, 'b>(
+    a_to_b: bool,
+    pool: &AccountInfo,
+    clock: &Clock,
+    extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> Result<DatedPrice>
+where
+    'a: 'b,
+{
+    let reserves = get_reserves(pool, extra_accounts)?;
+    get_price_from_reserves(a_to_b, pool.key, &reserves, clock, generic_data)
+}
+
+/// Gives the reserves of the given Orca legacy (token-swap) pool
+pub fn get_reserves<'a, 'b>(
+    pool: &AccountInfo,
+    extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+) -> Result<PoolReserves>
+where
+    'a: 'b,
+{
+    // Get extra accounts
+    let token_a_vault_account_info = extra_accounts
+        .next()
+        .ok_or(ScopeError::AccountsAndTokenMismatch)?;
+    let token_b_vault_account_info = extra_accounts
+        .next()
+        .ok_or(ScopeError::AccountsAndTokenMismatch)?;
+    let mint_token_a_account_info = extra_accounts
+        .next()
+        .ok_or(ScopeError::AccountsAndTokenMismatch)?;
+    let mint_token_b_account_info = extra_accounts
+        .next()
+        .ok_or(ScopeError::AccountsAndTokenMismatch)?;
+
+    // Load main account
+    let swap = SwapV1::from_account(pool)?;
+
+    // Load extra accounts
+    Ok(PoolReserves {
+        reserve_a: get_vault_amount(token_a_vault_account_info, &swap.token_a)?,
+        reserve_b: get_vault_amount(token_b_vault_account_info, &swap.token_b)?,
+        decimals_a: get_mint_decimals(mint_token_a_account_info, &swap.token_a_mint)?,
+        decimals_b: get_mint_decimals(mint_token_b_account_info, &swap.token_b_mint)?,
+        lp_mint: swap.pool_mint,
+    })
+}
+
+pub fn validate_pool_account(pool: &AccountInfo) -> Result<()> {
+    let _ = SwapV1::from_account(pool)?;
+    Ok(())
+}
+
+pub mod orca_swap {
+    use anchor_lang::prelude::*;
+    use arrayref::array_ref;
+
+    use crate::{Result, ScopeError};
+
+    declare_id!("9W959DqEETiGZocYWCQPaJ6sBmUzgfxXfqGeTEdp3aQP");
+
+    pub const SWAP_V1_VERSION: u8 = 1;
+    pub const CONSTANT_PRODUCT_CURVE: u8 = 0;
+
+    /// Fields of a token-swap `SwapV1` needed to price the pool
+    pub struct SwapV1 {
+        pub token_a: Pubkey,
+        pub token_b: Pubkey,
+        pub pool_mint: Pubkey,
+        pub token_a_mint: Pubkey,
+        pub token_b_mint: Pubkey,
+    }
+
+    impl SwapV1 {
+        /// Including the leading version byte
+        pub const LEN: usize = 324;
+
+        const IS_INITIALIZED_OFFSET: usize = 1;
+        const TOKEN_A_OFFSET: usize = 35;
+        const TOKEN_B_OFFSET: usize = 67;
+        const POOL_MINT_OFFSET: usize = 99;
+        const TOKEN_A_MINT_OFFSET: usize = 131;
+        const TOKEN_B_MINT_OFFSET: usize = 163;
+        const CURVE_TYPE_OFFSET: usize = 291;
+
+        pub fn from_account(account: &AccountInfo) -> Result<Self> {
+            require_keys_eq!(*account.owner, ID, ScopeError::UnexpectedAccount);
+            Self::unpack(&account.data.borrow())
+        }
+
+        pub fn unpack(data: &[u8]) -> Result<Self> {
+            if data.len() != Self::LEN
+                || data[0] != SWAP_V1_VERSION
+                || data[Self::IS_INITIALIZED_OFFSET] != 1
+            {
+                msg!("Orca swap account is not a valid swap");
+                return err!(ScopeError::UnableToDeserializeAccount);
+            }
+            if data[Self::CURVE_TYPE_OFFSET] != CONSTANT_PRODUCT_CURVE {
+                msg!("Orca swap account is not a constant-product pool");
+                return err!(ScopeError::UnexpectedAccount);
+            }
+            let read_pubkey = |offset: usize| Pubkey::new_from_array(*array_ref![data, offset, 32]);
+            Ok(Self {
+                token_a: read_pubkey(Self::TOKEN_A_OFFSET),
+                token_b: read_pubkey(Self::TOKEN_B_OFFSET),
+                pool_mint: read_pubkey(Self::POOL_MINT_OFFSET),
+                token_a_mint: read_pubkey(Self::TOKEN_A_MINT_OFFSET),
+                token_b_mint: read_pubkey(Self::TOKEN_B_MINT_OFFSET),
+            })
+        }
+    }
+}
@@ -3,16 +3,20 @@ use anchor_spl::token::spl_token::state::Mint;
 use solana_program::program_pack::Pack;
 use whirlpool::state::Whirlpool;
 
-use crate::utils::account_deserialize;
-use crate::utils::math::sqrt_price_to_price;
-use crate::{DatedPrice, Result, ScopeError};
+use crate::utils::math::{clmm_quote_virtual_reserve, sqrt_price_to_price};
+use crate::utils::{account_deserialize, check_min_liquidity};
+use crate::{DatedPrice, Result, ScopeError, GENERIC_DATA_LEN};
 
 /// Gives the price of the given token pair in the given pool
+///
+/// The in-range liquidity of the pool is checked against the minimum configured in the entry
+/// generic data, see [`crate::utils::get_min_liquidity`].
 pub fn get_price<'a, 'b>(
     a_to_b: bool,
     pool: &AccountInfo,
     clock: &Clock,
     extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+    generic_data: &[u8; GENERIC_DATA_LEN],
 ) -> Result<DatedPrice>
 where
     'a: 'b,
@@ -41,6 +45,11 @@ where
         ScopeError::AccountsAndTokenMismatch
     );
 
+    // Check pool liquidity
+    let quote_liquidity =
+        clmm_quote_virtual_reserve(a_to_b, pool_data.liquidity, pool_data.sqrt_price);
+    check_min_liquidity(pool.key, quote_liquidity, generic_data)?;
+
     // Load extra accounts
     let mint_a_decimals = {
         let mint_borrow = mint_token_a_account_info.data.borrow();
@@ -0,0 +1,280 @@
+//! Shared logic of the oracles pricing a market from the top of its orderbook
+//!
+//! The price is the mid-price between the best bid and the best ask of the market (quote per base).
+//! The entry generic data configures the checks performed on the orderbook, see [`OrderbookConfig`].
+//!
+//! When enabled, the best bid and best ask are stored in `DatedPrice::bid_ask` (`[bid, ask]`)
+//! with the same exponent as the mid-price.
+
+use anchor_lang::prelude::*;
+use arrayref::array_ref;
+use decimal_wad::common::{TryAdd, TryDiv, TryMul, TrySub};
+use decimal_wad::decimal::Decimal;
+
+use crate::{DatedPrice, Price, ScopeError, ScopeResult, GENERIC_DATA_LEN};
+
+/// Maximum number of orders read on each side of the book to compute the depth
+pub const MAX_DEPTH_ORDERS: usize = 32;
+
+/// An order of the book
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BookOrder {
+    /// Price of the order in quote tokens per base token
+    pub price: Decimal,
+    /// Remaining size of the order in lamports of the base token
+    pub base_amount: u64,
+}
+
+/// Orderbook checks configured in the entry generic data
+///
+/// - `[0..8]`: minimum depth (u64, little endian) in lamports of the base token that must be
+///   available on each side of the book within the maximum spread from the mid-price.
+///   A minimum of 0 disables the check.
+/// - `[8..10]`: maximum spread between the best bid and the best ask (u16, little endian), in bps
+///   of the mid-price. Must not be 0.
+/// - `[10]`: flags, bit 0 set to store the best bid and best ask along the mid-price.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OrderbookConfig {
+    pub min_depth: u64,
+    pub max_spread_bps: u16,
+    pub store_bid_ask: bool,
+}
+
+impl OrderbookConfig {
+    const STORE_BID_ASK_FLAG: u8 = 1;
+
+    pub fn from_generic_data(generic_data: &[u8; GENERIC_DATA_LEN]) -> ScopeResult<Self> {
+        let max_spread_bps = u16::from_le_bytes(*array_ref![generic_data, 8, 2]);
+        if max_spread_bps == 0 {
+            msg!("The orderbook maximum spread must be configured");
+            return Err(ScopeError::InvalidGenericData);
+        }
+        Ok(Self {
+            min_depth: u64::from_le_bytes(*array_ref![generic_data, 0, 8]),
+            max_spread_bps,
+            store_bid_ask: generic_data[10] & Self::STORE_BID_ASK_FLAG != 0,
+        })
+    }
+}
+
+/// Gives the mid-price of a market from its orders, best orders first on each side
+pub fn get_mid_price(
+    market: &Pubkey,
+    bids: &[BookOrder],
+    asks: &[BookOrder],
+    clock: &Clock,
+    config: &OrderbookConfig,
+) -> ScopeResult<DatedPrice> {
+    let (best_bid, best_ask) = match (bids.first(), asks.first()) {
+        (Some(best_bid), Some(best_ask)) => (best_bid, best_ask),
+        _ => {
+            msg!("Market {market} has no bid or no ask");
+            return Err(ScopeError::OrderbookEmpty);
+        }
+    };
+    if best_bid.price >= best_ask.price {
+        msg!("Market {market} orderbook is crossed");
+        return Err(ScopeError::PriceNotValid);
+    }
+
+    let mid_price = best_bid.price.try_add(best_ask.price)?.try_div(2)?;
+
+    // Check spread
+    let max_spread = Decimal::from(u64::from(config.max_spread_bps)).try_div(10_000)?;
+    let max_spread_value = mid_price.try_mul(max_spread)?;
+    let spread = best_ask.price.try_sub(best_bid.price)?;
+    if spread > max_spread_value {
+        msg!("Market {market} spread is above the configured maximum");
+        return Err(ScopeError::OrderbookSpreadTooWide);
+    }
+
+    // Check depth within the maximum spread around the mid-price
+    if config.min_depth > 0 {
+        let lowest_bid = mid_price.try_sub(max_spread_value)?;
+        let highest_ask = mid_price.try_add(max_spread_value)?;
+        let bid_depth = depth(bids, |price| price >= lowest_bid)?;
+        let ask_depth = depth(asks, |price| price <= highest_ask)?;
+        if bid_depth < config.min_depth || ask_depth < config.min_depth {
+            msg!(
+                "Market {market} depth (bids {bid_depth}, asks {ask_depth}) is below the configured minimum {}",
+                config.min_depth
+            );
+            return Err(ScopeError::OrderbookDepthTooLow);
+        }
+    }
+
+    let price: Price = mid_price.try_into()?;
+    let bid_ask = if config.store_bid_ask {
+        let exp = u8::try_from(price.exp)?;
+        let bid = Price::try_from(best_bid.price)?.to_scaled_value(exp)?;
+        let ask = Price::try_from(best_ask.price)?.to_scaled_value(exp)?;
+        [u64::try_from(bid)?, u64::try_from(ask)?]
+    } else {
+        [0; 2]
+    };
+
+    Ok(DatedPrice {
+        price,
+        last_updated_slot: clock.slot,
+        unix_timestamp: clock.unix_timestamp as u64,
+        bid_ask,
+        ..Default::default()
+    })
+}
+
+/// Cumulated base amount of the best orders of one side while `in_range` is true for their price
+fn depth(orders: &[BookOrder], in_range: impl Fn(Decimal) -> bool) -> ScopeResult<u64> {
+    orders
+        .iter()
+        .take_while(|order| in_range(order.price))
+        .try_fold(0_u64, |depth, order| {
+            depth
+                .checked_add(order.base_amount)
+                .ok_or(ScopeError::IntegerOverflow)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(price_cents: u64, base_amount: u64) -> BookOrder {
+        BookOrder {
+            price: Decimal::from(price_cents).try_div(100).unwrap(),
+            base_amount,
+        }
+    }
+
+    fn config(min_depth: u64, max_spread_bps: u16, store_bid_ask: bool) -> OrderbookConfig {
+        OrderbookConfig {
+            min_depth,
+            max_spread_bps,
+            store_bid_ask,
+        }
+    }
+
+    #[test]
+    fn test_mid_price() {
+        let bids = [order(9_990, 10), order(9_980, 10)];
+        let asks = [order(10_010, 10), order(10_020, 10)];
+        let price = get_mid_price(
+            &Pubkey::default(),
+            &bids,
+            &asks,
+            &Clock::default(),
+            &config(20, 50, true),
+        )
+        .unwrap();
+        assert_eq!(price.price, Price { value: 100, exp: 0 });
+        assert_eq!(
+            Price {
+                value: price.bid_ask[0],
+                exp: price.price.exp
+            },
+            Price {
+                value: 9_990,
+                exp: 2
+            }
+        );
+        assert_eq!(
+            Price {
+                value: price.bid_ask[1],
+                exp: price.price.exp
+            },
+            Price {
+                value: 10_010,
+                exp: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_spread_too_wide() {
+        let bids = [order(9_900, 10)];
+        let asks = [order(10_100, 10)];
+        let clock = Clock::default();
+        assert!(get_mid_price(
+            &Pubkey::default(),
+            &bids,
+            &asks,
+            &clock,
+            &config(0, 200, false)
+        )
+        .is_ok());
+        assert_eq!(
+            get_mid_price(
+                &Pubkey::default(),
+                &bids,
+                &asks,
+                &clock,
+                &config(0, 199, false)
+            )
+            .unwrap_err(),
+            ScopeError::OrderbookSpreadTooWide
+        );
+    }
+
+    #[test]
+    fn test_depth_too_low() {
+        // The second bid is outside of the 1% band around the mid-price
+        let bids = [order(9_990, 10), order(9_800, 100)];
+        let asks = [order(10_010, 10), order(10_020, 10)];
+        let clock = Clock::default();
+        assert_eq!(
+            get_mid_price(
+                &Pubkey::default(),
+                &bids,
+                &asks,
+                &clock,
+                &config(20, 100, false)
+            )
+            .unwrap_err(),
+            ScopeError::OrderbookDepthTooLow
+        );
+        assert!(get_mid_price(
+            &Pubkey::default(),
+            &bids,
+            &asks,
+            &clock,
+            &config(10, 100, false)
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_empty_or_crossed_book() {
+        let clock = Clock::default();
+        let config = config(0, 100, false);
+        assert_eq!(
+            get_mid_price(&Pubkey::default(), &[], &[order(100, 1)], &clock, &config).unwrap_err(),
+            ScopeError::OrderbookEmpty
+        );
+        assert_eq!(
+            get_mid_price(
+                &Pubkey::default(),
+                &[order(101, 1)],
+                &[order(100, 1)],
+                &clock,
+                &config
+            )
+            .unwrap_err(),
+            ScopeError::PriceNotValid
+        );
+    }
+
+    #[test]
+    fn test_config_from_generic_data() {
+        let mut generic_data = [0; GENERIC_DATA_LEN];
+        assert_eq!(
+            OrderbookConfig::from_generic_data(&generic_data).unwrap_err(),
+            ScopeError::InvalidGenericData
+        );
+        generic_data[0..8].copy_from_slice(&1_000_u64.to_le_bytes());
+        generic_data[8..10].copy_from_slice(&50_u16.to_le_bytes());
+        generic_data[10] = 1;
+        assert_eq!(
+            OrderbookConfig::from_generic_data(&generic_data).unwrap(),
+            config(1_000, 50, true)
+        );
+    }
+}
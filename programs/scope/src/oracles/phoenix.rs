@@ -0,0 +1,377 @@
+//! Phoenix market mid-price
+use anchor_lang::prelude::*;
+use decimal_wad::common::{TryDiv, TryMul};
+use decimal_wad::decimal::Decimal;
+
+use self::phoenix_v1::{MarketHeader, RestingOrder};
+use super::orderbook::{self, BookOrder, OrderbookConfig, MAX_DEPTH_ORDERS};
+use crate::{utils::math::ten_pow, DatedPrice, Result, ScopeError, ScopeResult, GENERIC_DATA_LEN};
+
+/// Gives the mid-price of a Phoenix market (quote per base)
+pub fn get_price(
+    market_account: &AccountInfo,
+    clock: &Clock,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> Result<DatedPrice> {
+    let config = OrderbookConfig::from_generic_data(generic_data)?;
+    require_keys_eq!(
+        *market_account.owner,
+        phoenix_v1::ID,
+        ScopeError::UnexpectedAccount
+    );
+    let data = market_account.data.borrow();
+    let header = MarketHeader::unpack(&data)?;
+
+    let now_ts = u64::try_from(clock.unix_timestamp).map_err(|_| ScopeError::BadTimestamp)?;
+    let to_book_orders = |orders: Vec<RestingOrder>| -> ScopeResult<Vec<BookOrder>> {
+        orders
+            .into_iter()
+            .map(|order| -> ScopeResult<BookOrder> {
+                Ok(BookOrder {
+                    price: header.ticks_to_ui_price(order.price_in_ticks)?,
+                    base_amount: header.base_lots_to_native(order.num_base_lots)?,
+                })
+            })
+            .collect()
+    };
+    let bids = to_book_orders(header.best_orders(
+        &data,
+        phoenix_v1::Side::Bid,
+        clock.slot,
+        now_ts,
+        MAX_DEPTH_ORDERS,
+    )?)?;
+    let asks = to_book_orders(header.best_orders(
+        &data,
+        phoenix_v1::Side::Ask,
+        clock.slot,
+        now_ts,
+        MAX_DEPTH_ORDERS,
+    )?)?;
+
+    Ok(orderbook::get_mid_price(
+        market_account.key,
+        &bids,
+        &asks,
+        clock,
+        &config,
+    )?)
+}
+
+pub fn validate_market_account(
+    market_account: &AccountInfo,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> Result<()> {
+    let _ = OrderbookConfig::from_generic_data(generic_data)?;
+    require_keys_eq!(
+        *market_account.owner,
+        phoenix_v1::ID,
+        ScopeError::UnexpectedAccount
+    );
+    let _ = MarketHeader::unpack(&market_account.data.borrow())?;
+    Ok(())
+}
+
+/// Partial layout of the Phoenix market account
+///
+/// The market account is made of a header followed by the FIFO market state holding the bids and
+/// asks red-black trees. Both trees are sorted best order first.
+pub mod phoenix_v1 {
+    use arrayref::array_ref;
+
+    use super::*;
+
+    declare_id!("PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY");
+
+    pub const MARKET_DISCRIMINATOR: [u8; 8] = [85, 153, 127, 98, 215, 115, 0, 175];
+
+    const STATUS_OFFSET: usize = 8;
+    const BIDS_SIZE_OFFSET: usize = 16;
+    const ASKS_SIZE_OFFSET: usize = 24;
+    const BASE_LOT_SIZE_OFFSET: usize = 112;
+    const QUOTE_DECIMALS_OFFSET: usize = 120;
+    const TICK_SIZE_OFFSET: usize = 200;
+    const RAW_BASE_UNITS_PER_BASE_UNIT_OFFSET: usize = 312;
+    const HEADER_LEN: usize = 576;
+
+    /// Offset of the bids tree, after the header and the FIFO market fields
+    const BIDS_TREE_OFFSET: usize = HEADER_LEN + 304;
+    /// Root (u32) and padding before the nodes allocator
+    const TREE_HEADER_LEN: usize = 16;
+    /// Size (u64), bump index (u32) and free list head (u32) before the nodes
+    const ALLOCATOR_HEADER_LEN: usize = 16;
+    const NODE_LEN: usize = 64;
+    /// Null node address
+    const SENTINEL: u32 = 0;
+
+    /// Status of a market where the book is live
+    const ACTIVE_STATUS: u64 = 1;
+    const POST_ONLY_STATUS: u64 = 2;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Side {
+        Bid,
+        Ask,
+    }
+
+    /// Market header (partial)
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct MarketHeader {
+        pub status: u64,
+        pub bids_size: u64,
+        pub asks_size: u64,
+        pub base_lot_size: u64,
+        pub quote_decimals: u32,
+        pub tick_size_in_quote_atoms_per_base_unit: u64,
+        pub raw_base_units_per_base_unit: u32,
+    }
+
+    /// A resting order of the book (partial)
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct RestingOrder {
+        pub price_in_ticks: u64,
+        pub num_base_lots: u64,
+        pub last_valid_slot: u64,
+        pub last_valid_unix_timestamp_in_seconds: u64,
+    }
+
+    impl RestingOrder {
+        pub fn is_expired(&self, current_slot: u64, now_ts: u64) -> bool {
+            (self.last_valid_slot != 0 && self.last_valid_slot < current_slot)
+                || (self.last_valid_unix_timestamp_in_seconds != 0
+                    && self.last_valid_unix_timestamp_in_seconds < now_ts)
+        }
+    }
+
+    impl MarketHeader {
+        pub fn unpack(data: &[u8]) -> Result<Self> {
+            if data.len() < BIDS_TREE_OFFSET {
+                msg!("Phoenix market account has an invalid size");
+                return err!(ScopeError::UnableToDeserializeAccount);
+            }
+            if data[..8] != MARKET_DISCRIMINATOR {
+                msg!("Phoenix market account has an invalid discriminator");
+                return err!(ScopeError::InvalidAccountDiscriminator);
+            }
+            let read_u64 = |offset: usize| u64::from_le_bytes(*array_ref![data, offset, 8]);
+            let read_u32 = |offset: usize| u32::from_le_bytes(*array_ref![data, offset, 4]);
+            let header = Self {
+                status: read_u64(STATUS_OFFSET),
+                bids_size: read_u64(BIDS_SIZE_OFFSET),
+                asks_size: read_u64(ASKS_SIZE_OFFSET),
+                base_lot_size: read_u64(BASE_LOT_SIZE_OFFSET),
+                quote_decimals: read_u32(QUOTE_DECIMALS_OFFSET),
+                tick_size_in_quote_atoms_per_base_unit: read_u64(TICK_SIZE_OFFSET),
+                raw_base_units_per_base_unit: read_u32(RAW_BASE_UNITS_PER_BASE_UNIT_OFFSET),
+            };
+            if header.status != ACTIVE_STATUS && header.status != POST_ONLY_STATUS {
+                msg!("Phoenix market is not active (status {})", header.status);
+                return err!(ScopeError::PriceNotValid);
+            }
+            if data.len() < header.asks_tree_offset()? + header.tree_len(header.asks_size)? {
+                msg!("Phoenix market account is too small for its size parameters");
+                return err!(ScopeError::UnableToDeserializeAccount);
+            }
+            Ok(header)
+        }
+
+        fn tree_len(&self, size: u64) -> ScopeResult<usize> {
+            usize::try_from(size)?
+                .checked_mul(NODE_LEN)
+                .and_then(|nodes_len| nodes_len.checked_add(TREE_HEADER_LEN + ALLOCATOR_HEADER_LEN))
+                .ok_or(ScopeError::IntegerOverflow)
+        }
+
+        fn asks_tree_offset(&self) -> ScopeResult<usize> {
+            Ok(BIDS_TREE_OFFSET + self.tree_len(self.bids_size)?)
+        }
+
+        /// Convert a price in ticks to a price in quote tokens per base token
+        pub fn ticks_to_ui_price(&self, price_in_ticks: u64) -> ScopeResult<Decimal> {
+            // Older markets have the raw base units per base unit unset
+            let raw_base_units_per_base_unit = self.raw_base_units_per_base_unit.max(1);
+            Ok(Decimal::from(price_in_ticks)
+                .try_mul(self.tick_size_in_quote_atoms_per_base_unit)?
+                .try_div(u64::from(raw_base_units_per_base_unit))?
+                .try_div(Decimal::from(ten_pow(u8::try_from(self.quote_decimals)?)?))?)
+        }
+
+        /// Convert a quantity in base lots to lamports of the base token
+        pub fn base_lots_to_native(&self, base_lots: u64) -> ScopeResult<u64> {
+            base_lots
+                .checked_mul(self.base_lot_size)
+                .ok_or(ScopeError::IntegerOverflow)
+        }
+
+        /// Get up to `max_orders` non-expired orders of one side of the book, best first
+        pub fn best_orders(
+            &self,
+            data: &[u8],
+            side: Side,
+            current_slot: u64,
+            now_ts: u64,
+            max_orders: usize,
+        ) -> ScopeResult<Vec<RestingOrder>> {
+            let (tree_offset, tree_size) = match side {
+                Side::Bid => (BIDS_TREE_OFFSET, self.bids_size),
+                Side::Ask => (self.asks_tree_offset()?, self.asks_size),
+            };
+            let tree_size = usize::try_from(tree_size)?;
+            let nodes_offset = tree_offset + TREE_HEADER_LEN + ALLOCATOR_HEADER_LEN;
+            let node = |address: u32| -> ScopeResult<&[u8]> {
+                // Node addresses start at 1, 0 being the sentinel
+                let index = usize::try_from(address)? - 1;
+                if index >= tree_size {
+                    return Err(ScopeError::UnableToDeserializeAccount);
+                }
+                let offset = nodes_offset + index * NODE_LEN;
+                Ok(&data[offset..offset + NODE_LEN])
+            };
+            let left = |node: &[u8]| u32::from_le_bytes(*array_ref![node, 0, 4]);
+            let right = |node: &[u8]| u32::from_le_bytes(*array_ref![node, 4, 4]);
+
+            // In-order traversal of the tree
+            let mut orders = Vec::with_capacity(max_orders);
+            let mut stack = Vec::new();
+            let mut current = u32::from_le_bytes(*array_ref![data, tree_offset, 4]);
+            let mut visited_nodes = 0;
+            while current != SENTINEL || !stack.is_empty() {
+                visited_nodes += 1;
+                if visited_nodes > 2 * tree_size {
+                    // Prevent looping on a corrupted tree
+                    return Err(ScopeError::UnableToDeserializeAccount);
+                }
+                if current != SENTINEL {
+                    stack.push(current);
+                    current = left(node(current)?);
+                    continue;
+                }
+                let address = stack.pop().ok_or(ScopeError::UnableToDeserializeAccount)?;
+                let node = node(address)?;
+                let order = RestingOrder {
+                    price_in_ticks: u64::from_le_bytes(*array_ref![node, 16, 8]),
+                    num_base_lots: u64::from_le_bytes(*array_ref![node, 40, 8]),
+                    last_valid_slot: u64::from_le_bytes(*array_ref![node, 48, 8]),
+                    last_valid_unix_timestamp_in_seconds: u64::from_le_bytes(*array_ref![
+                        node, 56, 8
+                    ]),
+                };
+                if !order.is_expired(current_slot, now_ts) {
+                    orders.push(order);
+                    if orders.len() == max_orders {
+                        break;
+                    }
+                }
+                current = right(node);
+            }
+            Ok(orders)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::phoenix_v1::*;
+    use super::*;
+
+    const TREE_SIZE: u64 = 4;
+
+    fn header() -> MarketHeader {
+        MarketHeader {
+            status: 1,
+            bids_size: TREE_SIZE,
+            asks_size: TREE_SIZE,
+            base_lot_size: 1_000_000,
+            quote_decimals: 6,
+            tick_size_in_quote_atoms_per_base_unit: 1_000,
+            raw_base_units_per_base_unit: 1,
+        }
+    }
+
+    fn market_data() -> Vec<u8> {
+        let header = header();
+        let mut data = vec![0_u8; 880 + 2 * (32 + TREE_SIZE as usize * 64)];
+        data[..8].copy_from_slice(&MARKET_DISCRIMINATOR);
+        data[8..16].copy_from_slice(&header.status.to_le_bytes());
+        data[16..24].copy_from_slice(&header.bids_size.to_le_bytes());
+        data[24..32].copy_from_slice(&header.asks_size.to_le_bytes());
+        data[112..120].copy_from_slice(&header.base_lot_size.to_le_bytes());
+        data[120..124].copy_from_slice(&header.quote_decimals.to_le_bytes());
+        data[200..208]
+            .copy_from_slice(&header.tick_size_in_quote_atoms_per_base_unit.to_le_bytes());
+        data[312..316].copy_from_slice(&header.raw_base_units_per_base_unit.to_le_bytes());
+        data
+    }
+
+    /// Write a node of the tree starting at `tree_offset`
+    fn write_node(
+        data: &mut [u8],
+        tree_offset: usize,
+        address: u32,
+        children: [u32; 2],
+        price_in_ticks: u64,
+        last_valid_slot: u64,
+    ) {
+        let offset = tree_offset + 32 + (address as usize - 1) * 64;
+        data[offset..offset + 4].copy_from_slice(&children[0].to_le_bytes());
+        data[offset + 4..offset + 8].copy_from_slice(&children[1].to_le_bytes());
+        data[offset + 16..offset + 24].copy_from_slice(&price_in_ticks.to_le_bytes());
+        data[offset + 40..offset + 48].copy_from_slice(&1_u64.to_le_bytes());
+        data[offset + 48..offset + 56].copy_from_slice(&last_valid_slot.to_le_bytes());
+    }
+
+    #[test]
+    fn test_unpack_header() {
+        let data = market_data();
+        assert_eq!(MarketHeader::unpack(&data).unwrap(), header());
+    }
+
+    #[test]
+    fn test_ticks_to_ui_price() {
+        // 20_000 ticks of 0.001 USDC per SOL
+        let price = header().ticks_to_ui_price(20_000).unwrap();
+        assert_eq!(price, Decimal::from(20u64));
+    }
+
+    #[test]
+    fn test_ticks_to_ui_price_unsupported_decimals() {
+        for quote_decimals in [31, 256] {
+            let header = MarketHeader {
+                quote_decimals,
+                ..header()
+            };
+            assert!(header.ticks_to_ui_price(20_000).is_err());
+        }
+    }
+
+    #[test]
+    fn test_best_orders() {
+        let mut data = market_data();
+        let asks_offset = 880 + 32 + TREE_SIZE as usize * 64;
+        // Asks tree: root (2) -> [1, 3], the best order (1) is expired
+        data[asks_offset..asks_offset + 4].copy_from_slice(&2_u32.to_le_bytes());
+        write_node(&mut data, asks_offset, 1, [0, 0], 100, 5);
+        write_node(&mut data, asks_offset, 2, [1, 3], 101, 0);
+        write_node(&mut data, asks_offset, 3, [0, 0], 102, 0);
+        let header = MarketHeader::unpack(&data).unwrap();
+
+        let asks: Vec<u64> = header
+            .best_orders(&data, Side::Ask, 10, 0, 10)
+            .unwrap()
+            .iter()
+            .map(|order| order.price_in_ticks)
+            .collect();
+        assert_eq!(asks, vec![101, 102]);
+
+        let asks: Vec<u64> = header
+            .best_orders(&data, Side::Ask, 1, 0, 2)
+            .unwrap()
+            .iter()
+            .map(|order| order.price_in_ticks)
+            .collect();
+        assert_eq!(asks, vec![100, 101]);
+
+        let bids = header.best_orders(&data, Side::Bid, 10, 0, 10).unwrap();
+        assert!(bids.is_empty());
+    }
+}
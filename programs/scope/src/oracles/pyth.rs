@@ -9,7 +9,7 @@
 //! 2. Upon usage the current price state is checked in [`validate_valid_price`]
 //! 3. The confidence interval is also checked in this same function with [`ORACLE_CONFIDENCE_FACTOR`]
 
-use std::convert::{TryFrom, TryInto};
+use std::convert::TryFrom;
 
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::clock::DEFAULT_MS_PER_SLOT;
@@ -28,7 +28,7 @@ pub fn get_price(price_info: &AccountInfo, clock: &Clock) -> Result<DatedPrice>
     let data = price_info.try_borrow_data()?;
     let price_account: &pyth_client::SolanaPriceAccount =
         pyth_client::load_price_account(data.as_ref())
-            .map_err(|_| error!(ScopeError::PriceNotValid))?;
+            .map_err(|_| error!(ScopeError::PythAccountNotValid))?;
 
     let oldest_accepted_slot = clock.slot.saturating_sub(STALENESS_SLOT_THRESHOLD);
 
@@ -61,7 +61,7 @@ pub fn get_price(price_info: &AccountInfo, clock: &Clock) -> Result<DatedPrice>
         )
     } else {
         msg!("No valid price in pyth account {}", price_info.key);
-        return err!(ScopeError::PriceNotValid);
+        return err!(ScopeError::PythPriceStale);
     };
 
     if pyth_price.expo > 0 {
@@ -69,7 +69,7 @@ pub fn get_price(price_info: &AccountInfo, clock: &Clock) -> Result<DatedPrice>
             "Pyth price account provided has a negative price exponent: {}",
             pyth_price.expo
         );
-        return err!(ScopeError::PriceNotValid);
+        return err!(ScopeError::PythPriceNotValid);
     }
 
     let price = validate_valid_price(&pyth_price, ORACLE_CONFIDENCE_FACTOR).map_err(|e| {
@@ -83,10 +83,10 @@ pub fn get_price(price_info: &AccountInfo, clock: &Clock) -> Result<DatedPrice>
     Ok(DatedPrice {
         price: Price {
             value: price,
-            exp: pyth_price.expo.abs().try_into().unwrap(),
+            exp: pyth_price.expo.unsigned_abs().into(),
         },
         last_updated_slot: slot,
-        unix_timestamp: u64::try_from(timestamp).unwrap(),
+        unix_timestamp: u64::try_from(timestamp).map_err(|_| ScopeError::BadTimestamp)?,
         ..Default::default()
     })
 }
@@ -95,18 +95,21 @@ pub fn validate_valid_price(
     pyth_price: &pyth_client::Price,
     oracle_confidence_factor: u64,
 ) -> Result<u64> {
+    let price =
+        u64::try_from(pyth_price.price).map_err(|_| error!(ScopeError::PythPriceNotValid))?;
     if cfg!(feature = "skip_price_validation") {
-        return Ok(u64::try_from(pyth_price.price).unwrap());
+        return Ok(price);
     }
 
-    let price = u64::try_from(pyth_price.price).unwrap();
     if price == 0 {
-        return err!(ScopeError::PriceNotValid);
+        return err!(ScopeError::PythPriceNotValid);
     }
     let conf: u64 = pyth_price.conf;
-    let conf_50x: u64 = conf.checked_mul(oracle_confidence_factor).unwrap();
+    let conf_50x: u64 = conf
+        .checked_mul(oracle_confidence_factor)
+        .ok_or(ScopeError::PythConfidenceTooWide)?;
     if conf_50x > price {
-        return err!(ScopeError::PriceNotValid);
+        return err!(ScopeError::PythConfidenceTooWide);
     };
     Ok(price)
 }
@@ -114,19 +117,19 @@ pub fn validate_valid_price(
 fn validate_pyth_price(pyth_price: &pyth_client::SolanaPriceAccount) -> Result<()> {
     if pyth_price.magic != pyth_client::MAGIC {
         msg!("Pyth price account provided is not a valid Pyth account");
-        return err!(ScopeError::PriceNotValid);
+        return err!(ScopeError::PythAccountNotValid);
     }
     if !matches!(pyth_price.ptype, PriceType::Price) {
         msg!("Pyth price account provided has invalid price type");
-        return err!(ScopeError::PriceNotValid);
+        return err!(ScopeError::PythAccountNotValid);
     }
     if pyth_price.ver != pyth_client::VERSION_2 {
         msg!("Pyth price account provided has a different version than the Pyth client");
-        return err!(ScopeError::PriceNotValid);
+        return err!(ScopeError::PythAccountNotValid);
     }
     if !matches!(pyth_price.agg.status, pyth_client::PriceStatus::Trading) {
         msg!("Pyth price account provided is not active");
-        return err!(ScopeError::PriceNotValid);
+        return err!(ScopeError::PythAccountNotValid);
     }
     Ok(())
 }
@@ -136,7 +139,8 @@ pub fn validate_pyth_price_info(pyth_price_info: &AccountInfo) -> Result<()> {
         return Ok(());
     }
     let pyth_price_data = pyth_price_info.try_borrow_data()?;
-    let pyth_price = pyth_client::load_price_account(&pyth_price_data).unwrap();
+    let pyth_price = pyth_client::load_price_account(&pyth_price_data)
+        .map_err(|_| error!(ScopeError::PythAccountNotValid))?;
 
     validate_pyth_price(pyth_price)
 }
@@ -185,7 +189,10 @@ mod tests {
         let mut buff = valid_price_bytes();
         write_bytes(&mut buff, incorrect_price_type, PRICE_TYPE_OFFSET);
         let price = pyth_client::load_price_account(&buff).unwrap();
-        assert_err(super::validate_pyth_price(price), ScopeError::PriceNotValid);
+        assert_err(
+            super::validate_pyth_price(price),
+            ScopeError::PythAccountNotValid,
+        );
     }
 
     #[test]
@@ -205,7 +212,10 @@ mod tests {
         let mut buff = valid_price_bytes();
         write_bytes(&mut buff, &incorrect_price_status, PRICE_STATUS_OFFSET);
         let price = pyth_client::load_price_account(&buff).unwrap();
-        assert_err(super::validate_pyth_price(price), ScopeError::PriceNotValid);
+        assert_err(
+            super::validate_pyth_price(price),
+            ScopeError::PythAccountNotValid,
+        );
     }
 
     fn valid_price_bytes() -> [u8; PRICE_ACCT_SIZE] {
@@ -9,7 +9,7 @@
 //! 2. Upon usage the current ema price state is checked in [`validate_valid_price`]
 //! 3. The confidence interval is also checked in this same function with [`ORACLE_CONFIDENCE_FACTOR`]
 
-use std::convert::{TryFrom, TryInto};
+use std::convert::TryFrom;
 
 use anchor_lang::prelude::*;
 use pyth_sdk_solana::state as pyth_client;
@@ -26,7 +26,7 @@ pub fn get_price(price_info: &AccountInfo, clock: &Clock) -> Result<DatedPrice>
     let data = price_info.try_borrow_data()?;
     let price_account: &pyth_client::SolanaPriceAccount =
         pyth_client::load_price_account(data.as_ref())
-            .map_err(|_| error!(ScopeError::PriceNotValid))?;
+            .map_err(|_| error!(ScopeError::PythAccountNotValid))?;
 
     let pyth_raw = price_account.to_price_feed(price_info.key);
 
@@ -38,13 +38,13 @@ pub fn get_price(price_info: &AccountInfo, clock: &Clock) -> Result<DatedPrice>
     {
         if price_account.agg.status != pyth_client::PriceStatus::Trading {
             msg!("No valid EMA price in pyth account {}", price_info.key);
-            return err!(ScopeError::PriceNotValid);
+            return err!(ScopeError::PythPriceStale);
         }
         // Or use the current valid price if available
         pyth_ema_price
     } else {
         msg!("No valid EMA price in pyth account {}", price_info.key);
-        return err!(ScopeError::PriceNotValid);
+        return err!(ScopeError::PythPriceStale);
     };
 
     if pyth_ema_price.expo > 0 {
@@ -52,7 +52,7 @@ pub fn get_price(price_info: &AccountInfo, clock: &Clock) -> Result<DatedPrice>
             "Pyth price account provided has a negative EMA price exponent: {}",
             pyth_ema_price.expo
         );
-        return err!(ScopeError::PriceNotValid);
+        return err!(ScopeError::PythPriceNotValid);
     }
 
     let ema_price =
@@ -65,10 +65,11 @@ pub fn get_price(price_info: &AccountInfo, clock: &Clock) -> Result<DatedPrice>
     Ok(DatedPrice {
         price: Price {
             value: ema_price,
-            exp: pyth_ema_price.expo.abs().try_into().unwrap(),
+            exp: pyth_ema_price.expo.unsigned_abs().into(),
         },
         last_updated_slot: price_account.valid_slot,
-        unix_timestamp: u64::try_from(price_account.timestamp).unwrap(),
+        unix_timestamp: u64::try_from(price_account.timestamp)
+            .map_err(|_| ScopeError::BadTimestamp)?,
         ..Default::default()
     })
 }
@@ -0,0 +1,135 @@
+use anchor_lang::prelude::*;
+
+use self::raydium_amm::AmmInfo;
+use super::constant_product::{get_price_from_reserves, get_vault_amount, PoolReserves};
+use crate::{DatedPrice, Result, ScopeError, GENERIC_DATA_LEN};
+
+/// Gives the price of the given token pair in the given Raydium AMM v4 pool
+///
+/// Token A is the "coin" and token B the "pc" of the pool, see [`get_reserves`].
+pub fn get_price<'a, 'b>(
+    a_to_b: bool,
+    pool: &AccountInfo,
+    clock: &Clock,
+    extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> Result<DatedPrice>
+where
+    'a: 'b,
+{
+    let reserves = get_reserves(pool, extra_accounts)?;
+    get_price_from_reserves(a_to_b, pool.key, &reserves, clock, generic_data)
+}
+
+/// Gives the reserves of the given Raydium AMM v4 pool
+///
+/// The reserves are the vault balances minus the pnl owed to the pool owner.
+/// Funds placed on the order book are not taken into account.
+pub fn get_reserves<'a, 'b>(
+    pool: &AccountInfo,
+    extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+) -> Result<PoolReserves>
+where
+    'a: 'b,
+{
+    // Get extra accounts
+    let coin_vault_account_info = extra_accounts
+        .next()
+        .ok_or(ScopeError::AccountsAndTokenMismatch)?;
+    let pc_vault_account_info = extra_accounts
+        .next()
+        .ok_or(ScopeError::AccountsAndTokenMismatch)?;
+
+    // Load main account
+    let amm_info = AmmInfo::from_account(pool)?;
+
+    // Load extra accounts
+    let coin_vault_amount = get_vault_amount(coin_vault_account_info, &amm_info.coin_vault)?;
+    let pc_vault_amount = get_vault_amount(pc_vault_account_info, &amm_info.pc_vault)?;
+
+    let coin_reserve = coin_vault_amount
+        .checked_sub(amm_info.need_take_pnl_coin)
+        .ok_or(ScopeError::MathOverflow)?;
+    let pc_reserve = pc_vault_amount
+        .checked_sub(amm_info.need_take_pnl_pc)
+        .ok_or(ScopeError::MathOverflow)?;
+
+    Ok(PoolReserves {
+        reserve_a: coin_reserve,
+        reserve_b: pc_reserve,
+        decimals_a: amm_info.coin_decimals,
+        decimals_b: amm_info.pc_decimals,
+        lp_mint: amm_info.lp_mint,
+    })
+}
+
+pub fn validate_pool_account(pool: &AccountInfo) -> Result<()> {
+    let _ = AmmInfo::from_account(pool)?;
+    Ok(())
+}
+
+pub mod raydium_amm {
+    use anchor_lang::prelude::*;
+    use arrayref::array_ref;
+
+    use crate::{Result, ScopeError};
+
+    declare_id!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
+
+    /// Fields of Raydium's AMM v4 `AmmInfo` needed to price the pool (the account has no discriminator)
+    pub struct AmmInfo {
+        pub coin_decimals: u8,
+        pub pc_decimals: u8,
+        pub need_take_pnl_coin: u64,
+        pub need_take_pnl_pc: u64,
+        pub coin_vault: Pubkey,
+        pub pc_vault: Pubkey,
+        pub coin_vault_mint: Pubkey,
+        pub pc_vault_mint: Pubkey,
+        pub lp_mint: Pubkey,
+    }
+
+    impl AmmInfo {
+        pub const LEN: usize = 752;
+
+        const COIN_DECIMALS_OFFSET: usize = 4 * 8;
+        const PC_DECIMALS_OFFSET: usize = 5 * 8;
+        // After the 16 u64 parameters and the 8 u64 fees
+        const NEED_TAKE_PNL_COIN_OFFSET: usize = 24 * 8;
+        const NEED_TAKE_PNL_PC_OFFSET: usize = 25 * 8;
+        const COIN_VAULT_OFFSET: usize = 336;
+        const PC_VAULT_OFFSET: usize = 368;
+        const COIN_VAULT_MINT_OFFSET: usize = 400;
+        const PC_VAULT_MINT_OFFSET: usize = 432;
+        const LP_MINT_OFFSET: usize = 464;
+
+        pub fn from_account(account: &AccountInfo) -> Result<Self> {
+            require_keys_eq!(*account.owner, ID, ScopeError::UnexpectedAccount);
+            Self::unpack(&account.data.borrow())
+        }
+
+        pub fn unpack(data: &[u8]) -> Result<Self> {
+            if data.len() != Self::LEN {
+                msg!("Raydium AMM account has an invalid size");
+                return err!(ScopeError::UnableToDeserializeAccount);
+            }
+            let read_u64 = |offset: usize| u64::from_le_bytes(*array_ref![data, offset, 8]);
+            let read_pubkey = |offset: usize| Pubkey::new_from_array(*array_ref![data, offset, 32]);
+            Ok(Self {
+                coin_decimals: read_u64(Self::COIN_DECIMALS_OFFSET)
+                    .try_into()
+                    .map_err(|_| ScopeError::UnableToDeserializeAccount)?,
+                pc_decimals: read_u64(Self::PC_DECIMALS_OFFSET)
+                    .try_into()
+                    .map_err(|_| ScopeError::UnableToDeserializeAccount)?,
+                need_take_pnl_coin: read_u64(Self::NEED_TAKE_PNL_COIN_OFFSET),
+                need_take_pnl_pc: read_u64(Self::NEED_TAKE_PNL_PC_OFFSET),
+                coin_vault: read_pubkey(Self::COIN_VAULT_OFFSET),
+                pc_vault: read_pubkey(Self::PC_VAULT_OFFSET),
+                coin_vault_mint: read_pubkey(Self::COIN_VAULT_MINT_OFFSET),
+                pc_vault_mint: read_pubkey(Self::PC_VAULT_MINT_OFFSET),
+                lp_mint: read_pubkey(Self::LP_MINT_OFFSET),
+            })
+        }
+    }
+}
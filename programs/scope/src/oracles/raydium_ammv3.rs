@@ -1,15 +1,35 @@
+use std::cell::Ref;
+
 use anchor_lang::prelude::*;
+use arrayref::array_ref;
+use decimal_wad::decimal::U192;
+use raydium_amm_v3::states::{ObservationState, PoolState};
+
+use crate::utils::math::{clmm_quote_virtual_reserve, sqrt_price_to_price};
+use crate::utils::{account_deserialize, check_min_liquidity, zero_copy_deserialize};
+use crate::{DatedPrice, Result, ScopeError, ScopeResult, GENERIC_DATA_LEN};
 
-use crate::utils::account_deserialize;
-use crate::utils::math::sqrt_price_to_price;
-use crate::{DatedPrice, Result};
-use raydium_amm_v3::states::PoolState;
+/// Minimum accepted TWAP window, shorter windows are as easy to move as the spot price
+const MIN_TWAP_WINDOW_S: u32 = 60;
 
 /// Gives the price of the given token pair in the given pool
-pub fn get_price(a_to_b: bool, pool: &AccountInfo, clock: &Clock) -> Result<DatedPrice> {
+///
+/// The in-range liquidity of the pool is checked against the minimum configured in the entry
+/// generic data, see [`crate::utils::get_min_liquidity`].
+pub fn get_price(
+    a_to_b: bool,
+    pool: &AccountInfo,
+    clock: &Clock,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> Result<DatedPrice> {
     // Load main account
     let pool_data: PoolState = account_deserialize(pool)?;
 
+    // Check pool liquidity
+    let quote_liquidity =
+        clmm_quote_virtual_reserve(a_to_b, pool_data.liquidity, pool_data.sqrt_price_x64);
+    check_min_liquidity(pool.key, quote_liquidity, generic_data)?;
+
     // Compute price
     let price = sqrt_price_to_price(
         a_to_b,
@@ -27,7 +47,194 @@ pub fn get_price(a_to_b: bool, pool: &AccountInfo, clock: &Clock) -> Result<Date
     })
 }
 
+/// Gives the time-weighted average price of the given token pair in the given pool
+///
+/// The average is computed from the pool's own observations ring buffer (extra account)
+/// over the window configured in the entry generic data:
+///
+/// - bytes `[0..4]`: TWAP window in seconds (u32, little endian)
+pub fn get_twap_price<'a, 'b>(
+    a_to_b: bool,
+    pool: &AccountInfo,
+    clock: &Clock,
+    extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> Result<DatedPrice>
+where
+    'a: 'b,
+{
+    // Get extra accounts
+    let observation_account_info = extra_accounts
+        .next()
+        .ok_or(ScopeError::AccountsAndTokenMismatch)?;
+
+    // Load main account
+    let pool_data: PoolState = account_deserialize(pool)?;
+
+    // Check extra accounts pubkeys
+    require_keys_eq!(
+        pool_data.observation_key,
+        observation_account_info.key(),
+        ScopeError::AccountsAndTokenMismatch
+    );
+
+    // Load extra accounts
+    let observation_state: Ref<'_, ObservationState> =
+        zero_copy_deserialize(observation_account_info)?;
+
+    require_keys_eq!(
+        observation_state.pool_id,
+        pool.key(),
+        ScopeError::AccountsAndTokenMismatch
+    );
+
+    let window_s = get_twap_window(generic_data)?;
+    let current_ts: u64 = clock
+        .unix_timestamp
+        .try_into()
+        .map_err(|_| ScopeError::BadTimestamp)?;
+
+    // Iterate on the observations from the most recent to the oldest one
+    let observations_len = observation_state.observations.len();
+    let last_observation_index = usize::from(pool_data.observation_index);
+    if last_observation_index >= observations_len {
+        msg!("Raydium pool observation index {last_observation_index} is out of range");
+        return err!(ScopeError::TwapNotEnoughPoolObservations);
+    }
+    let observations = (0..observations_len).map(|i| {
+        let index = (last_observation_index + observations_len - i) % observations_len;
+        let observation = observation_state.observations[index];
+        (
+            u64::from(observation.block_timestamp),
+            observation.sqrt_price_x64,
+        )
+    });
+
+    let twap_sqrt_price_x64 = time_weighted_sqrt_price(observations, current_ts, window_s.into())
+        .map_err(|e| {
+        msg!(
+            "Raydium pool {} observations cannot cover a TWAP window of {window_s}s",
+            pool.key
+        );
+        e
+    })?;
+
+    // Compute price
+    let price = sqrt_price_to_price(
+        a_to_b,
+        twap_sqrt_price_x64,
+        pool_data.mint_decimals_0,
+        pool_data.mint_decimals_1,
+    )?;
+
+    // Return price
+    Ok(DatedPrice {
+        price,
+        last_updated_slot: clock.slot,
+        unix_timestamp: current_ts,
+        ..Default::default()
+    })
+}
+
 pub fn validate_pool_account(pool: &AccountInfo) -> Result<()> {
     let _: PoolState = account_deserialize(pool)?;
     Ok(())
 }
+
+pub fn validate_twap_pool_account(
+    pool: &AccountInfo,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> Result<()> {
+    validate_pool_account(pool)?;
+    let window_s = get_twap_window(generic_data)?;
+    msg!("Raydium pool TWAP window set to {window_s}s");
+    Ok(())
+}
+
+fn get_twap_window(generic_data: &[u8; GENERIC_DATA_LEN]) -> ScopeResult<u32> {
+    let window_s = u32::from_le_bytes(*array_ref![generic_data, 0, 4]);
+    if window_s < MIN_TWAP_WINDOW_S {
+        msg!("Raydium TWAP window {window_s}s is shorter than the minimum of {MIN_TWAP_WINDOW_S}s");
+        return Err(ScopeError::InvalidGenericData);
+    }
+    Ok(window_s)
+}
+
+/// Compute the time-weighted average of the sqrt prices over `[current_ts - window_s, current_ts]`
+///
+/// `observations` yields `(timestamp, sqrt_price_x64)` from the most recent to the oldest.
+/// Each observation is considered valid until the next one and the most recent one until `current_ts`.
+/// Fails if the observations do not cover the whole window.
+fn time_weighted_sqrt_price(
+    observations: impl Iterator<Item = (u64, u128)>,
+    current_ts: u64,
+    window_s: u64,
+) -> ScopeResult<u128> {
+    let window_start_ts = current_ts
+        .checked_sub(window_s)
+        .ok_or(ScopeError::BadTimestamp)?;
+
+    let mut weighted_sum = U192::zero();
+    let mut period_end_ts = current_ts;
+    for (observation_ts, sqrt_price_x64) in observations {
+        if observation_ts == 0 || observation_ts > period_end_ts {
+            // Uninitialized observation or we looped back to the most recent ones
+            break;
+        }
+        let period_start_ts = observation_ts.max(window_start_ts);
+        weighted_sum =
+            weighted_sum + U192::from(sqrt_price_x64) * U192::from(period_end_ts - period_start_ts);
+
+        if observation_ts <= window_start_ts {
+            let twap = weighted_sum / U192::from(window_s);
+            return Ok(twap.as_u128());
+        }
+        period_end_ts = observation_ts;
+    }
+
+    Err(ScopeError::TwapNotEnoughPoolObservations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE_X64: u128 = 1 << 64;
+
+    #[test]
+    fn test_twap_constant_price() {
+        let observations = [(1_000, ONE_X64), (900, ONE_X64), (100, ONE_X64)];
+        let twap = time_weighted_sqrt_price(observations.into_iter(), 1_060, 600).unwrap();
+        assert_eq!(twap, ONE_X64);
+    }
+
+    #[test]
+    fn test_twap_is_time_weighted() {
+        // Price 2 for the last 100s, price 1 for the 300s before
+        let observations = [(900, 2 * ONE_X64), (500, ONE_X64)];
+        let twap = time_weighted_sqrt_price(observations.into_iter(), 1_000, 400).unwrap();
+        assert_eq!(twap, ONE_X64 * 5 / 4);
+    }
+
+    #[test]
+    fn test_twap_ignores_observation_of_current_timestamp() {
+        // A manipulated observation written at the current timestamp has no weight
+        let observations = [(1_000, 100 * ONE_X64), (400, ONE_X64)];
+        let twap = time_weighted_sqrt_price(observations.into_iter(), 1_000, 600).unwrap();
+        assert_eq!(twap, ONE_X64);
+    }
+
+    #[test]
+    fn test_twap_not_enough_observations() {
+        let observations = [(1_000, ONE_X64), (900, ONE_X64), (0, 0)];
+        let res = time_weighted_sqrt_price(observations.into_iter(), 1_060, 600);
+        assert_eq!(res, Err(ScopeError::TwapNotEnoughPoolObservations));
+    }
+
+    #[test]
+    fn test_twap_stops_on_ring_buffer_wrap() {
+        let observations = [(1_000, ONE_X64), (900, ONE_X64), (1_100, ONE_X64)];
+        let res = time_weighted_sqrt_price(observations.into_iter(), 1_060, 600);
+        assert_eq!(res, Err(ScopeError::TwapNotEnoughPoolObservations));
+    }
+}
@@ -0,0 +1,155 @@
+use anchor_lang::prelude::*;
+
+use self::raydium_cp_swap::PoolState;
+use super::constant_product::{get_price_from_reserves, get_vault_amount, PoolReserves};
+use crate::{DatedPrice, Result, ScopeError, GENERIC_DATA_LEN};
+
+/// Gives the price of the given token pair in the given Raydium CPMM pool
+///
+/// Token A is the token 0 and token B the token 1 of the pool, see [`get_reserves`].
+pub fn get_price<'a, 'b>(
+    a_to_b: bool,
+    pool: &AccountInfo,
+    clock: &Clock,
+    extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> Result<DatedPrice>
+where
+    'a: 'b,
+{
+    let reserves = get_reserves(pool, extra_accounts)?;
+    get_price_from_reserves(a_to_b, pool.key, &reserves, clock, generic_data)
+}
+
+/// Gives the reserves of the given Raydium CPMM pool
+///
+/// The reserves are the vault balances minus the protocol and fund fees not yet collected.
+pub fn get_reserves<'a, 'b>(
+    pool: &AccountInfo,
+    extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+) -> Result<PoolReserves>
+where
+    'a: 'b,
+{
+    // Get extra accounts
+    let token_0_vault_account_info = extra_accounts
+        .next()
+        .ok_or(ScopeError::AccountsAndTokenMismatch)?;
+    let token_1_vault_account_info = extra_accounts
+        .next()
+        .ok_or(ScopeError::AccountsAndTokenMismatch)?;
+
+    // Load main account
+    let pool_state = PoolState::from_account(pool)?;
+
+    // Load extra accounts
+    let token_0_vault_amount =
+        get_vault_amount(token_0_vault_account_info, &pool_state.token_0_vault)?;
+    let token_1_vault_amount =
+        get_vault_amount(token_1_vault_account_info, &pool_state.token_1_vault)?;
+
+    let (token_0_reserve, token_1_reserve) =
+        pool_state.reserves_without_fees(token_0_vault_amount, token_1_vault_amount)?;
+
+    Ok(PoolReserves {
+        reserve_a: token_0_reserve,
+        reserve_b: token_1_reserve,
+        decimals_a: pool_state.mint_0_decimals,
+        decimals_b: pool_state.mint_1_decimals,
+        lp_mint: pool_state.lp_mint,
+    })
+}
+
+pub fn validate_pool_account(pool: &AccountInfo) -> Result<()> {
+    let _ = PoolState::from_account(pool)?;
+    Ok(())
+}
+
+pub mod raydium_cp_swap {
+    use anchor_lang::prelude::*;
+    use arrayref::array_ref;
+
+    use crate::{Result, ScopeError};
+
+    declare_id!("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C");
+
+    /// Fields of Raydium's CPMM `PoolState` needed to price the pool (the account is packed)
+    pub struct PoolState {
+        pub token_0_vault: Pubkey,
+        pub token_1_vault: Pubkey,
+        pub lp_mint: Pubkey,
+        pub token_0_mint: Pubkey,
+        pub token_1_mint: Pubkey,
+        pub mint_0_decimals: u8,
+        pub mint_1_decimals: u8,
+        pub protocol_fees_token_0: u64,
+        pub protocol_fees_token_1: u64,
+        pub fund_fees_token_0: u64,
+        pub fund_fees_token_1: u64,
+    }
+
+    impl PoolState {
+        pub const LEN: usize = 637;
+        pub const DISCRIMINATOR: [u8; 8] = [247, 237, 227, 245, 215, 195, 222, 70];
+
+        const TOKEN_0_VAULT_OFFSET: usize = 72;
+        const TOKEN_1_VAULT_OFFSET: usize = 104;
+        const LP_MINT_OFFSET: usize = 136;
+        const TOKEN_0_MINT_OFFSET: usize = 168;
+        const TOKEN_1_MINT_OFFSET: usize = 200;
+        const MINT_0_DECIMALS_OFFSET: usize = 331;
+        const MINT_1_DECIMALS_OFFSET: usize = 332;
+        const PROTOCOL_FEES_TOKEN_0_OFFSET: usize = 341;
+        const PROTOCOL_FEES_TOKEN_1_OFFSET: usize = 349;
+        const FUND_FEES_TOKEN_0_OFFSET: usize = 357;
+        const FUND_FEES_TOKEN_1_OFFSET: usize = 365;
+
+        pub fn from_account(account: &AccountInfo) -> Result<Self> {
+            require_keys_eq!(*account.owner, ID, ScopeError::UnexpectedAccount);
+            Self::unpack(&account.data.borrow())
+        }
+
+        pub fn unpack(data: &[u8]) -> Result<Self> {
+            if data.len() != Self::LEN {
+                msg!("Raydium CPMM account has an invalid size");
+                return err!(ScopeError::UnableToDeserializeAccount);
+            }
+            if data[..8] != Self::DISCRIMINATOR {
+                msg!("Raydium CPMM account has an invalid discriminator");
+                return err!(ScopeError::InvalidAccountDiscriminator);
+            }
+            let read_u64 = |offset: usize| u64::from_le_bytes(*array_ref![data, offset, 8]);
+            let read_pubkey = |offset: usize| Pubkey::new_from_array(*array_ref![data, offset, 32]);
+            Ok(Self {
+                token_0_vault: read_pubkey(Self::TOKEN_0_VAULT_OFFSET),
+                token_1_vault: read_pubkey(Self::TOKEN_1_VAULT_OFFSET),
+                lp_mint: read_pubkey(Self::LP_MINT_OFFSET),
+                token_0_mint: read_pubkey(Self::TOKEN_0_MINT_OFFSET),
+                token_1_mint: read_pubkey(Self::TOKEN_1_MINT_OFFSET),
+                mint_0_decimals: data[Self::MINT_0_DECIMALS_OFFSET],
+                mint_1_decimals: data[Self::MINT_1_DECIMALS_OFFSET],
+                protocol_fees_token_0: read_u64(Self::PROTOCOL_FEES_TOKEN_0_OFFSET),
+                protocol_fees_token_1: read_u64(Self::PROTOCOL_FEES_TOKEN_1_OFFSET),
+                fund_fees_token_0: read_u64(Self::FUND_FEES_TOKEN_0_OFFSET),
+                fund_fees_token_1: read_u64(Self::FUND_FEES_TOKEN_1_OFFSET),
+            })
+        }
+
+        /// Get the reserves of the pool from the vault balances, excluding the fees not yet collected
+        pub fn reserves_without_fees(
+            &self,
+            vault_0_amount: u64,
+            vault_1_amount: u64,
+        ) -> Result<(u64, u64)> {
+            let reserve_0 = vault_0_amount
+                .checked_sub(self.protocol_fees_token_0)
+                .and_then(|amount| amount.checked_sub(self.fund_fees_token_0))
+                .ok_or(ScopeError::MathOverflow)?;
+            let reserve_1 = vault_1_amount
+                .checked_sub(self.protocol_fees_token_1)
+                .and_then(|amount| amount.checked_sub(self.fund_fees_token_1))
+                .ok_or(ScopeError::MathOverflow)?;
+            Ok((reserve_0, reserve_1))
+        }
+    }
+}
@@ -0,0 +1,340 @@
+//! Prices pushed by whitelisted publishers through ed25519 signed messages
+//!
+//! The mapped account is the [`PricePublishers`] account of the entry holding the publishers
+//! whitelist. The refresh transaction must contain, before the refresh instruction, an ed25519
+//! program instruction verifying the signature of a message built with [`signed_price_message`]
+//! by one of the whitelisted publishers.
+//!
+//! Replay protection: the message is bound to the feed and the entry index, and its timestamp must
+//! be strictly more recent than the timestamp of the price currently stored in the entry.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    ed25519_program,
+    sysvar::instructions::{
+        load_current_index_checked, load_instruction_at_checked, ID as SYSVAR_INSTRUCTIONS_ID,
+    },
+};
+use arrayref::array_ref;
+
+use super::check_entry_account_feed;
+use crate::utils::{price_impl::MAX_PRICE_EXP, zero_copy_deserialize};
+use crate::{DatedPrice, OraclePrices, Price, PricePublishers, Result, ScopeError, ScopeResult};
+
+/// Size of the signed message, see [`signed_price_message`]
+pub const SIGNED_PRICE_MESSAGE_LEN: usize = 58;
+
+/// Only accept messages signed less than 10 minutes ago
+const MAX_MESSAGE_AGE_SECONDS: u64 = 10 * 60;
+/// Tolerance for publishers clocks ahead of the cluster clock
+const MAX_MESSAGE_CLOCK_DRIFT_SECONDS: u64 = 30;
+
+const ED25519_SIGNATURE_OFFSETS_START: usize = 2;
+const ED25519_SIGNATURE_OFFSETS_LEN: usize = 14;
+/// Instruction index meaning "the ed25519 instruction itself"
+const CURRENT_INSTRUCTION_INDEX: u16 = u16::MAX;
+
+/// Content of a signed price message
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SignedPrice {
+    pub oracle_prices: Pubkey,
+    pub index: u16,
+    pub price: Price,
+    pub unix_timestamp: u64,
+}
+
+/// Build the message to sign to publish a price:
+///
+/// `oracle_prices (32) | index (u16) | price value (u64) | price exp (u64) | unix timestamp (u64)`
+///
+/// All integers are little endian.
+pub fn signed_price_message(signed_price: &SignedPrice) -> [u8; SIGNED_PRICE_MESSAGE_LEN] {
+    let mut message = [0; SIGNED_PRICE_MESSAGE_LEN];
+    message[0..32].copy_from_slice(signed_price.oracle_prices.as_ref());
+    message[32..34].copy_from_slice(&signed_price.index.to_le_bytes());
+    message[34..42].copy_from_slice(&signed_price.price.value.to_le_bytes());
+    message[42..50].copy_from_slice(&signed_price.price.exp.to_le_bytes());
+    message[50..58].copy_from_slice(&signed_price.unix_timestamp.to_le_bytes());
+    message
+}
+
+fn parse_signed_price_message(message: &[u8]) -> Option<SignedPrice> {
+    if message.len() != SIGNED_PRICE_MESSAGE_LEN {
+        return None;
+    }
+    Some(SignedPrice {
+        oracle_prices: Pubkey::new_from_array(*array_ref![message, 0, 32]),
+        index: u16::from_le_bytes(*array_ref![message, 32, 2]),
+        price: Price {
+            value: u64::from_le_bytes(*array_ref![message, 34, 8]),
+            exp: u64::from_le_bytes(*array_ref![message, 42, 8]),
+        },
+        unix_timestamp: u64::from_le_bytes(*array_ref![message, 50, 8]),
+    })
+}
+
+/// Gives the latest price signed by a whitelisted publisher for the entry `index`
+///
+/// The only extra account is the instructions sysvar.
+pub fn get_price<'a, 'b>(
+    publishers_account: &AccountInfo,
+    clock: &Clock,
+    extra_accounts: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+    oracle_prices: &OraclePrices,
+    oracle_prices_pk: &Pubkey,
+    index: usize,
+) -> Result<DatedPrice>
+where
+    'a: 'b,
+{
+    let instruction_sysvar_account_info = extra_accounts
+        .next()
+        .ok_or(ScopeError::AccountsAndTokenMismatch)?;
+    require_keys_eq!(
+        instruction_sysvar_account_info.key(),
+        SYSVAR_INSTRUCTIONS_ID,
+        ScopeError::AccountsAndTokenMismatch
+    );
+
+    let publishers = load_publishers(publishers_account)?;
+    check_entry_account_feed(&publishers.oracle_prices, oracle_prices_pk)?;
+    if usize::from(publishers.index) != index {
+        msg!(
+            "Publishers account is configured for entry {} instead of {index}",
+            publishers.index
+        );
+        return err!(ScopeError::UnexpectedAccount);
+    }
+
+    let signed_price = find_signed_price(instruction_sysvar_account_info, &publishers, index)?;
+
+    // Replay protection and staleness checks
+    let now = u64::try_from(clock.unix_timestamp).map_err(|_| ScopeError::BadTimestamp)?;
+    let previous_timestamp = oracle_prices.prices[index].unix_timestamp;
+    if signed_price.unix_timestamp <= previous_timestamp {
+        msg!(
+            "Signed price timestamp {} is not more recent than the current price {previous_timestamp}",
+            signed_price.unix_timestamp
+        );
+        return err!(ScopeError::BadTimestamp);
+    }
+    if signed_price.unix_timestamp > now + MAX_MESSAGE_CLOCK_DRIFT_SECONDS
+        || signed_price.unix_timestamp < now.saturating_sub(MAX_MESSAGE_AGE_SECONDS)
+    {
+        msg!(
+            "Signed price timestamp {} is too far from the current time {now}",
+            signed_price.unix_timestamp
+        );
+        return err!(ScopeError::BadTimestamp);
+    }
+    validate_price(&signed_price.price)?;
+
+    Ok(DatedPrice {
+        price: signed_price.price,
+        last_updated_slot: clock.slot,
+        unix_timestamp: signed_price.unix_timestamp,
+        ..Default::default()
+    })
+}
+
+/// Reject zero prices and exponents too large to be converted to a `Decimal`
+fn validate_price(price: &Price) -> ScopeResult<()> {
+    if price.value == 0 || price.exp > MAX_PRICE_EXP {
+        msg!("Invalid signed price {}e-{}", price.value, price.exp);
+        return Err(ScopeError::PriceNotValid);
+    }
+    Ok(())
+}
+
+pub fn validate_publishers_account(
+    publishers_account: &AccountInfo,
+    oracle_prices_pk: &Pubkey,
+) -> Result<()> {
+    let publishers = load_publishers(publishers_account)?;
+    check_entry_account_feed(&publishers.oracle_prices, oracle_prices_pk)?;
+    Ok(())
+}
+
+fn load_publishers<'a>(
+    publishers_account: &'a AccountInfo,
+) -> Result<std::cell::Ref<'a, PricePublishers>> {
+    require_keys_eq!(
+        *publishers_account.owner,
+        crate::ID,
+        ScopeError::UnexpectedAccount
+    );
+    Ok(zero_copy_deserialize::<PricePublishers>(
+        publishers_account,
+    )?)
+}
+
+/// Find, in the ed25519 instructions preceding the current one, a price for the entry `index`
+/// signed by one of the publishers
+fn find_signed_price(
+    instruction_sysvar_account_info: &AccountInfo,
+    publishers: &PricePublishers,
+    index: usize,
+) -> Result<SignedPrice> {
+    let current_index: usize = load_current_index_checked(instruction_sysvar_account_info)?.into();
+    for ixn in 0..current_index {
+        let ix = load_instruction_at_checked(ixn, instruction_sysvar_account_info)?;
+        if ix.program_id != ed25519_program::ID {
+            continue;
+        }
+        for (publisher, message) in ed25519_verified_messages(&ix.data)? {
+            if !publishers.is_publisher(&publisher) {
+                continue;
+            }
+            if let Some(signed_price) = parse_signed_price_message(message) {
+                if signed_price.oracle_prices == publishers.oracle_prices
+                    && usize::from(signed_price.index) == index
+                {
+                    return Ok(signed_price);
+                }
+            }
+        }
+    }
+    msg!("No price signed by a whitelisted publisher found for entry {index}");
+    err!(ScopeError::PriceNotValid)
+}
+
+/// Get the public keys and messages verified by an ed25519 program instruction
+///
+/// Only the signatures whose data is fully contained in the instruction itself are returned.
+fn ed25519_verified_messages(data: &[u8]) -> ScopeResult<Vec<(Pubkey, &[u8])>> {
+    let num_signatures = usize::from(*data.first().ok_or(ScopeError::PriceNotValid)?);
+    let mut messages = Vec::with_capacity(num_signatures);
+    for i in 0..num_signatures {
+        let start = ED25519_SIGNATURE_OFFSETS_START + i * ED25519_SIGNATURE_OFFSETS_LEN;
+        let offsets = data
+            .get(start..start + ED25519_SIGNATURE_OFFSETS_LEN)
+            .ok_or(ScopeError::PriceNotValid)?;
+        let read_u16 = |offset: usize| u16::from_le_bytes(*array_ref![offsets, offset, 2]);
+        let signature_instruction_index = read_u16(2);
+        let public_key_offset = usize::from(read_u16(4));
+        let public_key_instruction_index = read_u16(6);
+        let message_data_offset = usize::from(read_u16(8));
+        let message_data_size = usize::from(read_u16(10));
+        let message_instruction_index = read_u16(12);
+        if signature_instruction_index != CURRENT_INSTRUCTION_INDEX
+            || public_key_instruction_index != CURRENT_INSTRUCTION_INDEX
+            || message_instruction_index != CURRENT_INSTRUCTION_INDEX
+        {
+            continue;
+        }
+        let public_key = data
+            .get(public_key_offset..public_key_offset + 32)
+            .ok_or(ScopeError::PriceNotValid)?;
+        let message = data
+            .get(message_data_offset..message_data_offset + message_data_size)
+            .ok_or(ScopeError::PriceNotValid)?;
+        messages.push((
+            Pubkey::new_from_array(*array_ref![public_key, 0, 32]),
+            message,
+        ));
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build an ed25519 instruction data with one signature, laid out as the ed25519 program
+    /// instruction helper does
+    fn ed25519_ix_data(publisher: &Pubkey, message: &[u8], instruction_index: u16) -> Vec<u8> {
+        let public_key_offset: u16 = 16;
+        let signature_offset: u16 = public_key_offset + 32;
+        let message_data_offset: u16 = signature_offset + 64;
+        let mut data = vec![1, 0];
+        for value in [
+            signature_offset,
+            instruction_index,
+            public_key_offset,
+            instruction_index,
+            message_data_offset,
+            message.len() as u16,
+            instruction_index,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(publisher.as_ref());
+        data.extend_from_slice(&[0; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    fn signed_price() -> SignedPrice {
+        SignedPrice {
+            oracle_prices: Pubkey::new_unique(),
+            index: 12,
+            price: Price {
+                value: 1_234_567,
+                exp: 6,
+            },
+            unix_timestamp: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn test_message_roundtrip() {
+        let signed_price = signed_price();
+        let message = signed_price_message(&signed_price);
+        assert_eq!(parse_signed_price_message(&message), Some(signed_price));
+        assert_eq!(parse_signed_price_message(&message[1..]), None);
+    }
+
+    #[test]
+    fn test_validate_price() {
+        assert!(validate_price(&Price { value: 1, exp: 6 }).is_ok());
+        assert!(validate_price(&Price {
+            value: 1,
+            exp: MAX_PRICE_EXP
+        })
+        .is_ok());
+        assert_eq!(
+            validate_price(&Price { value: 0, exp: 6 }),
+            Err(ScopeError::PriceNotValid)
+        );
+        assert_eq!(
+            validate_price(&Price {
+                value: 1,
+                exp: MAX_PRICE_EXP + 1
+            }),
+            Err(ScopeError::PriceNotValid)
+        );
+    }
+
+    #[test]
+    fn test_ed25519_verified_messages() {
+        let publisher = Pubkey::new_unique();
+        let message = signed_price_message(&signed_price());
+        let data = ed25519_ix_data(&publisher, &message, CURRENT_INSTRUCTION_INDEX);
+        let messages = ed25519_verified_messages(&data).unwrap();
+        assert_eq!(messages, vec![(publisher, &message[..])]);
+    }
+
+    #[test]
+    fn test_ed25519_messages_from_other_instructions_are_ignored() {
+        let publisher = Pubkey::new_unique();
+        let message = signed_price_message(&signed_price());
+        let data = ed25519_ix_data(&publisher, &message, 0);
+        assert!(ed25519_verified_messages(&data).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_is_publisher() {
+        let publisher = Pubkey::new_unique();
+        let mut publishers = PricePublishers {
+            oracle_prices: Pubkey::new_unique(),
+            index: 0,
+            _padding0: [0; 6],
+            publishers: [Pubkey::default(); crate::MAX_PRICE_PUBLISHERS],
+            _reserved: [0; 16],
+        };
+        assert!(!publishers.is_publisher(&publisher));
+        assert!(!publishers.is_publisher(&Pubkey::default()));
+        publishers.publishers[3] = publisher;
+        assert!(publishers.is_publisher(&publisher));
+    }
+}
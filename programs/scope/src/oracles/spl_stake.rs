@@ -39,7 +39,7 @@ pub fn get_price(
             // This is an accepted tradeoff as this price type is only used as reference and not to compute the value of the token.
             msg!("SPL Stake account has not been refreshed in current epoch");
             #[cfg(not(feature = "localnet"))]
-            return Err(ScopeError::PriceNotValid.into());
+            return Err(ScopeError::SplStakeNotRefreshed.into());
         }
     }
 
@@ -54,7 +54,8 @@ pub fn get_price(
     let dated_price = DatedPrice {
         price,
         last_updated_slot: current_clock.slot,
-        unix_timestamp: u64::try_from(current_clock.unix_timestamp).unwrap(),
+        unix_timestamp: u64::try_from(current_clock.unix_timestamp)
+            .map_err(|_| ScopeError::BadTimestamp)?,
         ..Default::default()
     };
 
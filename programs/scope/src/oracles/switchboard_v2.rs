@@ -12,14 +12,14 @@ const CONFIDENCE_FACTOR: u64 = 100 / MIN_CONFIDENCE_PERCENTAGE;
 
 pub fn get_price(switchboard_feed_info: &AccountInfo) -> Result<DatedPrice> {
     let feed = AggregatorAccountData::new(switchboard_feed_info)
-        .map_err(|_| ScopeError::SwitchboardV2Error)?;
+        .map_err(|_| ScopeError::SwitchboardV2AccountNotValid)?;
 
-    let price_switchboard_desc = feed.get_result().map_err(|_| {
+    let price_switchboard_desc = feed.get_result().map_err(|e| {
         msg!(
             "Switchboard v2 get result from feed {} failed",
             switchboard_feed_info.key()
         );
-        ScopeError::SwitchboardV2Error
+        e
     })?;
 
     let price: Price = price_switchboard_desc.try_into()?;
@@ -43,7 +43,7 @@ pub fn get_price(switchboard_feed_info: &AccountInfo) -> Result<DatedPrice> {
                     stdev_mantissa,
                     stdev_scale
                 );
-            return err!(ScopeError::SwitchboardV2Error);
+            return err!(ScopeError::SwitchboardV2ConfidenceTooWide);
         }
     };
 
@@ -52,7 +52,7 @@ pub fn get_price(switchboard_feed_info: &AccountInfo) -> Result<DatedPrice> {
         .latest_confirmed_round
         .round_open_timestamp
         .try_into()
-        .unwrap();
+        .map_err(|_| ScopeError::BadTimestamp)?;
 
     Ok(DatedPrice {
         price,
@@ -71,15 +71,9 @@ fn validate_confidence(
     // Step 1: compute scaling factor to bring the stdev to the same scale as the price.
     let (scale_op, scale_diff): (&dyn Fn(i128, i128) -> Option<i128>, _) =
         if price_scale >= stdev_scale {
-            (
-                &i128::checked_mul,
-                price_scale.checked_sub(stdev_scale).unwrap(),
-            )
+            (&i128::checked_mul, price_scale - stdev_scale)
         } else {
-            (
-                &i128::checked_div,
-                stdev_scale.checked_sub(price_scale).unwrap(),
-            )
+            (&i128::checked_div, stdev_scale - price_scale)
         };
 
     let scaling_factor = 10_i128
@@ -94,7 +88,7 @@ fn validate_confidence(
         .ok_or(ScopeError::MathOverflow)?;
 
     if stdev_x_confidence_factor_scaled >= price_mantissa {
-        Err(ScopeError::PriceNotValid)
+        Err(ScopeError::SwitchboardV2ConfidenceTooWide)
     } else {
         Ok(())
     }
@@ -106,7 +100,7 @@ impl TryFrom<SwitchboardDecimal> for Price {
     fn try_from(sb_decimal: SwitchboardDecimal) -> std::result::Result<Self, Self::Error> {
         if sb_decimal.mantissa < 0 {
             msg!("Switchboard v2 oracle price feed is negative");
-            return Err(ScopeError::PriceNotValid);
+            return Err(ScopeError::SwitchboardV2NegativePrice);
         }
         let (exp, value) = if sb_decimal.scale > MAX_EXPONENT {
             // exp is capped. Remove the extra digits from the mantissa.
@@ -202,17 +196,21 @@ mod switchboard {
         ) -> Result<Ref<'info, AggregatorAccountData>> {
             let data = switchboard_feed.try_borrow_data()?;
 
-            let mut disc_bytes = [0u8; 8];
-            disc_bytes.copy_from_slice(&data[..8]);
+            let end = 8 + std::mem::size_of::<AggregatorAccountData>();
+            if data.len() < end {
+                msg!("Switchboard aggregator account is too small");
+                return err!(ScopeError::SwitchboardV2AccountNotValid);
+            }
+            let disc_bytes = &data[..8];
             if disc_bytes != AggregatorAccountData::discriminator() {
                 msg!(
                     "Switchboard aggregator account has an invalid discriminator: {:?}",
                     disc_bytes
                 );
-                return err!(ScopeError::SwitchboardV2Error);
+                return err!(ScopeError::SwitchboardV2AccountNotValid);
             }
 
-            Ok(Ref::map(data, |data| bytemuck::from_bytes(&data[8..])))
+            Ok(Ref::map(data, |data| bytemuck::from_bytes(&data[8..end])))
         }
 
         pub fn get_result(&self) -> std::result::Result<SwitchboardDecimal, ScopeError> {
@@ -221,7 +219,7 @@ mod switchboard {
             let min_oracle_results = self.min_oracle_results;
             if min_oracle_results > latest_confirmed_round_success {
                 msg!("Switchboard price is invalid: min_oracle_results: {min_oracle_results} > latest_confirmed_round.num_success: {latest_confirmed_round_success}",);
-                Err(ScopeError::SwitchboardV2Error)
+                Err(ScopeError::SwitchboardV2NoResult)
             } else {
                 Ok(self.latest_confirmed_round.result)
             }
@@ -397,7 +395,7 @@ mod tests {
         let stdev = price * 10_i128.pow(stdev_scale) * 2 / 100;
         assert_eq!(
             validate_confidence(price, 0, stdev, stdev_scale).unwrap_err(),
-            ScopeError::PriceNotValid
+            ScopeError::SwitchboardV2ConfidenceTooWide
         );
     }
 
@@ -405,7 +403,7 @@ mod tests {
     fn test_invalid_switchboard_v2_price_stdev_2percent_std_exp_larger_than_price_exp_2() {
         assert_eq!(
             validate_confidence(100, 2, 20, 3).unwrap_err(),
-            ScopeError::PriceNotValid
+            ScopeError::SwitchboardV2ConfidenceTooWide
         );
     }
 
@@ -413,7 +411,7 @@ mod tests {
     fn test_invalid_switchboard_v2_price_stdev_above_2percent() {
         assert_eq!(
             validate_confidence(100, 0, 2001, 3).unwrap_err(),
-            ScopeError::PriceNotValid
+            ScopeError::SwitchboardV2ConfidenceTooWide
         );
     }
 
@@ -421,7 +419,7 @@ mod tests {
     fn test_invalid_switchboard_v2_price_stdev_above_2percent_2() {
         assert_eq!(
             validate_confidence(100, 1, 201, 3).unwrap_err(),
-            ScopeError::PriceNotValid
+            ScopeError::SwitchboardV2ConfidenceTooWide
         );
     }
 
@@ -429,7 +427,7 @@ mod tests {
     fn test_invalid_switchboard_v2_price_stdev_higher_than_price() {
         assert_eq!(
             validate_confidence(100, 0, 100001, 3).unwrap_err(),
-            ScopeError::PriceNotValid
+            ScopeError::SwitchboardV2ConfidenceTooWide
         );
     }
 
@@ -443,7 +441,7 @@ mod tests {
             let stdev_scale = scale + stdev_scale_diff;
             // 2% + 1 unit to be just above the 2% threshold
             let stdev_mantissa = mantissa * 2 * 10_i128.pow(stdev_scale_diff) / 100 + 1;
-            prop_assert!(matches!(validate_confidence(mantissa, scale, stdev_mantissa, stdev_scale), Err(ScopeError::PriceNotValid)));
+            prop_assert!(matches!(validate_confidence(mantissa, scale, stdev_mantissa, stdev_scale), Err(ScopeError::SwitchboardV2ConfidenceTooWide)));
         }
     }
 }
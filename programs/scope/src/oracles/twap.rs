@@ -2,8 +2,9 @@ use std::cmp::Ordering;
 
 use crate::ScopeError;
 use crate::ScopeError::PriceAccountNotExpected;
-use crate::{DatedPrice, OracleMappings, OracleTwaps, Price};
+use crate::{DatedPrice, OracleMappings, OracleTwaps, Price, EMA_RANGE_BUCKETS};
 use anchor_lang::prelude::*;
+use decimal_wad::decimal::Decimal;
 use intbits::Bits;
 
 use self::utils::{reset_ema_twap, update_ema_twap};
@@ -12,6 +13,20 @@ const EMA_1H_DURATION_SECONDS: u64 = 60 * 60;
 const MIN_SAMPLES_IN_PERIOD: u32 = 10;
 const NUM_SUB_PERIODS: usize = 3;
 const MIN_SAMPLES_IN_FIRST_AND_LAST_PERIOD: u32 = 1;
+const EMA_RANGE_BUCKET_DURATION_SECONDS: u64 = EMA_1H_DURATION_SECONDS / EMA_RANGE_BUCKETS as u64;
+
+/// Volatility and range of an entry over the EMA period
+///
+/// Like the EMA TWAP, the range is tracked with 18 decimals, see [`get_ema_price`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EmaStats {
+    /// Exponentially weighted variance of the log returns, per second
+    pub variance_per_second: Decimal,
+    /// Highest sample price over the EMA period
+    pub high: Price,
+    /// Lowest sample price over the EMA period
+    pub low: Price,
+}
 
 pub fn validate_price_account(account: &AccountInfo) -> Result<()> {
     if account.key().eq(&crate::id()) {
@@ -48,7 +63,7 @@ pub fn reset_twap(
         .twaps
         .get_mut(token)
         .ok_or(ScopeError::TwapSourceIndexOutOfRange)?;
-    reset_ema_twap(twap, price, price_ts, price_slot);
+    reset_ema_twap(twap, price, price_ts, price_slot)?;
     Ok(())
 }
 
@@ -61,20 +76,60 @@ pub fn get_price(
     let source_index = usize::from(oracle_mappings.twap_source[token]);
     msg!("Get twap price at index {source_index} for tk {token}",);
 
+    get_ema_price(oracle_twaps, source_index, clock)
+}
+
+/// Get the EMA TWAP of the entry `source_index` if it is valid
+///
+/// TWAPs are accumulated as [`Decimal`]s: prices with more than 18 decimals are truncated to 18
+/// decimals, unlike the derived entries computed from the spot prices.
+pub fn get_ema_price(
+    oracle_twaps: &OracleTwaps,
+    source_index: usize,
+    clock: &Clock,
+) -> Result<DatedPrice> {
     let twap = oracle_twaps
         .twaps
         .get(source_index)
         .ok_or(ScopeError::TwapSourceIndexOutOfRange)?;
 
-    let current_ts = clock.unix_timestamp.try_into().unwrap();
+    let current_ts = clock
+        .unix_timestamp
+        .try_into()
+        .map_err(|_| ScopeError::BadTimestamp)?;
     utils::validate_ema(twap, current_ts)?;
 
-    Ok(twap.as_dated_price(source_index.try_into().unwrap()))
+    Ok(twap.as_dated_price(
+        source_index
+            .try_into()
+            .map_err(|_| ScopeError::OutOfRangeIntegralConversion)?,
+    )?)
 }
 
-mod utils {
-    use decimal_wad::decimal::Decimal;
+/// Get the volatility and range of the entry `source_index` if its EMA TWAP is valid
+///
+/// The range covers the current sub-period of the EMA period and the previous ones, that is
+/// between 50 and 60 minutes of samples.
+pub fn get_ema_stats(
+    oracle_twaps: &OracleTwaps,
+    source_index: usize,
+    clock: &Clock,
+) -> Result<EmaStats> {
+    let twap = oracle_twaps
+        .twaps
+        .get(source_index)
+        .ok_or(ScopeError::TwapSourceIndexOutOfRange)?;
+
+    let current_ts = clock
+        .unix_timestamp
+        .try_into()
+        .map_err(|_| ScopeError::BadTimestamp)?;
+    utils::validate_ema(twap, current_ts)?;
 
+    Ok(utils::get_ema_stats(twap, current_ts)?)
+}
+
+mod utils {
     use crate::{EmaTwap, Price, ScopeResult};
 
     use super::*;
@@ -121,7 +176,9 @@ mod utils {
         // Skip update if the price is the same as the last one
         if price_slot > twap.last_update_slot {
             if twap.last_update_slot == 0 {
-                twap.current_ema_1h = Decimal::from(price).to_scaled_val().unwrap();
+                twap.current_ema_1h = Decimal::from(price)
+                    .to_scaled_val()
+                    .map_err(|_| ScopeError::IntegerOverflow)?;
             } else {
                 let ema_decimal = Decimal::from_scaled_val(twap.current_ema_1h);
                 let price_decimal = Decimal::from(price);
@@ -137,7 +194,13 @@ mod utils {
                 twap.current_ema_1h = new_ema
                     .to_scaled_val()
                     .map_err(|_| ScopeError::IntegerOverflow)?;
+
+                update_variance(twap, price_decimal, price_ts, smoothing_factor)?;
             }
+            update_range(twap, Decimal::from(price), price_ts)?;
+            twap.last_sample_price = Decimal::from(price)
+                .to_scaled_val()
+                .map_err(|_| ScopeError::IntegerOverflow)?;
             let mut tracker: EmaTracker = twap.updates_tracker_1h.into();
             tracker.update_tracker(
                 EMA_1H_DURATION_SECONDS,
@@ -151,11 +214,134 @@ mod utils {
         Ok(())
     }
 
-    pub(super) fn reset_ema_twap(twap: &mut EmaTwap, price: Price, price_ts: u64, price_slot: u64) {
-        twap.current_ema_1h = Decimal::from(price).to_scaled_val().unwrap();
+    pub(super) fn reset_ema_twap(
+        twap: &mut EmaTwap,
+        price: Price,
+        price_ts: u64,
+        price_slot: u64,
+    ) -> ScopeResult<()> {
+        twap.current_ema_1h = Decimal::from(price)
+            .to_scaled_val()
+            .map_err(|_| ScopeError::IntegerOverflow)?;
         twap.last_update_slot = price_slot;
         twap.last_update_unix_timestamp = price_ts;
         twap.updates_tracker_1h = 0;
+        twap.last_sample_price = twap.current_ema_1h;
+        twap.current_variance_1h = 0;
+        twap.highs_1h = [0; EMA_RANGE_BUCKETS];
+        twap.lows_1h = [0; EMA_RANGE_BUCKETS];
+        let bucket = range_bucket_index(price_ts);
+        twap.highs_1h[bucket] = twap.current_ema_1h;
+        twap.lows_1h[bucket] = twap.current_ema_1h;
+        Ok(())
+    }
+
+    /// Update the exponentially weighted variance of the log returns with the same smoothing
+    /// factor as the EMA. The squared returns are normalized per second so that samples taken at
+    /// irregular intervals are comparable.
+    fn update_variance(
+        twap: &mut EmaTwap,
+        price: Decimal,
+        price_ts: u64,
+        smoothing_factor: Decimal,
+    ) -> ScopeResult<()> {
+        let elapsed = price_ts.saturating_sub(twap.last_update_unix_timestamp);
+        if twap.last_sample_price == 0 || elapsed == 0 {
+            // No previous sample to compute a return from
+            return Ok(());
+        }
+        let last_price = Decimal::from_scaled_val(twap.last_sample_price);
+        let log_return = abs_log_return(last_price, price);
+        let sample_variance = log_return * log_return / elapsed;
+
+        let variance_decimal = Decimal::from_scaled_val(twap.current_variance_1h);
+        let new_variance = sample_variance * smoothing_factor
+            + (Decimal::one() - smoothing_factor) * variance_decimal;
+
+        twap.current_variance_1h = new_variance
+            .to_scaled_val()
+            .map_err(|_| ScopeError::IntegerOverflow)?;
+        Ok(())
+    }
+
+    /// |ln(b / a)| = 2 * atanh(y) with y = |b - a| / (b + a), approximated by the first terms of
+    /// its series: 2 * (y + y^3 / 3 + y^5 / 5). The relative error is below 1e-5 for a 50% move.
+    pub(super) fn abs_log_return(a: Decimal, b: Decimal) -> Decimal {
+        let diff = if b > a { b - a } else { a - b };
+        let y = diff / (a + b);
+        let y2 = y * y;
+        let y3 = y2 * y;
+        let y5 = y3 * y2;
+        (y + y3 / 3_u64 + y5 / 5_u64) * 2_u64
+    }
+
+    fn range_bucket(ts: u64) -> u64 {
+        ts / EMA_RANGE_BUCKET_DURATION_SECONDS
+    }
+
+    fn range_bucket_index(ts: u64) -> usize {
+        (range_bucket(ts) % EMA_RANGE_BUCKETS as u64) as usize
+    }
+
+    /// Clear the high/low buckets of the sub-periods elapsed since the last update.
+    fn erase_old_range_buckets(
+        highs: &mut [u128; EMA_RANGE_BUCKETS],
+        lows: &mut [u128; EMA_RANGE_BUCKETS],
+        last_update_ts: u64,
+        current_ts: u64,
+    ) {
+        let last_bucket = range_bucket(last_update_ts);
+        let current_bucket = range_bucket(current_ts);
+        if current_bucket <= last_bucket {
+            return;
+        }
+        let nb_buckets_to_clear = (current_bucket - last_bucket).min(EMA_RANGE_BUCKETS as u64);
+        for bucket in (current_bucket + 1 - nb_buckets_to_clear)..=current_bucket {
+            let index = (bucket % EMA_RANGE_BUCKETS as u64) as usize;
+            highs[index] = 0;
+            lows[index] = 0;
+        }
+    }
+
+    /// Track the highest and lowest sample prices of the current sub-period.
+    fn update_range(twap: &mut EmaTwap, price: Decimal, price_ts: u64) -> ScopeResult<()> {
+        let price: u128 = price
+            .to_scaled_val()
+            .map_err(|_| ScopeError::IntegerOverflow)?;
+        erase_old_range_buckets(
+            &mut twap.highs_1h,
+            &mut twap.lows_1h,
+            twap.last_update_unix_timestamp,
+            price_ts,
+        );
+        let index = range_bucket_index(price_ts);
+        twap.highs_1h[index] = twap.highs_1h[index].max(price);
+        if twap.lows_1h[index] == 0 || price < twap.lows_1h[index] {
+            twap.lows_1h[index] = price;
+        }
+        Ok(())
+    }
+
+    pub(super) fn get_ema_stats(twap: &EmaTwap, current_ts: u64) -> ScopeResult<EmaStats> {
+        let mut highs = twap.highs_1h;
+        let mut lows = twap.lows_1h;
+        erase_old_range_buckets(
+            &mut highs,
+            &mut lows,
+            twap.last_update_unix_timestamp,
+            current_ts,
+        );
+        let high = highs.into_iter().max().unwrap_or(0);
+        let low = lows.into_iter().filter(|low| *low != 0).min().unwrap_or(0);
+        if high == 0 || low == 0 {
+            return Err(ScopeError::TwapNotEnoughSamplesInPeriod);
+        }
+
+        Ok(EmaStats {
+            variance_per_second: Decimal::from_scaled_val(twap.current_variance_1h),
+            high: Decimal::from_scaled_val(high).try_into()?,
+            low: Decimal::from_scaled_val(low).try_into()?,
+        })
     }
 
     pub(super) fn validate_ema(twap: &EmaTwap, current_ts: u64) -> ScopeResult<()> {
@@ -340,7 +526,7 @@ mod tests_reset_twap {
         let price_ts = 100;
         let price_slot = 1;
 
-        reset_ema_twap(&mut twap, test_price, price_ts, price_slot);
+        reset_ema_twap(&mut twap, test_price, price_ts, price_slot).unwrap();
 
         assert_eq!(
             twap.current_ema_1h,
@@ -357,7 +543,7 @@ mod tests_reset_twap {
         let price_ts = 143;
         let price_slot = 10;
 
-        reset_ema_twap(&mut twap, test_price, price_ts, price_slot);
+        reset_ema_twap(&mut twap, test_price, price_ts, price_slot).unwrap();
 
         assert_eq!(
             twap.current_ema_1h,
@@ -383,7 +569,7 @@ mod tests_reset_twap {
         let price_ts = 143;
         let price_slot = 10;
 
-        reset_ema_twap(&mut twap, test_price, price_ts, price_slot);
+        reset_ema_twap(&mut twap, test_price, price_ts, price_slot).unwrap();
 
         assert_eq!(
             twap.current_ema_1h,
@@ -507,7 +693,7 @@ mod tests_update_ema_twap {
             ..Default::default()
         };
 
-        let test_price: Price = Decimal::from(1).into();
+        let test_price: Price = Decimal::from(1).try_into().unwrap();
         let price_ts = 80;
         let price_slot = 8;
 
@@ -530,7 +716,7 @@ mod tests_update_ema_twap {
             ..Default::default()
         };
 
-        let test_price: Price = Decimal::from(125_000_000).into();
+        let test_price: Price = Decimal::from(125_000_000).try_into().unwrap();
         let price_ts = 80;
         let price_slot = 8;
 
@@ -554,7 +740,7 @@ mod tests_update_ema_twap {
             ..Default::default()
         };
 
-        let test_price: Price = Decimal::from(120_000).into();
+        let test_price: Price = Decimal::from(120_000).try_into().unwrap();
 
         let price_ts = 100;
         let price_slot = 8;
@@ -576,7 +762,7 @@ mod tests_update_ema_twap {
             ..Default::default()
         };
 
-        let test_price: Price = Decimal::from(120_000).into();
+        let test_price: Price = Decimal::from(120_000).try_into().unwrap();
 
         let price_ts = 400;
         let price_slot = 8;
@@ -602,7 +788,7 @@ mod tests_update_ema_twap {
 
         let mut twap_with_late_sample = twap_with_early_sample;
 
-        let test_price: Price = Decimal::from(120_000).into();
+        let test_price: Price = Decimal::from(120_000).try_into().unwrap();
         let early_ts = 150;
         let early_slot = 8;
 
@@ -650,7 +836,7 @@ mod tests_update_ema_twap {
         let mut previous_twap = twap;
         for _ in 1..10 {
             price_value += 50;
-            let test_price = Decimal::from(price_value).into();
+            let test_price = Price::try_from(Decimal::from(price_value)).unwrap();
             price_ts += 50;
             price_slot += 4;
 
@@ -677,7 +863,7 @@ mod tests_update_ema_twap {
             ..Default::default()
         };
 
-        let test_price: Price = Decimal::from(20_000).into();
+        let test_price: Price = Decimal::from(20_000).try_into().unwrap();
 
         let price_ts = 100;
         let price_slot = 8;
@@ -699,7 +885,7 @@ mod tests_update_ema_twap {
             ..Default::default()
         };
 
-        let test_price: Price = Decimal::from(120_000).into();
+        let test_price: Price = Decimal::from(120_000).try_into().unwrap();
 
         let price_ts = 400;
         let price_slot = 8;
@@ -729,7 +915,7 @@ mod tests_update_ema_twap {
         let mut previous_twap = twap;
         for _ in 1..10 {
             price_value += 5;
-            let test_price = Decimal::from(price_value).into();
+            let test_price = Price::try_from(Decimal::from(price_value)).unwrap();
             price_ts += 30;
             price_slot += 60;
 
@@ -747,6 +933,88 @@ mod tests_update_ema_twap {
     }
 }
 
+#[cfg(test)]
+mod tests_ema_stats {
+    use decimal_wad::decimal::Decimal;
+
+    use crate::{EmaTwap, Price};
+
+    use super::utils::{abs_log_return, get_ema_stats, reset_ema_twap, update_ema_twap};
+
+    fn price(value: u64) -> Price {
+        Price { value, exp: 0 }
+    }
+
+    fn to_f64(value: Decimal) -> f64 {
+        value.to_scaled_val::<u128>().unwrap() as f64 / 1e18
+    }
+
+    #[test]
+    fn test_abs_log_return() {
+        for (a, b) in [(100, 101), (100, 90), (100, 150)] {
+            let expected = (b as f64 / a as f64).ln().abs();
+            let log_return = to_f64(abs_log_return(Decimal::from(a), Decimal::from(b)));
+            assert!((log_return - expected).abs() / expected < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_constant_price_has_no_variance() {
+        let mut twap = EmaTwap::default();
+        for i in 1..=10 {
+            update_ema_twap(&mut twap, price(100), 1_000 + i * 60, i).unwrap();
+        }
+        let stats = get_ema_stats(&twap, 1_600).unwrap();
+        assert_eq!(stats.variance_per_second, Decimal::zero());
+        assert_eq!(stats.high, price(100));
+        assert_eq!(stats.low, price(100));
+    }
+
+    #[test]
+    fn test_variance_of_one_return() {
+        let mut twap = EmaTwap::default();
+        update_ema_twap(&mut twap, price(100), 1_000, 1).unwrap();
+        update_ema_twap(&mut twap, price(110), 1_060, 2).unwrap();
+
+        // smoothing factor = 2 / (1 + 3600 / 60)
+        let expected = 2.0 / 61.0 * 1.1_f64.ln().powi(2) / 60.0;
+        let stats = get_ema_stats(&twap, 1_060).unwrap();
+        let variance = to_f64(stats.variance_per_second);
+        assert!((variance - expected).abs() / expected < 1e-4);
+        assert_eq!(stats.high, price(110));
+        assert_eq!(stats.low, price(100));
+    }
+
+    #[test]
+    fn test_range_forgets_old_samples() {
+        let mut twap = EmaTwap::default();
+        update_ema_twap(&mut twap, price(120), 0, 1).unwrap();
+        update_ema_twap(&mut twap, price(80), 600, 2).unwrap();
+        update_ema_twap(&mut twap, price(100), 3_600, 3).unwrap();
+
+        // The sub-period of the first sample is over
+        let stats = get_ema_stats(&twap, 3_600).unwrap();
+        assert_eq!(stats.high, price(100));
+        assert_eq!(stats.low, price(80));
+
+        // All the samples are older than the EMA period
+        assert!(get_ema_stats(&twap, 3_600 * 2).is_err());
+    }
+
+    #[test]
+    fn test_reset_range() {
+        let mut twap = EmaTwap::default();
+        update_ema_twap(&mut twap, price(120), 0, 1).unwrap();
+        update_ema_twap(&mut twap, price(80), 60, 2).unwrap();
+        reset_ema_twap(&mut twap, price(90), 120, 3).unwrap();
+
+        let stats = get_ema_stats(&twap, 120).unwrap();
+        assert_eq!(stats.variance_per_second, Decimal::zero());
+        assert_eq!(stats.high, price(90));
+        assert_eq!(stats.low, price(90));
+    }
+}
+
 #[cfg(test)]
 mod tests_samples_tracker {
     use super::EmaTracker;
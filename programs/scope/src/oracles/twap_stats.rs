@@ -0,0 +1,135 @@
+//! Volatility and range of the EMA TWAP of another entry of the feed
+//!
+//! Publishes one of the statistics of [`twap::get_ema_stats`] as a price, selected in the entry
+//! generic data (see [`TwapStatsConfig`]):
+//! - the annualized volatility of the log returns, as a fraction (0.8 for 80%)
+//! - the highest or lowest sample price over the EMA period
+//!
+//! The statistic carries the slot and timestamp of the last TWAP update. The TWAP is read from
+//! the feed twaps account, the entry is mapped to the scope program id.
+
+use anchor_lang::prelude::*;
+use arrayref::array_ref;
+use decimal_wad::{common::TryMul, decimal::Decimal};
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use raydium_amm_v3::libraries::U256;
+
+use super::{fixed_rate::SECONDS_PER_YEAR, twap};
+use crate::{
+    DatedPrice, OracleTwaps, Price, Result, ScopeError, ScopeResult, GENERIC_DATA_LEN, MAX_ENTRIES,
+};
+
+/// Scale of the decimal values
+const WAD: u64 = 1_000_000_000_000_000_000;
+
+/// Statistic of the EMA TWAP published by the entry
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
+#[repr(u8)]
+pub enum TwapStat {
+    /// Annualized volatility of the log returns, as a fraction
+    Volatility = 0,
+    /// Highest sample price over the EMA period
+    High = 1,
+    /// Lowest sample price over the EMA period
+    Low = 2,
+}
+
+/// Statistic configuration stored in the entry generic data
+///
+/// - `[0..2]`: index of the entry of the EMA TWAP (u16, little endian)
+/// - `[2]`: statistic, see [`TwapStat`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TwapStatsConfig {
+    pub source_index: usize,
+    pub stat: TwapStat,
+}
+
+impl TwapStatsConfig {
+    pub fn from_generic_data(generic_data: &[u8; GENERIC_DATA_LEN]) -> ScopeResult<Self> {
+        let source_index = usize::from(u16::from_le_bytes(*array_ref![generic_data, 0, 2]));
+        if source_index >= MAX_ENTRIES {
+            msg!("Invalid source entry index {source_index}");
+            return Err(ScopeError::InvalidGenericData);
+        }
+        let stat = TwapStat::try_from(generic_data[2]).map_err(|_| {
+            msg!("Invalid TWAP statistic {}", generic_data[2]);
+            ScopeError::InvalidGenericData
+        })?;
+        Ok(Self { source_index, stat })
+    }
+}
+
+pub fn get_price(
+    oracle_twaps: &OracleTwaps,
+    clock: &Clock,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> Result<DatedPrice> {
+    let config = TwapStatsConfig::from_generic_data(generic_data)?;
+    let stats = twap::get_ema_stats(oracle_twaps, config.source_index, clock)?;
+    let price = match config.stat {
+        TwapStat::Volatility => annualized_volatility(stats.variance_per_second)?,
+        TwapStat::High => stats.high,
+        TwapStat::Low => stats.low,
+    };
+
+    let twap = &oracle_twaps.twaps[config.source_index];
+    Ok(DatedPrice {
+        price,
+        last_updated_slot: twap.last_update_slot,
+        unix_timestamp: twap.last_update_unix_timestamp,
+        ..Default::default()
+    })
+}
+
+/// `sqrt(variance_per_second * SECONDS_PER_YEAR)`
+fn annualized_volatility(variance_per_second: Decimal) -> ScopeResult<Price> {
+    let variance_scaled: u128 = variance_per_second
+        .try_mul(SECONDS_PER_YEAR)?
+        .to_scaled_val()
+        .map_err(|_| ScopeError::IntegerOverflow)?;
+    // sqrt(x * 10^18) is the scaled value of sqrt(x)
+    let volatility_scaled = (U256::from(variance_scaled) * U256::from(WAD)).integer_sqrt();
+    Decimal::from_scaled_val(volatility_scaled.as_u128()).try_into()
+}
+
+#[cfg(test)]
+mod tests {
+    use decimal_wad::common::TryDiv;
+
+    use super::*;
+
+    #[test]
+    fn test_config_from_generic_data() {
+        let mut generic_data = [0; GENERIC_DATA_LEN];
+        generic_data[0..2].copy_from_slice(&7_u16.to_le_bytes());
+        generic_data[2] = 2;
+        assert_eq!(
+            TwapStatsConfig::from_generic_data(&generic_data).unwrap(),
+            TwapStatsConfig {
+                source_index: 7,
+                stat: TwapStat::Low,
+            }
+        );
+        generic_data[2] = 3;
+        assert_eq!(
+            TwapStatsConfig::from_generic_data(&generic_data).unwrap_err(),
+            ScopeError::InvalidGenericData
+        );
+    }
+
+    #[test]
+    fn test_annualized_volatility() {
+        // 80% a year is a variance of 0.64 a year
+        let variance_per_second = Decimal::from_scaled_val(640_000_000_000_000_000)
+            .try_div(SECONDS_PER_YEAR)
+            .unwrap();
+        let volatility = annualized_volatility(variance_per_second).unwrap();
+        crate::assert_fuzzy_price_eq!(
+            volatility,
+            Price { value: 8, exp: 1 },
+            Decimal::from_scaled_val(1_000_000_000),
+            "Volatility {:?}",
+            volatility
+        );
+    }
+}
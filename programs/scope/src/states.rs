@@ -1,4 +1,5 @@
-use crate::{MAX_ENTRIES, MAX_ENTRIES_U16};
+use crate::utils::price_impl::MAX_PRICE_EXP;
+use crate::{GENERIC_DATA_LEN, MAX_ENTRIES, MAX_ENTRIES_U16};
 use anchor_lang::prelude::*;
 use decimal_wad::decimal::Decimal;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
@@ -17,6 +18,7 @@ pub struct Price {
 
     // exponent represents the number of decimals
     // for example, 8 for btc
+    // at most `MAX_PRICE_EXP` for the prices written by scope
     pub exp: u64,
 }
 
@@ -24,10 +26,16 @@ pub struct Price {
 #[derive(Debug, Eq, PartialEq)]
 pub struct DatedPrice {
     pub price: Price,
+    // Slot and timestamp at which the source published the price (e.g. pyth `pub_slot`), or at
+    // which it was computed for prices derived from on-chain state.
     pub last_updated_slot: u64,
     pub unix_timestamp: u64,
-    pub _reserved: [u64; 2],
-    pub _reserved2: [u16; 3],
+    // Best bid and best ask (`[bid, ask]`) of the market for orderbook entries configured to store
+    // them, with the same exponent as `price`. Zero otherwise.
+    pub bid_ask: [u64; 2],
+    // Slot at which scope wrote the price, as a little endian 48 bits integer.
+    // See `refreshed_slot()`.
+    pub refreshed_slot_u48: [u16; 3],
     // Current index of the dated price.
     pub index: u16,
 }
@@ -38,13 +46,33 @@ impl Default for DatedPrice {
             price: Default::default(),
             last_updated_slot: Default::default(),
             unix_timestamp: Default::default(),
-            _reserved: Default::default(),
-            _reserved2: Default::default(),
+            bid_ask: Default::default(),
+            refreshed_slot_u48: Default::default(),
             index: MAX_ENTRIES_U16,
         }
     }
 }
 
+impl DatedPrice {
+    /// Slot at which the price was written by a refresh, 0 if it was written before this slot
+    /// was recorded.
+    ///
+    /// Unlike [`DatedPrice::last_updated_slot`] it does not depend on the oracle type, so the
+    /// age of the last refresh can be compared uniformly across entries.
+    ///
+    /// The refresh timestamp is not stored: the dated price has no reserved space left for it,
+    /// and the block time of the refreshed slot gives it when needed.
+    pub fn refreshed_slot(&self) -> u64 {
+        let [low, mid, high] = self.refreshed_slot_u48;
+        u64::from(low) | u64::from(mid) << 16 | u64::from(high) << 32
+    }
+
+    pub fn set_refreshed_slot(&mut self, slot: u64) {
+        // Truncation is expected: 48 bits of slots last for thousands of years.
+        self.refreshed_slot_u48 = [slot as u16, (slot >> 16) as u16, (slot >> 32) as u16];
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy, TryFromPrimitive, IntoPrimitive)]
 #[repr(usize)]
 pub enum EmaType {
@@ -62,9 +90,20 @@ pub struct EmaTwap {
     pub updates_tracker_1h: u64,
     pub padding_0: u64,
 
-    pub padding_1: [u128; 39],
+    /// Last sample price (scaled decimal), used to compute the log returns.
+    pub last_sample_price: u128,
+    /// Exponentially weighted variance of the log returns, per second (scaled decimal).
+    pub current_variance_1h: u128,
+    /// Highest and lowest sample prices (scaled decimals) of each sub-period of the EMA period,
+    /// see `EMA_RANGE_BUCKETS`. 0 if there is no sample in the sub-period.
+    pub highs_1h: [u128; EMA_RANGE_BUCKETS],
+    pub lows_1h: [u128; EMA_RANGE_BUCKETS],
+
+    pub padding_1: [u128; 25],
 }
 
+pub const EMA_RANGE_BUCKETS: usize = 6;
+
 impl Default for EmaTwap {
     fn default() -> Self {
         Self {
@@ -73,21 +112,25 @@ impl Default for EmaTwap {
             last_update_unix_timestamp: 0,
             updates_tracker_1h: 0,
             padding_0: 0,
-            padding_1: [0_u128; 39],
+            last_sample_price: 0,
+            current_variance_1h: 0,
+            highs_1h: [0; EMA_RANGE_BUCKETS],
+            lows_1h: [0; EMA_RANGE_BUCKETS],
+            padding_1: [0_u128; 25],
         }
     }
 }
 
 impl EmaTwap {
-    pub fn as_dated_price(&self, index: u16) -> DatedPrice {
-        DatedPrice {
-            price: Decimal::from_scaled_val(self.current_ema_1h).into(),
+    pub fn as_dated_price(&self, index: u16) -> crate::ScopeResult<DatedPrice> {
+        Ok(DatedPrice {
+            price: Decimal::from_scaled_val(self.current_ema_1h).try_into()?,
             last_updated_slot: self.last_update_slot,
             unix_timestamp: self.last_update_unix_timestamp,
-            _reserved: [0; 2],
-            _reserved2: [0; 3],
+            bid_ask: [0; 2],
+            refreshed_slot_u48: [0; 3],
             index,
-        }
+        })
     }
 }
 
@@ -113,11 +156,25 @@ pub struct OracleMappings {
     pub price_types: [u8; MAX_ENTRIES],
     pub twap_source: [u16; MAX_ENTRIES], // meaningful only if type == TWAP; the index of where we find the TWAP
     pub twap_enabled: [u8; MAX_ENTRIES], // true or false
-    pub _reserved1: [u8; MAX_ENTRIES],
+    pub history_enabled: [u8; MAX_ENTRIES], // true or false, see PriceHistory
     pub _reserved2: [u32; MAX_ENTRIES],
+    pub generic: [[u8; GENERIC_DATA_LEN]; MAX_ENTRIES], // oracle type specific configuration, see each oracle module
 }
 
 impl OracleMappings {
+    /// Size of the accounts created before `generic` was added. They must be reallocated to the
+    /// current size by the `migrate` instruction before they can be loaded.
+    pub const LEGACY_ACCOUNT_SIZE: usize =
+        8 + std::mem::size_of::<Self>() - GENERIC_DATA_LEN * MAX_ENTRIES;
+
+    /// Check that the account is large enough to be loaded, loading a legacy account would panic.
+    pub fn check_account_size(account: &AccountInfo) -> crate::ScopeResult<()> {
+        if account.data_len() < 8 + std::mem::size_of::<Self>() {
+            return Err(crate::ScopeError::FeedNotMigrated);
+        }
+        Ok(())
+    }
+
     pub fn is_twap_enabled(&self, token: usize) -> bool {
         self.twap_enabled[token] > 0
     }
@@ -125,6 +182,48 @@ impl OracleMappings {
     pub fn get_twap_source(&self, token: usize) -> usize {
         usize::from(self.twap_source[token])
     }
+
+    pub fn is_history_enabled(&self, token: usize) -> bool {
+        self.history_enabled[token] > 0
+    }
+
+    pub fn get_generic_data(&self, token: usize) -> &[u8; GENERIC_DATA_LEN] {
+        &self.generic[token]
+    }
+
+    fn is_scope_twap(&self, token: usize) -> bool {
+        self.price_types[token] == u8::from(crate::oracles::OracleType::ScopeTwap)
+    }
+
+    /// Check that a `ScopeTwap` entry reads the TWAP of a mapped, non TWAP, entry that has TWAP
+    /// enabled. Entries of other types are always valid.
+    pub fn check_twap_source(&self, token: usize) -> crate::ScopeResult<()> {
+        if !self.is_scope_twap(token) {
+            return Ok(());
+        }
+        let source = self.get_twap_source(token);
+        if source >= MAX_ENTRIES {
+            return Err(crate::ScopeError::TwapSourceIndexOutOfRange);
+        }
+        if self.price_info_accounts[source] == Pubkey::default() {
+            return Err(crate::ScopeError::TwapSourceNotMapped);
+        }
+        if self.is_scope_twap(source) {
+            return Err(crate::ScopeError::TwapSourceIsTwap);
+        }
+        if !self.is_twap_enabled(source) {
+            return Err(crate::ScopeError::TwapSourceNotEnabled);
+        }
+        Ok(())
+    }
+
+    /// List the `ScopeTwap` entries with an invalid source, see [`OracleMappings::check_twap_source`].
+    ///
+    /// `update_mapping` refuses to break the source of a TWAP entry, but feeds configured before
+    /// this check may still have such entries, so this is worth running over existing feeds.
+    pub fn twap_source_errors(&self) -> impl Iterator<Item = (usize, crate::ScopeError)> + '_ {
+        (0..MAX_ENTRIES).filter_map(|token| self.check_twap_source(token).err().map(|e| (token, e)))
+    }
 }
 
 #[account(zero_copy)]
@@ -140,6 +239,26 @@ pub struct TokenMetadata {
     pub _reserved: [u64; 16],
 }
 
+/// Layout version of the accounts of a feed, stored in its [`Configuration`].
+///
+/// One version covers all the accounts created by `initialize` (configuration, mappings, prices,
+/// twaps and tokens metadata): the `migrate` instruction takes all of them and upgrades them
+/// together, so they are never at different versions. The per-entry accounts (price publishers,
+/// index definitions, account field readers and price histories) were introduced in version 1
+/// and have a single layout so far.
+///
+/// - 0: accounts initialized before the layout was versioned
+/// - 1: `OracleMappings::generic` appended to the mappings account, which `migrate` reallocates.
+///   `OracleMappings::history_enabled`, the volatility and range fields of `EmaTwap`, the bid/ask
+///   and refreshed slot of `DatedPrice` and the refresh allowed programs of `Configuration` are
+///   carved from zeroed reserved space and need no data change.
+///
+/// Bump it whenever an account grows or fields are carved out of reserved space with a non-zero
+/// initial value and add the matching step to the `migrate` instruction.
+pub const FEED_LAYOUT_VERSION: u64 = 1;
+
+pub const MAX_REFRESH_ALLOWED_PROGRAMS: usize = 8;
+
 // Configuration account of the program
 #[account(zero_copy)]
 pub struct Configuration {
@@ -149,5 +268,154 @@ pub struct Configuration {
     pub tokens_metadata: Pubkey,
     pub oracle_twaps: Pubkey,
     pub admin_cached: Pubkey,
-    _padding: [u64; 1255],
+    pub version: u64, // layout version of all the accounts of the feed, see FEED_LAYOUT_VERSION
+    // Programs allowed to have instructions before a refresh, on top of compute budget and ed25519
+    pub refresh_allowed_programs_count: u64,
+    pub refresh_allowed_programs: [Pubkey; MAX_REFRESH_ALLOWED_PROGRAMS],
+    _padding: [u64; 1221],
+}
+
+impl Configuration {
+    pub fn is_migrated(&self) -> bool {
+        self.version == FEED_LAYOUT_VERSION
+    }
+
+    pub fn get_refresh_allowed_programs(&self) -> &[Pubkey] {
+        let count = usize::try_from(self.refresh_allowed_programs_count)
+            .unwrap_or(MAX_REFRESH_ALLOWED_PROGRAMS)
+            .min(MAX_REFRESH_ALLOWED_PROGRAMS);
+        &self.refresh_allowed_programs[..count]
+    }
+
+    pub fn set_refresh_allowed_programs(&mut self, programs: &[Pubkey]) -> crate::ScopeResult<()> {
+        if programs.len() > MAX_REFRESH_ALLOWED_PROGRAMS {
+            return Err(crate::ScopeError::TooManyRefreshAllowedPrograms);
+        }
+        self.refresh_allowed_programs = [Pubkey::default(); MAX_REFRESH_ALLOWED_PROGRAMS];
+        self.refresh_allowed_programs[..programs.len()].copy_from_slice(programs);
+        self.refresh_allowed_programs_count = programs.len() as u64;
+        Ok(())
+    }
+}
+
+pub const MAX_PRICE_PUBLISHERS: usize = 8;
+
+// Publishers allowed to push signed prices for one entry of a feed
+#[account(zero_copy)]
+pub struct PricePublishers {
+    pub oracle_prices: Pubkey,
+    pub index: u16,
+    pub _padding0: [u8; 6],
+    pub publishers: [Pubkey; MAX_PRICE_PUBLISHERS], // unused slots are set to the default pubkey
+    pub _reserved: [u64; 16],
+}
+
+impl PricePublishers {
+    pub fn set_publishers(&mut self, publishers: &[Pubkey]) -> crate::ScopeResult<()> {
+        if publishers.len() > MAX_PRICE_PUBLISHERS {
+            return Err(crate::ScopeError::TooManyPricePublishers);
+        }
+        self.publishers = [Pubkey::default(); MAX_PRICE_PUBLISHERS];
+        self.publishers[..publishers.len()].copy_from_slice(publishers);
+        Ok(())
+    }
+
+    pub fn is_publisher(&self, key: &Pubkey) -> bool {
+        *key != Pubkey::default() && self.publishers.contains(key)
+    }
+}
+
+pub const MAX_INDEX_COMPONENTS: usize = 16;
+
+#[zero_copy]
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct IndexComponent {
+    pub weight: u64,
+    pub entry: u16,
+    pub _padding: [u8; 6],
+}
+
+// Definition of a weighted basket index computed from other entries of a feed
+#[account(zero_copy)]
+pub struct IndexDefinition {
+    pub oracle_prices: Pubkey,
+    pub index: u16,
+    pub num_components: u16,
+    pub _padding0: [u8; 4],
+    pub weights_exp: u64,
+    pub divisor: u64, // same exponent as the weights, 0 for no divisor
+    pub components: [IndexComponent; MAX_INDEX_COMPONENTS],
+    pub _reserved: [u64; 16],
+}
+
+impl IndexDefinition {
+    /// Set the components of the index, `index` must already be set as an index cannot be one of
+    /// its own components.
+    pub fn set_components(
+        &mut self,
+        entries: &[u16],
+        weights: &[u64],
+        weights_exp: u8,
+        divisor: u64,
+    ) -> crate::ScopeResult<()> {
+        if entries.is_empty()
+            || entries.len() > MAX_INDEX_COMPONENTS
+            || entries.len() != weights.len()
+            || entries
+                .iter()
+                .any(|&entry| entry >= MAX_ENTRIES_U16 || entry == self.index)
+            || weights.iter().any(|&weight| weight == 0)
+            || u64::from(weights_exp) > MAX_PRICE_EXP
+        {
+            return Err(crate::ScopeError::InvalidIndexDefinition);
+        }
+        self.components = [IndexComponent::default(); MAX_INDEX_COMPONENTS];
+        for (component, (&entry, &weight)) in self
+            .components
+            .iter_mut()
+            .zip(entries.iter().zip(weights.iter()))
+        {
+            component.entry = entry;
+            component.weight = weight;
+        }
+        self.num_components = entries.len().try_into()?;
+        self.weights_exp = weights_exp.into();
+        self.divisor = divisor;
+        Ok(())
+    }
+
+    pub fn components(&self) -> &[IndexComponent] {
+        &self.components[..usize::from(self.num_components).min(MAX_INDEX_COMPONENTS)]
+    }
+}
+
+// Configuration of an entry reading an integer field of an account, see `oracles::account_field`
+#[account(zero_copy)]
+pub struct AccountFieldReader {
+    pub oracle_prices: Pubkey,
+    pub index: u16,
+    pub _padding0: [u8; 6],
+    pub account: Pubkey,        // account read
+    pub owner: Pubkey,          // expected owner program of the account read
+    pub discriminator: [u8; 8], // expected first 8 bytes of the account data
+    pub value_offset: u32,      // offset of the little endian value field
+    pub divisor_offset: u32,    // offset of the little endian divisor field, if any
+    pub value_size: u8,         // size of the value field in bytes (1, 2, 4, 8 or 16)
+    pub divisor_size: u8,       // size of the divisor field in bytes, 0 for no divisor
+    pub exp: u8,                // exponent applied to the value
+    pub _padding1: [u8; 5],
+    pub _reserved: [u64; 8],
+}
+
+pub const PRICE_HISTORY_LEN: usize = 128;
+
+// Last observations of one entry of a feed, appended on refresh when the slot advances
+#[account(zero_copy)]
+pub struct PriceHistory {
+    pub oracle_prices: Pubkey,
+    pub index: u16,
+    pub _padding0: [u8; 6],
+    pub num_observations: u64, // total number of observations ever appended
+    pub observations: [DatedPrice; PRICE_HISTORY_LEN], // ring buffer, oldest is overwritten first
+    pub _reserved: [u64; 16],
 }
@@ -1,3 +1,5 @@
+use anchor_lang::prelude::msg;
+
 use crate::{ScopeError, ScopeResult};
 use decimal_wad::decimal::U192;
 use decimal_wad::rate::U128;
@@ -6,16 +8,20 @@ use raydium_amm_v3::libraries::U256;
 use crate::Price;
 
 /// Transform sqrt price to normal price scaled by 2^64
-fn sqrt_price_to_x64_price(sqrt_price: u128, decimals_a: u8, decimals_b: u8) -> U192 {
+fn sqrt_price_to_x64_price(sqrt_price: u128, decimals_a: u8, decimals_b: u8) -> ScopeResult<U192> {
     let sqrt_price = U256::from(sqrt_price);
     let price = (sqrt_price * sqrt_price) >> U256::from(64);
     let price_u256 = if decimals_a >= decimals_b {
-        price * U256::from(ten_pow(decimals_a - decimals_b))
+        price
+            .checked_mul(U256::from(ten_pow(decimals_a - decimals_b)?))
+            .ok_or(ScopeError::IntegerOverflow)?
     } else {
-        price / U256::from(ten_pow(decimals_b - decimals_a))
+        price / U256::from(ten_pow(decimals_b - decimals_a)?)
     };
-    debug_assert_eq!(price_u256.0[3], 0, "price overflow: {:?}", price_u256); // should not overflow because of the shift
-    U192([price_u256.0[0], price_u256.0[1], price_u256.0[2]])
+    if price_u256.0[3] != 0 {
+        return Err(ScopeError::IntegerOverflow);
+    }
+    Ok(U192([price_u256.0[0], price_u256.0[1], price_u256.0[2]]))
 }
 
 pub fn sqrt_price_to_price(
@@ -29,11 +35,14 @@ pub fn sqrt_price_to_price(
     }
 
     let x64_price = if a_to_b {
-        sqrt_price_to_x64_price(sqrt_price, decimals_a, decimals_b)
+        sqrt_price_to_x64_price(sqrt_price, decimals_a, decimals_b)?
     } else {
         // invert the sqrt price
         let inverted_sqrt_price = (U192::one() << 128) / sqrt_price;
-        sqrt_price_to_x64_price(inverted_sqrt_price.as_u128(), decimals_b, decimals_a)
+        if inverted_sqrt_price > U192::from(u128::MAX) {
+            return Err(ScopeError::IntegerOverflow);
+        }
+        sqrt_price_to_x64_price(inverted_sqrt_price.as_u128(), decimals_b, decimals_a)?
     };
 
     q64x64_price_to_price(x64_price)
@@ -43,6 +52,9 @@ pub fn q64x64_price_to_price(x64_price: U192) -> ScopeResult<Price> {
     const MAX_INTEGER_PART: u128 = u64::MAX as u128;
 
     let integer_part_u192 = x64_price >> U192::from(64);
+    if integer_part_u192 > U192::from(MAX_INTEGER_PART) {
+        return Err(ScopeError::OutOfRangeIntegralConversion);
+    }
     let integer_part_u128 = integer_part_u192.as_u128();
 
     let (exp, factor) = match integer_part_u128 {
@@ -68,6 +80,9 @@ pub fn q64x64_price_to_price(x64_price: U192) -> ScopeResult<Price> {
         _ => return Err(ScopeError::OutOfRangeIntegralConversion),
     };
     let value_u192 = (x64_price * U192::from(factor)) >> U192::from(64);
+    if value_u192 > U192::from(u64::MAX) {
+        return Err(ScopeError::OutOfRangeIntegralConversion);
+    }
     let value: u64 = value_u192.as_u64();
     Ok(Price { value, exp })
 }
@@ -77,7 +92,7 @@ pub fn price_of_lamports_to_price_of_tokens(
     lamport_price: Price,
     token_a_decimals: u64,
     token_b_decimals: u64,
-) -> Price {
+) -> ScopeResult<Price> {
     // lamport_price = number_of_token_b_lamport / number_of_token_a_lamport
     // price = number_of_token_b / number_of_token_a
     // price = (number_of_token_b_lamport / 10^token_b_decimals) / (number_of_token_a_lamport / 10^token_a_decimals)
@@ -90,25 +105,34 @@ pub fn price_of_lamports_to_price_of_tokens(
         exp: lamport_exp,
     } = lamport_price;
 
-    if lamport_exp + token_b_decimals >= token_a_decimals {
-        let exp = lamport_exp + token_b_decimals - token_a_decimals;
-        Price {
+    let lamport_exp_b = lamport_exp
+        .checked_add(token_b_decimals)
+        .ok_or(ScopeError::IntegerOverflow)?;
+    if lamport_exp_b >= token_a_decimals {
+        let exp = lamport_exp_b - token_a_decimals;
+        Ok(Price {
             value: lamport_value,
             exp,
-        }
+        })
     } else {
-        let adjust_exp = token_a_decimals - (lamport_exp + token_b_decimals);
-        let value = lamport_value * 10_u64.pow(adjust_exp.try_into().unwrap());
-        Price { value, exp: 0 }
+        let adjust_exp = u32::try_from(token_a_decimals - lamport_exp_b)?;
+        let value = 10_u64
+            .checked_pow(adjust_exp)
+            .and_then(|factor| lamport_value.checked_mul(factor))
+            .ok_or(ScopeError::IntegerOverflow)?;
+        Ok(Price { value, exp: 0 })
     }
 }
 
-pub fn u64_div_to_price(numerator: u64, denominator: u64) -> Price {
+pub fn u64_div_to_price(numerator: u64, denominator: u64) -> ScopeResult<Price> {
     // this implementation aims to keep as much precision as possible
     // choose exp to be the nearest power of 10 to the denominator
     // so that the result is in the range [0, 10^18]
     let (exp, ten_pow_exp) = match denominator {
-        0 => panic!("Creating a price by dividing by 0"),
+        0 => {
+            msg!("Creating a price by dividing by 0");
+            return Err(ScopeError::MathOverflow);
+        }
         1..=10 => (0, 1_u64),
         11..=100 => (1, 10),
         101..=1000 => (2, 100),
@@ -131,13 +155,77 @@ pub fn u64_div_to_price(numerator: u64, denominator: u64) -> Price {
     };
     let numerator_scaled = U128::from(numerator) * U128::from(ten_pow_exp);
     let price_value = numerator_scaled / U128::from(denominator);
-    Price {
+    // The quotient is above u64::MAX only if the numerator is more than 10^18 times the denominator
+    if price_value > U128::from(u64::MAX) {
+        return Err(ScopeError::OutOfRangeIntegralConversion);
+    }
+    Ok(Price {
         value: price_value.as_u64(),
         exp,
+    })
+}
+
+/// Get the price of one base token in quote tokens from the reserves (in lamports) of a constant-product pool
+pub fn reserves_to_price(
+    base_reserve: u64,
+    quote_reserve: u64,
+    base_decimals: u8,
+    quote_decimals: u8,
+) -> ScopeResult<Price> {
+    if base_reserve == 0 {
+        return Err(ScopeError::PoolReservesEmpty);
     }
+    let lamport_price = u64_div_to_price(quote_reserve, base_reserve)?;
+    price_of_lamports_to_price_of_tokens(lamport_price, base_decimals.into(), quote_decimals.into())
 }
 
-pub fn ten_pow(exponent: u8) -> u128 {
+/// Get the virtual reserve of the quote token (in lamports) of a CLMM pool at the current price
+///
+/// `liquidity` is the in-range liquidity of the pool and `sqrt_price` the sqrt price A to B scaled by 2^64:
+/// - A to B (quote is token B): `liquidity * sqrt_price`
+/// - B to A (quote is token A): `liquidity / sqrt_price`
+pub fn clmm_quote_virtual_reserve(a_to_b: bool, liquidity: u128, sqrt_price: u128) -> u128 {
+    let reserve = if a_to_b {
+        (U256::from(liquidity) * U256::from(sqrt_price)) >> 64
+    } else if sqrt_price == 0 {
+        U256::zero()
+    } else {
+        (U256::from(liquidity) << 64) / U256::from(sqrt_price)
+    };
+    u256_to_u128_saturating(reserve)
+}
+
+/// Get the value (in lamports of the quote token) of the reserves of a DLMM bin
+///
+/// `x64_price` is the price of one lamport of token X in lamports of token Y scaled by 2^64:
+/// - A to B (quote is token Y): `amount_y + amount_x * price`
+/// - B to A (quote is token X): `amount_x + amount_y / price`
+pub fn dlmm_bin_value_in_quote(
+    a_to_b: bool,
+    amount_x: u64,
+    amount_y: u64,
+    x64_price: U128,
+) -> u128 {
+    let x64_price = U256::from(x64_price.as_u128());
+    let value = if a_to_b {
+        U256::from(amount_y) + ((U256::from(amount_x) * x64_price) >> 64)
+    } else if x64_price.is_zero() {
+        U256::from(amount_x)
+    } else {
+        U256::from(amount_x) + (U256::from(amount_y) << 64) / x64_price
+    };
+    u256_to_u128_saturating(value)
+}
+
+fn u256_to_u128_saturating(value: U256) -> u128 {
+    if value > U256::from(u128::MAX) {
+        u128::MAX
+    } else {
+        value.as_u128()
+    }
+}
+
+pub fn ten_pow(exponent: u8) -> ScopeResult<u128> {
     let value: u128 = match exponent {
         30 => 1_000_000_000_000_000_000_000_000_000_000,
         29 => 100_000_000_000_000_000_000_000_000_000,
@@ -170,10 +258,13 @@ pub fn ten_pow(exponent: u8) -> u128 {
         2 => 100,
         1 => 10,
         0 => 1,
-        _ => panic!("no support for exponent: {exponent}"),
+        _ => {
+            msg!("no support for exponent: {exponent}");
+            return Err(ScopeError::MathOverflow);
+        }
     };
 
-    value
+    Ok(value)
 }
 
 #[cfg(test)]
@@ -185,7 +276,7 @@ mod tests {
     proptest! {
         #[test]
         fn test_u64_div_to_price(numerator in 1_u64..=u64::MAX, denominator in 1_u64..=u64::MAX) {
-            let price = u64_div_to_price(numerator, denominator);
+            let price = u64_div_to_price(numerator, denominator).unwrap();
             let price_f64: f64 =  price.into();
             let expected_price_f64: f64 = numerator as f64 / denominator as f64;
             prop_assert!((price_f64 - expected_price_f64).abs() < expected_price_f64/1000000000.0, "price_f64: {}, expected_price_f64: {}", price_f64, expected_price_f64);
@@ -218,6 +309,61 @@ mod tests {
         );
     }
 
+    #[test_case(true, 1_000_000, 1.0, 1_000_000)]
+    #[test_case(true, 1_000_000, 2.0, 2_000_000)]
+    #[test_case(false, 1_000_000, 2.0, 500_000)]
+    #[test_case(false, 1_000_000, 0.0, 0)]
+    fn test_clmm_quote_virtual_reserve(
+        a_to_b: bool,
+        liquidity: u128,
+        sqrt_price: f64,
+        expected_reserve: u128,
+    ) {
+        let sqrt_price = (sqrt_price * (2.0_f64.powi(64))) as u128;
+        let reserve = clmm_quote_virtual_reserve(a_to_b, liquidity, sqrt_price);
+        assert_eq!(reserve, expected_reserve);
+    }
+
+    #[test_case(1_000_000_000_000, 20_000_000_000, 9, 6, 20.0;"sol usdc")]
+    #[test_case(20_000_000_000, 1_000_000_000_000, 6, 9, 0.05;"usdc sol")]
+    #[test_case(1_000_000, 3_000_000, 6, 6, 3.0;"equal decimals")]
+    #[test_case(1_000_000, 0, 6, 6, 0.0;"empty quote")]
+    fn test_reserves_to_price(
+        base_reserve: u64,
+        quote_reserve: u64,
+        base_decimals: u8,
+        quote_decimals: u8,
+        expected_price_f64: f64,
+    ) {
+        let price =
+            reserves_to_price(base_reserve, quote_reserve, base_decimals, quote_decimals).unwrap();
+        let price_f64: f64 = price.into();
+        assert!((price_f64 - expected_price_f64).abs() < 0.000001);
+    }
+
+    #[test]
+    fn test_reserves_to_price_empty_base() {
+        assert_eq!(
+            reserves_to_price(0, 1_000_000, 6, 6).unwrap_err(),
+            ScopeError::PoolReservesEmpty
+        );
+    }
+
+    #[test_case(true, 100, 50, 2, 250)]
+    #[test_case(false, 100, 50, 2, 125)]
+    #[test_case(true, 0, 0, 2, 0)]
+    fn test_dlmm_bin_value_in_quote(
+        a_to_b: bool,
+        amount_x: u64,
+        amount_y: u64,
+        price: u64,
+        expected_value: u128,
+    ) {
+        let x64_price = U128::from(price) << 64;
+        let value = dlmm_bin_value_in_quote(a_to_b, amount_x, amount_y, x64_price);
+        assert_eq!(value, expected_value);
+    }
+
     #[test_case(100, 0, 6, 6, 100, 0)]
     #[test_case(100, 0, 3, 6, 100000, 0)]
     #[test_case(100, 0, 6, 3, 100, 3)]
@@ -235,8 +381,43 @@ mod tests {
     ) {
         let price = Price { value, exp };
         let final_price =
-            price_of_lamports_to_price_of_tokens(price, token_a_decimals, token_b_decimals);
+            price_of_lamports_to_price_of_tokens(price, token_a_decimals, token_b_decimals)
+                .unwrap();
         assert_eq!(final_price.value, final_value);
         assert_eq!(final_price.exp, final_exp);
     }
+
+    #[test]
+    fn test_price_of_lamports_to_price_of_tokens_overflow() {
+        let price = Price {
+            value: u64::MAX,
+            exp: 0,
+        };
+        assert_eq!(
+            price_of_lamports_to_price_of_tokens(price, 6, 0).unwrap_err(),
+            ScopeError::IntegerOverflow
+        );
+        let price = Price {
+            value: 1,
+            exp: u64::MAX,
+        };
+        assert_eq!(
+            price_of_lamports_to_price_of_tokens(price, 0, 1).unwrap_err(),
+            ScopeError::IntegerOverflow
+        );
+    }
+
+    #[test]
+    fn test_u64_div_to_price_zero_denominator() {
+        assert_eq!(
+            u64_div_to_price(1, 0).unwrap_err(),
+            ScopeError::MathOverflow
+        );
+    }
+
+    #[test]
+    fn test_ten_pow_unsupported_exponent() {
+        assert_eq!(ten_pow(30).unwrap(), 10_u128.pow(30));
+        assert_eq!(ten_pow(31).unwrap_err(), ScopeError::MathOverflow);
+    }
 }
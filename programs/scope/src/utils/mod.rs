@@ -3,15 +3,16 @@ pub mod math;
 pub mod price_impl;
 pub mod scope_chain;
 
-use std::cell::Ref;
+use std::cell::{Ref, RefMut};
 
 use anchor_lang::{
     __private::bytemuck,
-    prelude::{msg, AccountDeserialize, AccountInfo},
+    prelude::{msg, AccountDeserialize, AccountInfo, Pubkey},
     Discriminator, Key,
 };
+use arrayref::array_ref;
 
-use crate::{ScopeError, ScopeResult};
+use crate::{ScopeError, ScopeResult, GENERIC_DATA_LEN};
 
 pub const SECONDS_PER_HOUR: i64 = 60 * 60;
 
@@ -48,7 +49,10 @@ pub fn account_deserialize<T: AccountDeserialize + Discriminator>(
 pub fn zero_copy_deserialize<'info, T: bytemuck::AnyBitPattern + Discriminator>(
     account: &'info AccountInfo,
 ) -> ScopeResult<Ref<'info, T>> {
-    let data = account.data.try_borrow().unwrap();
+    let data = account
+        .data
+        .try_borrow()
+        .map_err(|_| ScopeError::UnableToDeserializeAccount)?;
 
     let disc_bytes = data.get(..8).ok_or_else(|| {
         msg!(
@@ -69,3 +73,63 @@ pub fn zero_copy_deserialize<'info, T: bytemuck::AnyBitPattern + Discriminator>(
 
     Ok(Ref::map(data, |data| bytemuck::from_bytes(&data[8..])))
 }
+
+pub fn zero_copy_deserialize_mut<'info, T: bytemuck::Pod + Discriminator>(
+    account: &'info AccountInfo,
+) -> ScopeResult<RefMut<'info, T>> {
+    if !account.is_writable {
+        msg!("Account {:?} is not writable", account.key());
+        return Err(ScopeError::UnexpectedAccount);
+    }
+    let data = account
+        .data
+        .try_borrow_mut()
+        .map_err(|_| ScopeError::UnableToDeserializeAccount)?;
+
+    let disc_bytes = data.get(..8).ok_or_else(|| {
+        msg!(
+            "Account {:?} does not have enough bytes to be deserialized",
+            account.key()
+        );
+        ScopeError::UnableToDeserializeAccount
+    })?;
+    if disc_bytes != T::discriminator() {
+        msg!(
+            "Expected discriminator for account {:?} ({:?}) is different from received {:?}",
+            account.key(),
+            T::discriminator(),
+            disc_bytes
+        );
+        return Err(ScopeError::InvalidAccountDiscriminator);
+    }
+
+    Ok(RefMut::map(data, |data| {
+        bytemuck::from_bytes_mut(&mut data[8..])
+    }))
+}
+
+/// Get the minimum pool liquidity (in lamports of the quote token) configured in the first 8 bytes
+/// of the entry generic data (u64, little endian). A minimum of 0 disables the check.
+pub fn get_min_liquidity(generic_data: &[u8; GENERIC_DATA_LEN]) -> u64 {
+    u64::from_le_bytes(*array_ref![generic_data, 0, 8])
+}
+
+/// Check the liquidity of a pool (in lamports of the quote token) against the minimum configured
+/// in the entry generic data, see [`get_min_liquidity`].
+pub fn check_min_liquidity(
+    pool: &Pubkey,
+    quote_liquidity: u128,
+    generic_data: &[u8; GENERIC_DATA_LEN],
+) -> ScopeResult<()> {
+    let min_liquidity = get_min_liquidity(generic_data);
+    if quote_liquidity < u128::from(min_liquidity) {
+        msg!(
+            "Pool {} liquidity {} is below the configured minimum {}",
+            pool,
+            quote_liquidity,
+            min_liquidity
+        );
+        return Err(ScopeError::PoolLiquidityTooLow);
+    }
+    Ok(())
+}
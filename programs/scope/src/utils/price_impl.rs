@@ -1,38 +1,114 @@
-use decimal_wad::decimal::Decimal;
+use anchor_lang::prelude::msg;
+use std::cmp::Ordering;
 
-use crate::Price;
+use decimal_wad::{common::TryMul, decimal::Decimal};
+use raydium_amm_v3::libraries::U256;
 
-use super::math::ten_pow;
+use crate::{Price, ScopeError, ScopeResult};
+
+/// Highest exponent produced by the conversions below (highest supported by [`super::math::ten_pow`]).
+/// Mirrored as `scope_types::MAX_PRICE_EXP`.
+///
+/// Prices computed from a [`Decimal`] have at most 18 decimals, [`Price::from_ratio`] and
+/// [`Price::from_scaled_ratio`] can go further for very small prices.
+pub const MAX_PRICE_EXP: u64 = 30;
 
 impl From<Price> for f64 {
     fn from(val: Price) -> Self {
-        val.value as f64 / 10u64.pow(val.exp as u32) as f64
+        val.value as f64 / 10f64.powi(val.exp as i32)
     }
 }
 
 impl Price {
-    pub fn to_scaled_value(&self, decimals: u8) -> u128 {
-        let exp = u8::try_from(self.exp).expect("Price exp is too big");
+    /// Build a price from a mantissa that might not fit in a u64, dropping its least significant
+    /// digits (truncation) as long as the exponent allows it.
+    pub fn from_scaled_u128(value: u128, exp: u64) -> ScopeResult<Price> {
+        let mut value = value;
+        let mut exp = exp;
+        while value > u128::from(u64::MAX) {
+            if exp == 0 {
+                return Err(ScopeError::PriceOutOfRange);
+            }
+            value /= 10;
+            exp -= 1;
+        }
+        Ok(Price {
+            value: value as u64,
+            exp,
+        })
+    }
+
+    /// Price of `numerator / denominator` computed with integers only, keeping up to
+    /// [`MAX_PRICE_EXP`] decimals. This is more precise than going through a [`Decimal`] for very
+    /// small prices.
+    ///
+    /// Exponents above 18 are only kept when needed to have at least 16 significant digits.
+    pub fn from_ratio(numerator: u128, denominator: u128) -> ScopeResult<Price> {
+        Price::from_scaled_ratio(numerator.into(), 0, denominator.into())
+    }
+
+    /// Price of `numerator * 10^-exp / denominator`, see [`Price::from_ratio`].
+    pub fn from_scaled_ratio(numerator: U256, exp: u64, denominator: U256) -> ScopeResult<Price> {
+        if denominator.is_zero() {
+            return Err(ScopeError::MathOverflow);
+        }
+        // Scale the numerator as much as possible
+        let ten = U256::from(10);
+        let mut numerator = numerator;
+        let mut exp = exp;
+        while exp < MAX_PRICE_EXP && numerator <= U256::MAX / ten {
+            numerator = numerator * ten;
+            exp += 1;
+        }
+        // Drop the digits that do not fit in a price
+        let mut value = numerator / denominator;
+        while exp > MAX_PRICE_EXP || value > U256::from(u64::MAX) {
+            if exp == 0 {
+                return Err(ScopeError::PriceOutOfRange);
+            }
+            value = value / ten;
+            exp -= 1;
+        }
+        let mut price = Price {
+            value: value.as_u64(),
+            exp,
+        };
+        while price.exp > 18 && price.value >= 10_u64.pow(16) {
+            price.value /= 10;
+            price.exp -= 1;
+        }
+        Ok(price)
+    }
+
+    pub fn to_scaled_value(&self, decimals: u8) -> ScopeResult<u128> {
+        let exp = u8::try_from(self.exp)?;
         let value: u128 = self.value.into();
         if exp > decimals {
             let diff = exp - decimals;
-            value / ten_pow(diff)
+            // A divisor bigger than u128::MAX always rounds the value down to 0
+            Ok(10_u128
+                .checked_pow(diff.into())
+                .map_or(0, |factor| value / factor))
         } else {
             let diff = decimals - exp;
-            value * ten_pow(diff)
+            10_u128
+                .checked_pow(diff.into())
+                .and_then(|factor| value.checked_mul(factor))
+                .ok_or(ScopeError::IntegerOverflow)
         }
     }
 }
 
-fn decimal_to_price(decimal: Decimal) -> Price {
+fn decimal_to_price(decimal: Decimal) -> ScopeResult<Price> {
     // this implementation aims to keep as much precision as possible
     // choose exp to be as big as possible (minimize what is needed for the integer part)
 
     // Use a match instead of log10 to save some CUs
-    let (exp, ten_pow_exp) = match decimal
-        .try_round::<u64>()
-        .expect("Decimal integer part is too big")
-    {
+    let integer_part = decimal.try_round::<u64>().map_err(|_| {
+        msg!("Decimal {decimal} integer part is too big to be a price");
+        ScopeError::PriceOutOfRange
+    })?;
+    let (exp, ten_pow_exp) = match integer_part {
         0_u64 => (18, 10_u64.pow(18)),
         1..=9 => (17, 10_u64.pow(17)),
         10..=99 => (16, 10_u64.pow(16)),
@@ -53,46 +129,86 @@ fn decimal_to_price(decimal: Decimal) -> Price {
         10000000000000000..=99999999999999999 => (1, 10_u64.pow(1)),
         100000000000000000..=u64::MAX => (0, 1),
     };
-    let value = (decimal * ten_pow_exp)
-        .try_round::<u64>()
-        .unwrap_or_else(|e| {
-            panic!("Decimal {decimal} conversion to price failed (exp:{exp}): {e:?}");
-        });
-    Price { value, exp }
+    let value = decimal
+        .try_mul(ten_pow_exp)
+        .and_then(|scaled| scaled.try_round::<u64>())
+        .map_err(|e| {
+            msg!("Decimal {decimal} conversion to price failed (exp:{exp}): {e:?}");
+            ScopeError::PriceOutOfRange
+        })?;
+    Ok(Price { value, exp })
 }
 
-impl From<Decimal> for Price {
-    fn from(val: Decimal) -> Self {
+impl TryFrom<Decimal> for Price {
+    type Error = ScopeError;
+
+    fn try_from(val: Decimal) -> ScopeResult<Self> {
         decimal_to_price(val)
     }
 }
 
 impl From<Price> for Decimal {
     fn from(val: Price) -> Self {
-        Decimal::from(val.value) / 10u128.pow(val.exp as u32)
+        // A power of ten that does not fit a u128 (exp > 38) puts any u64 value below the 18 decimals
+        // of a `Decimal`, so the exact result is zero
+        u32::try_from(val.exp)
+            .ok()
+            .and_then(|exp| 10u128.checked_pow(exp))
+            .map_or_else(Decimal::zero, |factor| Decimal::from(val.value) / factor)
     }
 }
 
 impl PartialEq for Price {
     fn eq(&self, other: &Self) -> bool {
+        // Scale the value with the smaller exponent, an overflow means the prices differ
+        let scaled_eq = |value: u64, diff: u64, expected: u64| {
+            u32::try_from(diff)
+                .ok()
+                .and_then(|diff| 10u64.checked_pow(diff))
+                .and_then(|factor| value.checked_mul(factor))
+                .map_or(value == 0 && expected == 0, |scaled| scaled == expected)
+        };
         match self.exp.cmp(&other.exp) {
-            std::cmp::Ordering::Equal => self.value == other.value,
-            std::cmp::Ordering::Greater => {
-                let diff = self.exp - other.exp;
-                let other_value = other.value * 10u64.pow(diff as u32);
-                self.value == other_value
-            }
-            std::cmp::Ordering::Less => {
-                let diff = other.exp - self.exp;
-                let self_value = self.value * 10u64.pow(diff as u32);
-                self_value == other.value
-            }
+            Ordering::Equal => self.value == other.value,
+            Ordering::Greater => scaled_eq(other.value, self.exp - other.exp, self.value),
+            Ordering::Less => scaled_eq(self.value, other.exp - self.exp, other.value),
         }
     }
 }
 
 impl Eq for Price {}
 
+impl Ord for Price {
+    /// Exact comparison, unlike comparing the [`Decimal`] values which drops the decimals after
+    /// the 18th.
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Scale the value with the smaller exponent, an overflow means it is the greater price
+        let scaled_cmp = |value: u64, diff: u64, other_value: u64| {
+            u32::try_from(diff)
+                .ok()
+                .and_then(|diff| 10u128.checked_pow(diff))
+                .and_then(|factor| u128::from(value).checked_mul(factor))
+                .map_or_else(
+                    || value.cmp(&0).then(0.cmp(&other_value)),
+                    |scaled| scaled.cmp(&u128::from(other_value)),
+                )
+        };
+        match self.exp.cmp(&other.exp) {
+            Ordering::Equal => self.value.cmp(&other.value),
+            Ordering::Greater => {
+                scaled_cmp(other.value, self.exp - other.exp, self.value).reverse()
+            }
+            Ordering::Less => scaled_cmp(self.value, other.exp - self.exp, other.value),
+        }
+    }
+}
+
+impl PartialOrd for Price {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,7 +222,7 @@ mod tests {
         #[test]
         fn test_decimal_to_price(decimal_u128 in 0..=MAX_VALID_DECIMAL_U128) {
             let decimal = Decimal::from_scaled_val(decimal_u128);
-            let price = decimal_to_price(decimal);
+            let price = decimal_to_price(decimal).unwrap();
             let re_decimal = Decimal::from(price);
             let re_decimal_u128 =  re_decimal.to_scaled_val::<u128>().unwrap();
             prop_assert!(re_decimal_u128.abs_diff(decimal_u128) < decimal_u128/100_000_000, "decimal: {}, re_decimal: {}, price: {:?}", decimal, re_decimal, price);
@@ -122,7 +238,137 @@ mod tests {
             value: price,
             exp: exp.into(),
         };
-        let scaled_value = price.to_scaled_value(target_scale);
+        let scaled_value = price.to_scaled_value(target_scale).unwrap();
         assert_eq!(scaled_value, expected);
     }
+
+    #[test]
+    fn test_decimal_from_huge_exp() {
+        for exp in [39, 255, u64::MAX] {
+            let price = Price {
+                value: u64::MAX,
+                exp,
+            };
+            assert_eq!(Decimal::from(price), Decimal::zero());
+        }
+    }
+
+    #[test]
+    fn test_decimal_too_big() {
+        let decimal = Decimal::from(u64::MAX) * 2_u64;
+        assert_eq!(
+            Price::try_from(decimal).unwrap_err(),
+            ScopeError::PriceOutOfRange
+        );
+    }
+
+    #[test]
+    fn test_from_scaled_u128() {
+        assert_eq!(
+            Price::from_scaled_u128(u128::from(u64::MAX) * 100, 3).unwrap(),
+            Price {
+                value: u64::MAX,
+                exp: 1
+            }
+        );
+        assert_eq!(
+            Price::from_scaled_u128(u128::from(u64::MAX) * 100, 1).unwrap_err(),
+            ScopeError::PriceOutOfRange
+        );
+    }
+
+    #[test]
+    fn test_from_ratio_small_price() {
+        // 1 / 7e15 keeps all its significant digits instead of the 3 left with 18 decimals
+        let price = Price::from_ratio(1, 7_000_000_000_000_000).unwrap();
+        assert_eq!(price.exp, MAX_PRICE_EXP);
+        assert_eq!(price.value, 142_857_142_857_142);
+        let decimal_price = Price::try_from(Decimal::one() / 7_000_000_000_000_000_u64).unwrap();
+        assert!(decimal_price.value < 1_000);
+    }
+
+    #[test]
+    fn test_from_ratio_big_price() {
+        assert_eq!(
+            Price::from_ratio(u128::from(u64::MAX), 1).unwrap(),
+            Price {
+                value: u64::MAX,
+                exp: 0
+            }
+        );
+        assert_eq!(
+            Price::from_ratio(u128::from(u64::MAX) * 2, 1).unwrap_err(),
+            ScopeError::PriceOutOfRange
+        );
+        assert_eq!(
+            Price::from_ratio(1, 0).unwrap_err(),
+            ScopeError::MathOverflow
+        );
+    }
+
+    #[test]
+    fn test_from_scaled_ratio() {
+        // 1e-25 / 3 keeps its significant digits down to the 30th decimal
+        let price = Price::from_scaled_ratio(1.into(), 25, 3.into()).unwrap();
+        assert_eq!(
+            price,
+            Price {
+                value: 33_333,
+                exp: MAX_PRICE_EXP
+            }
+        );
+        // Digits beyond the 30th decimal are dropped
+        let price = Price::from_scaled_ratio(123_456.into(), 33, 1.into()).unwrap();
+        assert_eq!(
+            price,
+            Price {
+                value: 123,
+                exp: MAX_PRICE_EXP
+            }
+        );
+    }
+
+    #[test]
+    fn test_cmp_with_far_exponents() {
+        let small = Price {
+            value: 2,
+            exp: MAX_PRICE_EXP,
+        };
+        let smaller = Price {
+            value: 1,
+            exp: MAX_PRICE_EXP,
+        };
+        // Both are zero once converted to a `Decimal`
+        assert_eq!(Decimal::from(small), Decimal::from(smaller));
+        assert!(smaller < small);
+        assert!(Price { value: 1, exp: 0 } > small);
+        assert!(
+            Price { value: 1, exp: 0 }
+                > Price {
+                    value: u64::MAX,
+                    exp: 40
+                }
+        );
+        assert_eq!(
+            Price { value: 0, exp: 0 }.cmp(&Price { value: 0, exp: 40 }),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn test_eq_with_far_exponents() {
+        let small = Price {
+            value: 1,
+            exp: MAX_PRICE_EXP,
+        };
+        assert_ne!(small, Price { value: 1, exp: 0 });
+        assert_eq!(Price { value: 0, exp: 0 }, Price { value: 0, exp: 25 });
+        assert_eq!(
+            Price { value: 5, exp: 1 },
+            Price {
+                value: 500_000_000_000_000_000,
+                exp: 18
+            }
+        );
+    }
 }
@@ -15,6 +15,7 @@ pub fn refresh_simple_oracle_ix(feed: &ScopeFeedDefinition, oracle: OracleConf)
     .to_account_metas(None);
 
     accounts.push(AccountMeta::new_readonly(oracle.pubkey, false));
+    accounts.push(AccountMeta::new_readonly(feed.conf, false));
 
     let args = scope::instruction::RefreshPriceList {
         tokens: vec![oracle.token.try_into().unwrap()],
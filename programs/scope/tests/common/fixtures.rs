@@ -81,6 +81,7 @@ pub async fn setup_mapping_for_token_with_twap(
         price_type: token_oracle.price_type.to_u8(),
         twap_enabled: token_oracle.twap_enabled,
         twap_source: token_oracle.twap_source.unwrap_or(u16::MAX),
+        generic_data: [0; 20],
     };
 
     let ix = Instruction {
@@ -104,6 +105,7 @@ pub async fn setup_mapping_for_token_with_twap(
         price_type: twap_oracle.price_type.to_u8(),
         twap_enabled: twap_oracle.twap_enabled,
         twap_source: twap_oracle.twap_source.unwrap_or(u16::MAX),
+        generic_data: [0; 20],
     };
 
     let ix = Instruction {
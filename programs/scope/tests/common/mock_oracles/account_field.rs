@@ -0,0 +1,42 @@
+use anchor_lang::{prelude::Pubkey, Discriminator};
+use scope::{AccountFieldReader, Price};
+
+use crate::common::types::{OracleConf, ScopeFeedDefinition};
+
+/// Discriminator of the accounts read by the account field test entries
+const READ_ACCOUNT_DISCRIMINATOR: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+/// Offset of the value field in the accounts read
+const VALUE_OFFSET: u32 = 8;
+
+/// Reader of the value field of an account, holding the price value with its exponent
+pub(super) fn get_account_field_accounts(
+    feed: &ScopeFeedDefinition,
+    conf: &OracleConf,
+    price: &Price,
+) -> super::PriceSourceAccounts {
+    let owner = Pubkey::new_unique();
+    let mut account_data = vec![0; 16];
+    account_data[..8].copy_from_slice(&READ_ACCOUNT_DISCRIMINATOR);
+    account_data[8..16].copy_from_slice(&price.value.to_le_bytes());
+    let account = super::add_acc(Pubkey::new_unique(), owner, account_data);
+
+    let mut reader: AccountFieldReader = bytemuck::Zeroable::zeroed();
+    reader.oracle_prices = feed.prices;
+    reader.index = u16::try_from(conf.token).unwrap();
+    reader.account = account.address;
+    reader.owner = owner;
+    reader.discriminator = READ_ACCOUNT_DISCRIMINATOR;
+    reader.value_offset = VALUE_OFFSET;
+    reader.value_size = 8;
+    reader.exp = u8::try_from(price.exp).unwrap();
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&AccountFieldReader::DISCRIMINATOR);
+    data.extend_from_slice(bytemuck::bytes_of(&reader));
+
+    super::PriceSourceAccounts {
+        oracle_data: data,
+        owner: scope::ID,
+        additional_accs: vec![account],
+    }
+}
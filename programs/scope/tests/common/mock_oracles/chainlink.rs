@@ -0,0 +1,27 @@
+use scope::oracles::chainlink::ocr2;
+use scope::Price;
+use solana_program::clock::Clock;
+
+const LIVE_LENGTH: u32 = 2;
+
+/// Transmissions account with a single round answered in the current slot
+pub(super) fn get_transmissions_accounts(
+    price: &Price,
+    clock: &Clock,
+) -> super::PriceSourceAccounts {
+    let mut data = vec![0_u8; ocr2::HEADER_LEN + LIVE_LENGTH as usize * ocr2::TRANSMISSION_LEN];
+    data[..8].copy_from_slice(&ocr2::TRANSMISSIONS_DISCRIMINATOR);
+    data[8] = 2; // version
+    data[138] = u8::try_from(price.exp).unwrap(); // decimals
+    data[143..147].copy_from_slice(&1_u32.to_le_bytes()); // latest_round_id
+    data[148..152].copy_from_slice(&LIVE_LENGTH.to_le_bytes()); // live_length
+    data[152..156].copy_from_slice(&1_u32.to_le_bytes()); // live_cursor
+
+    // First transmission of the live ring buffer
+    let round = &mut data[ocr2::HEADER_LEN..ocr2::HEADER_LEN + ocr2::TRANSMISSION_LEN];
+    round[..8].copy_from_slice(&clock.slot.to_le_bytes());
+    round[8..12].copy_from_slice(&u32::try_from(clock.unix_timestamp).unwrap().to_le_bytes());
+    round[16..32].copy_from_slice(&i128::from(price.value).to_le_bytes());
+
+    super::sp(data, ocr2::id())
+}
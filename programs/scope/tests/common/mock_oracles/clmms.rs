@@ -4,13 +4,15 @@ use anchor_spl::token::spl_token::state::Mint;
 use decimal_wad::common::WAD;
 use decimal_wad::decimal::Decimal;
 use raydium_amm_v3::libraries::U256;
-use raydium_amm_v3::states::PoolState as RaydiumPoolState;
+use raydium_amm_v3::states::{ObservationState, PoolState as RaydiumPoolState};
 use scope::utils::math::ten_pow;
 use scope::Price;
-use solana_program::program_pack::Pack;
+use solana_program::{clock::Clock, program_pack::Pack};
 use solana_sdk::pubkey;
 use whirlpool::state::Whirlpool;
 
+use crate::common::types::TEST_RAYDIUM_TWAP_WINDOW_S;
+
 const MINT_DECIMALS: u8 = 6;
 
 pub(super) fn get_orca_whirlpool_accounts(
@@ -65,6 +67,52 @@ pub(super) fn get_raydium_amm_v3_accounts(
     }
 }
 
+/// Raydium pool whose observations hold the given price over the whole TWAP window
+pub(super) fn get_raydium_amm_v3_twap_accounts(
+    pool_pk: &Pubkey,
+    price: &Price,
+    a_to_b: bool,
+    clock: &Clock,
+) -> super::PriceSourceAccounts {
+    let price_sqrt = price_to_sqrt_price(price, a_to_b, MINT_DECIMALS, MINT_DECIMALS);
+    let (observation_pk, _) =
+        Pubkey::find_program_address(&[b"observation", pool_pk.as_ref()], &raydium_amm_v3::id());
+
+    let pool = RaydiumPoolState {
+        mint_decimals_0: MINT_DECIMALS,
+        mint_decimals_1: MINT_DECIMALS,
+        sqrt_price_x64: price_sqrt,
+        observation_key: observation_pk,
+        observation_index: 0,
+        ..Default::default()
+    };
+    let mut pool_data = Vec::new();
+    pool_data.extend_from_slice(&RaydiumPoolState::DISCRIMINATOR);
+    pool_data.extend_from_slice(bytemuck::bytes_of(&pool));
+
+    // A single observation, older than the window
+    let mut observation_state: ObservationState = bytemuck::Zeroable::zeroed();
+    observation_state.pool_id = *pool_pk;
+    let mut observations = observation_state.observations;
+    observations[0].block_timestamp =
+        u32::try_from(clock.unix_timestamp).unwrap() - TEST_RAYDIUM_TWAP_WINDOW_S - 1;
+    observations[0].sqrt_price_x64 = price_sqrt;
+    observation_state.observations = observations;
+    let mut observation_data = Vec::new();
+    observation_data.extend_from_slice(&ObservationState::DISCRIMINATOR);
+    observation_data.extend_from_slice(bytemuck::bytes_of(&observation_state));
+
+    super::PriceSourceAccounts {
+        oracle_data: pool_data,
+        owner: raydium_amm_v3::id(),
+        additional_accs: vec![super::add_acc(
+            observation_pk,
+            raydium_amm_v3::id(),
+            observation_data,
+        )],
+    }
+}
+
 fn get_mint_acc(mint_pk: Pubkey) -> super::AdditionalAccount {
     let mint = Mint {
         supply: 10000000000,
@@ -101,9 +149,9 @@ pub fn decimal_price_to_sqrt_price(price: Decimal, decimals_a: u8, decimals_b: u
     let scaled_price_x64 = (price_u256 << 128) / WAD;
 
     let price = if decimals_b >= decimals_a {
-        scaled_price_x64 * U256::from(ten_pow(decimals_b - decimals_a))
+        scaled_price_x64 * U256::from(ten_pow(decimals_b - decimals_a).unwrap())
     } else {
-        scaled_price_x64 / U256::from(ten_pow(decimals_a - decimals_b))
+        scaled_price_x64 / U256::from(ten_pow(decimals_a - decimals_b).unwrap())
     };
     price.integer_sqrt().as_u128()
 }
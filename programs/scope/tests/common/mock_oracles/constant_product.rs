@@ -0,0 +1,207 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::token::spl_token::{
+    self,
+    state::{Account as TokenAccount, AccountState, Mint},
+};
+use scope::oracles::{
+    meteora_dynamic_amm::meteora_amm, orca_token_swap::orca_swap, raydium_amm_v4::raydium_amm,
+    raydium_cpmm::raydium_cp_swap,
+};
+use scope::Price;
+use solana_program::program_pack::Pack;
+
+use super::{add_acc, AdditionalAccount, PriceSourceAccounts};
+
+const MINT_DECIMALS: u8 = 6;
+
+/// Reserves (in lamports) of a pool of two tokens with [`MINT_DECIMALS`] giving the requested price
+fn price_to_reserves(price: &Price, a_to_b: bool) -> (u64, u64) {
+    let base_reserve = 10_u64.pow(u32::try_from(price.exp).unwrap()) * 1_000_000;
+    let quote_reserve = price.value * 1_000_000;
+    if a_to_b {
+        (base_reserve, quote_reserve)
+    } else {
+        (quote_reserve, base_reserve)
+    }
+}
+
+/// Raydium AMM v4 pool, token A is the "coin" and token B the "pc"
+pub(super) fn get_raydium_amm_v4_accounts(price: &Price, a_to_b: bool) -> PriceSourceAccounts {
+    let (reserve_a, reserve_b) = price_to_reserves(price, a_to_b);
+    let coin_vault = get_vault_acc(reserve_a);
+    let pc_vault = get_vault_acc(reserve_b);
+
+    let mut pool_data = vec![0; raydium_amm::AmmInfo::LEN];
+    write_u64(&mut pool_data, 32, MINT_DECIMALS.into()); // coin_decimals
+    write_u64(&mut pool_data, 40, MINT_DECIMALS.into()); // pc_decimals
+    write_pubkey(&mut pool_data, 336, &coin_vault.address);
+    write_pubkey(&mut pool_data, 368, &pc_vault.address);
+
+    PriceSourceAccounts {
+        oracle_data: pool_data,
+        owner: raydium_amm::id(),
+        additional_accs: vec![coin_vault, pc_vault],
+    }
+}
+
+/// Raydium CPMM pool without fees to collect
+pub(super) fn get_raydium_cpmm_accounts(price: &Price, a_to_b: bool) -> PriceSourceAccounts {
+    let (reserve_a, reserve_b) = price_to_reserves(price, a_to_b);
+    get_raydium_cpmm_pool_accounts(reserve_a, reserve_b, &Pubkey::new_unique())
+}
+
+/// LP token of a Raydium CPMM pool holding 1M of each token
+///
+/// The LP supply is set so that the LP token is worth the requested price when both tokens are
+/// worth 1 (the LP price scales with the geometric mean of the token prices).
+pub(super) fn get_constant_product_lp_accounts(price: &Price) -> PriceSourceAccounts {
+    let reserve = 1_000_000_000_000;
+    // Fair pool value is `2 * reserve`, in tokens
+    let lp_supply = if price.value == 0 {
+        0
+    } else {
+        u64::try_from(
+            2 * u128::from(reserve) * 10_u128.pow(u32::try_from(price.exp).unwrap())
+                / u128::from(price.value),
+        )
+        .unwrap()
+    };
+    let lp_mint = get_mint_acc(lp_supply);
+
+    let mut accounts = get_raydium_cpmm_pool_accounts(reserve, reserve, &lp_mint.address);
+    accounts.additional_accs.push(lp_mint);
+    accounts
+}
+
+fn get_raydium_cpmm_pool_accounts(
+    reserve_0: u64,
+    reserve_1: u64,
+    lp_mint: &Pubkey,
+) -> PriceSourceAccounts {
+    let token_0_vault = get_vault_acc(reserve_0);
+    let token_1_vault = get_vault_acc(reserve_1);
+
+    let mut pool_data = vec![0; raydium_cp_swap::PoolState::LEN];
+    pool_data[..8].copy_from_slice(&raydium_cp_swap::PoolState::DISCRIMINATOR);
+    write_pubkey(&mut pool_data, 72, &token_0_vault.address);
+    write_pubkey(&mut pool_data, 104, &token_1_vault.address);
+    write_pubkey(&mut pool_data, 136, lp_mint);
+    pool_data[331] = MINT_DECIMALS; // mint_0_decimals
+    pool_data[332] = MINT_DECIMALS; // mint_1_decimals
+
+    PriceSourceAccounts {
+        oracle_data: pool_data,
+        owner: raydium_cp_swap::id(),
+        additional_accs: vec![token_0_vault, token_1_vault],
+    }
+}
+
+/// Orca legacy constant-product pool
+pub(super) fn get_orca_token_swap_accounts(price: &Price, a_to_b: bool) -> PriceSourceAccounts {
+    let (reserve_a, reserve_b) = price_to_reserves(price, a_to_b);
+    let token_a_vault = get_vault_acc(reserve_a);
+    let token_b_vault = get_vault_acc(reserve_b);
+    let token_a_mint = get_mint_acc(0);
+    let token_b_mint = get_mint_acc(0);
+
+    let mut pool_data = vec![0; orca_swap::SwapV1::LEN];
+    pool_data[0] = orca_swap::SWAP_V1_VERSION;
+    pool_data[1] = 1; // is_initialized
+    write_pubkey(&mut pool_data, 35, &token_a_vault.address);
+    write_pubkey(&mut pool_data, 67, &token_b_vault.address);
+    write_pubkey(&mut pool_data, 131, &token_a_mint.address);
+    write_pubkey(&mut pool_data, 163, &token_b_mint.address);
+    pool_data[291] = orca_swap::CONSTANT_PRODUCT_CURVE;
+
+    PriceSourceAccounts {
+        oracle_data: pool_data,
+        owner: orca_swap::id(),
+        additional_accs: vec![token_a_vault, token_b_vault, token_a_mint, token_b_mint],
+    }
+}
+
+/// Meteora dynamic AMM constant-product pool owning all the LP tokens of its two vaults
+pub(super) fn get_meteora_dynamic_amm_accounts(price: &Price, a_to_b: bool) -> PriceSourceAccounts {
+    let (reserve_a, reserve_b) = price_to_reserves(price, a_to_b);
+    let token_a_mint = get_mint_acc(0);
+    let token_b_mint = get_mint_acc(0);
+    let [a_vault, a_vault_lp, a_vault_lp_mint] = get_meteora_vault_accs(reserve_a);
+    let [b_vault, b_vault_lp, b_vault_lp_mint] = get_meteora_vault_accs(reserve_b);
+
+    // Constant-product curve type is 0
+    let mut pool_data = vec![0; 875];
+    pool_data[..8].copy_from_slice(&meteora_amm::Pool::DISCRIMINATOR);
+    write_pubkey(&mut pool_data, 40, &token_a_mint.address);
+    write_pubkey(&mut pool_data, 72, &token_b_mint.address);
+    write_pubkey(&mut pool_data, 104, &a_vault.address);
+    write_pubkey(&mut pool_data, 136, &b_vault.address);
+    write_pubkey(&mut pool_data, 168, &a_vault_lp.address);
+    write_pubkey(&mut pool_data, 200, &b_vault_lp.address);
+
+    PriceSourceAccounts {
+        oracle_data: pool_data,
+        owner: meteora_amm::id(),
+        additional_accs: vec![
+            token_a_mint,
+            token_b_mint,
+            a_vault,
+            b_vault,
+            a_vault_lp,
+            b_vault_lp,
+            a_vault_lp_mint,
+            b_vault_lp_mint,
+        ],
+    }
+}
+
+/// Meteora vault without locked profit, its pool LP token account and its LP mint
+fn get_meteora_vault_accs(total_amount: u64) -> [AdditionalAccount; 3] {
+    let lp_supply = 1_000_000_000;
+    let vault_lp = get_vault_acc(lp_supply);
+    let vault_lp_mint = get_mint_acc(lp_supply);
+
+    let mut vault_data = vec![0; 1227];
+    vault_data[..8].copy_from_slice(&meteora_amm::Vault::DISCRIMINATOR);
+    write_u64(&mut vault_data, 11, total_amount);
+    write_pubkey(&mut vault_data, 115, &vault_lp_mint.address);
+    let vault = add_acc(
+        Pubkey::new_unique(),
+        meteora_amm::vault_program::id(),
+        vault_data,
+    );
+
+    [vault, vault_lp, vault_lp_mint]
+}
+
+fn get_vault_acc(amount: u64) -> AdditionalAccount {
+    let vault = TokenAccount {
+        mint: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        amount,
+        state: AccountState::Initialized,
+        ..Default::default()
+    };
+    let mut vault_data = [0; TokenAccount::LEN];
+    vault.pack_into_slice(&mut vault_data);
+    add_acc(Pubkey::new_unique(), spl_token::id(), vault_data.to_vec())
+}
+
+fn get_mint_acc(supply: u64) -> AdditionalAccount {
+    let mint = Mint {
+        supply,
+        decimals: MINT_DECIMALS,
+        is_initialized: true,
+        ..Default::default()
+    };
+    let mut mint_data = [0; Mint::LEN];
+    mint.pack_into_slice(&mut mint_data);
+    add_acc(Pubkey::new_unique(), spl_token::id(), mint_data.to_vec())
+}
+
+fn write_u64(data: &mut [u8], offset: usize, value: u64) {
+    data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+}
+
+fn write_pubkey(data: &mut [u8], offset: usize, pubkey: &Pubkey) {
+    data[offset..offset + 32].copy_from_slice(pubkey.as_ref());
+}
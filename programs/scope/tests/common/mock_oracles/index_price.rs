@@ -0,0 +1,25 @@
+use anchor_lang::Discriminator;
+use scope::IndexDefinition;
+
+use crate::common::types::{OracleConf, ScopeFeedDefinition};
+
+/// Index definition averaging the prices of two entries of the feed
+pub(super) fn get_index_definition_accounts(
+    feed: &ScopeFeedDefinition,
+    conf: &OracleConf,
+    first_entry: u16,
+    second_entry: u16,
+) -> super::PriceSourceAccounts {
+    let mut index_definition: IndexDefinition = bytemuck::Zeroable::zeroed();
+    index_definition.oracle_prices = feed.prices;
+    index_definition.index = u16::try_from(conf.token).unwrap();
+    index_definition
+        .set_components(&[first_entry, second_entry], &[1, 1], 0, 2)
+        .unwrap();
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&IndexDefinition::DISCRIMINATOR);
+    data.extend_from_slice(bytemuck::bytes_of(&index_definition));
+
+    super::sp(data, scope::ID)
+}
@@ -5,11 +5,19 @@ use scope::Price;
 use super::types::{OracleConf, TestContext};
 use crate::common::types::{ScopeFeedDefinition, TestOracleType};
 
+mod account_field;
+mod chainlink;
 mod clmms;
+mod constant_product;
+mod index_price;
 mod jupiter_lp;
+mod klend;
 #[cfg(feature = "yvaults")]
 mod ktoken;
+mod orderbooks;
 mod pyth;
+pub mod signed_price;
+mod solend;
 mod spl_stake;
 mod switchboard_v2;
 
@@ -60,6 +68,47 @@ pub async fn set_price(
         }
         TestOracleType::OrcaWhirlpool(a_to_b) => clmms::get_orca_whirlpool_accounts(price, a_to_b),
         TestOracleType::RaydiumAmmV3(a_to_b) => clmms::get_raydium_amm_v3_accounts(price, a_to_b),
+        TestOracleType::RaydiumAmmV3Twap(a_to_b) => {
+            clmms::get_raydium_amm_v3_twap_accounts(&conf.pubkey, price, a_to_b, &clock)
+        }
+        TestOracleType::RaydiumAmmV4(a_to_b) => {
+            constant_product::get_raydium_amm_v4_accounts(price, a_to_b)
+        }
+        TestOracleType::RaydiumCpmm(a_to_b) => {
+            constant_product::get_raydium_cpmm_accounts(price, a_to_b)
+        }
+        TestOracleType::OrcaTokenSwap(a_to_b) => {
+            constant_product::get_orca_token_swap_accounts(price, a_to_b)
+        }
+        TestOracleType::MeteoraDynamicAmm(a_to_b) => {
+            constant_product::get_meteora_dynamic_amm_accounts(price, a_to_b)
+        }
+        TestOracleType::ConstantProductLpToken(_, _) => {
+            constant_product::get_constant_product_lp_accounts(price)
+        }
+        TestOracleType::KLendCToken => klend::get_reserve_accounts(price, &clock),
+        TestOracleType::SolendBorrowRate | TestOracleType::SolendSupplyRate => {
+            solend::get_reserve_accounts(price, &clock)
+        }
+        TestOracleType::Chainlink => chainlink::get_transmissions_accounts(price, &clock),
+        TestOracleType::OpenBookV2MidPrice => orderbooks::get_openbook_v2_accounts(price),
+        TestOracleType::PhoenixMidPrice => orderbooks::get_phoenix_accounts(price),
+        TestOracleType::SignedPrice => signed_price::get_publishers_accounts(feed, conf),
+        TestOracleType::AccountField => {
+            account_field::get_account_field_accounts(feed, conf, price)
+        }
+        TestOracleType::IndexPrice(first_entry, second_entry) => {
+            index_price::get_index_definition_accounts(feed, conf, first_entry, second_entry)
+        }
+        TestOracleType::FixedRate
+        | TestOracleType::CappedFloored(_)
+        | TestOracleType::MinOfTwo(_, _)
+        | TestOracleType::MaxOfTwo(_, _)
+        | TestOracleType::InversePrice(_)
+        | TestOracleType::TwapStats(_, _) => {
+            // Computed from the feed, there is no price account to mock
+            return;
+        }
     };
     additional_accs.into_iter().for_each(|a| {
         let AdditionalAccount {
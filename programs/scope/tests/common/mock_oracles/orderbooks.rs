@@ -0,0 +1,83 @@
+use scope::oracles::{openbook_v2::openbook, phoenix::phoenix_v1};
+use scope::Price;
+
+/// Upstream SOL/USDC market, 1 lot of price is 0.001 USDC per SOL
+const OPENBOOK_SOL_USDC_MARKET: &[u8] =
+    include_bytes!("../../fixtures/openbook_v2_sol_usdc_market.bin");
+
+/// Price in 0.001 increments of the best bid and best ask around the given mid-price
+///
+/// The book is empty for a null price.
+fn best_bid_ask(price: &Price) -> Option<(u64, u64)> {
+    let price_ticks = u64::try_from(
+        u128::from(price.value) * 1000 / 10_u128.pow(u32::try_from(price.exp).unwrap()),
+    )
+    .unwrap();
+    (price_ticks > 1).then_some((price_ticks - 1, price_ticks + 1))
+}
+
+/// OpenBook v2 market with a single order on each side of the book
+pub(super) fn get_openbook_v2_accounts(price: &Price) -> super::PriceSourceAccounts {
+    let market = openbook::Market::unpack(OPENBOOK_SOL_USDC_MARKET).unwrap();
+    let (bids, asks) = match best_bid_ask(price) {
+        Some((bid, ask)) => (
+            get_openbook_book_side(Some(bid)),
+            get_openbook_book_side(Some(ask)),
+        ),
+        None => (get_openbook_book_side(None), get_openbook_book_side(None)),
+    };
+
+    super::PriceSourceAccounts {
+        oracle_data: OPENBOOK_SOL_USDC_MARKET.to_vec(),
+        owner: openbook::id(),
+        additional_accs: vec![
+            super::add_acc(market.bids, openbook::id(), bids),
+            super::add_acc(market.asks, openbook::id(), asks),
+        ],
+    }
+}
+
+/// Book side whose fixed-price tree is a single leaf (the root) at the given price, if any
+fn get_openbook_book_side(price_lots: Option<u64>) -> Vec<u8> {
+    let mut data = vec![0_u8; 840 + openbook::MAX_ORDERTREE_NODES * 88];
+    data[..8].copy_from_slice(&openbook::BOOK_SIDE_DISCRIMINATOR);
+    if let Some(price_lots) = price_lots {
+        // Root at index 0 and 1 leaf
+        data[12..16].copy_from_slice(&1_u32.to_le_bytes());
+        let leaf = &mut data[840..840 + 88];
+        leaf[0] = 2; // leaf tag
+        leaf[8..24].copy_from_slice(&(u128::from(price_lots) << 64).to_le_bytes());
+        leaf[56..64].copy_from_slice(&1_000_i64.to_le_bytes()); // quantity
+    }
+    data
+}
+
+/// Phoenix SOL/USDC market with a single order on each side of the book
+pub(super) fn get_phoenix_accounts(price: &Price) -> super::PriceSourceAccounts {
+    const TREE_SIZE: usize = 1;
+    const TREE_LEN: usize = 32 + TREE_SIZE * 64;
+    const BIDS_TREE_OFFSET: usize = 880;
+    const ASKS_TREE_OFFSET: usize = BIDS_TREE_OFFSET + TREE_LEN;
+
+    let mut data = vec![0_u8; ASKS_TREE_OFFSET + TREE_LEN];
+    data[..8].copy_from_slice(&phoenix_v1::MARKET_DISCRIMINATOR);
+    data[8..16].copy_from_slice(&1_u64.to_le_bytes()); // active status
+    data[16..24].copy_from_slice(&(TREE_SIZE as u64).to_le_bytes()); // bids_size
+    data[24..32].copy_from_slice(&(TREE_SIZE as u64).to_le_bytes()); // asks_size
+    data[112..120].copy_from_slice(&1_000_000_u64.to_le_bytes()); // base_lot_size
+    data[120..124].copy_from_slice(&6_u32.to_le_bytes()); // quote_decimals
+    data[200..208].copy_from_slice(&1_000_u64.to_le_bytes()); // tick size
+    data[312..316].copy_from_slice(&1_u32.to_le_bytes()); // raw_base_units_per_base_unit
+
+    if let Some((bid, ask)) = best_bid_ask(price) {
+        for (tree_offset, price_in_ticks) in [(BIDS_TREE_OFFSET, bid), (ASKS_TREE_OFFSET, ask)] {
+            // Root is the node at address 1, without children
+            data[tree_offset..tree_offset + 4].copy_from_slice(&1_u32.to_le_bytes());
+            let node = &mut data[tree_offset + 32..tree_offset + 32 + 64];
+            node[16..24].copy_from_slice(&price_in_ticks.to_le_bytes());
+            node[40..48].copy_from_slice(&1_000_u64.to_le_bytes()); // num_base_lots
+        }
+    }
+
+    super::sp(data, phoenix_v1::id())
+}
@@ -0,0 +1,84 @@
+use anchor_lang::Discriminator;
+use scope::{
+    oracles::signed_price::{signed_price_message, SignedPrice},
+    PricePublishers,
+};
+use solana_program::{ed25519_program, instruction::Instruction};
+use solana_sdk::{
+    signature::Keypair,
+    signer::{keypair::keypair_from_seed, Signer},
+};
+
+use crate::common::types::{OracleConf, ScopeFeedDefinition};
+
+/// Instruction index meaning "the ed25519 instruction itself"
+const CURRENT_INSTRUCTION_INDEX: u16 = u16::MAX;
+
+/// Publisher whitelisted in the publishers account of the signed price test entries
+pub fn test_price_publisher() -> Keypair {
+    keypair_from_seed(&[42; 32]).unwrap()
+}
+
+/// Publishers account whitelisting [`test_price_publisher`]
+///
+/// The price itself is not stored on chain but signed in the refresh transaction, see
+/// [`get_signed_price_ix`].
+pub(super) fn get_publishers_accounts(
+    feed: &ScopeFeedDefinition,
+    conf: &OracleConf,
+) -> super::PriceSourceAccounts {
+    let mut publishers: PricePublishers = bytemuck::Zeroable::zeroed();
+    publishers.oracle_prices = feed.prices;
+    publishers.index = u16::try_from(conf.token).unwrap();
+    publishers
+        .set_publishers(&[test_price_publisher().pubkey()])
+        .unwrap();
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&PricePublishers::DISCRIMINATOR);
+    data.extend_from_slice(bytemuck::bytes_of(&publishers));
+
+    super::sp(data, scope::ID)
+}
+
+/// Ed25519 program instruction verifying the signature of `signed_price` by `publisher`
+pub fn get_signed_price_ix(publisher: &Keypair, signed_price: &SignedPrice) -> Instruction {
+    let message = signed_price_message(signed_price);
+    let signature = publisher.sign_message(&message);
+
+    let public_key_offset: u16 = 16;
+    let signature_offset: u16 = public_key_offset + 32;
+    let message_data_offset: u16 = signature_offset + 64;
+    let mut data = vec![1, 0];
+    for value in [
+        signature_offset,
+        CURRENT_INSTRUCTION_INDEX,
+        public_key_offset,
+        CURRENT_INSTRUCTION_INDEX,
+        message_data_offset,
+        u16::try_from(message.len()).unwrap(),
+        CURRENT_INSTRUCTION_INDEX,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(publisher.pubkey().as_ref());
+    data.extend_from_slice(signature.as_ref());
+    data.extend_from_slice(&message);
+
+    Instruction::new_with_bytes(ed25519_program::id(), &data, vec![])
+}
+
+/// Message for the entry of `conf` in `feed`
+pub fn get_signed_price(
+    feed: &ScopeFeedDefinition,
+    conf: &OracleConf,
+    price: scope::Price,
+    unix_timestamp: u64,
+) -> SignedPrice {
+    SignedPrice {
+        oracle_prices: feed.prices,
+        index: u16::try_from(conf.token).unwrap(),
+        price,
+        unix_timestamp,
+    }
+}
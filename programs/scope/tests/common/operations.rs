@@ -1,6 +1,8 @@
 use anchor_lang::{InstructionData, ToAccountMetas};
 use solana_program::{
-    instruction::Instruction, pubkey::Pubkey, sysvar::instructions::ID as SYSVAR_INSTRUCTIONS_ID,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    sysvar::instructions::ID as SYSVAR_INSTRUCTIONS_ID,
 };
 use solana_program_test::BanksClientError;
 use solana_sdk::signature::{Keypair, Signer};
@@ -16,6 +18,15 @@ pub async fn update_oracle_mapping(
     feed: &types::ScopeFeedDefinition,
     conf: &OracleConf,
 ) {
+    let ix = update_oracle_mapping_ix(ctx, feed, conf);
+    ctx.send_transaction(&[ix]).await.unwrap();
+}
+
+pub fn update_oracle_mapping_ix(
+    ctx: &TestContext,
+    feed: &types::ScopeFeedDefinition,
+    conf: &OracleConf,
+) -> Instruction {
     let accounts = scope::accounts::UpdateOracleMapping {
         admin: ctx.admin.pubkey(),
         configuration: feed.conf,
@@ -28,13 +39,13 @@ pub async fn update_oracle_mapping(
         price_type: conf.price_type.to_u8(),
         twap_enabled: conf.twap_enabled,
         twap_source: conf.twap_source.unwrap_or(u16::MAX),
+        generic_data: conf.price_type.generic_data(),
     };
-    let ix = Instruction {
+    Instruction {
         program_id: scope::id(),
         accounts: accounts.to_account_metas(None),
         data: args.data(),
-    };
-    ctx.send_transaction(&[ix]).await.unwrap();
+    }
 }
 
 pub async fn refresh_price(
@@ -51,6 +62,7 @@ pub async fn refresh_price(
     .to_account_metas(None);
     let mut refresh_accounts = utils::get_refresh_list_accounts(ctx, conf).await;
     accounts.append(&mut refresh_accounts);
+    accounts.push(AccountMeta::new_readonly(feed.conf, false));
 
     let args = scope::instruction::RefreshPriceList {
         tokens: vec![conf.token.try_into().unwrap()],
@@ -109,3 +121,187 @@ pub async fn approve_admin_cached(
     };
     ctx.send_transaction_with_payer(&[ix], admin_cached).await
 }
+
+pub async fn close_feed(
+    ctx: &mut TestContext,
+    feed: &types::ScopeFeedDefinition,
+    tokens_metadata: &Pubkey,
+) -> Result<(), BanksClientError> {
+    let accounts = scope::accounts::CloseFeed {
+        admin: ctx.admin.pubkey(),
+        configuration: feed.conf,
+        oracle_prices: feed.prices,
+        oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        tokens_metadata: *tokens_metadata,
+    }
+    .to_account_metas(None);
+
+    let args = scope::instruction::CloseFeed {
+        feed_name: feed.feed_name.clone(),
+    };
+
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+    ctx.send_transaction(&[ix]).await
+}
+
+pub async fn close_entry_accounts(
+    ctx: &mut TestContext,
+    feed: &types::ScopeFeedDefinition,
+    entry_accounts: &[Pubkey],
+) -> Result<(), BanksClientError> {
+    let mut accounts = scope::accounts::CloseEntryAccounts {
+        admin: ctx.admin.pubkey(),
+        configuration: feed.conf,
+        oracle_prices: feed.prices,
+        oracle_mappings: feed.mapping,
+    }
+    .to_account_metas(None);
+    accounts.extend(
+        entry_accounts
+            .iter()
+            .map(|entry_account| AccountMeta::new(*entry_account, false)),
+    );
+
+    let args = scope::instruction::CloseEntryAccounts {
+        feed_name: feed.feed_name.clone(),
+    };
+
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts,
+        data: args.data(),
+    };
+    ctx.send_transaction(&[ix]).await
+}
+
+pub async fn migrate(
+    ctx: &mut TestContext,
+    feed: &types::ScopeFeedDefinition,
+    tokens_metadata: &Pubkey,
+) -> Result<(), BanksClientError> {
+    let accounts = scope::accounts::Migrate {
+        admin: ctx.admin.pubkey(),
+        configuration: feed.conf,
+        oracle_prices: feed.prices,
+        oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        tokens_metadata: *tokens_metadata,
+        system_program: solana_program::system_program::id(),
+    }
+    .to_account_metas(None);
+
+    let args = scope::instruction::Migrate {
+        feed_name: feed.feed_name.clone(),
+    };
+
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+    ctx.send_transaction(&[ix]).await
+}
+
+pub async fn update_refresh_allowed_programs(
+    ctx: &mut TestContext,
+    feed: &types::ScopeFeedDefinition,
+    programs: Vec<Pubkey>,
+) -> Result<(), BanksClientError> {
+    let accounts = scope::accounts::UpdateRefreshAllowedPrograms {
+        admin: ctx.admin.pubkey(),
+        configuration: feed.conf,
+    };
+
+    let args = scope::instruction::UpdateRefreshAllowedPrograms {
+        feed_name: feed.feed_name.clone(),
+        programs,
+    };
+
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+    ctx.send_transaction(&[ix]).await
+}
+
+pub fn price_history_pda(feed: &types::ScopeFeedDefinition, token: usize) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"history",
+            feed.prices.as_ref(),
+            &(token as u64).to_le_bytes(),
+        ],
+        &scope::id(),
+    )
+    .0
+}
+
+pub async fn init_price_history(
+    ctx: &mut TestContext,
+    feed: &types::ScopeFeedDefinition,
+    token: usize,
+) -> Result<(), BanksClientError> {
+    let accounts = scope::accounts::InitPriceHistory {
+        admin: ctx.admin.pubkey(),
+        configuration: feed.conf,
+        oracle_prices: feed.prices,
+        oracle_mappings: feed.mapping,
+        price_history: price_history_pda(feed, token),
+        system_program: solana_program::system_program::id(),
+    };
+
+    let args = scope::instruction::InitPriceHistory {
+        token: token as u64,
+        feed_name: feed.feed_name.clone(),
+    };
+
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+    ctx.send_transaction(&[ix]).await
+}
+
+pub async fn update_price_history(
+    ctx: &mut TestContext,
+    feed: &types::ScopeFeedDefinition,
+    token: usize,
+    enabled: bool,
+) -> Result<(), BanksClientError> {
+    let ix = update_price_history_ix(ctx, feed, token, enabled);
+    ctx.send_transaction(&[ix]).await
+}
+
+pub fn update_price_history_ix(
+    ctx: &TestContext,
+    feed: &types::ScopeFeedDefinition,
+    token: usize,
+    enabled: bool,
+) -> Instruction {
+    let accounts = scope::accounts::UpdatePriceHistory {
+        admin: ctx.admin.pubkey(),
+        configuration: feed.conf,
+        oracle_prices: feed.prices,
+        oracle_mappings: feed.mapping,
+        price_history: price_history_pda(feed, token),
+    };
+
+    let args = scope::instruction::UpdatePriceHistory {
+        token: token as u64,
+        feed_name: feed.feed_name.clone(),
+        enabled,
+    };
+
+    Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
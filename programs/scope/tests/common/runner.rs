@@ -14,6 +14,7 @@ use solana_sdk::{
     signer::Signer,
     signers::Signers,
     transaction::Transaction,
+    transaction_context::TransactionReturnData,
 };
 use types::*;
 
@@ -250,6 +251,25 @@ impl TestContext {
         self.context.banks_client.process_transaction(tx).await
     }
 
+    pub async fn send_transaction_with_bot_and_get_return_data(
+        &mut self,
+        ixs: &[Instruction],
+    ) -> Result<Option<TransactionReturnData>, BanksClientError> {
+        let tx = Transaction::new_signed_with_payer(
+            ixs,
+            Some(&self.bot.pubkey()),
+            &[&self.bot],
+            self.context.get_new_latest_blockhash().await?,
+        );
+        let res = self
+            .context
+            .banks_client
+            .process_transaction_with_metadata(tx)
+            .await?;
+        res.result.map_err(BanksClientError::TransactionError)?;
+        Ok(res.metadata.and_then(|metadata| metadata.return_data))
+    }
+
     pub async fn send_transaction_with_payer(
         &mut self,
         ixs: &[Instruction],
@@ -1,5 +1,8 @@
 use anchor_lang::prelude::{Pubkey, Rent};
-use scope::oracles::OracleType;
+use scope::{
+    oracles::{fixed_rate::FixedRateConfig, twap_stats::TwapStat, OracleType},
+    Price, GENERIC_DATA_LEN,
+};
 use solana_program_test::{BanksClientError, ProgramTestContext};
 use solana_sdk::signature::Keypair;
 use thiserror::Error;
@@ -50,13 +53,120 @@ pub enum TestOracleType {
     OrcaWhirlpool(bool),
     /// Raydium's AMM v3 price (CLMM) (bool: A to B)
     RaydiumAmmV3(bool),
+    /// Raydium's AMM v3 TWAP from the pool observations (CLMM) (bool: A to B)
+    RaydiumAmmV3Twap(bool),
+    /// Raydium's AMM v4 price (constant product) (bool: A to B)
+    RaydiumAmmV4(bool),
+    /// Raydium's CPMM price (constant product) (bool: A to B)
+    RaydiumCpmm(bool),
+    /// Orca's legacy token-swap price (constant product) (bool: A to B)
+    OrcaTokenSwap(bool),
+    /// Meteora's dynamic AMM price (constant product) (bool: A to B)
+    MeteoraDynamicAmm(bool),
+    /// LP token of a constant-product pool (token A and token B price entries)
+    ConstantProductLpToken(u16, u16),
+    /// Kamino Lending's cTokens
+    KLendCToken,
+    /// Solend's reserve borrow APR
+    SolendBorrowRate,
+    /// Solend's reserve supply APR
+    SolendSupplyRate,
+    /// Chainlink's OCR2 feed
+    Chainlink,
+    /// OpenBook v2 market mid-price
+    OpenBookV2MidPrice,
+    /// Phoenix market mid-price
+    PhoenixMidPrice,
+    /// Price signed by a whitelisted publisher
+    SignedPrice,
+    /// Price accruing at a fixed rate from [`TEST_FIXED_RATE_START_PRICE`]
+    FixedRate,
+    /// Price of another entry clamped to [`TEST_PRICE_FLOOR`] and [`TEST_PRICE_CAP`] (source index)
+    CappedFloored(u16),
+    /// Lower price of two entries (entry indexes)
+    MinOfTwo(u16, u16),
+    /// Higher price of two entries (entry indexes)
+    MaxOfTwo(u16, u16),
+    /// Average of the prices of two entries (entry indexes)
+    IndexPrice(u16, u16),
+    /// Inverse of the price of another entry (source index)
+    InversePrice(u16),
+    /// Integer field of an account
+    AccountField,
+    /// Statistic of the EMA TWAP of another entry (source index, statistic)
+    TwapStats(u16, TwapStat),
 }
 
+/// TWAP window of the `RaydiumAmmV3Twap` test entries
+pub const TEST_RAYDIUM_TWAP_WINDOW_S: u32 = 600;
+
+/// Maximum spread of the orderbook test entries, which also store the best bid and ask
+pub const TEST_ORDERBOOK_MAX_SPREAD_BPS: u16 = 100;
+
+/// Start price of the `FixedRate` test entries, mapped without accrual
+pub const TEST_FIXED_RATE_START_PRICE: Price = Price {
+    value: 100_000_000,
+    exp: 8,
+};
+
+/// Floor of the `CappedFloored` test entries
+pub const TEST_PRICE_FLOOR: Price = Price { value: 95, exp: 2 };
+
+/// Cap of the `CappedFloored` test entries, with the same exponent as the floor
+pub const TEST_PRICE_CAP: Price = Price { value: 105, exp: 2 };
+
 impl TestOracleType {
     pub fn to_u8(self) -> u8 {
         let oracle_type: OracleType = self.into();
         oracle_type.into()
     }
+
+    /// Generic data of the mapping of the entry
+    pub fn generic_data(self) -> [u8; GENERIC_DATA_LEN] {
+        let mut generic_data = [0; GENERIC_DATA_LEN];
+        match self {
+            TestOracleType::RaydiumAmmV3Twap(_) => {
+                generic_data[..4].copy_from_slice(&TEST_RAYDIUM_TWAP_WINDOW_S.to_le_bytes());
+            }
+            TestOracleType::ConstantProductLpToken(price_a_index, price_b_index) => {
+                generic_data[..2].copy_from_slice(&price_a_index.to_le_bytes());
+                generic_data[2..4].copy_from_slice(&price_b_index.to_le_bytes());
+            }
+            TestOracleType::OpenBookV2MidPrice | TestOracleType::PhoenixMidPrice => {
+                generic_data[8..10].copy_from_slice(&TEST_ORDERBOOK_MAX_SPREAD_BPS.to_le_bytes());
+                generic_data[10] = 1;
+            }
+            TestOracleType::FixedRate => {
+                generic_data = FixedRateConfig {
+                    start_price: TEST_FIXED_RATE_START_PRICE,
+                    start_timestamp: 0,
+                    apy_bps: 0,
+                }
+                .to_generic_data()
+                .unwrap();
+            }
+            TestOracleType::CappedFloored(source_index) => {
+                generic_data[..2].copy_from_slice(&source_index.to_le_bytes());
+                generic_data[2..10].copy_from_slice(&TEST_PRICE_FLOOR.value.to_le_bytes());
+                generic_data[10..18].copy_from_slice(&TEST_PRICE_CAP.value.to_le_bytes());
+                generic_data[18] = TEST_PRICE_FLOOR.exp.try_into().unwrap();
+            }
+            TestOracleType::MinOfTwo(first_index, second_index)
+            | TestOracleType::MaxOfTwo(first_index, second_index) => {
+                generic_data[..2].copy_from_slice(&first_index.to_le_bytes());
+                generic_data[2..4].copy_from_slice(&second_index.to_le_bytes());
+            }
+            TestOracleType::InversePrice(source_index) => {
+                generic_data[..2].copy_from_slice(&source_index.to_le_bytes());
+            }
+            TestOracleType::TwapStats(source_index, stat) => {
+                generic_data[..2].copy_from_slice(&source_index.to_le_bytes());
+                generic_data[2] = stat.into();
+            }
+            _ => {}
+        }
+        generic_data
+    }
 }
 
 impl From<TestOracleType> for OracleType {
@@ -85,6 +195,57 @@ impl From<TestOracleType> for OracleType {
                     OracleType::RaydiumAmmV3BtoA
                 }
             }
+            TestOracleType::RaydiumAmmV3Twap(dir) => {
+                if dir {
+                    OracleType::RaydiumAmmV3TwapAtoB
+                } else {
+                    OracleType::RaydiumAmmV3TwapBtoA
+                }
+            }
+            TestOracleType::RaydiumAmmV4(dir) => {
+                if dir {
+                    OracleType::RaydiumAmmV4AtoB
+                } else {
+                    OracleType::RaydiumAmmV4BtoA
+                }
+            }
+            TestOracleType::RaydiumCpmm(dir) => {
+                if dir {
+                    OracleType::RaydiumCpmmAtoB
+                } else {
+                    OracleType::RaydiumCpmmBtoA
+                }
+            }
+            TestOracleType::OrcaTokenSwap(dir) => {
+                if dir {
+                    OracleType::OrcaTokenSwapAtoB
+                } else {
+                    OracleType::OrcaTokenSwapBtoA
+                }
+            }
+            TestOracleType::MeteoraDynamicAmm(dir) => {
+                if dir {
+                    OracleType::MeteoraDynamicAmmAtoB
+                } else {
+                    OracleType::MeteoraDynamicAmmBtoA
+                }
+            }
+            TestOracleType::ConstantProductLpToken(_, _) => OracleType::ConstantProductLpToken,
+            TestOracleType::KLendCToken => OracleType::KLendCToken,
+            TestOracleType::SolendBorrowRate => OracleType::SolendBorrowRate,
+            TestOracleType::SolendSupplyRate => OracleType::SolendSupplyRate,
+            TestOracleType::Chainlink => OracleType::Chainlink,
+            TestOracleType::OpenBookV2MidPrice => OracleType::OpenBookV2MidPrice,
+            TestOracleType::PhoenixMidPrice => OracleType::PhoenixMidPrice,
+            TestOracleType::SignedPrice => OracleType::SignedPrice,
+            TestOracleType::FixedRate => OracleType::FixedRate,
+            TestOracleType::CappedFloored(_) => OracleType::CappedFloored,
+            TestOracleType::MinOfTwo(_, _) => OracleType::MinOfTwo,
+            TestOracleType::MaxOfTwo(_, _) => OracleType::MaxOfTwo,
+            TestOracleType::IndexPrice(_, _) => OracleType::IndexPrice,
+            TestOracleType::InversePrice(_) => OracleType::InversePrice,
+            TestOracleType::AccountField => OracleType::AccountField,
+            TestOracleType::TwapStats(_, _) => OracleType::TwapStats,
         }
     }
 }
@@ -2,7 +2,11 @@ use std::fmt::Debug;
 
 use num_enum::TryFromPrimitive;
 use scope::oracles::OracleType;
-use solana_program::instruction::{AccountMeta, InstructionError};
+use solana_program::{
+    instruction::{AccountMeta, InstructionError},
+    pubkey::Pubkey,
+    sysvar::instructions::ID as SYSVAR_INSTRUCTIONS_ID,
+};
 use solana_program_test::BanksClientError;
 use solana_sdk::transaction::TransactionError;
 
@@ -39,12 +43,33 @@ pub async fn get_remaining_accounts(ctx: &mut TestContext, conf: &OracleConf) ->
         | OracleType::PythEMA
         | OracleType::MsolStake
         | OracleType::ScopeTwap
+        | OracleType::FixedRate
+        | OracleType::CappedFloored
+        | OracleType::MinOfTwo
+        | OracleType::MaxOfTwo
+        | OracleType::IndexPrice
+        | OracleType::InversePrice
+        | OracleType::TwapStats
+        | OracleType::KLendCToken
+        | OracleType::SolendBorrowRate
+        | OracleType::SolendSupplyRate
+        | OracleType::Chainlink
+        | OracleType::PhoenixMidPrice
         | OracleType::RaydiumAmmV3AtoB
         | OracleType::RaydiumAmmV3BtoA => {}
         OracleType::JupiterLpFetch => {
             accounts.extend_from_slice(&get_jlp_fetch_remaining_accounts(conf))
         }
         OracleType::CToken => panic!("CToken is not supported in tests"),
+        OracleType::OpenBookV2MidPrice => {
+            accounts.extend_from_slice(&get_openbook_v2_remaining_accounts(ctx, conf).await)
+        }
+        OracleType::SignedPrice => {
+            accounts.push(AccountMeta::new_readonly(SYSVAR_INSTRUCTIONS_ID, false))
+        }
+        OracleType::AccountField => {
+            accounts.extend_from_slice(&get_account_field_remaining_accounts(ctx, conf).await)
+        }
         OracleType::OrcaWhirlpoolAtoB | OracleType::OrcaWhirlpoolBtoA => {
             accounts.extend_from_slice(&get_orca_whirlpool_remaining_accounts(ctx, conf).await)
         }
@@ -54,6 +79,27 @@ pub async fn get_remaining_accounts(ctx: &mut TestContext, conf: &OracleConf) ->
         OracleType::MeteoraDlmmAtoB | OracleType::MeteoraDlmmBtoA => {
             unimplemented!("MeteoraDlmm is not yet supported in tests")
         }
+        OracleType::RaydiumAmmV3TwapAtoB | OracleType::RaydiumAmmV3TwapBtoA => {
+            accounts.extend_from_slice(&get_raydium_twap_remaining_accounts(ctx, conf).await)
+        }
+        OracleType::RaydiumAmmV4AtoB
+        | OracleType::RaydiumAmmV4BtoA
+        | OracleType::RaydiumCpmmAtoB
+        | OracleType::RaydiumCpmmBtoA
+        | OracleType::OrcaTokenSwapAtoB
+        | OracleType::OrcaTokenSwapBtoA
+        | OracleType::MeteoraDynamicAmmAtoB
+        | OracleType::MeteoraDynamicAmmBtoA => {
+            let (mut pool_accounts, _) =
+                get_constant_product_remaining_accounts(ctx, &conf.pubkey).await;
+            accounts.append(&mut pool_accounts)
+        }
+        OracleType::ConstantProductLpToken => {
+            let (mut pool_accounts, lp_mint) =
+                get_constant_product_remaining_accounts(ctx, &conf.pubkey).await;
+            accounts.append(&mut pool_accounts);
+            accounts.push(AccountMeta::new_readonly(lp_mint, false));
+        }
         OracleType::DeprecatedPlaceholder1 | OracleType::DeprecatedPlaceholder2 => {
             panic!("DeprecatedPlaceholder is not a valid oracle type")
         }
@@ -103,6 +149,97 @@ pub async fn get_orca_whirlpool_remaining_accounts(
     ]
 }
 
+pub async fn get_raydium_twap_remaining_accounts(
+    ctx: &mut TestContext,
+    conf: &OracleConf,
+) -> [AccountMeta; 1] {
+    let pool: raydium_amm_v3::states::PoolState =
+        ctx.get_zero_copy_account(&conf.pubkey).await.unwrap();
+    [AccountMeta::new_readonly(pool.observation_key, false)]
+}
+
+pub async fn get_openbook_v2_remaining_accounts(
+    ctx: &mut TestContext,
+    conf: &OracleConf,
+) -> [AccountMeta; 2] {
+    use scope::oracles::openbook_v2::openbook;
+    let market_data = ctx.get_account_data(&conf.pubkey).await.unwrap();
+    let market = openbook::Market::unpack(&market_data).unwrap();
+    [
+        AccountMeta::new_readonly(market.bids, false),
+        AccountMeta::new_readonly(market.asks, false),
+    ]
+}
+
+pub async fn get_account_field_remaining_accounts(
+    ctx: &mut TestContext,
+    conf: &OracleConf,
+) -> [AccountMeta; 1] {
+    let reader: scope::AccountFieldReader = ctx.get_zero_copy_account(&conf.pubkey).await.unwrap();
+    [AccountMeta::new_readonly(reader.account, false)]
+}
+
+/// Extra accounts and LP mint of a constant-product pool (type selected from the pool owner)
+pub async fn get_constant_product_remaining_accounts(
+    ctx: &mut TestContext,
+    pool_pk: &Pubkey,
+) -> (Vec<AccountMeta>, Pubkey) {
+    use scope::oracles::{
+        meteora_dynamic_amm::meteora_amm, orca_token_swap::orca_swap, raydium_amm_v4::raydium_amm,
+        raydium_cpmm::raydium_cp_swap,
+    };
+
+    let pool = ctx.get_account(pool_pk).await.unwrap();
+    let (pks, lp_mint) = if pool.owner == raydium_amm::id() {
+        let amm_info = raydium_amm::AmmInfo::unpack(&pool.data).unwrap();
+        (
+            vec![amm_info.coin_vault, amm_info.pc_vault],
+            amm_info.lp_mint,
+        )
+    } else if pool.owner == raydium_cp_swap::id() {
+        let pool_state = raydium_cp_swap::PoolState::unpack(&pool.data).unwrap();
+        (
+            vec![pool_state.token_0_vault, pool_state.token_1_vault],
+            pool_state.lp_mint,
+        )
+    } else if pool.owner == orca_swap::id() {
+        let swap = orca_swap::SwapV1::unpack(&pool.data).unwrap();
+        (
+            vec![
+                swap.token_a,
+                swap.token_b,
+                swap.token_a_mint,
+                swap.token_b_mint,
+            ],
+            swap.pool_mint,
+        )
+    } else if pool.owner == meteora_amm::id() {
+        let pool_data = meteora_amm::Pool::unpack(&pool.data).unwrap();
+        let a_vault_data = ctx.get_account_data(&pool_data.a_vault).await.unwrap();
+        let b_vault_data = ctx.get_account_data(&pool_data.b_vault).await.unwrap();
+        let a_vault = meteora_amm::Vault::unpack(&a_vault_data).unwrap();
+        let b_vault = meteora_amm::Vault::unpack(&b_vault_data).unwrap();
+        let pks = vec![
+            pool_data.token_a_mint,
+            pool_data.token_b_mint,
+            pool_data.a_vault,
+            pool_data.b_vault,
+            pool_data.a_vault_lp,
+            pool_data.b_vault_lp,
+            a_vault.lp_mint,
+            b_vault.lp_mint,
+        ];
+        (pks, pool_data.lp_mint)
+    } else {
+        panic!("Pool {pool_pk} is not owned by a supported program")
+    };
+    let accounts = pks
+        .into_iter()
+        .map(|pk| AccountMeta::new_readonly(pk, false))
+        .collect();
+    (accounts, lp_mint)
+}
+
 #[cfg(feature = "yvaults")]
 mod ktokens {
     use kamino::state::{GlobalConfig, WhirlpoolStrategy};
@@ -0,0 +1,203 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use scope::{
+    oracles::OracleType, Configuration, IndexDefinition, OracleMappings, PriceHistory, ScopeError,
+};
+use solana_program_test::tokio;
+use solana_sdk::signer::Signer;
+
+use crate::common::{
+    operations::{close_entry_accounts, close_feed, init_price_history, price_history_pda},
+    setup::{new_keypair, setup_scope_feed},
+    types::{ScopeFeedDefinition, TestError},
+    utils::map_anchor_error,
+    utils::{map_scope_error, AnchorErrorCode},
+};
+// - [x] Close with wrong admin
+// - [x] Close with wrong feed accounts
+// - [x] Close per entry accounts of another feed
+// - [x] Close an entry account still mapped
+
+#[tokio::test]
+async fn test_working_close_feed() {
+    let (mut ctx, scope_feed_definition) = setup_scope_feed().await;
+
+    let config_state = ctx
+        .get_anchor_account::<Configuration>(&scope_feed_definition.conf)
+        .await
+        .unwrap();
+    let admin_balance_before = ctx.get_account(&ctx.admin.pubkey()).await.unwrap().lamports;
+
+    close_feed(
+        &mut ctx,
+        &scope_feed_definition,
+        &config_state.tokens_metadata,
+    )
+    .await
+    .unwrap();
+
+    for closed in [
+        scope_feed_definition.conf,
+        scope_feed_definition.prices,
+        scope_feed_definition.mapping,
+        scope_feed_definition.twaps,
+        config_state.tokens_metadata,
+    ] {
+        assert!(matches!(
+            ctx.get_account(&closed).await,
+            Err(TestError::AccountNotFound)
+        ));
+    }
+    let admin_balance_after = ctx.get_account(&ctx.admin.pubkey()).await.unwrap().lamports;
+    assert!(admin_balance_after > admin_balance_before);
+}
+
+// Close with wrong admin
+#[tokio::test]
+async fn test_security_close_feed_wrong_admin() {
+    let (mut ctx, scope_feed_definition) = setup_scope_feed().await;
+
+    let config_state = ctx
+        .get_anchor_account::<Configuration>(&scope_feed_definition.conf)
+        .await
+        .unwrap();
+
+    ctx.admin = new_keypair(&mut ctx, 100000000).await;
+
+    let res = close_feed(
+        &mut ctx,
+        &scope_feed_definition,
+        &config_state.tokens_metadata,
+    )
+    .await;
+
+    assert_eq!(map_anchor_error(res), AnchorErrorCode::ConstraintHasOne);
+}
+
+// Close with wrong feed accounts
+#[tokio::test]
+async fn test_security_close_feed_wrong_accounts() {
+    let (mut ctx, scope_feed_definition) = setup_scope_feed().await;
+
+    let config_state = ctx
+        .get_anchor_account::<Configuration>(&scope_feed_definition.conf)
+        .await
+        .unwrap();
+
+    // Create fake feed accounts
+    let fake_tokens_metadata = Pubkey::new_unique();
+    ctx.clone_account(&config_state.tokens_metadata, &fake_tokens_metadata)
+        .await;
+    let fake_twaps = Pubkey::new_unique();
+    ctx.clone_account(&scope_feed_definition.twaps, &fake_twaps)
+        .await;
+
+    let res = close_feed(&mut ctx, &scope_feed_definition, &fake_tokens_metadata).await;
+    assert_eq!(map_anchor_error(res), AnchorErrorCode::ConstraintHasOne);
+
+    let wrong_feed_definition = ScopeFeedDefinition {
+        feed_name: scope_feed_definition.feed_name.clone(),
+        twaps: fake_twaps,
+        ..scope_feed_definition
+    };
+    let res = close_feed(
+        &mut ctx,
+        &wrong_feed_definition,
+        &config_state.tokens_metadata,
+    )
+    .await;
+    assert_eq!(map_anchor_error(res), AnchorErrorCode::ConstraintHasOne);
+}
+
+#[tokio::test]
+async fn test_working_close_entry_accounts() {
+    let (mut ctx, scope_feed_definition) = setup_scope_feed().await;
+
+    init_price_history(&mut ctx, &scope_feed_definition, 3)
+        .await
+        .unwrap();
+    let price_history = price_history_pda(&scope_feed_definition, 3);
+
+    close_entry_accounts(&mut ctx, &scope_feed_definition, &[price_history])
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        ctx.get_account(&price_history).await,
+        Err(TestError::AccountNotFound)
+    ));
+    // The refresh no longer expects the closed history account
+    let mappings = ctx
+        .get_zero_copy_account_boxed::<OracleMappings>(&scope_feed_definition.mapping)
+        .await
+        .unwrap();
+    assert!(!mappings.is_history_enabled(3));
+
+    let config_state = ctx
+        .get_anchor_account::<Configuration>(&scope_feed_definition.conf)
+        .await
+        .unwrap();
+    close_feed(
+        &mut ctx,
+        &scope_feed_definition,
+        &config_state.tokens_metadata,
+    )
+    .await
+    .unwrap();
+}
+
+// Entries mapped to a closed account are unmapped
+#[tokio::test]
+async fn test_close_mapped_entry_account() {
+    let (mut ctx, scope_feed_definition) = setup_scope_feed().await;
+
+    let mut index_definition: IndexDefinition = bytemuck::Zeroable::zeroed();
+    index_definition.oracle_prices = scope_feed_definition.prices;
+    index_definition.index = 5;
+    index_definition.set_components(&[0], &[1], 0, 0).unwrap();
+    let index_definition_pk = Pubkey::new_unique();
+    ctx.set_zero_copy_account(&index_definition_pk, &index_definition);
+
+    let mut mappings = ctx
+        .get_zero_copy_account_boxed::<OracleMappings>(&scope_feed_definition.mapping)
+        .await
+        .unwrap();
+    mappings.price_info_accounts[5] = index_definition_pk;
+    mappings.price_types[5] = OracleType::IndexPrice.into();
+    ctx.set_zero_copy_account(&scope_feed_definition.mapping, &*mappings);
+
+    close_entry_accounts(&mut ctx, &scope_feed_definition, &[index_definition_pk])
+        .await
+        .unwrap();
+
+    let mappings = ctx
+        .get_zero_copy_account_boxed::<OracleMappings>(&scope_feed_definition.mapping)
+        .await
+        .unwrap();
+    assert_eq!(mappings.price_info_accounts[5], Pubkey::default());
+    assert_eq!(mappings.price_types[5], 0);
+}
+
+// Close per entry accounts of another feed
+#[tokio::test]
+async fn test_security_close_entry_accounts_of_another_feed() {
+    let (mut ctx, scope_feed_definition) = setup_scope_feed().await;
+
+    let mut price_history: PriceHistory = bytemuck::Zeroable::zeroed();
+    price_history.oracle_prices = Pubkey::new_unique();
+    let other_feed_history = Pubkey::new_unique();
+    ctx.set_zero_copy_account(&other_feed_history, &price_history);
+
+    let res = close_entry_accounts(&mut ctx, &scope_feed_definition, &[other_feed_history]).await;
+    assert_eq!(map_scope_error(res), ScopeError::UnexpectedAccount);
+
+    // Feed accounts are not per entry accounts
+    let res = close_entry_accounts(
+        &mut ctx,
+        &scope_feed_definition,
+        &[scope_feed_definition.twaps],
+    )
+    .await;
+    assert_eq!(map_scope_error(res), ScopeError::UnexpectedAccount);
+}
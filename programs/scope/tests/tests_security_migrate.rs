@@ -0,0 +1,150 @@
+mod common;
+
+use common::*;
+use scope::{Configuration, OracleMappings, ScopeError, FEED_LAYOUT_VERSION, GENERIC_DATA_LEN};
+use solana_program_test::tokio;
+use solana_sdk::pubkey;
+use types::*;
+
+use crate::common::{
+    client::refresh_simple_oracle_ix,
+    operations::migrate,
+    setup::{new_keypair, setup_scope_feed},
+    utils::{map_anchor_error, map_scope_error, AnchorErrorCode},
+};
+
+const TEST_PYTH_ORACLE: OracleConf = OracleConf {
+    pubkey: pubkey!("SomePythPriceAccount11111111111111111111111"),
+    token: 0,
+    price_type: TestOracleType::Pyth,
+    twap_enabled: false,
+    twap_source: None,
+};
+
+// - [x] Migrate with wrong admin
+// - [x] Migrate from an unknown version
+// - [x] Migrate a mappings account created without generic data
+
+#[tokio::test]
+async fn test_working_migrate() {
+    let (mut ctx, scope_feed_definition) = setup_scope_feed().await;
+
+    let mut config_state = ctx
+        .get_zero_copy_account::<Configuration>(&scope_feed_definition.conf)
+        .await
+        .unwrap();
+    assert_eq!(config_state.version, FEED_LAYOUT_VERSION);
+
+    // Feed initialized before the layout was versioned
+    config_state.version = 0;
+    ctx.set_zero_copy_account(&scope_feed_definition.conf, &config_state);
+
+    migrate(
+        &mut ctx,
+        &scope_feed_definition,
+        &config_state.tokens_metadata,
+    )
+    .await
+    .unwrap();
+
+    let config_state = ctx
+        .get_zero_copy_account::<Configuration>(&scope_feed_definition.conf)
+        .await
+        .unwrap();
+    assert_eq!(config_state.version, FEED_LAYOUT_VERSION);
+
+    // Migrating an up to date feed is a no-op
+    migrate(
+        &mut ctx,
+        &scope_feed_definition,
+        &config_state.tokens_metadata,
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_migrate_legacy_mappings_account() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+
+    // Feed initialized before the layout was versioned, its mappings have no generic data
+    let mut config_state = ctx
+        .get_zero_copy_account::<Configuration>(&feed.conf)
+        .await
+        .unwrap();
+    config_state.version = 0;
+    ctx.set_zero_copy_account(&feed.conf, &config_state);
+    let mut data = ctx.get_account_data(&feed.mapping).await.unwrap();
+    data.truncate(OracleMappings::LEGACY_ACCOUNT_SIZE);
+    ctx.set_account(&feed.mapping, data, &scope::id());
+
+    let res = ctx
+        .send_transaction_with_bot(&[refresh_simple_oracle_ix(&feed, TEST_PYTH_ORACLE)])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::FeedNotMigrated);
+
+    migrate(&mut ctx, &feed, &config_state.tokens_metadata)
+        .await
+        .unwrap();
+
+    let oracle_mappings = ctx
+        .get_zero_copy_account_boxed::<OracleMappings>(&feed.mapping)
+        .await
+        .unwrap();
+    assert_eq!(
+        oracle_mappings.price_info_accounts[TEST_PYTH_ORACLE.token],
+        TEST_PYTH_ORACLE.pubkey
+    );
+    assert_eq!(
+        oracle_mappings.generic[TEST_PYTH_ORACLE.token],
+        [0; GENERIC_DATA_LEN]
+    );
+
+    ctx.send_transaction_with_bot(&[refresh_simple_oracle_ix(&feed, TEST_PYTH_ORACLE)])
+        .await
+        .unwrap();
+}
+
+// Migrate with wrong admin
+#[tokio::test]
+async fn test_security_migrate_wrong_admin() {
+    let (mut ctx, scope_feed_definition) = setup_scope_feed().await;
+
+    let config_state = ctx
+        .get_zero_copy_account::<Configuration>(&scope_feed_definition.conf)
+        .await
+        .unwrap();
+
+    ctx.admin = new_keypair(&mut ctx, 100000000).await;
+
+    let res = migrate(
+        &mut ctx,
+        &scope_feed_definition,
+        &config_state.tokens_metadata,
+    )
+    .await;
+
+    assert_eq!(map_anchor_error(res), AnchorErrorCode::ConstraintHasOne);
+}
+
+// Migrate from an unknown version
+#[tokio::test]
+async fn test_security_migrate_unknown_version() {
+    let (mut ctx, scope_feed_definition) = setup_scope_feed().await;
+
+    let mut config_state = ctx
+        .get_zero_copy_account::<Configuration>(&scope_feed_definition.conf)
+        .await
+        .unwrap();
+    config_state.version = FEED_LAYOUT_VERSION + 1;
+    ctx.set_zero_copy_account(&scope_feed_definition.conf, &config_state);
+
+    let res = migrate(
+        &mut ctx,
+        &scope_feed_definition,
+        &config_state.tokens_metadata,
+    )
+    .await;
+
+    assert_eq!(map_scope_error(res), ScopeError::UnknownFeedVersion);
+}
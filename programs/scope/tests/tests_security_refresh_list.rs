@@ -1,15 +1,19 @@
 #![allow(clippy::items_after_test_module)]
 use anchor_lang::{
     prelude::{AccountMeta, Pubkey},
-    InstructionData, ToAccountMetas,
+    AnchorDeserialize, InstructionData, ToAccountMetas,
 };
 use common::*;
-use scope::{assert_fuzzy_price_eq, OraclePrices, Price, ScopeError};
+use scope::{
+    assert_fuzzy_price_eq, OracleMappings, OraclePrices, Price, PriceHistory, RefreshStatus,
+    ScopeError,
+};
 use solana_program::{
-    instruction::Instruction, sysvar::instructions::ID as SYSVAR_INSTRUCTIONS_ID,
+    instruction::Instruction, system_instruction, system_program,
+    sysvar::instructions::ID as SYSVAR_INSTRUCTIONS_ID,
 };
 use solana_program_test::tokio;
-use solana_sdk::{pubkey, signer::Signer};
+use solana_sdk::{pubkey, signer::Signer, transaction_context::TransactionReturnData};
 use test_case::test_case;
 use types::*;
 
@@ -105,6 +109,15 @@ const TEST_ORACLE_CONF: [OracleConf; 7] = [
 // - [x] Wrong sysvar instruction account
 // - [x] Instruction executed in CPI
 // - [x] Instruction preceded by non ComputeBudget instruction
+// - [x] Instruction preceded by an instruction of an allowed program
+// - [x] Instruction of an allowed program writing to a refreshed account
+// - [x] Instruction preceded by an allowed program ix without the configuration
+// - [x] Account list of the clients built before the configuration was read
+// - [x] Configuration of another feed
+// - [x] Failing entry with a history account followed by another entry
+// - [x] Failing history append
+// - [x] History enabled without its history account
+// - [x] Disabled history
 
 // KTokens:
 // - [x] Wrong kToken additional global config account
@@ -181,9 +194,300 @@ async fn test_working_refresh_list() {
             conf
         );
         assert!(data.prices[conf.token].last_updated_slot > 0);
+        assert!(data.prices[conf.token].refreshed_slot() > 0);
+    }
+}
+
+#[tokio::test]
+async fn test_refresh_list_return_statuses() {
+    let oracles = vec![TEST_PYTH_ORACLE, TEST_PYTH2_ORACLE];
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, oracles.clone()).await;
+    let unmapped_token: u16 = 2;
+
+    for conf in oracles.iter() {
+        mock_oracles::set_price(&mut ctx, &feed, conf, &Price { value: 1, exp: 6 }).await;
+    }
+
+    let mut accounts = scope::accounts::RefreshList {
+        oracle_prices: feed.prices,
+        oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+    }
+    .to_account_metas(None);
+    for conf in oracles.iter() {
+        let mut refresh_accounts = utils::get_refresh_list_accounts(&mut ctx, conf).await;
+        accounts.append(&mut refresh_accounts);
+    }
+    accounts.push(AccountMeta::new_readonly(scope::id(), false));
+
+    let mut tokens: Vec<u16> = oracles.iter().map(|conf| conf.token as u16).collect();
+    tokens.push(unmapped_token);
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts,
+        data: scope::instruction::RefreshPriceList { tokens }.data(),
+    };
+
+    let get_statuses = |return_data: Option<TransactionReturnData>| {
+        let return_data = return_data.expect("refresh should set return data");
+        assert_eq!(return_data.program_id, scope::id());
+        Vec::<RefreshStatus>::try_from_slice(&return_data.data).unwrap()
+    };
+
+    // First refresh updates the mapped prices
+    let return_data = ctx
+        .send_transaction_with_bot_and_get_return_data(&[ix.clone()])
+        .await
+        .unwrap();
+    assert_eq!(
+        get_statuses(return_data),
+        vec![
+            RefreshStatus::Updated,
+            RefreshStatus::Updated,
+            RefreshStatus::SkippedUnmapped
+        ]
+    );
+
+    // Without new prices from the sources, the second refresh leaves the prices unchanged
+    let return_data = ctx
+        .send_transaction_with_bot_and_get_return_data(&[ix])
+        .await
+        .unwrap();
+    assert_eq!(
+        get_statuses(return_data),
+        vec![
+            RefreshStatus::Unchanged,
+            RefreshStatus::Unchanged,
+            RefreshStatus::SkippedUnmapped
+        ]
+    );
+}
+
+/// Refresh instruction of `oracles` with the given price histories after the price accounts
+async fn refresh_list_with_histories_ix(
+    ctx: &mut TestContext,
+    feed: &ScopeFeedDefinition,
+    oracles: &[OracleConf],
+    mut price_histories: Vec<AccountMeta>,
+) -> Instruction {
+    let mut accounts = scope::accounts::RefreshList {
+        oracle_prices: feed.prices,
+        oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+    }
+    .to_account_metas(None);
+    for conf in oracles.iter() {
+        let mut refresh_accounts = utils::get_refresh_list_accounts(ctx, conf).await;
+        accounts.append(&mut refresh_accounts);
+    }
+    accounts.append(&mut price_histories);
+    accounts.push(AccountMeta::new_readonly(feed.conf, false));
+
+    let tokens: Vec<u16> = oracles.iter().map(|conf| conf.token as u16).collect();
+    Instruction {
+        program_id: scope::id(),
+        accounts,
+        data: scope::instruction::RefreshPriceList { tokens }.data(),
+    }
+}
+
+async fn refresh_statuses(ctx: &mut TestContext, ix: Instruction) -> Vec<RefreshStatus> {
+    let return_data = ctx
+        .send_transaction_with_bot_and_get_return_data(&[ix])
+        .await
+        .unwrap()
+        .expect("refresh should set return data");
+    Vec::<RefreshStatus>::try_from_slice(&return_data.data).unwrap()
+}
+
+async fn get_num_observations(
+    ctx: &mut TestContext,
+    feed: &ScopeFeedDefinition,
+    token: usize,
+) -> u64 {
+    ctx.get_zero_copy_account_boxed::<PriceHistory>(&operations::price_history_pda(feed, token))
+        .await
+        .unwrap()
+        .num_observations
+}
+
+#[tokio::test]
+async fn test_refresh_list_failing_entry_with_history() {
+    let oracles = vec![TEST_PYTH_ORACLE, TEST_PYTH2_ORACLE];
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, oracles.clone()).await;
+
+    for conf in oracles.iter() {
+        operations::init_price_history(&mut ctx, &feed, conf.token)
+            .await
+            .unwrap();
+        mock_oracles::set_price(&mut ctx, &feed, conf, &Price { value: 1, exp: 6 }).await;
+    }
+    // The price of the first entry can't be computed anymore
+    let pyth_account = ctx.get_account(&TEST_PYTH_ORACLE.pubkey).await.unwrap();
+    ctx.set_account(
+        &TEST_PYTH_ORACLE.pubkey,
+        vec![0; pyth_account.data.len()],
+        &pyth_account.owner,
+    );
+
+    let price_histories = oracles
+        .iter()
+        .map(|conf| AccountMeta::new(operations::price_history_pda(&feed, conf.token), false))
+        .collect();
+    let ix = refresh_list_with_histories_ix(&mut ctx, &feed, &oracles, price_histories).await;
+
+    // The failure of the first entry does not prevent the refresh of the second one
+    let statuses = refresh_statuses(&mut ctx, ix).await;
+    assert!(matches!(statuses[0], RefreshStatus::Failed { .. }));
+    assert_eq!(statuses[1], RefreshStatus::Updated);
+
+    for (conf, expected_observations) in oracles.iter().zip([0, 1]) {
+        assert_eq!(
+            get_num_observations(&mut ctx, &feed, conf.token).await,
+            expected_observations
+        );
     }
 }
 
+#[tokio::test]
+async fn test_refresh_list_failing_history_append() {
+    let oracles = vec![TEST_PYTH_ORACLE, TEST_PYTH2_ORACLE];
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, oracles.clone()).await;
+
+    for conf in oracles.iter() {
+        operations::init_price_history(&mut ctx, &feed, conf.token)
+            .await
+            .unwrap();
+        mock_oracles::set_price(&mut ctx, &feed, conf, &Price { value: 1, exp: 6 }).await;
+    }
+
+    // The history of the first entry can't be written
+    let price_histories = vec![
+        AccountMeta::new_readonly(
+            operations::price_history_pda(&feed, TEST_PYTH_ORACLE.token),
+            false,
+        ),
+        AccountMeta::new(
+            operations::price_history_pda(&feed, TEST_PYTH2_ORACLE.token),
+            false,
+        ),
+    ];
+    let ix = refresh_list_with_histories_ix(&mut ctx, &feed, &oracles, price_histories).await;
+
+    let statuses = refresh_statuses(&mut ctx, ix).await;
+    assert!(matches!(statuses[0], RefreshStatus::Failed { .. }));
+    assert_eq!(statuses[1], RefreshStatus::Updated);
+
+    // The price of the failed entry is left unchanged
+    let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    assert_eq!(data.prices[TEST_PYTH_ORACLE.token].price, Price::default());
+    assert_eq!(
+        data.prices[TEST_PYTH2_ORACLE.token].price,
+        Price { value: 1, exp: 6 }
+    );
+    assert_eq!(
+        get_num_observations(&mut ctx, &feed, TEST_PYTH2_ORACLE.token).await,
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_refresh_list_without_history_account() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+    operations::init_price_history(&mut ctx, &feed, TEST_PYTH_ORACLE.token)
+        .await
+        .unwrap();
+    mock_oracles::set_price(
+        &mut ctx,
+        &feed,
+        &TEST_PYTH_ORACLE,
+        &Price { value: 1, exp: 6 },
+    )
+    .await;
+
+    // Clients unaware of the history still refresh the entry
+    let ix = refresh_list_with_histories_ix(&mut ctx, &feed, &[TEST_PYTH_ORACLE], vec![]).await;
+    let statuses = refresh_statuses(&mut ctx, ix).await;
+    assert_eq!(statuses, vec![RefreshStatus::Updated]);
+    assert_eq!(
+        get_num_observations(&mut ctx, &feed, TEST_PYTH_ORACLE.token).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_disabled_price_history() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+    operations::init_price_history(&mut ctx, &feed, TEST_PYTH_ORACLE.token)
+        .await
+        .unwrap();
+    let price_history = operations::price_history_pda(&feed, TEST_PYTH_ORACLE.token);
+
+    operations::update_price_history(&mut ctx, &feed, TEST_PYTH_ORACLE.token, false)
+        .await
+        .unwrap();
+    let mappings: OracleMappings = ctx.get_zero_copy_account(&feed.mapping).await.unwrap();
+    assert!(!mappings.is_history_enabled(TEST_PYTH_ORACLE.token));
+
+    mock_oracles::set_price(
+        &mut ctx,
+        &feed,
+        &TEST_PYTH_ORACLE,
+        &Price { value: 1, exp: 6 },
+    )
+    .await;
+    let ix = refresh_list_with_histories_ix(
+        &mut ctx,
+        &feed,
+        &[TEST_PYTH_ORACLE],
+        vec![AccountMeta::new(price_history, false)],
+    )
+    .await;
+    assert_eq!(
+        refresh_statuses(&mut ctx, ix).await,
+        vec![RefreshStatus::Updated]
+    );
+    assert_eq!(
+        get_num_observations(&mut ctx, &feed, TEST_PYTH_ORACLE.token).await,
+        0
+    );
+
+    // Enabled again
+    operations::update_price_history(&mut ctx, &feed, TEST_PYTH_ORACLE.token, true)
+        .await
+        .unwrap();
+    mock_oracles::set_price(
+        &mut ctx,
+        &feed,
+        &TEST_PYTH_ORACLE,
+        &Price { value: 2, exp: 6 },
+    )
+    .await;
+    let ix = refresh_list_with_histories_ix(
+        &mut ctx,
+        &feed,
+        &[TEST_PYTH_ORACLE],
+        vec![AccountMeta::new(price_history, false)],
+    )
+    .await;
+    assert_eq!(
+        refresh_statuses(&mut ctx, ix).await,
+        vec![RefreshStatus::Updated]
+    );
+    assert_eq!(
+        get_num_observations(&mut ctx, &feed, TEST_PYTH_ORACLE.token).await,
+        1
+    );
+
+    // Only the admin can update the history
+    let mut ix = operations::update_price_history_ix(&ctx, &feed, TEST_PYTH_ORACLE.token, false);
+    ix.accounts[0].pubkey = ctx.bot.pubkey();
+    let res = ctx.send_transaction_with_bot(&[ix]).await;
+    assert_eq!(map_anchor_error(res), AnchorErrorCode::ConstraintHasOne);
+}
+
 // - [ ] Wrong oracle mapping
 #[tokio::test]
 async fn test_wrong_oracle_mapping() {
@@ -476,6 +780,7 @@ async fn test_refresh_with_unexpected_ix() {
         price_type: TEST_PYTH_ORACLE.price_type.to_u8(),
         twap_enabled: false,
         twap_source: u16::MAX,
+        generic_data: [0; 20],
     };
 
     let extra_ix = Instruction {
@@ -512,6 +817,199 @@ async fn test_refresh_with_unexpected_ix() {
     assert_eq!(map_scope_error(res), ScopeError::RefreshWithUnexpectedIxs);
 }
 
+// - [x] Instruction preceded by an instruction of an allowed program
+#[tokio::test]
+async fn test_refresh_with_allowed_program_ix() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+    mock_oracles::set_price(
+        &mut ctx,
+        &feed,
+        &TEST_PYTH_ORACLE,
+        &Price { value: 1, exp: 6 },
+    )
+    .await;
+
+    let tip_ix = system_instruction::transfer(&ctx.bot.pubkey(), &Pubkey::new_unique(), 1_000_000);
+    let ix = client::refresh_simple_oracle_ix(&feed, TEST_PYTH_ORACLE);
+
+    // Not allowed yet
+    let res = ctx
+        .send_transaction_with_bot(&[tip_ix.clone(), ix.clone()])
+        .await;
+    assert_eq!(map_scope_error(res), ScopeError::RefreshWithUnexpectedIxs);
+
+    operations::update_refresh_allowed_programs(&mut ctx, &feed, vec![system_program::id()])
+        .await
+        .unwrap();
+
+    ctx.send_transaction_with_bot(&[tip_ix, ix]).await.unwrap();
+
+    let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    assert_eq!(
+        data.prices[TEST_PYTH_ORACLE.token].price,
+        Price { value: 1, exp: 6 }
+    );
+}
+
+// - [x] Instruction preceded by an allowed program ix without the configuration
+#[tokio::test]
+async fn test_refresh_without_configuration() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+    mock_oracles::set_price(
+        &mut ctx,
+        &feed,
+        &TEST_PYTH_ORACLE,
+        &Price { value: 1, exp: 6 },
+    )
+    .await;
+
+    operations::update_refresh_allowed_programs(&mut ctx, &feed, vec![system_program::id()])
+        .await
+        .unwrap();
+
+    let mut accounts = scope::accounts::RefreshList {
+        oracle_prices: feed.prices,
+        oracle_mappings: feed.mapping,
+        oracle_twaps: feed.twaps,
+        instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+    }
+    .to_account_metas(None);
+    accounts.append(&mut utils::get_refresh_list_accounts(&mut ctx, &TEST_PYTH_ORACLE).await);
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts,
+        data: scope::instruction::RefreshPriceList {
+            tokens: vec![TEST_PYTH_ORACLE.token.try_into().unwrap()],
+        }
+        .data(),
+    };
+
+    // The allowed programs of the feed are unknown without the configuration
+    let tip_ix = system_instruction::transfer(&ctx.bot.pubkey(), &Pubkey::new_unique(), 1_000_000);
+    let res = ctx.send_transaction_with_bot(&[tip_ix, ix.clone()]).await;
+    assert_eq!(map_scope_error(res), ScopeError::RefreshWithUnexpectedIxs);
+
+    ctx.send_transaction_with_bot(&[ix]).await.unwrap();
+
+    let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    assert_eq!(
+        data.prices[TEST_PYTH_ORACLE.token].price,
+        Price { value: 1, exp: 6 }
+    );
+}
+
+// - [x] Account list of the clients built before the configuration was read
+#[tokio::test]
+async fn test_refresh_with_legacy_account_list() {
+    const TEST_TWAP_SOURCE: OracleConf = OracleConf {
+        twap_enabled: true,
+        ..TEST_PYTH_ORACLE
+    };
+    const TEST_TWAP: OracleConf = OracleConf {
+        pubkey: scope::ID,
+        token: 1,
+        price_type: TestOracleType::ScopeTwap(1),
+        twap_enabled: false,
+        twap_source: Some(0),
+    };
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+    mock_oracles::set_price(&mut ctx, &feed, &TEST_TWAP_SOURCE, &Price::default()).await;
+    fixtures::setup_mapping_for_token_with_twap(&mut ctx, &feed, TEST_TWAP_SOURCE, TEST_TWAP).await;
+    mock_oracles::set_price(
+        &mut ctx,
+        &feed,
+        &TEST_TWAP_SOURCE,
+        &Price { value: 1, exp: 6 },
+    )
+    .await;
+
+    // Fixed accounts then one price account per token, the computed entry being mapped to the
+    // program id, and nothing after them
+    let accounts = vec![
+        AccountMeta::new(feed.prices, false),
+        AccountMeta::new_readonly(feed.mapping, false),
+        AccountMeta::new(feed.twaps, false),
+        AccountMeta::new_readonly(SYSVAR_INSTRUCTIONS_ID, false),
+        AccountMeta::new_readonly(TEST_TWAP_SOURCE.pubkey, false),
+        AccountMeta::new_readonly(scope::ID, false),
+    ];
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts,
+        data: scope::instruction::RefreshPriceList {
+            tokens: vec![TEST_TWAP_SOURCE.token as u16, TEST_TWAP.token as u16],
+        }
+        .data(),
+    };
+    let return_data = ctx
+        .send_transaction_with_bot_and_get_return_data(&[ix])
+        .await
+        .unwrap()
+        .expect("refresh should set return data");
+
+    // The computed entry is reached with its account, its TWAP has not enough samples yet
+    let statuses = Vec::<RefreshStatus>::try_from_slice(&return_data.data).unwrap();
+    assert_eq!(statuses[0], RefreshStatus::Updated);
+    assert!(matches!(statuses[1], RefreshStatus::Failed { .. }));
+    let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    assert_eq!(
+        data.prices[TEST_TWAP_SOURCE.token].price,
+        Price { value: 1, exp: 6 }
+    );
+}
+
+// - [x] Configuration of another feed
+#[tokio::test]
+async fn test_refresh_with_configuration_of_another_feed() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+
+    let mut other_configuration: Box<scope::Configuration> =
+        ctx.get_zero_copy_account_boxed(&feed.conf).await.unwrap();
+    other_configuration.oracle_prices = Pubkey::new_unique();
+    let other_configuration_pk = Pubkey::new_unique();
+    ctx.set_zero_copy_account(&other_configuration_pk, other_configuration.as_ref());
+
+    let mut ix = client::refresh_simple_oracle_ix(&feed, TEST_PYTH_ORACLE);
+    ix.accounts.last_mut().unwrap().pubkey = other_configuration_pk;
+
+    let res = ctx.send_transaction_with_bot(&[ix]).await;
+    assert_eq!(map_scope_error(res), ScopeError::UnexpectedAccount);
+}
+
+// - [x] Instruction of an allowed program writing to a refreshed account
+#[tokio::test]
+async fn test_refresh_with_allowed_program_writing_price_account() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+
+    operations::update_refresh_allowed_programs(&mut ctx, &feed, vec![system_program::id()])
+        .await
+        .unwrap();
+
+    let extra_ix = system_instruction::transfer(&ctx.bot.pubkey(), &TEST_PYTH_ORACLE.pubkey, 1);
+    let ix = client::refresh_simple_oracle_ix(&feed, TEST_PYTH_ORACLE);
+
+    let res = ctx.send_transaction_with_bot(&[extra_ix, ix]).await;
+    assert_eq!(
+        map_scope_error(res),
+        ScopeError::RefreshedAccountWrittenBeforeRefresh
+    );
+}
+
+// - [x] Too many allowed programs
+#[tokio::test]
+async fn test_too_many_refresh_allowed_programs() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+
+    let programs = (0..=scope::MAX_REFRESH_ALLOWED_PROGRAMS)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+    let res = operations::update_refresh_allowed_programs(&mut ctx, &feed, programs).await;
+    assert_eq!(
+        map_scope_error(res),
+        ScopeError::TooManyRefreshAllowedPrograms
+    );
+}
+
 #[cfg(feature = "yvaults")]
 mod ktoken_tests {
     use kamino::state::{GlobalConfig, WhirlpoolStrategy};
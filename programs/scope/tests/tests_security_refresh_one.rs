@@ -32,6 +32,67 @@ use crate::utils::map_scope_error;
 // - [x] Wrong Jupiter LP additional custodies account
 // - [x] Wrong Jupiter LP additional oracles account
 
+// Constant product pools:
+// - [x] Working case, Raydium AMM v4 pool
+// - [x] Working case, Raydium CPMM pool
+// - [x] Working case, Orca token-swap pool
+// - [x] Working case, Meteora dynamic AMM pool
+// - [x] Wrong pool additional vault account
+
+// Constant product LP tokens:
+// - [x] Working case, priced from the token entries refreshed in the same transaction
+// - [x] Wrong LP token additional mint account
+
+// Kamino Lending cTokens:
+// - [x] Working case, reserve updated in the current slot
+// - [x] Fail case, reserve not owned by the klend program
+
+// Solend rates:
+// - [x] Working case, borrow and supply rates of the same reserve
+// - [x] Fail case, mapping a reserve not owned by the Solend program
+
+// Chainlink:
+// - [x] Working case, round answered in the current slot
+// - [x] Fail case, latest round older than 10 minutes
+
+// Orderbooks:
+// - [x] Working case, OpenBook v2 mid-price with the best bid and ask
+// - [x] Working case, Phoenix mid-price with the best bid and ask
+// - [x] Wrong OpenBook additional bids and asks accounts
+
+// Signed prices:
+// - [x] Working case, price signed by a whitelisted publisher
+// - [x] Fail case, replayed signed price
+// - [x] Fail case, price signed by an unknown publisher
+
+// Fixed rate:
+// - [x] Working case, start price before any accrual
+// - [x] Working case, price accrued for a year after an update of the rate
+
+// Capped and floored prices:
+// - [x] Working case, source price above the cap, below the floor and in range
+// - [x] Fail case, source entry without price
+
+// Min and max of two prices:
+// - [x] Working case, lower and higher of two prices refreshed in the same transaction
+// - [x] Fail case, input entry without price
+
+// Index prices:
+// - [x] Working case, average of two prices refreshed in the same transaction
+// - [x] Fail case, index definition of another entry
+
+// Inverse prices:
+// - [x] Working case, inverse of a price refreshed in the same transaction
+// - [x] Fail case, source entry without price
+
+// Account fields:
+// - [x] Working case, value field read with the configured exponent
+// - [x] Fail case, account read not owned by the configured program
+
+// Raydium AMM v3 TWAP:
+// - [x] Working case, price averaged from the pool observations
+// - [x] Wrong Raydium additional observation account
+
 // Spl Stake:
 // - [x] Working case, refreshed in current epoch
 // - [x] Working case, refreshed in previous epoch but less than 1hour ago
@@ -1089,7 +1150,7 @@ mod test_spl_stake {
         };
 
         let res = ctx.send_transaction_with_bot(&[ix]).await;
-        assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+        assert_eq!(map_scope_error(res), ScopeError::SplStakeNotRefreshed);
     }
 
     // - [] Fail case, refreshed in more than 1 epoch ago and epoch started less than 1hour ago
@@ -1134,7 +1195,7 @@ mod test_spl_stake {
         };
 
         let res = ctx.send_transaction_with_bot(&[ix]).await;
-        assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+        assert_eq!(map_scope_error(res), ScopeError::SplStakeNotRefreshed);
     }
 
     #[tokio::test]
@@ -1178,7 +1239,7 @@ mod test_spl_stake {
         };
 
         let res = ctx.send_transaction_with_bot(&[ix]).await;
-        assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+        assert_eq!(map_scope_error(res), ScopeError::SplStakeNotRefreshed);
     }
 
     #[tokio::test]
@@ -1222,6 +1283,1629 @@ mod test_spl_stake {
         };
 
         let res = ctx.send_transaction_with_bot(&[ix]).await;
-        assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+        assert_eq!(map_scope_error(res), ScopeError::SplStakeNotRefreshed);
+    }
+}
+
+mod test_raydium_twap {
+    use super::*;
+
+    const TEST_RAYDIUM_TWAP_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomeRaydiumTwapAccount111111111111111111111"),
+        token: 0,
+        price_type: TestOracleType::RaydiumAmmV3Twap(true),
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    #[tokio::test]
+    async fn test_working_refresh_one_raydium_twap() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_RAYDIUM_TWAP_ORACLE]).await;
+
+        let price = Price {
+            value: 2_500_000,
+            exp: 6,
+        };
+        // Change price
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_RAYDIUM_TWAP_ORACLE, &price).await;
+
+        // Refresh
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+
+        accounts.append(
+            &mut utils::get_refresh_list_accounts(&mut ctx, &TEST_RAYDIUM_TWAP_ORACLE).await,
+        );
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: vec![TEST_RAYDIUM_TWAP_ORACLE.token.try_into().unwrap()],
+        };
+
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts: accounts.to_account_metas(None),
+            data: args.data(),
+        };
+
+        ctx.send_transaction_with_bot(&[ix]).await.unwrap();
+
+        // Check price
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        assert_fuzzy_price_eq!(
+            data.prices[TEST_RAYDIUM_TWAP_ORACLE.token].price,
+            price,
+            decimal_wad::decimal::Decimal::from(price) / 10000,
+            "Price {:?}",
+            data.prices[TEST_RAYDIUM_TWAP_ORACLE.token]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_one_raydium_twap_wrong_observation() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_RAYDIUM_TWAP_ORACLE]).await;
+
+        let price = Price {
+            value: 2_500_000,
+            exp: 6,
+        };
+        // Change price
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_RAYDIUM_TWAP_ORACLE, &price).await;
+
+        // Refresh
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+
+        let mut remaining_accounts =
+            utils::get_refresh_list_accounts(&mut ctx, &TEST_RAYDIUM_TWAP_ORACLE).await;
+
+        // Observations of the pool cloned to another account
+        let observation = &mut remaining_accounts[1];
+        let wrong_observation_pk = Pubkey::new_unique();
+        ctx.clone_account(&observation.pubkey, &wrong_observation_pk)
+            .await;
+        observation.pubkey = wrong_observation_pk;
+        accounts.append(&mut remaining_accounts);
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: vec![TEST_RAYDIUM_TWAP_ORACLE.token.try_into().unwrap()],
+        };
+
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts: accounts.to_account_metas(None),
+            data: args.data(),
+        };
+
+        let res = ctx.send_transaction_with_bot(&[ix]).await;
+        assert_eq!(map_scope_error(res), ScopeError::AccountsAndTokenMismatch);
+    }
+}
+
+mod test_constant_product {
+    use solana_program::instruction::AccountMeta;
+    use solana_program_test::BanksClientError;
+
+    use super::*;
+
+    const TEST_RAYDIUM_AMM_V4_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomeRaydiumAmmV4Account11111111111111111111"),
+        token: 0,
+        price_type: TestOracleType::RaydiumAmmV4(true),
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    const TEST_RAYDIUM_CPMM_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomeRaydiumCpmmAccount111111111111111111111"),
+        token: 1,
+        price_type: TestOracleType::RaydiumCpmm(false),
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    const TEST_ORCA_TOKEN_SWAP_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomeTokenSwapAccount11111111111111111111111"),
+        token: 2,
+        price_type: TestOracleType::OrcaTokenSwap(true),
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    const TEST_METEORA_DYNAMIC_AMM_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomeMeteoraDynamicAmmAccount111111111111111"),
+        token: 3,
+        price_type: TestOracleType::MeteoraDynamicAmm(false),
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    async fn refresh_one(
+        ctx: &mut TestContext,
+        feed: &ScopeFeedDefinition,
+        conf: &OracleConf,
+        mut remaining_accounts: Vec<AccountMeta>,
+    ) -> Result<(), BanksClientError> {
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+
+        accounts.append(&mut remaining_accounts);
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: vec![conf.token.try_into().unwrap()],
+        };
+
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts: accounts.to_account_metas(None),
+            data: args.data(),
+        };
+
+        ctx.send_transaction_with_bot(&[ix]).await
+    }
+
+    async fn check_working_refresh_one(conf: OracleConf) {
+        let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![conf]).await;
+
+        let price = Price {
+            value: 2_500_000,
+            exp: 6,
+        };
+        // Change price
+        mock_oracles::set_price(&mut ctx, &feed, &conf, &price).await;
+
+        // Refresh
+        let remaining_accounts = utils::get_refresh_list_accounts(&mut ctx, &conf).await;
+        refresh_one(&mut ctx, &feed, &conf, remaining_accounts)
+            .await
+            .unwrap();
+
+        // Check price
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        assert_fuzzy_price_eq!(
+            data.prices[conf.token].price,
+            price,
+            decimal_wad::decimal::Decimal::from(price) / 1_000_000,
+            "Price {:?}",
+            data.prices[conf.token]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_working_refresh_one_raydium_amm_v4() {
+        check_working_refresh_one(TEST_RAYDIUM_AMM_V4_ORACLE).await;
+    }
+
+    #[tokio::test]
+    async fn test_working_refresh_one_raydium_cpmm() {
+        check_working_refresh_one(TEST_RAYDIUM_CPMM_ORACLE).await;
+    }
+
+    #[tokio::test]
+    async fn test_working_refresh_one_orca_token_swap() {
+        check_working_refresh_one(TEST_ORCA_TOKEN_SWAP_ORACLE).await;
+    }
+
+    #[tokio::test]
+    async fn test_working_refresh_one_meteora_dynamic_amm() {
+        check_working_refresh_one(TEST_METEORA_DYNAMIC_AMM_ORACLE).await;
+    }
+
+    #[tokio::test]
+    async fn test_refresh_one_constant_product_wrong_vault() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_RAYDIUM_AMM_V4_ORACLE]).await;
+
+        let price = Price {
+            value: 2_500_000,
+            exp: 6,
+        };
+        // Change price
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_RAYDIUM_AMM_V4_ORACLE, &price).await;
+
+        let mut remaining_accounts =
+            utils::get_refresh_list_accounts(&mut ctx, &TEST_RAYDIUM_AMM_V4_ORACLE).await;
+
+        // Coin vault of the pool cloned to another account
+        let coin_vault = &mut remaining_accounts[1];
+        let wrong_vault_pk = Pubkey::new_unique();
+        ctx.clone_account(&coin_vault.pubkey, &wrong_vault_pk).await;
+        coin_vault.pubkey = wrong_vault_pk;
+
+        let res = refresh_one(
+            &mut ctx,
+            &feed,
+            &TEST_RAYDIUM_AMM_V4_ORACLE,
+            remaining_accounts,
+        )
+        .await;
+        assert_eq!(map_scope_error(res), ScopeError::AccountsAndTokenMismatch);
+    }
+}
+
+mod test_constant_product_lp {
+    use super::*;
+
+    const TEST_TOKEN_A_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomePythPriceAccountA1111111111111111111111"),
+        token: 0,
+        price_type: TestOracleType::Pyth,
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    const TEST_TOKEN_B_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomePythPriceAccountB1111111111111111111111"),
+        token: 1,
+        price_type: TestOracleType::Pyth,
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    const TEST_LP_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomeConstantProductPairAccount1111111111111"),
+        token: 2,
+        price_type: TestOracleType::ConstantProductLpToken(0, 1),
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    #[tokio::test]
+    async fn test_working_refresh_one_constant_product_lp() {
+        let (mut ctx, feed) = fixtures::setup_scope(
+            DEFAULT_FEED_NAME,
+            vec![TEST_TOKEN_A_ORACLE, TEST_TOKEN_B_ORACLE, TEST_LP_ORACLE],
+        )
+        .await;
+
+        // Token A is worth 4 and token B 1
+        let price_a = Price {
+            value: 400_000_000,
+            exp: 8,
+        };
+        let price_b = Price {
+            value: 100_000_000,
+            exp: 8,
+        };
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_TOKEN_A_ORACLE, &price_a).await;
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_TOKEN_B_ORACLE, &price_b).await;
+        // LP token worth 1.5 if both tokens were worth 1
+        let lp_price = Price {
+            value: 1_500_000,
+            exp: 6,
+        };
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_LP_ORACLE, &lp_price).await;
+
+        // Refresh the token prices before the LP token one
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+
+        for conf in [TEST_TOKEN_A_ORACLE, TEST_TOKEN_B_ORACLE, TEST_LP_ORACLE] {
+            accounts.append(&mut utils::get_refresh_list_accounts(&mut ctx, &conf).await);
+        }
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: vec![
+                TEST_TOKEN_A_ORACLE.token.try_into().unwrap(),
+                TEST_TOKEN_B_ORACLE.token.try_into().unwrap(),
+                TEST_LP_ORACLE.token.try_into().unwrap(),
+            ],
+        };
+
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts: accounts.to_account_metas(None),
+            data: args.data(),
+        };
+
+        ctx.send_transaction_with_bot(&[ix]).await.unwrap();
+
+        // Check price, scaled by the geometric mean of the token prices (sqrt(4 * 1) = 2)
+        let expected_price = Price {
+            value: 3_000_000,
+            exp: 6,
+        };
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        assert_fuzzy_price_eq!(
+            data.prices[TEST_LP_ORACLE.token].price,
+            expected_price,
+            decimal_wad::decimal::Decimal::from(expected_price) / 1_000_000,
+            "Price {:?}",
+            data.prices[TEST_LP_ORACLE.token]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_one_constant_product_lp_wrong_lp_mint() {
+        let (mut ctx, feed) = fixtures::setup_scope(
+            DEFAULT_FEED_NAME,
+            vec![TEST_TOKEN_A_ORACLE, TEST_TOKEN_B_ORACLE, TEST_LP_ORACLE],
+        )
+        .await;
+
+        let lp_price = Price {
+            value: 1_500_000,
+            exp: 6,
+        };
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_LP_ORACLE, &lp_price).await;
+
+        // Refresh
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+
+        let mut remaining_accounts =
+            utils::get_refresh_list_accounts(&mut ctx, &TEST_LP_ORACLE).await;
+
+        // LP mint cloned to another account
+        let lp_mint = remaining_accounts.last_mut().unwrap();
+        let wrong_lp_mint_pk = Pubkey::new_unique();
+        ctx.clone_account(&lp_mint.pubkey, &wrong_lp_mint_pk).await;
+        lp_mint.pubkey = wrong_lp_mint_pk;
+        accounts.append(&mut remaining_accounts);
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: vec![TEST_LP_ORACLE.token.try_into().unwrap()],
+        };
+
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts: accounts.to_account_metas(None),
+            data: args.data(),
+        };
+
+        let res = ctx.send_transaction_with_bot(&[ix]).await;
+        assert_eq!(map_scope_error(res), ScopeError::AccountsAndTokenMismatch);
+    }
+}
+
+mod test_klend_ctoken {
+    use super::*;
+
+    const TEST_KLEND_CTOKEN_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomeKLendReserveAccount11111111111111111111"),
+        token: 0,
+        price_type: TestOracleType::KLendCToken,
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    #[tokio::test]
+    async fn test_working_refresh_one_klend_ctoken() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_KLEND_CTOKEN_ORACLE]).await;
+
+        let price = Price {
+            value: 1_250_000,
+            exp: 6,
+        };
+        // Change price
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_KLEND_CTOKEN_ORACLE, &price).await;
+
+        // Refresh
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+
+        accounts.append(
+            &mut utils::get_refresh_list_accounts(&mut ctx, &TEST_KLEND_CTOKEN_ORACLE).await,
+        );
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: vec![TEST_KLEND_CTOKEN_ORACLE.token.try_into().unwrap()],
+        };
+
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts: accounts.to_account_metas(None),
+            data: args.data(),
+        };
+
+        ctx.send_transaction_with_bot(&[ix]).await.unwrap();
+
+        // Check price
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        assert_fuzzy_price_eq!(
+            data.prices[TEST_KLEND_CTOKEN_ORACLE.token].price,
+            price,
+            decimal_wad::decimal::Decimal::from(price) / 1_000_000,
+            "Price {:?}",
+            data.prices[TEST_KLEND_CTOKEN_ORACLE.token]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_one_klend_ctoken_wrong_owner() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_KLEND_CTOKEN_ORACLE]).await;
+
+        let price = Price {
+            value: 1_250_000,
+            exp: 6,
+        };
+        // Change price
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_KLEND_CTOKEN_ORACLE, &price).await;
+
+        // Same reserve data owned by another program
+        ctx.clone_account_with_different_owner(
+            TEST_KLEND_CTOKEN_ORACLE.pubkey,
+            &TEST_KLEND_CTOKEN_ORACLE.pubkey,
+            &Pubkey::new_unique(),
+        )
+        .await;
+
+        // Refresh
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+
+        accounts.append(
+            &mut utils::get_refresh_list_accounts(&mut ctx, &TEST_KLEND_CTOKEN_ORACLE).await,
+        );
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: vec![TEST_KLEND_CTOKEN_ORACLE.token.try_into().unwrap()],
+        };
+
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts: accounts.to_account_metas(None),
+            data: args.data(),
+        };
+
+        let res = ctx.send_transaction_with_bot(&[ix]).await;
+        assert_eq!(map_scope_error(res), ScopeError::UnexpectedAccount);
+    }
+}
+
+mod test_solend_rates {
+    use super::*;
+
+    const TEST_SOLEND_BORROW_RATE_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomeSoLendReserveAccount1111111111111111111"),
+        token: 0,
+        price_type: TestOracleType::SolendBorrowRate,
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    const TEST_SOLEND_SUPPLY_RATE_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomeSoLendReserveAccount1111111111111111111"),
+        token: 1,
+        price_type: TestOracleType::SolendSupplyRate,
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    #[tokio::test]
+    async fn test_working_refresh_solend_rates() {
+        let (mut ctx, feed) = fixtures::setup_scope(
+            DEFAULT_FEED_NAME,
+            vec![
+                TEST_SOLEND_BORROW_RATE_ORACLE,
+                TEST_SOLEND_SUPPLY_RATE_ORACLE,
+            ],
+        )
+        .await;
+
+        // 25% utilization rate
+        let utilization_rate = Price { value: 25, exp: 2 };
+        // Change price (both entries read the same reserve)
+        mock_oracles::set_price(
+            &mut ctx,
+            &feed,
+            &TEST_SOLEND_BORROW_RATE_ORACLE,
+            &utilization_rate,
+        )
+        .await;
+
+        // Refresh
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+
+        for conf in [
+            TEST_SOLEND_BORROW_RATE_ORACLE,
+            TEST_SOLEND_SUPPLY_RATE_ORACLE,
+        ] {
+            accounts.append(&mut utils::get_refresh_list_accounts(&mut ctx, &conf).await);
+        }
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: vec![
+                TEST_SOLEND_BORROW_RATE_ORACLE.token.try_into().unwrap(),
+                TEST_SOLEND_SUPPLY_RATE_ORACLE.token.try_into().unwrap(),
+            ],
+        };
+
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts: accounts.to_account_metas(None),
+            data: args.data(),
+        };
+
+        ctx.send_transaction_with_bot(&[ix]).await.unwrap();
+
+        // Check rates, the borrow rate is the utilization rate and all of it goes to the depositors
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        let borrow_rate = utilization_rate;
+        assert_fuzzy_price_eq!(
+            data.prices[TEST_SOLEND_BORROW_RATE_ORACLE.token].price,
+            borrow_rate,
+            decimal_wad::decimal::Decimal::from(borrow_rate) / 1_000_000,
+            "Price {:?}",
+            data.prices[TEST_SOLEND_BORROW_RATE_ORACLE.token]
+        );
+        let supply_rate = Price { value: 625, exp: 4 };
+        assert_fuzzy_price_eq!(
+            data.prices[TEST_SOLEND_SUPPLY_RATE_ORACLE.token].price,
+            supply_rate,
+            decimal_wad::decimal::Decimal::from(supply_rate) / 1_000_000,
+            "Price {:?}",
+            data.prices[TEST_SOLEND_SUPPLY_RATE_ORACLE.token]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_map_solend_rate_wrong_owner() {
+        let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+        // Reserve data owned by another program
+        mock_oracles::set_price(
+            &mut ctx,
+            &feed,
+            &TEST_SOLEND_BORROW_RATE_ORACLE,
+            &Price::default(),
+        )
+        .await;
+        ctx.clone_account_with_different_owner(
+            TEST_SOLEND_BORROW_RATE_ORACLE.pubkey,
+            &TEST_SOLEND_BORROW_RATE_ORACLE.pubkey,
+            &Pubkey::new_unique(),
+        )
+        .await;
+
+        // The reserve owner is only checked when mapping the entry
+        let ix = operations::update_oracle_mapping_ix(&ctx, &feed, &TEST_SOLEND_BORROW_RATE_ORACLE);
+        let res = ctx.send_transaction(&[ix]).await;
+        assert_eq!(map_scope_error(res), ScopeError::UnexpectedAccount);
+    }
+}
+
+mod test_chainlink {
+    use super::*;
+
+    const TEST_CHAINLINK_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomeChainLinkTransmissionsAccount1111111111"),
+        token: 0,
+        price_type: TestOracleType::Chainlink,
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    #[tokio::test]
+    async fn test_working_refresh_one_chainlink() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_CHAINLINK_ORACLE]).await;
+
+        let price = Price {
+            value: 14_250_000_000,
+            exp: 8,
+        };
+        // Change price
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_CHAINLINK_ORACLE, &price).await;
+
+        // Refresh
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+
+        accounts
+            .append(&mut utils::get_refresh_list_accounts(&mut ctx, &TEST_CHAINLINK_ORACLE).await);
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: vec![TEST_CHAINLINK_ORACLE.token.try_into().unwrap()],
+        };
+
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts: accounts.to_account_metas(None),
+            data: args.data(),
+        };
+
+        ctx.send_transaction_with_bot(&[ix]).await.unwrap();
+
+        // Check price
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        assert_eq!(data.prices[TEST_CHAINLINK_ORACLE.token].price, price);
+    }
+
+    #[tokio::test]
+    async fn test_fail_refresh_one_stale_chainlink() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_CHAINLINK_ORACLE]).await;
+
+        let price = Price {
+            value: 14_250_000_000,
+            exp: 8,
+        };
+        // Change price
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_CHAINLINK_ORACLE, &price).await;
+
+        // Latest round is more than 10 minutes old
+        ctx.fast_forward_seconds(11 * 60).await;
+
+        // Refresh
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+
+        accounts
+            .append(&mut utils::get_refresh_list_accounts(&mut ctx, &TEST_CHAINLINK_ORACLE).await);
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: vec![TEST_CHAINLINK_ORACLE.token.try_into().unwrap()],
+        };
+
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts: accounts.to_account_metas(None),
+            data: args.data(),
+        };
+
+        let res = ctx.send_transaction_with_bot(&[ix]).await;
+        assert_eq!(map_scope_error(res), ScopeError::ChainlinkPriceStale);
+    }
+}
+
+mod test_orderbooks {
+    use super::*;
+
+    const TEST_OPENBOOK_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomeBookV2MarketAccount11111111111111111111"),
+        token: 0,
+        price_type: TestOracleType::OpenBookV2MidPrice,
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    const TEST_PHOENIX_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomePhoenixMarketAccount1111111111111111111"),
+        token: 1,
+        price_type: TestOracleType::PhoenixMidPrice,
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    async fn check_working_refresh_one(conf: OracleConf) {
+        let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![conf]).await;
+
+        let price = Price {
+            value: 20_500_000,
+            exp: 6,
+        };
+        // Change price, the best bid and ask are 0.001 away from the mid-price
+        mock_oracles::set_price(&mut ctx, &feed, &conf, &price).await;
+
+        // Refresh
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+
+        accounts.append(&mut utils::get_refresh_list_accounts(&mut ctx, &conf).await);
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: vec![conf.token.try_into().unwrap()],
+        };
+
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts: accounts.to_account_metas(None),
+            data: args.data(),
+        };
+
+        ctx.send_transaction_with_bot(&[ix]).await.unwrap();
+
+        // Check price and best bid and ask
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        let dated_price = data.prices[conf.token];
+        assert_fuzzy_price_eq!(
+            dated_price.price,
+            price,
+            decimal_wad::decimal::Decimal::from(price) / 1_000_000,
+            "Price {:?}",
+            dated_price
+        );
+        let [bid, ask] = dated_price.bid_ask.map(|value| Price {
+            value,
+            exp: dated_price.price.exp,
+        });
+        let expected_bid = Price {
+            value: 20_499,
+            exp: 3,
+        };
+        let expected_ask = Price {
+            value: 20_501,
+            exp: 3,
+        };
+        assert_fuzzy_price_eq!(
+            bid,
+            expected_bid,
+            decimal_wad::decimal::Decimal::from(expected_bid) / 1_000_000,
+            "Best bid {:?}",
+            bid
+        );
+        assert_fuzzy_price_eq!(
+            ask,
+            expected_ask,
+            decimal_wad::decimal::Decimal::from(expected_ask) / 1_000_000,
+            "Best ask {:?}",
+            ask
+        );
+    }
+
+    #[tokio::test]
+    async fn test_working_refresh_one_openbook_v2() {
+        check_working_refresh_one(TEST_OPENBOOK_ORACLE).await;
+    }
+
+    #[tokio::test]
+    async fn test_working_refresh_one_phoenix() {
+        check_working_refresh_one(TEST_PHOENIX_ORACLE).await;
+    }
+
+    #[tokio::test]
+    async fn test_refresh_one_openbook_v2_swapped_book_sides() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_OPENBOOK_ORACLE]).await;
+
+        let price = Price {
+            value: 20_500_000,
+            exp: 6,
+        };
+        // Change price
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_OPENBOOK_ORACLE, &price).await;
+
+        // Refresh
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+
+        // Asks given as bids and bids as asks
+        let mut remaining_accounts =
+            utils::get_refresh_list_accounts(&mut ctx, &TEST_OPENBOOK_ORACLE).await;
+        remaining_accounts.swap(1, 2);
+        accounts.append(&mut remaining_accounts);
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: vec![TEST_OPENBOOK_ORACLE.token.try_into().unwrap()],
+        };
+
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts: accounts.to_account_metas(None),
+            data: args.data(),
+        };
+
+        let res = ctx.send_transaction_with_bot(&[ix]).await;
+        assert_eq!(map_scope_error(res), ScopeError::AccountsAndTokenMismatch);
+    }
+}
+
+mod test_signed_price {
+    use solana_program_test::BanksClientError;
+    use solana_sdk::signature::Keypair;
+
+    use super::*;
+    use crate::mock_oracles::signed_price::{
+        get_signed_price, get_signed_price_ix, test_price_publisher,
+    };
+
+    const TEST_SIGNED_PRICE_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomeSignedPricePubLishersAccount11111111111"),
+        token: 0,
+        price_type: TestOracleType::SignedPrice,
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    const PRICE: Price = Price {
+        value: 1_234_500,
+        exp: 6,
+    };
+
+    /// Refresh the entry with a price signed by `publisher` at `unix_timestamp`
+    async fn signed_refresh(
+        ctx: &mut TestContext,
+        feed: &ScopeFeedDefinition,
+        publisher: &Keypair,
+        unix_timestamp: u64,
+    ) -> Result<(), BanksClientError> {
+        let signed_price = get_signed_price(feed, &TEST_SIGNED_PRICE_ORACLE, PRICE, unix_timestamp);
+
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+        accounts
+            .append(&mut utils::get_refresh_list_accounts(ctx, &TEST_SIGNED_PRICE_ORACLE).await);
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: vec![TEST_SIGNED_PRICE_ORACLE.token.try_into().unwrap()],
+        };
+
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts,
+            data: args.data(),
+        };
+
+        ctx.send_transaction_with_bot(&[get_signed_price_ix(publisher, &signed_price), ix])
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_working_refresh_one_signed_price() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_SIGNED_PRICE_ORACLE]).await;
+
+        let now = ctx.get_now_timestamp().await;
+        signed_refresh(&mut ctx, &feed, &test_price_publisher(), now)
+            .await
+            .unwrap();
+
+        // Check price
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        let dated_price = data.prices[TEST_SIGNED_PRICE_ORACLE.token];
+        assert_eq!(dated_price.price, PRICE);
+        assert_eq!(dated_price.unix_timestamp, now);
+    }
+
+    #[tokio::test]
+    async fn test_fail_refresh_one_replayed_signed_price() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_SIGNED_PRICE_ORACLE]).await;
+
+        let now = ctx.get_now_timestamp().await;
+        signed_refresh(&mut ctx, &feed, &test_price_publisher(), now)
+            .await
+            .unwrap();
+
+        // The same message cannot be used twice
+        ctx.fast_forward_seconds(10).await;
+        let res = signed_refresh(&mut ctx, &feed, &test_price_publisher(), now).await;
+        assert_eq!(map_scope_error(res), ScopeError::BadTimestamp);
+    }
+
+    #[tokio::test]
+    async fn test_fail_refresh_one_unknown_publisher() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_SIGNED_PRICE_ORACLE]).await;
+
+        let now = ctx.get_now_timestamp().await;
+        let res = signed_refresh(&mut ctx, &feed, &Keypair::new(), now).await;
+        assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+    }
+}
+
+mod test_fixed_rate {
+    use scope::oracles::fixed_rate::SECONDS_PER_YEAR;
+    use solana_sdk::signer::Signer;
+
+    use super::*;
+
+    const TEST_FIXED_RATE_ORACLE: OracleConf = OracleConf {
+        pubkey: scope::ID,
+        token: 0,
+        price_type: TestOracleType::FixedRate,
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    async fn refresh_one(ctx: &mut TestContext, feed: &ScopeFeedDefinition) {
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+        accounts.append(&mut utils::get_refresh_list_accounts(ctx, &TEST_FIXED_RATE_ORACLE).await);
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: vec![TEST_FIXED_RATE_ORACLE.token.try_into().unwrap()],
+        };
+
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts,
+            data: args.data(),
+        };
+
+        ctx.send_transaction_with_bot(&[ix]).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_working_refresh_one_fixed_rate() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_FIXED_RATE_ORACLE]).await;
+
+        refresh_one(&mut ctx, &feed).await;
+
+        // Check price
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        assert_eq!(
+            data.prices[TEST_FIXED_RATE_ORACLE.token].price,
+            TEST_FIXED_RATE_START_PRICE
+        );
+    }
+
+    #[tokio::test]
+    async fn test_working_refresh_one_updated_fixed_rate() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_FIXED_RATE_ORACLE]).await;
+
+        // Accrue 5% a year from now
+        let now = ctx.get_now_timestamp().await;
+        let accounts = scope::accounts::UpdateFixedRate {
+            admin: ctx.admin.pubkey(),
+            configuration: feed.conf,
+            oracle_mappings: feed.mapping,
+        };
+        let args = scope::instruction::UpdateFixedRate {
+            token: TEST_FIXED_RATE_ORACLE.token.try_into().unwrap(),
+            feed_name: feed.feed_name.clone(),
+            start_price_value: TEST_FIXED_RATE_START_PRICE.value,
+            start_price_exp: TEST_FIXED_RATE_START_PRICE.exp.try_into().unwrap(),
+            start_timestamp: now,
+            apy_bps: 500,
+        };
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts: accounts.to_account_metas(None),
+            data: args.data(),
+        };
+        ctx.send_transaction(&[ix]).await.unwrap();
+
+        ctx.fast_forward_seconds(SECONDS_PER_YEAR).await;
+        refresh_one(&mut ctx, &feed).await;
+
+        // Check price, compounded every second: e^0.05 ~= 1.051271
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        let expected_price = Price {
+            value: 105_127_100,
+            exp: 8,
+        };
+        assert_fuzzy_price_eq!(
+            data.prices[TEST_FIXED_RATE_ORACLE.token].price,
+            expected_price,
+            decimal_wad::decimal::Decimal::from(expected_price) / 1_000_000,
+            "Accrued price {:?}",
+            data.prices[TEST_FIXED_RATE_ORACLE.token].price
+        );
+    }
+}
+
+mod test_capped_floored {
+    use solana_program_test::BanksClientError;
+
+    use super::*;
+
+    const TEST_SOURCE_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomePythPriceAccount11111111111111111111111"),
+        token: 0,
+        price_type: TestOracleType::Pyth,
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    const TEST_CAPPED_FLOORED_ORACLE: OracleConf = OracleConf {
+        pubkey: scope::ID,
+        token: 1,
+        price_type: TestOracleType::CappedFloored(0),
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    async fn refresh_list(
+        ctx: &mut TestContext,
+        feed: &ScopeFeedDefinition,
+        confs: &[OracleConf],
+    ) -> Result<(), BanksClientError> {
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+        for conf in confs {
+            accounts.append(&mut utils::get_refresh_list_accounts(ctx, conf).await);
+        }
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: confs
+                .iter()
+                .map(|conf| conf.token.try_into().unwrap())
+                .collect(),
+        };
+
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts,
+            data: args.data(),
+        };
+
+        ctx.send_transaction_with_bot(&[ix]).await
+    }
+
+    #[tokio::test]
+    async fn test_working_refresh_one_capped_floored() {
+        let (mut ctx, feed) = fixtures::setup_scope(
+            DEFAULT_FEED_NAME,
+            vec![TEST_SOURCE_ORACLE, TEST_CAPPED_FLOORED_ORACLE],
+        )
+        .await;
+
+        for (source_price, expected_price) in [
+            (Price { value: 110, exp: 2 }, TEST_PRICE_CAP),
+            (Price { value: 90, exp: 2 }, TEST_PRICE_FLOOR),
+            (Price { value: 100, exp: 2 }, Price { value: 100, exp: 2 }),
+        ] {
+            // Change price
+            mock_oracles::set_price(&mut ctx, &feed, &TEST_SOURCE_ORACLE, &source_price).await;
+
+            // Refresh the source before the clamped entry
+            refresh_list(
+                &mut ctx,
+                &feed,
+                &[TEST_SOURCE_ORACLE, TEST_CAPPED_FLOORED_ORACLE],
+            )
+            .await
+            .unwrap();
+
+            // Check price
+            let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+            assert_eq!(
+                data.prices[TEST_CAPPED_FLOORED_ORACLE.token].price,
+                expected_price
+            );
+            assert_eq!(
+                data.prices[TEST_CAPPED_FLOORED_ORACLE.token].unix_timestamp,
+                data.prices[TEST_SOURCE_ORACLE.token].unix_timestamp
+            );
+
+            ctx.fast_forward_seconds(10).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fail_refresh_one_capped_floored_without_source_price() {
+        let (mut ctx, feed) = fixtures::setup_scope(
+            DEFAULT_FEED_NAME,
+            vec![TEST_SOURCE_ORACLE, TEST_CAPPED_FLOORED_ORACLE],
+        )
+        .await;
+
+        // The source entry has never been refreshed
+        let res = refresh_list(&mut ctx, &feed, &[TEST_CAPPED_FLOORED_ORACLE]).await;
+        assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+    }
+}
+
+mod test_min_max {
+    use solana_program_test::BanksClientError;
+
+    use super::*;
+
+    const TEST_FIRST_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomePythPriceAccountA1111111111111111111111"),
+        token: 0,
+        price_type: TestOracleType::Pyth,
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    const TEST_SECOND_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomePythPriceAccountB1111111111111111111111"),
+        token: 1,
+        price_type: TestOracleType::Pyth,
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    const TEST_MIN_ORACLE: OracleConf = OracleConf {
+        pubkey: scope::ID,
+        token: 2,
+        price_type: TestOracleType::MinOfTwo(0, 1),
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    const TEST_MAX_ORACLE: OracleConf = OracleConf {
+        pubkey: scope::ID,
+        token: 3,
+        price_type: TestOracleType::MaxOfTwo(0, 1),
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    async fn refresh_list(
+        ctx: &mut TestContext,
+        feed: &ScopeFeedDefinition,
+        confs: &[OracleConf],
+    ) -> Result<(), BanksClientError> {
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+        for conf in confs {
+            accounts.append(&mut utils::get_refresh_list_accounts(ctx, conf).await);
+        }
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: confs
+                .iter()
+                .map(|conf| conf.token.try_into().unwrap())
+                .collect(),
+        };
+
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts,
+            data: args.data(),
+        };
+
+        ctx.send_transaction_with_bot(&[ix]).await
+    }
+
+    #[tokio::test]
+    async fn test_working_refresh_min_max() {
+        let (mut ctx, feed) = fixtures::setup_scope(
+            DEFAULT_FEED_NAME,
+            vec![
+                TEST_FIRST_ORACLE,
+                TEST_SECOND_ORACLE,
+                TEST_MIN_ORACLE,
+                TEST_MAX_ORACLE,
+            ],
+        )
+        .await;
+
+        // Prices with different exponents
+        let first_price = Price {
+            value: 300_000_000,
+            exp: 8,
+        };
+        let second_price = Price {
+            value: 2_000_000,
+            exp: 6,
+        };
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_FIRST_ORACLE, &first_price).await;
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_SECOND_ORACLE, &second_price).await;
+
+        // Refresh the inputs before the min and max entries
+        refresh_list(
+            &mut ctx,
+            &feed,
+            &[
+                TEST_FIRST_ORACLE,
+                TEST_SECOND_ORACLE,
+                TEST_MIN_ORACLE,
+                TEST_MAX_ORACLE,
+            ],
+        )
+        .await
+        .unwrap();
+
+        // Check prices
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        assert_eq!(data.prices[TEST_MIN_ORACLE.token].price, second_price);
+        assert_eq!(data.prices[TEST_MAX_ORACLE.token].price, first_price);
+    }
+
+    #[tokio::test]
+    async fn test_fail_refresh_one_min_without_input_price() {
+        let (mut ctx, feed) = fixtures::setup_scope(
+            DEFAULT_FEED_NAME,
+            vec![TEST_FIRST_ORACLE, TEST_SECOND_ORACLE, TEST_MIN_ORACLE],
+        )
+        .await;
+
+        let price = Price {
+            value: 300_000_000,
+            exp: 8,
+        };
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_FIRST_ORACLE, &price).await;
+
+        // Only the first input is refreshed
+        refresh_list(&mut ctx, &feed, &[TEST_FIRST_ORACLE])
+            .await
+            .unwrap();
+        let res = refresh_list(&mut ctx, &feed, &[TEST_MIN_ORACLE]).await;
+        assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+    }
+}
+
+mod test_index_price {
+    use scope::IndexDefinition;
+    use solana_program_test::BanksClientError;
+
+    use super::*;
+
+    const TEST_FIRST_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomePythPriceAccountA1111111111111111111111"),
+        token: 0,
+        price_type: TestOracleType::Pyth,
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    const TEST_SECOND_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomePythPriceAccountB1111111111111111111111"),
+        token: 1,
+        price_type: TestOracleType::Pyth,
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    const TEST_INDEX_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomeBasketDefinitionAccount1111111111111111"),
+        token: 2,
+        price_type: TestOracleType::IndexPrice(0, 1),
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    async fn refresh_list(
+        ctx: &mut TestContext,
+        feed: &ScopeFeedDefinition,
+        confs: &[OracleConf],
+    ) -> Result<(), BanksClientError> {
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+        for conf in confs {
+            accounts.append(&mut utils::get_refresh_list_accounts(ctx, conf).await);
+        }
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: confs
+                .iter()
+                .map(|conf| conf.token.try_into().unwrap())
+                .collect(),
+        };
+
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts,
+            data: args.data(),
+        };
+
+        ctx.send_transaction_with_bot(&[ix]).await
+    }
+
+    async fn set_input_prices(ctx: &mut TestContext, feed: &ScopeFeedDefinition) {
+        let first_price = Price {
+            value: 200_000_000,
+            exp: 8,
+        };
+        let second_price = Price {
+            value: 3_000_000,
+            exp: 6,
+        };
+        mock_oracles::set_price(ctx, feed, &TEST_FIRST_ORACLE, &first_price).await;
+        mock_oracles::set_price(ctx, feed, &TEST_SECOND_ORACLE, &second_price).await;
+        refresh_list(ctx, feed, &[TEST_FIRST_ORACLE, TEST_SECOND_ORACLE])
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_working_refresh_one_index_price() {
+        let (mut ctx, feed) = fixtures::setup_scope(
+            DEFAULT_FEED_NAME,
+            vec![TEST_FIRST_ORACLE, TEST_SECOND_ORACLE, TEST_INDEX_ORACLE],
+        )
+        .await;
+
+        set_input_prices(&mut ctx, &feed).await;
+        refresh_list(&mut ctx, &feed, &[TEST_INDEX_ORACLE])
+            .await
+            .unwrap();
+
+        // Check price, average of 2 and 3
+        let expected_price = Price {
+            value: 2_500_000,
+            exp: 6,
+        };
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        assert_fuzzy_price_eq!(
+            data.prices[TEST_INDEX_ORACLE.token].price,
+            expected_price,
+            decimal_wad::decimal::Decimal::from(expected_price) / 1_000_000,
+            "Price {:?}",
+            data.prices[TEST_INDEX_ORACLE.token]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fail_refresh_one_index_price_of_another_entry() {
+        let (mut ctx, feed) = fixtures::setup_scope(
+            DEFAULT_FEED_NAME,
+            vec![TEST_FIRST_ORACLE, TEST_SECOND_ORACLE, TEST_INDEX_ORACLE],
+        )
+        .await;
+
+        set_input_prices(&mut ctx, &feed).await;
+
+        // The mapped definition is changed to the one of another entry
+        let mut index_definition: IndexDefinition = ctx
+            .get_zero_copy_account(&TEST_INDEX_ORACLE.pubkey)
+            .await
+            .unwrap();
+        index_definition.index = 3;
+        ctx.set_zero_copy_account(&TEST_INDEX_ORACLE.pubkey, &index_definition);
+
+        let res = refresh_list(&mut ctx, &feed, &[TEST_INDEX_ORACLE]).await;
+        assert_eq!(map_scope_error(res), ScopeError::UnexpectedAccount);
+    }
+}
+
+mod test_inverse_price {
+    use solana_program_test::BanksClientError;
+
+    use super::*;
+
+    const TEST_SOURCE_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomePythPriceAccount11111111111111111111111"),
+        token: 0,
+        price_type: TestOracleType::Pyth,
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    const TEST_INVERSE_ORACLE: OracleConf = OracleConf {
+        pubkey: scope::ID,
+        token: 1,
+        price_type: TestOracleType::InversePrice(0),
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    async fn refresh_list(
+        ctx: &mut TestContext,
+        feed: &ScopeFeedDefinition,
+        confs: &[OracleConf],
+    ) -> Result<(), BanksClientError> {
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+        for conf in confs {
+            accounts.append(&mut utils::get_refresh_list_accounts(ctx, conf).await);
+        }
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: confs
+                .iter()
+                .map(|conf| conf.token.try_into().unwrap())
+                .collect(),
+        };
+
+        let ix = Instruction {
+            program_id: scope::id(),
+            accounts,
+            data: args.data(),
+        };
+
+        ctx.send_transaction_with_bot(&[ix]).await
+    }
+
+    #[tokio::test]
+    async fn test_working_refresh_one_inverse_price() {
+        let (mut ctx, feed) = fixtures::setup_scope(
+            DEFAULT_FEED_NAME,
+            vec![TEST_SOURCE_ORACLE, TEST_INVERSE_ORACLE],
+        )
+        .await;
+
+        // Change price
+        let price = Price {
+            value: 800_000_000,
+            exp: 8,
+        };
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_SOURCE_ORACLE, &price).await;
+
+        // Refresh the source before its inverse
+        refresh_list(&mut ctx, &feed, &[TEST_SOURCE_ORACLE, TEST_INVERSE_ORACLE])
+            .await
+            .unwrap();
+
+        // Check price, 1 / 8
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        assert_eq!(
+            data.prices[TEST_INVERSE_ORACLE.token].price,
+            Price { value: 125, exp: 3 }
+        );
+        assert_eq!(
+            data.prices[TEST_INVERSE_ORACLE.token].unix_timestamp,
+            data.prices[TEST_SOURCE_ORACLE.token].unix_timestamp
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fail_refresh_one_inverse_price_without_source_price() {
+        let (mut ctx, feed) = fixtures::setup_scope(
+            DEFAULT_FEED_NAME,
+            vec![TEST_SOURCE_ORACLE, TEST_INVERSE_ORACLE],
+        )
+        .await;
+
+        // The source entry has never been refreshed
+        let res = refresh_list(&mut ctx, &feed, &[TEST_INVERSE_ORACLE]).await;
+        assert_eq!(map_scope_error(res), ScopeError::PriceNotValid);
+    }
+}
+
+mod test_account_field {
+    use scope::AccountFieldReader;
+    use solana_program::instruction::AccountMeta;
+
+    use super::*;
+
+    const TEST_ACCOUNT_FIELD_ORACLE: OracleConf = OracleConf {
+        pubkey: pubkey!("SomeAccountReaderAccount1111111111111111111"),
+        token: 0,
+        price_type: TestOracleType::AccountField,
+        twap_enabled: false,
+        twap_source: None,
+    };
+
+    fn refresh_one_ix(
+        feed: &ScopeFeedDefinition,
+        mut refresh_accounts: Vec<AccountMeta>,
+    ) -> Instruction {
+        let mut accounts = scope::accounts::RefreshList {
+            oracle_prices: feed.prices,
+            oracle_mappings: feed.mapping,
+            oracle_twaps: feed.twaps,
+            instruction_sysvar_account_info: SYSVAR_INSTRUCTIONS_ID,
+        }
+        .to_account_metas(None);
+        accounts.append(&mut refresh_accounts);
+
+        let args = scope::instruction::RefreshPriceList {
+            tokens: vec![TEST_ACCOUNT_FIELD_ORACLE.token.try_into().unwrap()],
+        };
+
+        Instruction {
+            program_id: scope::id(),
+            accounts,
+            data: args.data(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_working_refresh_one_account_field() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_ACCOUNT_FIELD_ORACLE]).await;
+
+        let price = Price {
+            value: 1_050_000_000,
+            exp: 9,
+        };
+        // Change price
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_ACCOUNT_FIELD_ORACLE, &price).await;
+
+        // Refresh
+        let refresh_accounts =
+            utils::get_refresh_list_accounts(&mut ctx, &TEST_ACCOUNT_FIELD_ORACLE).await;
+        ctx.send_transaction_with_bot(&[refresh_one_ix(&feed, refresh_accounts)])
+            .await
+            .unwrap();
+
+        // Check price
+        let data: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+        assert_eq!(data.prices[TEST_ACCOUNT_FIELD_ORACLE.token].price, price);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_one_account_field_wrong_owner() {
+        let (mut ctx, feed) =
+            fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_ACCOUNT_FIELD_ORACLE]).await;
+
+        let price = Price {
+            value: 1_050_000_000,
+            exp: 9,
+        };
+        // Change price
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_ACCOUNT_FIELD_ORACLE, &price).await;
+
+        // The account read changes owner
+        let reader: AccountFieldReader = ctx
+            .get_zero_copy_account(&TEST_ACCOUNT_FIELD_ORACLE.pubkey)
+            .await
+            .unwrap();
+        ctx.clone_account_with_different_owner(
+            reader.account,
+            &reader.account,
+            &Pubkey::new_unique(),
+        )
+        .await;
+
+        // Refresh
+        let refresh_accounts =
+            utils::get_refresh_list_accounts(&mut ctx, &TEST_ACCOUNT_FIELD_ORACLE).await;
+        let res = ctx
+            .send_transaction_with_bot(&[refresh_one_ix(&feed, refresh_accounts)])
+            .await;
+        assert_eq!(map_scope_error(res), ScopeError::UnexpectedAccount);
     }
 }
@@ -2,13 +2,19 @@ mod common;
 
 use anchor_lang::{prelude::Pubkey, InstructionData, ToAccountMetas};
 use common::*;
-use scope::Price;
+use scope::{oracles::OracleType, IndexDefinition, OracleMappings, Price, ScopeError};
 use solana_program::instruction::Instruction;
 use solana_program_test::tokio;
 use solana_sdk::{pubkey, signature::Keypair, signer::Signer};
 use types::*;
 
-use crate::{common::utils::AnchorErrorCode, utils::map_anchor_error};
+use crate::{
+    common::{
+        client::refresh_simple_oracle_ix,
+        utils::{map_scope_error, AnchorErrorCode},
+    },
+    utils::map_anchor_error,
+};
 
 const TEST_PYTH_ORACLE: OracleConf = OracleConf {
     pubkey: pubkey!("SomePythPriceAccount11111111111111111111111"),
@@ -22,6 +28,10 @@ const TEST_PYTH_ORACLE: OracleConf = OracleConf {
 // - [x] Wrong config account
 // - [x] Wrong mapping account
 // - [x] Wrong admin
+// - [x] Mappings account not migrated to the current size
+// - [x] Computed oracle type mapped without a price account
+// - [x] Entry configuration account of another feed
+// - [x] Solend reserve not owned by the Solend program
 
 // Working update mapping
 #[tokio::test]
@@ -43,6 +53,7 @@ async fn test_working_update_mapping() {
         price_type: TEST_PYTH_ORACLE.price_type.to_u8(),
         twap_enabled: false,
         twap_source: u16::MAX,
+        generic_data: [0; 20],
     };
 
     let ix = Instruction {
@@ -74,6 +85,7 @@ async fn test_wrong_feed_name() {
         price_type: TEST_PYTH_ORACLE.price_type.to_u8(),
         twap_enabled: false,
         twap_source: u16::MAX,
+        generic_data: [0; 20],
     };
 
     let ix = Instruction {
@@ -113,6 +125,7 @@ async fn test_wrong_config_account() {
         price_type: TEST_PYTH_ORACLE.price_type.to_u8(),
         twap_enabled: false,
         twap_source: u16::MAX,
+        generic_data: [0; 20],
     };
 
     let ix = Instruction {
@@ -152,6 +165,7 @@ async fn test_wrong_mapping_account() {
         price_type: TEST_PYTH_ORACLE.price_type.to_u8(),
         twap_enabled: false,
         twap_source: u16::MAX,
+        generic_data: [0; 20],
     };
 
     let ix = Instruction {
@@ -192,6 +206,7 @@ async fn test_wrong_admin() {
         price_type: TEST_PYTH_ORACLE.price_type.to_u8(),
         twap_enabled: false,
         twap_source: u16::MAX,
+        generic_data: [0; 20],
     };
 
     let ix = Instruction {
@@ -205,3 +220,166 @@ async fn test_wrong_admin() {
         AnchorErrorCode::ConstraintHasOne,
     );
 }
+
+// Mappings account created before the generic data was added and not migrated yet
+#[tokio::test]
+async fn test_legacy_mapping_account() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+
+    let mut data = ctx.get_account_data(&feed.mapping).await.unwrap();
+    data.truncate(OracleMappings::LEGACY_ACCOUNT_SIZE);
+    ctx.set_account(&feed.mapping, data, &scope::id());
+
+    let accounts = scope::accounts::UpdateOracleMapping {
+        admin: ctx.admin.pubkey(),
+        configuration: feed.conf,
+        oracle_mappings: feed.mapping,
+        price_info: Some(TEST_PYTH_ORACLE.pubkey),
+    };
+    let args = scope::instruction::UpdateMapping {
+        feed_name: feed.feed_name.clone(),
+        token: TEST_PYTH_ORACLE.token.try_into().unwrap(),
+        price_type: TEST_PYTH_ORACLE.price_type.to_u8(),
+        twap_enabled: false,
+        twap_source: u16::MAX,
+        generic_data: [0; 20],
+    };
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+    let res = ctx.send_transaction(&[ix]).await;
+    assert_eq!(map_scope_error(res), ScopeError::FeedNotMigrated);
+
+    let ix = refresh_simple_oracle_ix(&feed, TEST_PYTH_ORACLE);
+    let res = ctx.send_transaction_with_bot(&[ix]).await;
+    assert_eq!(map_scope_error(res), ScopeError::FeedNotMigrated);
+}
+
+fn update_mapping_ix(
+    ctx: &TestContext,
+    feed: &ScopeFeedDefinition,
+    token: u64,
+    price_type: OracleType,
+    price_info: Option<Pubkey>,
+    generic_data: [u8; 20],
+) -> Instruction {
+    let accounts = scope::accounts::UpdateOracleMapping {
+        admin: ctx.admin.pubkey(),
+        configuration: feed.conf,
+        oracle_mappings: feed.mapping,
+        price_info,
+    };
+    let args = scope::instruction::UpdateMapping {
+        feed_name: feed.feed_name.clone(),
+        token,
+        price_type: price_type.into(),
+        twap_enabled: false,
+        twap_source: u16::MAX,
+        generic_data,
+    };
+    Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+// Entries computed from the feed are mapped to the program id without a price account
+#[tokio::test]
+async fn test_computed_mapping_without_account() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+
+    // Capped at 1.0 over the pyth entry
+    let mut capped_generic_data = [0; 20];
+    capped_generic_data[19] = 1;
+    // Min of the pyth entry and its EMA TWAP
+    let mut min_generic_data = [0; 20];
+    min_generic_data[4] = 1;
+    let inverse_generic_data = [0; 20];
+    for (token, price_type, generic_data) in [
+        (1, OracleType::CappedFloored, capped_generic_data),
+        (2, OracleType::MinOfTwo, min_generic_data),
+        (3, OracleType::MaxOfTwo, min_generic_data),
+        (4, OracleType::InversePrice, inverse_generic_data),
+    ] {
+        let ix = update_mapping_ix(&ctx, &feed, token, price_type, None, generic_data);
+        ctx.send_transaction(&[ix]).await.unwrap();
+    }
+
+    let mappings = ctx
+        .get_zero_copy_account_boxed::<OracleMappings>(&feed.mapping)
+        .await
+        .unwrap();
+    for token in 1..=4 {
+        assert_eq!(mappings.price_info_accounts[token], scope::id());
+    }
+
+    // Invalid generic data is rejected as when a price account is given
+    let mut bad_generic_data = capped_generic_data;
+    bad_generic_data[18] = 31;
+    let ix = update_mapping_ix(
+        &ctx,
+        &feed,
+        5,
+        OracleType::CappedFloored,
+        None,
+        bad_generic_data,
+    );
+    let res = ctx.send_transaction(&[ix]).await;
+    assert_eq!(map_scope_error(res), ScopeError::InvalidGenericData);
+}
+
+// Index definitions (like publishers and field readers) are bound to the prices account of a feed
+#[tokio::test]
+async fn test_index_definition_of_another_feed() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+
+    let mut index_definition: IndexDefinition = bytemuck::Zeroable::zeroed();
+    index_definition.index = 1;
+    index_definition.set_components(&[0], &[1], 0, 0).unwrap();
+
+    let own_definition = Pubkey::new_unique();
+    index_definition.oracle_prices = feed.prices;
+    ctx.set_zero_copy_account(&own_definition, &index_definition);
+    let other_definition = Pubkey::new_unique();
+    index_definition.oracle_prices = Pubkey::new_unique();
+    ctx.set_zero_copy_account(&other_definition, &index_definition);
+
+    let ix = update_mapping_ix(
+        &ctx,
+        &feed,
+        1,
+        OracleType::IndexPrice,
+        Some(other_definition),
+        [0; 20],
+    );
+    let res = ctx.send_transaction(&[ix]).await;
+    assert_eq!(map_scope_error(res), ScopeError::UnexpectedAccount);
+
+    let ix = update_mapping_ix(
+        &ctx,
+        &feed,
+        1,
+        OracleType::IndexPrice,
+        Some(own_definition),
+        [0; 20],
+    );
+    ctx.send_transaction(&[ix]).await.unwrap();
+}
+
+// Solend rates are only read from reserves of the Solend program
+#[tokio::test]
+async fn test_solend_rate_of_another_program() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, vec![TEST_PYTH_ORACLE]).await;
+
+    let reserve = Pubkey::new_unique();
+    ctx.set_account(&reserve, vec![1; 619], &Pubkey::new_unique());
+
+    for price_type in [OracleType::SolendBorrowRate, OracleType::SolendSupplyRate] {
+        let ix = update_mapping_ix(&ctx, &feed, 1, price_type, Some(reserve), [0; 20]);
+        let res = ctx.send_transaction(&[ix]).await;
+        assert_eq!(map_scope_error(res), ScopeError::UnexpectedAccount);
+    }
+}
@@ -7,7 +7,8 @@ use anchor_lang::{InstructionData, ToAccountMetas};
 use common::*;
 use decimal_wad::decimal::Decimal;
 use scope::{
-    assert_fuzzy_eq, EmaTwap, OracleMappings, OraclePrices, OracleTwaps, Price, ScopeError,
+    assert_fuzzy_eq, oracles::twap_stats::TwapStat, EmaTwap, OracleMappings, OraclePrices,
+    OracleTwaps, Price, ScopeError,
 };
 use solana_program::instruction::Instruction;
 use solana_program_test::tokio;
@@ -50,6 +51,7 @@ async fn test_update_mapping() {
         price_type: TEST_PYTH_ORACLE.price_type.to_u8(),
         twap_enabled: TEST_PYTH_ORACLE.twap_enabled,
         twap_source: TEST_PYTH_ORACLE.twap_source.unwrap_or(u16::MAX),
+        generic_data: [0; 20],
     };
 
     let ix = Instruction {
@@ -73,6 +75,7 @@ async fn test_update_mapping() {
         price_type: TEST_TWAP.price_type.to_u8(),
         twap_enabled: TEST_TWAP.twap_enabled,
         twap_source: TEST_TWAP.twap_source.unwrap_or(u16::MAX),
+        generic_data: [0; 20],
     };
 
     let ix = Instruction {
@@ -119,6 +122,72 @@ async fn test_update_mapping() {
     );
 }
 
+#[tokio::test]
+async fn test_update_mapping_rejects_invalid_twap_source() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    mock_oracles::set_price(&mut ctx, &feed, &TEST_PYTH_ORACLE, &Price::default()).await;
+
+    // Source not mapped yet
+    let ix = operations::update_oracle_mapping_ix(&ctx, &feed, &TEST_TWAP);
+    let res = ctx.send_transaction(&[ix]).await;
+    assert_eq!(map_scope_error(res), ScopeError::TwapSourceNotMapped);
+
+    // Source mapped without TWAP
+    let source_without_twap = OracleConf {
+        twap_enabled: false,
+        ..TEST_PYTH_ORACLE
+    };
+    operations::update_oracle_mapping(&mut ctx, &feed, &source_without_twap).await;
+    let ix = operations::update_oracle_mapping_ix(&ctx, &feed, &TEST_TWAP);
+    let res = ctx.send_transaction(&[ix]).await;
+    assert_eq!(map_scope_error(res), ScopeError::TwapSourceNotEnabled);
+
+    // Source is a TWAP entry
+    let twap_of_twap = OracleConf {
+        twap_source: Some(TEST_TWAP.token.try_into().unwrap()),
+        ..TEST_TWAP
+    };
+    let ix = operations::update_oracle_mapping_ix(&ctx, &feed, &twap_of_twap);
+    let res = ctx.send_transaction(&[ix]).await;
+    assert_eq!(map_scope_error(res), ScopeError::TwapSourceIsTwap);
+
+    // Valid once the source has TWAP enabled
+    operations::update_oracle_mapping(&mut ctx, &feed, &TEST_PYTH_ORACLE).await;
+    operations::update_oracle_mapping(&mut ctx, &feed, &TEST_TWAP).await;
+
+    // Disabling the TWAP of the source afterwards would break the TWAP entry
+    let ix = operations::update_oracle_mapping_ix(&ctx, &feed, &source_without_twap);
+    let res = ctx.send_transaction(&[ix]).await;
+    assert_eq!(map_scope_error(res), ScopeError::TwapSourceNotEnabled);
+
+    // So would unmapping the source
+    let accounts = scope::accounts::UpdateOracleMapping {
+        admin: ctx.admin.pubkey(),
+        configuration: feed.conf,
+        oracle_mappings: feed.mapping,
+        price_info: None,
+    };
+    let args = scope::instruction::UpdateMapping {
+        feed_name: feed.feed_name.clone(),
+        token: TEST_PYTH_ORACLE.token.try_into().unwrap(),
+        price_type: TEST_PYTH_ORACLE.price_type.to_u8(),
+        twap_enabled: false,
+        twap_source: u16::MAX,
+        generic_data: [0; 20],
+    };
+    let ix = Instruction {
+        program_id: scope::id(),
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    };
+    let res = ctx.send_transaction(&[ix]).await;
+    assert_eq!(map_scope_error(res), ScopeError::TwapSourceNotMapped);
+
+    let oracle_mappings: OracleMappings = ctx.get_zero_copy_account(&feed.mapping).await.unwrap();
+    assert_eq!(oracle_mappings.twap_source_errors().count(), 0);
+}
+
 #[tokio::test]
 async fn test_set_price_sets_initial_twap() {
     let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
@@ -793,3 +862,62 @@ async fn test_reset_twap() {
         );
     }
 }
+
+#[tokio::test]
+async fn test_twap_stats() {
+    let (mut ctx, feed) = fixtures::setup_scope(DEFAULT_FEED_NAME, Vec::new()).await;
+
+    let low_price = Price { value: 500, exp: 8 };
+    let high_price = Price { value: 600, exp: 8 };
+    mock_oracles::set_price(&mut ctx, &feed, &TEST_PYTH_ORACLE, &low_price).await;
+    setup_mapping_for_token_with_twap(&mut ctx, &feed, TEST_PYTH_ORACLE, TEST_TWAP).await;
+
+    // Statistics of the TWAP of the Pyth entry
+    let stats_oracle = |token, stat| OracleConf {
+        pubkey: scope::ID,
+        token,
+        price_type: TestOracleType::TwapStats(TEST_PYTH_ORACLE.token.try_into().unwrap(), stat),
+        twap_enabled: false,
+        twap_source: None,
+    };
+    let volatility_oracle = stats_oracle(2, TwapStat::Volatility);
+    let high_oracle = stats_oracle(3, TwapStat::High);
+    let low_oracle = stats_oracle(4, TwapStat::Low);
+    for conf in [volatility_oracle, high_oracle, low_oracle] {
+        operations::update_oracle_mapping(&mut ctx, &feed, &conf).await;
+    }
+
+    // Not enough samples yet
+    let refresh_ix = refresh_simple_oracle_ix(&feed, high_oracle);
+    let res = ctx.send_transaction_with_bot(&[refresh_ix]).await;
+    assert_eq!(
+        map_scope_error(res),
+        ScopeError::TwapNotEnoughSamplesInPeriod
+    );
+
+    // One sample a minute for an hour, alternating between the low and high prices
+    for index in 0..=60 {
+        let token_price = if index % 2 == 0 {
+            low_price
+        } else {
+            high_price
+        };
+        mock_oracles::set_price(&mut ctx, &feed, &TEST_PYTH_ORACLE, &token_price).await;
+        let refresh_ix = refresh_simple_oracle_ix(&feed, TEST_PYTH_ORACLE);
+        ctx.send_transaction_with_bot(&[refresh_ix]).await.unwrap();
+        ctx.fast_forward_seconds(60).await;
+    }
+
+    for conf in [volatility_oracle, high_oracle, low_oracle] {
+        let refresh_ix = refresh_simple_oracle_ix(&feed, conf);
+        ctx.send_transaction_with_bot(&[refresh_ix]).await.unwrap();
+    }
+
+    let oracle_prices: OraclePrices = ctx.get_zero_copy_account(&feed.prices).await.unwrap();
+    let volatility: Decimal = oracle_prices.prices[volatility_oracle.token].price.into();
+    let high: Decimal = oracle_prices.prices[high_oracle.token].price.into();
+    let low: Decimal = oracle_prices.prices[low_oracle.token].price.into();
+    assert!(volatility > Decimal::zero());
+    assert_eq!(high, Decimal::from(high_price));
+    assert_eq!(low, Decimal::from(low_price));
+}
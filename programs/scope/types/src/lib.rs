@@ -19,6 +19,17 @@ pub const MAX_ENTRIES_U16: u16 = 512;
 // Note: Need to be directly integer value to not confuse the IDL generator
 pub const MAX_ENTRIES: usize = 512;
 
+/// Highest exponent (number of decimals) of the prices written by scope.
+///
+/// Most oracle types produce at most 18 decimals. Signed prices, inverse and index prices keep
+/// up to 30 decimals for very small prices, and the entries clamping or selecting another price
+/// keep the exponent of their source. TWAPs are computed with 18 decimals.
+///
+/// Prices above `u64::MAX` (with an exponent of 0) are out of scope: the refresh of an entry
+/// producing such a price fails with a `PriceOutOfRange` error and the entry keeps its previous
+/// price.
+pub const MAX_PRICE_EXP: u64 = 30;
+
 #[zero_copy]
 #[derive(Debug, Eq, PartialEq, Default)]
 pub struct Price {
@@ -33,6 +44,7 @@ pub struct Price {
 
     // exponent represents the number of decimals
     // for example, 8 for btc
+    // at most `MAX_PRICE_EXP` for the prices written by scope
     pub exp: u64,
 }
 
@@ -40,10 +52,16 @@ pub struct Price {
 #[derive(Debug, Eq, PartialEq)]
 pub struct DatedPrice {
     pub price: Price,
+    // Slot and timestamp at which the source published the price (e.g. pyth `pub_slot`), or at
+    // which it was computed for prices derived from on-chain state.
     pub last_updated_slot: u64,
     pub unix_timestamp: u64,
-    pub _reserved: [u64; 2],
-    pub _reserved2: [u16; 3],
+    // Best bid and best ask (`[bid, ask]`) of the market for orderbook entries configured to store
+    // them, with the same exponent as `price`. Zero otherwise.
+    pub bid_ask: [u64; 2],
+    // Slot at which scope wrote the price, as a little endian 48 bits integer.
+    // See `refreshed_slot()`.
+    pub refreshed_slot_u48: [u16; 3],
     // Current index of the dated price.
     pub index: u16,
 }
@@ -54,13 +72,33 @@ impl Default for DatedPrice {
             price: Default::default(),
             last_updated_slot: Default::default(),
             unix_timestamp: Default::default(),
-            _reserved: Default::default(),
-            _reserved2: Default::default(),
+            bid_ask: Default::default(),
+            refreshed_slot_u48: Default::default(),
             index: MAX_ENTRIES_U16,
         }
     }
 }
 
+impl DatedPrice {
+    /// Slot at which the price was written by a refresh, 0 if it was written before this slot
+    /// was recorded.
+    ///
+    /// Unlike [`DatedPrice::last_updated_slot`] it does not depend on the oracle type, so the
+    /// age of the last refresh can be compared uniformly across entries.
+    ///
+    /// The refresh timestamp is not stored: the dated price has no reserved space left for it,
+    /// and the block time of the refreshed slot gives it when needed.
+    pub fn refreshed_slot(&self) -> u64 {
+        let [low, mid, high] = self.refreshed_slot_u48;
+        u64::from(low) | u64::from(mid) << 16 | u64::from(high) << 32
+    }
+
+    pub fn set_refreshed_slot(&mut self, slot: u64) {
+        // Truncation is expected: 48 bits of slots last for thousands of years.
+        self.refreshed_slot_u48 = [slot as u16, (slot >> 16) as u16, (slot >> 32) as u16];
+    }
+}
+
 // Account to store dated TWAP prices
 #[account(zero_copy)]
 pub struct OracleTwaps {
@@ -83,17 +121,37 @@ pub struct EmaTwap {
     pub last_update_unix_timestamp: u64,
 
     pub current_ema_1h: u128,
-
-    pub padding: [u128; 40],
+    /// The sample tracker is a 64 bit number where each bit represents a point in time.
+    pub updates_tracker_1h: u64,
+    pub padding_0: u64,
+
+    /// Last sample price (scaled decimal), used to compute the log returns.
+    pub last_sample_price: u128,
+    /// Exponentially weighted variance of the log returns, per second (scaled decimal).
+    pub current_variance_1h: u128,
+    /// Highest and lowest sample prices (scaled decimals) of each sub-period of the EMA period.
+    /// 0 if there is no sample in the sub-period.
+    pub highs_1h: [u128; EMA_RANGE_BUCKETS],
+    pub lows_1h: [u128; EMA_RANGE_BUCKETS],
+
+    pub padding_1: [u128; 25],
 }
 
+pub const EMA_RANGE_BUCKETS: usize = 6;
+
 impl Default for EmaTwap {
     fn default() -> Self {
         Self {
             current_ema_1h: 0,
             last_update_slot: 0,
             last_update_unix_timestamp: 0,
-            padding: [0_u128; 40],
+            updates_tracker_1h: 0,
+            padding_0: 0,
+            last_sample_price: 0,
+            current_variance_1h: 0,
+            highs_1h: [0; EMA_RANGE_BUCKETS],
+            lows_1h: [0; EMA_RANGE_BUCKETS],
+            padding_1: [0_u128; 25],
         }
     }
 }
@@ -105,8 +163,9 @@ pub struct OracleMappings {
     pub price_types: [u8; MAX_ENTRIES],
     pub twap_source: [u16; MAX_ENTRIES], // meaningful only if type == TWAP; the index of where we find the TWAP
     pub twap_enabled: [u8; MAX_ENTRIES], // true or false
-    pub _reserved1: [u8; MAX_ENTRIES],
+    pub history_enabled: [u8; MAX_ENTRIES], // true or false, see PriceHistory
     pub _reserved2: [u32; MAX_ENTRIES],
+    pub generic: [[u8; 20]; MAX_ENTRIES], // oracle type specific configuration
 }
 
 impl OracleMappings {
@@ -117,8 +176,18 @@ impl OracleMappings {
     pub fn get_twap_source(&self, token: usize) -> usize {
         usize::from(self.twap_source[token])
     }
+
+    pub fn is_history_enabled(&self, token: usize) -> bool {
+        self.history_enabled[token] > 0
+    }
 }
 
+/// Layout version of the accounts of a feed described by these types.
+/// Compare it with [`Configuration::version`] to detect a layout mismatch.
+pub const FEED_LAYOUT_VERSION: u64 = 1;
+
+pub const MAX_REFRESH_ALLOWED_PROGRAMS: usize = 8;
+
 // Configuration account of the program
 #[account(zero_copy)]
 pub struct Configuration {
@@ -127,7 +196,12 @@ pub struct Configuration {
     pub oracle_prices: Pubkey,
     pub tokens_metadata: Pubkey,
     pub oracle_twaps: Pubkey,
-    _padding: [u64; 1259],
+    pub admin_cached: Pubkey,
+    pub version: u64, // layout version of all the accounts of the feed, see FEED_LAYOUT_VERSION
+    // Programs allowed to have instructions before a refresh, on top of compute budget and ed25519
+    pub refresh_allowed_programs_count: u64,
+    pub refresh_allowed_programs: [Pubkey; MAX_REFRESH_ALLOWED_PROGRAMS],
+    _padding: [u64; 1221],
 }
 
 #[account(zero_copy)]
@@ -128,6 +128,7 @@ describe('Scope crank bot tests', () => {
           false,
           new BN(65_535),
           PRICE_FEED,
+          Array(20).fill(0),
           {
             accounts: {
               admin: admin.publicKey,
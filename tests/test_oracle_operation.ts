@@ -109,15 +109,23 @@ describe('Scope tests', () => {
       testTokens.map(async (fakeOracleAccount, idx): Promise<any> => {
         // console.log(`Set mapping of ${fakeOracleAccount.ticker} ${fakeOracleAccount.getType()}`);
 
-        await program.rpc.updateMapping(new BN(idx), fakeOracleAccount.getType(), false, new BN(65_535), PRICE_FEED, {
-          accounts: {
-            admin: admin.publicKey,
-            configuration: confAccount,
-            oracleMappings: oracleMappingAccount,
-            priceInfo: fakeOracleAccount.account,
-          },
-          signers: [admin],
-        });
+        await program.rpc.updateMapping(
+          new BN(idx),
+          fakeOracleAccount.getType(),
+          false,
+          new BN(65_535),
+          PRICE_FEED,
+          Array(20).fill(0),
+          {
+            accounts: {
+              admin: admin.publicKey,
+              configuration: confAccount,
+              oracleMappings: oracleMappingAccount,
+              priceInfo: fakeOracleAccount.account,
+            },
+            signers: [admin],
+          }
+        );
       })
     );
   });
@@ -172,6 +180,7 @@ describe('Scope tests', () => {
           false,
           new BN(65_535),
           PRICE_FEED,
+          Array(20).fill(0),
           {
             accounts: {
               admin: admin.publicKey,
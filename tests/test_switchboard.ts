@@ -104,15 +104,23 @@ describe('Switchboard Scope tests', () => {
       testTokens.map(async (fakeOracleAccount, idx): Promise<any> => {
         // console.log(`Set mapping of ${fakeOracleAccount.ticker}`);
 
-        await program.rpc.updateMapping(new BN(idx), fakeOracleAccount.getType(), false, new BN(65_535), PRICE_FEED, {
-          accounts: {
-            admin: admin.publicKey,
-            configuration: confAccount,
-            oracleMappings: oracleMappingAccount,
-            priceInfo: fakeOracleAccount.account,
-          },
-          signers: [admin],
-        });
+        await program.rpc.updateMapping(
+          new BN(idx),
+          fakeOracleAccount.getType(),
+          false,
+          new BN(65_535),
+          PRICE_FEED,
+          Array(20).fill(0),
+          {
+            accounts: {
+              admin: admin.publicKey,
+              configuration: confAccount,
+              oracleMappings: oracleMappingAccount,
+              priceInfo: fakeOracleAccount.account,
+            },
+            signers: [admin],
+          }
+        );
       })
     );
   });